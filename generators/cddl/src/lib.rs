@@ -0,0 +1,566 @@
+//! CDDL generation from TreeLDR layouts.
+//!
+//! Generates a [CDDL (RFC 8610)][cddl] description of the CBOR values
+//! produced by [`TypedValue::try_into_tagged_serde_cbor`], including the tags
+//! given by the [CBOR extension `tag` property][tag].
+//!
+//! [cddl]: <https://www.rfc-editor.org/rfc/rfc8610>
+//! [`TypedValue::try_into_tagged_serde_cbor`]: treeldr_layouts::TypedValue
+//! [tag]: treeldr_layouts::value::cbor::CBOR_TAG_IRI
+use std::{
+	collections::{BTreeMap, HashMap, HashSet, VecDeque},
+	fmt::Write,
+	hash::Hash,
+};
+
+use rdf_types::{
+	interpretation::{IriInterpretation, ReverseIriInterpretation, ReverseLiteralInterpretation},
+	Term, Vocabulary, RDFS_COMMENT,
+};
+use treeldr_gen_rust::utils::type_name_from_iri;
+use treeldr_layouts::{
+	distill::RdfContext,
+	layout::{DataLayout, LayoutType, ListLayout, LiteralLayout},
+	value::cbor::{get_layout_tag, InvalidTag},
+	Layout, LayoutRegistry, Literal, PresetLayout, Ref, Value,
+};
+
+/// Indentation used in the generated description.
+const INDENT: &str = "  ";
+
+/// Names defined by the CDDL standard prelude, that cannot be used as rule
+/// names.
+const PRELUDE: &[&str] = &[
+	"any",
+	"b64legacy",
+	"b64url",
+	"bigfloat",
+	"bigint",
+	"bignint",
+	"biguint",
+	"bool",
+	"bstr",
+	"bytes",
+	"cbor-any",
+	"decfrac",
+	"eb16",
+	"eb64legacy",
+	"eb64url",
+	"encoded-cbor",
+	"false",
+	"float",
+	"float16",
+	"float16-32",
+	"float32",
+	"float32-64",
+	"float64",
+	"int",
+	"integer",
+	"mime-message",
+	"nil",
+	"nint",
+	"null",
+	"number",
+	"regexp",
+	"tdate",
+	"text",
+	"time",
+	"true",
+	"tstr",
+	"uint",
+	"undefined",
+	"unsigned",
+	"uri",
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error<R = Term> {
+	#[error("missing type identifier for layout {0}")]
+	MissingTypeIdentifier(R),
+
+	#[error("layout {0} not found")]
+	LayoutNotFound(R),
+
+	#[error("layout {0} cannot be described with CDDL")]
+	Unsupported(R),
+
+	#[error("invalid CBOR tag for layout {0}: {1}")]
+	InvalidTag(R, InvalidTag),
+}
+
+pub struct Options<R = Term> {
+	idents: HashMap<Ref<LayoutType, R>, String>,
+}
+
+impl<R> Options<R> {
+	pub fn new() -> Self {
+		Self {
+			idents: HashMap::new(),
+		}
+	}
+
+	/// Sets the rule name of the given layout.
+	pub fn set_ident(&mut self, layout_ref: Ref<LayoutType, R>, ident: String)
+	where
+		R: Eq + Hash,
+	{
+		self.idents.insert(layout_ref, ident);
+	}
+
+	pub fn layout_ident<V, I>(
+		&self,
+		rdf: RdfContext<V, I>,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Option<String>
+	where
+		V: Vocabulary,
+		I: ReverseIriInterpretation<Resource = R, Iri = V::Iri>,
+		R: Eq + Hash,
+	{
+		self.idents
+			.get(layout_ref)
+			.cloned()
+			.or_else(|| default_layout_ident(rdf, layout_ref))
+	}
+}
+
+impl<R> Default for Options<R> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Checks that the given string is a valid CDDL rule name.
+pub fn is_ident(s: &str) -> bool {
+	is_bareword(s) && !PRELUDE.contains(&s)
+}
+
+/// Checks that the given string is a valid CDDL identifier, that can be used
+/// unquoted as a map key.
+fn is_bareword(s: &str) -> bool {
+	let is_ealpha = |c: char| c.is_ascii_alphabetic() || matches!(c, '@' | '_' | '$');
+
+	let mut chars = s.chars();
+	match chars.next() {
+		Some(c) if is_ealpha(c) => {
+			let mut separated = false;
+			for c in chars {
+				if matches!(c, '-' | '.') {
+					separated = true
+				} else if is_ealpha(c) || c.is_ascii_digit() {
+					separated = false
+				} else {
+					return false;
+				}
+			}
+
+			!separated
+		}
+		_ => false,
+	}
+}
+
+pub fn default_layout_ident<V, I>(
+	rdf: RdfContext<V, I>,
+	layout_ref: &Ref<LayoutType, I::Resource>,
+) -> Option<String>
+where
+	V: Vocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri>,
+{
+	let mut selected: Option<String> = None;
+
+	for i in rdf.interpretation.iris_of(layout_ref.id()) {
+		let iri = rdf.vocabulary.iri(i).unwrap();
+		if let Some(name) = type_name_from_iri(iri).filter(|name| is_ident(name)) {
+			if !selected.as_ref().is_some_and(|s| *s < name) {
+				selected = Some(name)
+			}
+		}
+	}
+
+	selected
+}
+
+/// Generates the CDDL rules describing the given layout and all its named
+/// dependencies.
+///
+/// The first rule describes the given layout. Anonymous layouts (without
+/// identifier) are inlined where they are used. Text string patterns are not
+/// described.
+pub fn generate<V, I>(
+	rdf: RdfContext<V, I>,
+	layouts: impl LayoutRegistry<I::Resource>,
+	layout_ref: &Ref<LayoutType, I::Resource>,
+	options: &Options<I::Resource>,
+) -> Result<String, Error<I::Resource>>
+where
+	V: Vocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri>
+		+ IriInterpretation<V::Iri>
+		+ ReverseLiteralInterpretation<Literal = V::Literal>,
+	I::Resource: Clone + Ord + Hash,
+{
+	let ident = options
+		.layout_ident(rdf, layout_ref)
+		.ok_or_else(|| Error::MissingTypeIdentifier(layout_ref.id().clone()))?;
+
+	let mut generator = Generator {
+		rdf,
+		layouts: &layouts,
+		options,
+		declared: HashSet::new(),
+		queue: VecDeque::new(),
+	};
+
+	generator.schedule(layout_ref, ident);
+
+	let mut output = String::new();
+	output.push_str("; Generated by TreeLDR. Do not edit.\n");
+
+	while let Some((layout_ref, ident)) = generator.queue.pop_front() {
+		output.push('\n');
+		output.push_str(&generator.rule(&layout_ref, &ident)?);
+	}
+
+	Ok(output)
+}
+
+struct Generator<'a, V, I: ReverseIriInterpretation, L> {
+	rdf: RdfContext<'a, V, I>,
+	layouts: &'a L,
+	options: &'a Options<I::Resource>,
+	declared: HashSet<Ref<LayoutType, I::Resource>>,
+	queue: VecDeque<(Ref<LayoutType, I::Resource>, String)>,
+}
+
+impl<'a, V, I, L> Generator<'a, V, I, L>
+where
+	V: Vocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri>
+		+ IriInterpretation<V::Iri>
+		+ ReverseLiteralInterpretation<Literal = V::Literal>,
+	I::Resource: Clone + Ord + Hash,
+	L: LayoutRegistry<I::Resource>,
+{
+	fn get(
+		&self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Result<&'a Layout<I::Resource>, Error<I::Resource>> {
+		self.layouts
+			.get(layout_ref)
+			.ok_or_else(|| Error::LayoutNotFound(layout_ref.id().clone()))
+	}
+
+	fn schedule(&mut self, layout_ref: &Ref<LayoutType, I::Resource>, ident: String) {
+		if self.declared.insert(layout_ref.clone()) {
+			self.queue.push_back((layout_ref.clone(), ident))
+		}
+	}
+
+	fn preset(&self, layout_ref: &Ref<LayoutType, I::Resource>) -> Option<PresetLayout> {
+		self.rdf
+			.interpretation
+			.iris_of(layout_ref.id())
+			.find_map(|i| PresetLayout::from_iri(self.rdf.vocabulary.iri(i).unwrap()))
+	}
+
+	fn iri(&self, resource: &I::Resource) -> Option<String> {
+		self.rdf
+			.interpretation
+			.iris_of(resource)
+			.next()
+			.map(|i| self.rdf.vocabulary.iri(i).unwrap().to_string())
+	}
+
+	fn is_integer_datatype(&self, datatype: &I::Resource) -> bool {
+		self.rdf.interpretation.iris_of(datatype).any(|i| {
+			xsd_types::IntegerDatatype::from_iri(self.rdf.vocabulary.iri(i).unwrap()).is_some()
+		})
+	}
+
+	/// Returns the `rdfs:comment` found in the given extra properties, if any.
+	fn comment(&self, properties: &BTreeMap<I::Resource, I::Resource>) -> Option<String> {
+		properties.iter().find_map(|(prop, value)| {
+			let is_comment = self
+				.rdf
+				.interpretation
+				.iris_of(prop)
+				.any(|i| self.rdf.vocabulary.iri(i).unwrap() == RDFS_COMMENT);
+
+			if is_comment {
+				self.rdf
+					.interpretation
+					.literals_of(value)
+					.find_map(|l| self.rdf.vocabulary.literal(l))
+					.map(|l| l.value.to_owned())
+			} else {
+				None
+			}
+		})
+	}
+
+	/// Returns the CBOR tag of the given layout, if any.
+	fn tag(
+		&self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Result<Option<u64>, Error<I::Resource>> {
+		get_layout_tag(
+			self.rdf.vocabulary,
+			self.rdf.interpretation,
+			self.layouts,
+			layout_ref,
+		)
+		.map_err(|e| Error::InvalidTag(layout_ref.id().clone(), e))
+	}
+
+	/// Generates the rule of a named layout.
+	fn rule(
+		&mut self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+		ident: &str,
+	) -> Result<String, Error<I::Resource>> {
+		let layout = self.get(layout_ref)?;
+		let mut result = String::new();
+
+		let mut description = Vec::new();
+		if let Some(comment) = self.comment(layout.extra_properties()) {
+			description.push(comment)
+		}
+		if let Some(iri) = self.iri(layout_ref.id()) {
+			description.push(format!("Layout `{iri}`."))
+		}
+		result.push_str(&comment(&description.join("\n\n"), ""));
+
+		let ty = match layout {
+			Layout::Sum(layout) if !layout.variants.is_empty() => {
+				// One variant per line.
+				let indent = INDENT.to_owned();
+				let mut ty = String::new();
+				for (i, variant) in layout.variants.iter().enumerate() {
+					ty.push('\n');
+					let mut variant_comment = format!("Variant `{}`.", variant.name);
+					if let Some(c) = self.comment(&variant.extra_properties) {
+						write!(variant_comment, "\n\n{c}").unwrap();
+					}
+					ty.push_str(&comment(&variant_comment, &indent));
+					ty.push_str(&indent);
+					ty.push_str(&self.type_ref(&variant.value.layout, &indent)?);
+					if i + 1 < layout.variants.len() {
+						ty.push_str(" /")
+					}
+				}
+
+				ty
+			}
+			other => format!(" {}", self.type_expr(other, layout_ref, "")?),
+		};
+
+		match self.tag(layout_ref)? {
+			Some(tag) if ty.starts_with('\n') => {
+				writeln!(result, "{ident} = #6.{tag}({ty}\n)").unwrap()
+			}
+			Some(tag) => writeln!(result, "{ident} = #6.{tag}({})", ty.trim_start()).unwrap(),
+			None => writeln!(result, "{ident} ={ty}").unwrap(),
+		}
+
+		Ok(result)
+	}
+
+	/// Returns the type referencing the given layout.
+	///
+	/// Preset layouts are mapped to CDDL prelude types, named layouts are
+	/// referenced by rule name and anonymous layouts are inlined, along with
+	/// their tag.
+	fn type_ref(
+		&mut self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+		indent: &str,
+	) -> Result<String, Error<I::Resource>> {
+		match self.preset(layout_ref) {
+			None => (),
+			Some(PresetLayout::Unit) => return Ok("nil".to_owned()),
+			Some(PresetLayout::Boolean) => return Ok("bool".to_owned()),
+			Some(PresetLayout::U8) => return Ok("0..255".to_owned()),
+			Some(PresetLayout::U16) => return Ok("0..65535".to_owned()),
+			Some(PresetLayout::U32) => return Ok("0..4294967295".to_owned()),
+			Some(PresetLayout::U64) => return Ok("uint".to_owned()),
+			Some(PresetLayout::I8) => return Ok("-128..127".to_owned()),
+			Some(PresetLayout::I16) => return Ok("-32768..32767".to_owned()),
+			Some(PresetLayout::I32) => return Ok("-2147483648..2147483647".to_owned()),
+			Some(PresetLayout::I64) => return Ok("int".to_owned()),
+			Some(PresetLayout::Id | PresetLayout::String) => return Ok("tstr".to_owned()),
+		}
+
+		match self.options.layout_ident(self.rdf, layout_ref) {
+			Some(ident) => {
+				self.schedule(layout_ref, ident.clone());
+				Ok(ident)
+			}
+			None => {
+				let layout = self.get(layout_ref)?;
+				let ty = self.type_expr(layout, layout_ref, indent)?;
+				match self.tag(layout_ref)? {
+					Some(tag) => Ok(format!("#6.{tag}({ty})")),
+					None => Ok(ty),
+				}
+			}
+		}
+	}
+
+	/// Returns the inline type of the given layout, without its tag.
+	fn type_expr(
+		&mut self,
+		layout: &Layout<I::Resource>,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+		indent: &str,
+	) -> Result<String, Error<I::Resource>> {
+		match layout {
+			Layout::Never => Err(Error::Unsupported(layout_ref.id().clone())),
+			Layout::Always => Ok("any".to_owned()),
+			Layout::Literal(LiteralLayout::Data(layout)) => match layout {
+				DataLayout::Unit(layout) => Ok(value_literal(&layout.const_)),
+				DataLayout::Boolean(_) => Ok("bool".to_owned()),
+				DataLayout::Number(layout) => {
+					if self.is_integer_datatype(&layout.datatype) {
+						Ok("integer".to_owned())
+					} else {
+						// Integral numbers are encoded as integers.
+						Ok("integer / float".to_owned())
+					}
+				}
+				DataLayout::ByteString(_) => Ok("bstr".to_owned()),
+				DataLayout::TextString(_) => Ok("tstr".to_owned()),
+			},
+			Layout::Literal(LiteralLayout::Id(_)) => Ok("tstr".to_owned()),
+			Layout::Product(layout) => {
+				if layout.fields.is_empty() {
+					return Ok("{}".to_owned());
+				}
+
+				let inner_indent = format!("{indent}{INDENT}");
+				let mut result = "{\n".to_owned();
+				for (key, field) in &layout.fields {
+					if let Some(c) = self.comment(&field.extra_properties) {
+						result.push_str(&comment(&c, &inner_indent));
+					}
+
+					let optional = if field.required { "" } else { "? " };
+					let ty = self.type_ref(&field.value.layout, &inner_indent)?;
+					writeln!(result, "{inner_indent}{optional}{}{ty},", member_key(key)).unwrap();
+				}
+				write!(result, "{indent}}}").unwrap();
+
+				Ok(result)
+			}
+			Layout::Sum(layout) => {
+				if layout.variants.is_empty() {
+					return Err(Error::Unsupported(layout_ref.id().clone()));
+				}
+
+				let variants = layout
+					.variants
+					.iter()
+					.map(|v| self.type_ref(&v.value.layout, indent))
+					.collect::<Result<Vec<_>, _>>()?;
+
+				Ok(format!("({})", variants.join(" / ")))
+			}
+			Layout::List(ListLayout::Unordered(layout)) => {
+				let item = self.type_ref(&layout.item.value.layout, indent)?;
+				Ok(format!("[* {item}]"))
+			}
+			Layout::List(ListLayout::Ordered(layout)) => {
+				let item = self.type_ref(&layout.node.value.layout, indent)?;
+				Ok(format!("[* {item}]"))
+			}
+			Layout::List(ListLayout::Sized(layout)) => {
+				let items = layout
+					.items
+					.iter()
+					.map(|item| self.type_ref(&item.value.layout, indent))
+					.collect::<Result<Vec<_>, _>>()?;
+
+				Ok(format!("[{}]", items.join(", ")))
+			}
+		}
+	}
+}
+
+/// Returns the member key (including the separator) of a map entry.
+fn member_key(key: &Value) -> String {
+	match key {
+		Value::Literal(Literal::TextString(name)) if is_bareword(name) => format!("{name}: "),
+		Value::Literal(Literal::TextString(_)) => format!("{}: ", value_literal(key)),
+		other => format!("{} => ", value_literal(other)),
+	}
+}
+
+/// Returns the CDDL type matching exactly the given value.
+fn value_literal(value: &Value) -> String {
+	match value {
+		Value::Literal(Literal::Unit) => "nil".to_owned(),
+		Value::Literal(Literal::Boolean(b)) => b.to_string(),
+		Value::Literal(Literal::Number(n)) => match n.decimal_representation() {
+			Some(n) if !n.contains('.') => n,
+			_ => n.to_f64().to_string(),
+		},
+		Value::Literal(Literal::TextString(s)) => string_literal(s),
+		Value::Literal(Literal::ByteString(bytes)) => {
+			let mut result = "h'".to_owned();
+			for b in bytes {
+				write!(result, "{b:02x}").unwrap();
+			}
+			result.push('\'');
+			result
+		}
+		Value::Map(map) => {
+			let entries = map
+				.iter()
+				.map(|(key, value)| format!("{} => {}", value_literal(key), value_literal(value)))
+				.collect::<Vec<_>>();
+
+			format!("{{ {} }}", entries.join(", "))
+		}
+		Value::List(items) => {
+			let items = items.iter().map(value_literal).collect::<Vec<_>>();
+			format!("[{}]", items.join(", "))
+		}
+	}
+}
+
+/// Returns the CDDL text string literal of the given string.
+///
+/// CDDL text literals follow the JSON string conventions.
+fn string_literal(s: &str) -> String {
+	let mut result = String::with_capacity(s.len() + 2);
+	result.push('"');
+
+	for c in s.chars() {
+		match c {
+			'"' => result.push_str("\\\""),
+			'\\' => result.push_str("\\\\"),
+			'\n' => result.push_str("\\n"),
+			'\r' => result.push_str("\\r"),
+			'\t' => result.push_str("\\t"),
+			c if c.is_control() => write!(result, "\\u{:04x}", c as u32).unwrap(),
+			c => result.push(c),
+		}
+	}
+
+	result.push('"');
+	result
+}
+
+/// Formats the given text as a comment with the given indentation.
+fn comment(text: &str, indent: &str) -> String {
+	let mut result = String::new();
+	for line in text.lines() {
+		if line.is_empty() {
+			writeln!(result, "{indent};").unwrap();
+		} else {
+			writeln!(result, "{indent}; {line}").unwrap();
+		}
+	}
+	result
+}
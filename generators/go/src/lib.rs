@@ -0,0 +1,1147 @@
+//! Go code generation from TreeLDR layouts.
+//!
+//! Generates Go type declarations describing the tree values matched by a
+//! layout, along with `Validate` methods checking the constraints that cannot
+//! be expressed by the Go type system.
+use std::{
+	collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+	fmt::{self, Write},
+	hash::Hash,
+};
+
+use rdf_types::{
+	interpretation::{ReverseIriInterpretation, ReverseLiteralInterpretation},
+	vocabulary::{IriVocabulary, LiteralVocabulary},
+	Term, RDFS_COMMENT,
+};
+use treeldr_gen_rust::utils::type_name_from_iri;
+use treeldr_layouts::{
+	distill::RdfContext,
+	layout::{DataLayout, LayoutType, ListLayout, LiteralLayout},
+	utils::DetAutomaton,
+	Layout, LayoutRegistry, Literal, PresetLayout, Ref, Value,
+};
+
+/// Go keywords, that cannot be used as identifiers.
+const KEYWORDS: &[&str] = &[
+	"break",
+	"case",
+	"chan",
+	"const",
+	"continue",
+	"default",
+	"defer",
+	"else",
+	"fallthrough",
+	"for",
+	"func",
+	"go",
+	"goto",
+	"if",
+	"import",
+	"interface",
+	"map",
+	"package",
+	"range",
+	"return",
+	"select",
+	"struct",
+	"switch",
+	"type",
+	"var",
+];
+
+/// Common initialisms, written in upper case in Go identifiers.
+const INITIALISMS: &[&str] = &["id", "iri", "json", "uri", "url", "uuid"];
+
+/// Runtime functions included in every generated file.
+const RUNTIME: &str = include_str!("runtime.go");
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error<R = Term> {
+	#[error("missing type identifier for layout {0}")]
+	MissingTypeIdentifier(R),
+
+	#[error("layout {0} not found")]
+	LayoutNotFound(R),
+
+	#[error("invalid field name `{0}`")]
+	InvalidFieldName(Value),
+
+	#[error("invalid package name `{0}`")]
+	InvalidPackageName(String),
+}
+
+pub struct Options<R = Term> {
+	idents: HashMap<Ref<LayoutType, R>, String>,
+	package: String,
+}
+
+impl<R> Options<R> {
+	pub fn new() -> Self {
+		Self {
+			idents: HashMap::new(),
+			package: "model".to_owned(),
+		}
+	}
+
+	/// Sets the name of the generated package.
+	pub fn with_package(self, package: String) -> Self {
+		Self { package, ..self }
+	}
+
+	/// Sets the Go identifier of the given layout.
+	pub fn set_ident(&mut self, layout_ref: Ref<LayoutType, R>, ident: String)
+	where
+		R: Eq + Hash,
+	{
+		self.idents.insert(layout_ref, ident);
+	}
+
+	pub fn layout_ident<V, I>(
+		&self,
+		rdf: RdfContext<V, I>,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Option<String>
+	where
+		V: IriVocabulary,
+		I: ReverseIriInterpretation<Resource = R, Iri = V::Iri>,
+		R: Eq + Hash,
+	{
+		self.idents
+			.get(layout_ref)
+			.cloned()
+			.or_else(|| default_layout_ident(rdf, layout_ref))
+	}
+}
+
+impl<R> Default for Options<R> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Checks that the given string is a valid Go identifier.
+pub fn is_ident(s: &str) -> bool {
+	let mut chars = s.chars();
+	match chars.next() {
+		Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+			chars.all(|c| c.is_ascii_alphanumeric() || c == '_') && !KEYWORDS.contains(&s)
+		}
+		_ => false,
+	}
+}
+
+pub fn default_layout_ident<V, I>(
+	rdf: RdfContext<V, I>,
+	layout_ref: &Ref<LayoutType, I::Resource>,
+) -> Option<String>
+where
+	V: IriVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri>,
+{
+	let mut selected: Option<String> = None;
+
+	for i in rdf.interpretation.iris_of(layout_ref.id()) {
+		let iri = rdf.vocabulary.iri(i).unwrap();
+		if let Some(name) = type_name_from_iri(iri).filter(|name| is_ident(name)) {
+			if !selected.as_ref().is_some_and(|s| *s < name) {
+				selected = Some(name)
+			}
+		}
+	}
+
+	selected
+}
+
+/// Generates the Go source file defining the given layout and all its
+/// dependencies.
+///
+/// Records and sums are always declared as named types. Anonymous records
+/// and sums are given a name derived from where they are used. Other
+/// anonymous layouts are inlined.
+pub fn generate<V, I>(
+	rdf: RdfContext<V, I>,
+	layouts: impl LayoutRegistry<I::Resource>,
+	layout_ref: &Ref<LayoutType, I::Resource>,
+	options: &Options<I::Resource>,
+) -> Result<String, Error<I::Resource>>
+where
+	V: IriVocabulary + LiteralVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri> + ReverseLiteralInterpretation<Literal = V::Literal>,
+	I::Resource: Clone + Ord + Hash,
+{
+	if !is_ident(&options.package) {
+		return Err(Error::InvalidPackageName(options.package.clone()));
+	}
+
+	let ident = options
+		.layout_ident(rdf, layout_ref)
+		.ok_or_else(|| Error::MissingTypeIdentifier(layout_ref.id().clone()))?;
+
+	let mut generator = Generator {
+		rdf,
+		layouts: &layouts,
+		options,
+		names: HashMap::new(),
+		taken: HashSet::new(),
+		queue: VecDeque::new(),
+		patterns: Vec::new(),
+		imports: BTreeSet::from(["bytes", "encoding/json"]),
+	};
+
+	generator.declare(layout_ref, ident);
+
+	let mut declarations = Vec::new();
+	while let Some((layout_ref, ident)) = generator.queue.pop_front() {
+		declarations.push(generator.declaration(&layout_ref, &ident)?);
+	}
+
+	let mut output = String::new();
+	output.push_str("// Code generated by TreeLDR. DO NOT EDIT.\n\n");
+	writeln!(output, "package {}\n", options.package).unwrap();
+
+	output.push_str("import (\n");
+	for import in &generator.imports {
+		writeln!(output, "\t\"{import}\"").unwrap();
+	}
+	output.push_str(")\n");
+
+	for declaration in declarations {
+		output.push('\n');
+		output.push_str(&declaration);
+	}
+
+	for (i, pattern) in generator.patterns.iter().enumerate() {
+		write!(output, "\nvar tldrPattern{i} = {pattern}\n").unwrap();
+	}
+
+	output.push('\n');
+	output.push_str(RUNTIME);
+
+	Ok(output)
+}
+
+/// Go type expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Type {
+	/// Predeclared type (`bool`, `int64`, `any`, etc.).
+	Basic(&'static str),
+
+	/// Text string, with an optional pattern (index of the automaton).
+	String(Option<usize>),
+
+	/// Absolute IRI, stored as a text string, with an optional pattern.
+	Iri(Option<usize>),
+
+	/// Byte string.
+	Bytes,
+
+	/// Declared type.
+	Named(String, Kind),
+
+	/// Slice.
+	Slice(Box<Self>),
+
+	/// Array of the given length.
+	Array(usize, Box<Self>),
+
+	/// Pointer, used for optional fields.
+	Pointer(Box<Self>),
+}
+
+/// Kind of declared type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+	/// Interface implemented by each variant of a sum layout.
+	Sum,
+
+	/// Any other type.
+	Other,
+}
+
+impl Type {
+	/// Checks if the zero value of this type is `nil`, meaning that it does
+	/// not need a pointer to be optional.
+	fn is_nilable(&self) -> bool {
+		matches!(
+			self,
+			Self::Basic("any") | Self::Bytes | Self::Named(_, Kind::Sum) | Self::Slice(_)
+		)
+	}
+
+	/// Checks if values of this type cannot be decoded by `json.Unmarshal`
+	/// without help, because they contain a sum type interface.
+	fn contains_sum(&self) -> bool {
+		match self {
+			Self::Named(_, kind) => *kind == Kind::Sum,
+			Self::Slice(item) | Self::Array(_, item) | Self::Pointer(item) => item.contains_sum(),
+			_ => false,
+		}
+	}
+}
+
+impl fmt::Display for Type {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Basic(name) => f.write_str(name),
+			Self::String(_) | Self::Iri(_) => f.write_str("string"),
+			Self::Bytes => f.write_str("[]byte"),
+			Self::Named(name, _) => f.write_str(name),
+			Self::Slice(item) => write!(f, "[]{item}"),
+			Self::Array(len, item) => write!(f, "[{len}]{item}"),
+			Self::Pointer(item) => write!(f, "*{item}"),
+		}
+	}
+}
+
+/// Generated struct field.
+struct Field {
+	name: String,
+	key: String,
+	ty: Type,
+	required: bool,
+	comment: Option<String>,
+}
+
+struct Generator<'a, V, I: ReverseIriInterpretation, L> {
+	rdf: RdfContext<'a, V, I>,
+	layouts: &'a L,
+	options: &'a Options<I::Resource>,
+	names: HashMap<Ref<LayoutType, I::Resource>, String>,
+	taken: HashSet<String>,
+	queue: VecDeque<(Ref<LayoutType, I::Resource>, String)>,
+	patterns: Vec<String>,
+	imports: BTreeSet<&'static str>,
+}
+
+impl<'a, V, I, L> Generator<'a, V, I, L>
+where
+	V: IriVocabulary + LiteralVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri> + ReverseLiteralInterpretation<Literal = V::Literal>,
+	I::Resource: Clone + Ord + Hash,
+	L: LayoutRegistry<I::Resource>,
+{
+	fn get(
+		&self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Result<&'a Layout<I::Resource>, Error<I::Resource>> {
+		self.layouts
+			.get(layout_ref)
+			.ok_or_else(|| Error::LayoutNotFound(layout_ref.id().clone()))
+	}
+
+	/// Schedules the declaration of the given layout under the given name,
+	/// unless it is already declared, and returns its name.
+	fn declare(&mut self, layout_ref: &Ref<LayoutType, I::Resource>, ident: String) -> String {
+		match self.names.get(layout_ref) {
+			Some(name) => name.clone(),
+			None => {
+				let name = self.fresh_name(ident);
+				self.names.insert(layout_ref.clone(), name.clone());
+				self.queue.push_back((layout_ref.clone(), name.clone()));
+				name
+			}
+		}
+	}
+
+	/// Reserves a type name, based on the given identifier.
+	fn fresh_name(&mut self, ident: String) -> String {
+		let mut name = ident.clone();
+		let mut i = 1;
+		while self.taken.contains(&name) {
+			i += 1;
+			name = format!("{ident}{i}");
+		}
+
+		self.taken.insert(name.clone());
+		name
+	}
+
+	fn preset(&self, layout_ref: &Ref<LayoutType, I::Resource>) -> Option<PresetLayout> {
+		self.rdf
+			.interpretation
+			.iris_of(layout_ref.id())
+			.find_map(|i| PresetLayout::from_iri(self.rdf.vocabulary.iri(i).unwrap()))
+	}
+
+	fn iri(&self, resource: &I::Resource) -> Option<String> {
+		self.rdf
+			.interpretation
+			.iris_of(resource)
+			.next()
+			.map(|i| self.rdf.vocabulary.iri(i).unwrap().to_string())
+	}
+
+	fn is_integer_datatype(&self, datatype: &I::Resource) -> bool {
+		self.rdf.interpretation.iris_of(datatype).any(|i| {
+			xsd_types::IntegerDatatype::from_iri(self.rdf.vocabulary.iri(i).unwrap()).is_some()
+		})
+	}
+
+	/// Returns the `rdfs:comment` found in the given extra properties, if any.
+	fn comment(&self, properties: &BTreeMap<I::Resource, I::Resource>) -> Option<String> {
+		properties.iter().find_map(|(prop, value)| {
+			let is_comment = self
+				.rdf
+				.interpretation
+				.iris_of(prop)
+				.any(|i| self.rdf.vocabulary.iri(i).unwrap() == RDFS_COMMENT);
+
+			if is_comment {
+				self.rdf
+					.interpretation
+					.literals_of(value)
+					.find_map(|l| self.rdf.vocabulary.literal(l))
+					.map(|l| l.value.to_owned())
+			} else {
+				None
+			}
+		})
+	}
+
+	/// Generates the declaration of the given layout, with its methods.
+	fn declaration(
+		&mut self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+		ident: &str,
+	) -> Result<String, Error<I::Resource>> {
+		let layout = self.get(layout_ref)?;
+
+		let mut result = match self.iri(layout_ref.id()) {
+			Some(iri) => format!("// {ident} is generated from the `{iri}` layout.\n"),
+			None => format!("// {ident} is generated from an anonymous layout.\n"),
+		};
+
+		if let Some(comment) = self.comment(layout.extra_properties()) {
+			result.push_str("//\n");
+			result.push_str(&comment_lines(&comment, ""));
+		}
+
+		match layout {
+			Layout::Product(layout) => {
+				let mut fields = Vec::new();
+				let mut field_names = HashSet::new();
+				for (key, field) in &layout.fields {
+					let key = match key {
+						Value::Literal(Literal::TextString(key)) if is_tag_name(key) => key,
+						other => return Err(Error::InvalidFieldName(other.clone())),
+					};
+
+					let mut name = field_name(key);
+					let base_len = name.len();
+					let mut i = 1;
+					while !field_names.insert(name.clone()) {
+						i += 1;
+						name.truncate(base_len);
+						write!(name, "{i}").unwrap();
+					}
+
+					let hint = format!("{ident}{}", field_name(key));
+					let mut ty = self.type_ref(&field.value.layout, &hint)?;
+					if !field.required && !ty.is_nilable() {
+						ty = Type::Pointer(Box::new(ty))
+					}
+
+					fields.push(Field {
+						name,
+						key: key.clone(),
+						ty,
+						required: field.required,
+						comment: self.comment(&field.extra_properties),
+					})
+				}
+
+				self.struct_declaration(&mut result, ident, &fields);
+			}
+			Layout::Sum(layout) if !layout.variants.is_empty() => {
+				let mut variants = Vec::new();
+				let mut variant_names = HashSet::new();
+				for v in &layout.variants {
+					let hint = format!("{ident}{}", field_name(&v.name));
+					let ty = self.type_ref(&v.value.layout, &format!("{hint}Value"))?;
+
+					let mut name = hint;
+					let base_len = name.len();
+					let mut i = 1;
+					while self.taken.contains(&name) || !variant_names.insert(name.clone()) {
+						i += 1;
+						name.truncate(base_len);
+						write!(name, "{i}").unwrap();
+					}
+					self.taken.insert(name.clone());
+
+					variants.push((v.name.as_str(), name, ty));
+				}
+
+				self.sum_declaration(&mut result, ident, &variants);
+			}
+			other => {
+				let ty = self.type_expr(other, ident)?;
+				writeln!(result, "type {ident} {ty}").unwrap();
+
+				if ty.contains_sum() {
+					let decode = self.decode(&mut 0, "(*v)", "data", &ty);
+					write!(
+						result,
+						"\n// UnmarshalJSON decodes the value, resolving the sum type variants.\n\
+						func (v *{ident}) UnmarshalJSON(data []byte) error {{\n\
+						{}\
+						\treturn nil\n\
+						}}\n",
+						indent(&decode, 1)
+					)
+					.unwrap();
+				}
+
+				let validate = self.validate(&mut 0, "v", &ty, None);
+				write_validate(&mut result, ident, validate.as_deref());
+			}
+		}
+
+		Ok(result)
+	}
+
+	/// Writes the declaration of a struct type and its methods.
+	fn struct_declaration(&mut self, result: &mut String, ident: &str, fields: &[Field]) {
+		if fields.is_empty() {
+			writeln!(result, "type {ident} struct{{}}").unwrap();
+		} else {
+			writeln!(result, "type {ident} struct {{").unwrap();
+			let mut rows = Vec::new();
+			for (i, field) in fields.iter().enumerate() {
+				if let Some(comment) = &field.comment {
+					if i > 0 {
+						rows.push(Row::Blank);
+					}
+					rows.push(Row::Comment(comment.clone()));
+				}
+
+				let tag = if field.required {
+					format!("`json:\"{}\"`", field.key)
+				} else {
+					format!("`json:\"{},omitempty\"`", field.key)
+				};
+
+				rows.push(Row::Cells([field.name.clone(), field.ty.to_string(), tag]));
+			}
+			result.push_str(&aligned(&rows));
+			result.push_str("}\n");
+		}
+
+		// Decoding.
+		let raw_rows: Vec<_> = fields
+			.iter()
+			.map(|f| {
+				Row::Cells([
+					f.name.clone(),
+					"json.RawMessage".to_owned(),
+					format!("`json:\"{}\"`", f.key),
+				])
+			})
+			.collect();
+
+		let mut decode = String::new();
+		if raw_rows.is_empty() {
+			decode.push_str("var raw struct{}\n");
+		} else {
+			decode.push_str("var raw struct {\n");
+			decode.push_str(&aligned(&raw_rows));
+			decode.push_str("}\n");
+		}
+		decode.push_str("if err := tldrDecodeStrict(data, &raw); err != nil {\n\treturn err\n}\n");
+
+		let mut counter = 0;
+		for field in fields {
+			let raw = format!("raw.{}", field.name);
+			let target = format!("v.{}", field.name);
+			let stmt = self.decode(&mut counter, &target, &raw, &field.ty);
+			if field.required {
+				self.imports.insert("fmt");
+				write!(
+					decode,
+					"if {raw} == nil {{\n\
+					\treturn fmt.Errorf(\"missing required field %q\", {})\n\
+					}}\n\
+					{stmt}",
+					string_literal(&field.key)
+				)
+				.unwrap();
+			} else {
+				write!(decode, "if {raw} != nil {{\n{}}}\n", indent(&stmt, 1)).unwrap();
+			}
+		}
+
+		write!(
+			result,
+			"\n// UnmarshalJSON decodes the value, checking that all the required fields\n\
+			// are present and that no unknown field is present.\n\
+			func (v *{ident}) UnmarshalJSON(data []byte) error {{\n\
+			{}\
+			\treturn nil\n\
+			}}\n",
+			indent(&decode, 1)
+		)
+		.unwrap();
+
+		// Validation.
+		let mut validate = String::new();
+		let mut counter = 0;
+		for field in fields {
+			let expr = format!("v.{}", field.name);
+			let key = string_literal(&field.key);
+
+			// `nil` is a valid unit value.
+			if field.required && field.ty.is_nilable() && field.ty != Type::Basic("any") {
+				self.imports.insert("fmt");
+				write!(
+					validate,
+					"if {expr} == nil {{\n\
+					\treturn fmt.Errorf(\"missing required field %q\", {key})\n\
+					}}\n"
+				)
+				.unwrap();
+			}
+
+			// A required sum value is known not to be `nil` past this point.
+			let ty = match &field.ty {
+				Type::Named(name, Kind::Sum) if field.required => {
+					Type::Named(name.clone(), Kind::Other)
+				}
+				ty => ty.clone(),
+			};
+
+			if let Some(stmt) = self.validate(&mut counter, &expr, &ty, Some(&key)) {
+				validate.push_str(&stmt);
+			}
+		}
+
+		write_validate(
+			result,
+			ident,
+			(!validate.is_empty()).then_some(validate.as_str()),
+		);
+	}
+
+	/// Writes the declaration of a sum type interface, its variants and
+	/// decoding function.
+	fn sum_declaration(
+		&mut self,
+		result: &mut String,
+		ident: &str,
+		variants: &[(&str, String, Type)],
+	) {
+		writeln!(
+			result,
+			"type {ident} interface {{\n\
+			\tis{ident}()\n\
+			\tValidate() error\n\
+			}}"
+		)
+		.unwrap();
+
+		for (variant_name, name, ty) in variants {
+			write!(
+				result,
+				"\n// {name} is the `{variant_name}` variant of {ident}.\n\
+				type {name} struct {{\n\
+				\tValue {ty}\n\
+				}}\n\
+				\n\
+				func ({name}) is{ident}() {{}}\n\
+				\n\
+				// MarshalJSON encodes the variant value.\n\
+				func (v {name}) MarshalJSON() ([]byte, error) {{\n\
+				\treturn json.Marshal(v.Value)\n\
+				}}\n"
+			)
+			.unwrap();
+
+			let decode = self.decode(&mut 0, "v.Value", "data", ty);
+			write!(
+				result,
+				"\n// UnmarshalJSON decodes the variant value.\n\
+				func (v *{name}) UnmarshalJSON(data []byte) error {{\n\
+				{}\
+				\treturn nil\n\
+				}}\n",
+				indent(&decode, 1)
+			)
+			.unwrap();
+
+			let validate = self.validate(&mut 0, "v.Value", ty, None);
+			write_validate(result, name, validate.as_deref());
+		}
+
+		self.imports.insert("errors");
+		write!(
+			result,
+			"\n// Unmarshal{ident} decodes a {ident} value, selecting the only variant\n\
+			// matching the input.\n\
+			func Unmarshal{ident}(data []byte) ({ident}, error) {{\n\
+			\tvar candidates []{ident}\n"
+		)
+		.unwrap();
+
+		for (i, (_, name, _)) in variants.iter().enumerate() {
+			write!(
+				result,
+				"\n\tvar v{i} {name}\n\
+				\tif json.Unmarshal(data, &v{i}) == nil && v{i}.Validate() == nil {{\n\
+				\t\tcandidates = append(candidates, v{i})\n\
+				\t}}\n"
+			)
+			.unwrap();
+		}
+
+		write!(
+			result,
+			"\n\tswitch len(candidates) {{\n\
+			\tcase 0:\n\
+			\t\treturn nil, errors.New(\"no {ident} variant matches the input\")\n\
+			\tcase 1:\n\
+			\t\treturn candidates[0], nil\n\
+			\tdefault:\n\
+			\t\treturn nil, errors.New(\"more than one {ident} variant matches the input\")\n\
+			\t}}\n\
+			}}\n"
+		)
+		.unwrap();
+	}
+
+	/// Returns the type referencing the given layout.
+	fn type_ref(
+		&mut self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+		hint: &str,
+	) -> Result<Type, Error<I::Resource>> {
+		match self.preset(layout_ref) {
+			None => (),
+			Some(PresetLayout::Unit) => return Ok(Type::Basic("any")),
+			Some(PresetLayout::Boolean) => return Ok(Type::Basic("bool")),
+			Some(PresetLayout::U8) => return Ok(Type::Basic("uint8")),
+			Some(PresetLayout::U16) => return Ok(Type::Basic("uint16")),
+			Some(PresetLayout::U32) => return Ok(Type::Basic("uint32")),
+			Some(PresetLayout::U64) => return Ok(Type::Basic("uint64")),
+			Some(PresetLayout::I8) => return Ok(Type::Basic("int8")),
+			Some(PresetLayout::I16) => return Ok(Type::Basic("int16")),
+			Some(PresetLayout::I32) => return Ok(Type::Basic("int32")),
+			Some(PresetLayout::I64) => return Ok(Type::Basic("int64")),
+			Some(PresetLayout::Id) => return Ok(Type::Iri(None)),
+			Some(PresetLayout::String) => return Ok(Type::String(None)),
+		}
+
+		let layout = self.get(layout_ref)?;
+		let kind = match layout {
+			Layout::Sum(layout) if !layout.variants.is_empty() => Kind::Sum,
+			_ => Kind::Other,
+		};
+
+		let name = match self.options.layout_ident(self.rdf, layout_ref) {
+			Some(ident) => self.declare(layout_ref, ident),
+			None => match layout {
+				Layout::Product(_) => self.declare(layout_ref, hint.to_owned()),
+				_ if kind == Kind::Sum => self.declare(layout_ref, hint.to_owned()),
+				_ => return self.type_expr(layout, hint),
+			},
+		};
+
+		Ok(Type::Named(name, kind))
+	}
+
+	/// Returns the inline type of the given layout.
+	///
+	/// Records and non-empty sums must be declared, and are not handled by
+	/// this function.
+	fn type_expr(
+		&mut self,
+		layout: &Layout<I::Resource>,
+		hint: &str,
+	) -> Result<Type, Error<I::Resource>> {
+		match layout {
+			Layout::Never | Layout::Always | Layout::Product(_) | Layout::Sum(_) => {
+				Ok(Type::Basic("any"))
+			}
+			Layout::Literal(LiteralLayout::Data(layout)) => match layout {
+				DataLayout::Unit(_) => Ok(Type::Basic("any")),
+				DataLayout::Boolean(_) => Ok(Type::Basic("bool")),
+				DataLayout::Number(layout) => {
+					if self.is_integer_datatype(&layout.datatype) {
+						Ok(Type::Basic("int64"))
+					} else {
+						Ok(Type::Basic("float64"))
+					}
+				}
+				DataLayout::ByteString(_) => Ok(Type::Bytes),
+				DataLayout::TextString(layout) => {
+					Ok(Type::String(self.pattern(layout.pattern.as_ref())))
+				}
+			},
+			Layout::Literal(LiteralLayout::Id(layout)) => {
+				Ok(Type::Iri(self.pattern(layout.pattern.as_ref())))
+			}
+			Layout::List(ListLayout::Unordered(layout)) => {
+				let item = self.type_ref(&layout.item.value.layout, &format!("{hint}Item"))?;
+				Ok(Type::Slice(Box::new(item)))
+			}
+			Layout::List(ListLayout::Ordered(layout)) => {
+				let item = self.type_ref(&layout.node.value.layout, &format!("{hint}Item"))?;
+				Ok(Type::Slice(Box::new(item)))
+			}
+			Layout::List(ListLayout::Sized(layout)) => {
+				let items = layout
+					.items
+					.iter()
+					.enumerate()
+					.map(|(i, item)| self.type_ref(&item.value.layout, &format!("{hint}Item{i}")))
+					.collect::<Result<Vec<_>, _>>()?;
+
+				match items.split_first() {
+					Some((first, rest)) if rest.iter().all(|item| item == first) => {
+						Ok(Type::Array(items.len(), Box::new(first.clone())))
+					}
+					Some(_) => Ok(Type::Slice(Box::new(Type::Basic("any")))),
+					None => Ok(Type::Array(0, Box::new(Type::Basic("any")))),
+				}
+			}
+		}
+	}
+
+	/// Declares the given pattern automaton and returns its index.
+	fn pattern(&mut self, automaton: Option<&DetAutomaton<usize>>) -> Option<usize> {
+		automaton.map(|automaton| {
+			let i = self.patterns.len();
+			self.patterns.push(automaton_literal(automaton));
+			i
+		})
+	}
+
+	/// Returns the statements decoding the JSON value `raw` into `target`.
+	///
+	/// Types containing sum type interfaces are decoded element by element,
+	/// other types are decoded by `json.Unmarshal`.
+	fn decode(&mut self, counter: &mut usize, target: &str, raw: &str, ty: &Type) -> String {
+		if !ty.contains_sum() {
+			return format!(
+				"if err := json.Unmarshal({raw}, &{target}); err != nil {{\n\treturn err\n}}\n"
+			);
+		}
+
+		let n = *counter;
+		*counter += 1;
+
+		match ty {
+			Type::Named(name, _) => {
+				format!(
+					"value{n}, err := Unmarshal{name}({raw})\n\
+					if err != nil {{\n\
+					\treturn err\n\
+					}}\n\
+					{target} = value{n}\n"
+				)
+			}
+			Type::Slice(item) => {
+				let item_decode = self.decode(
+					counter,
+					&format!("{target}[i{n}]"),
+					&format!("item{n}"),
+					item,
+				);
+				format!(
+					"var items{n} []json.RawMessage\n\
+					if err := json.Unmarshal({raw}, &items{n}); err != nil {{\n\
+					\treturn err\n\
+					}}\n\
+					{target} = make({ty}, len(items{n}))\n\
+					for i{n}, item{n} := range items{n} {{\n\
+					{}\
+					}}\n",
+					indent(&item_decode, 1)
+				)
+			}
+			Type::Array(len, item) => {
+				self.imports.insert("fmt");
+				let item_decode = self.decode(
+					counter,
+					&format!("{target}[i{n}]"),
+					&format!("item{n}"),
+					item,
+				);
+				format!(
+					"var items{n} []json.RawMessage\n\
+					if err := json.Unmarshal({raw}, &items{n}); err != nil {{\n\
+					\treturn err\n\
+					}}\n\
+					if len(items{n}) != {len} {{\n\
+					\treturn fmt.Errorf(\"expected {len} items, found %d\", len(items{n}))\n\
+					}}\n\
+					for i{n}, item{n} := range items{n} {{\n\
+					{}\
+					}}\n",
+					indent(&item_decode, 1)
+				)
+			}
+			Type::Pointer(item) => {
+				let item_decode = self.decode(counter, &format!("value{n}"), raw, item);
+				format!("var value{n} {item}\n{item_decode}{target} = &value{n}\n")
+			}
+			_ => unreachable!(),
+		}
+	}
+
+	/// Returns the statements validating the value of `expr`, if any
+	/// validation is required.
+	///
+	/// If `field` is given, it is the Go string literal of the field name
+	/// used to give context to the returned errors.
+	fn validate(
+		&mut self,
+		counter: &mut usize,
+		expr: &str,
+		ty: &Type,
+		field: Option<&str>,
+	) -> Option<String> {
+		let wrap = || match field {
+			Some(field) => format!("fmt.Errorf(\"field %q: %w\", {field}, err)"),
+			None => "err".to_owned(),
+		};
+
+		match ty {
+			Type::String(Some(i)) => {
+				self.imports.insert("fmt");
+				let err = match field {
+					Some(field) => {
+						format!("fmt.Errorf(\"field %q: invalid value %q\", {field}, {expr})")
+					}
+					None => format!("fmt.Errorf(\"invalid value %q\", {expr})"),
+				};
+
+				Some(format!(
+					"if !tldrPattern{i}.matches(string({expr})) {{\n\treturn {err}\n}}\n"
+				))
+			}
+			Type::Iri(pattern) => {
+				self.imports.insert("fmt");
+				let err = match field {
+					Some(field) => {
+						format!("fmt.Errorf(\"field %q: invalid IRI %q\", {field}, {expr})")
+					}
+					None => format!("fmt.Errorf(\"invalid IRI %q\", {expr})"),
+				};
+
+				let mut check = format!("if !tldrIsIri(string({expr})) {{\n\treturn {err}\n}}\n");
+				if let Some(i) = pattern {
+					check.push_str(&self.validate(counter, expr, &Type::String(Some(*i)), field)?)
+				}
+
+				Some(check)
+			}
+			Type::Named(_, kind) => {
+				if field.is_some() {
+					self.imports.insert("fmt");
+				}
+
+				let check = format!(
+					"if err := {expr}.Validate(); err != nil {{\n\treturn {}\n}}\n",
+					wrap()
+				);
+
+				match kind {
+					Kind::Sum => Some(format!("if {expr} != nil {{\n{}}}\n", indent(&check, 1))),
+					Kind::Other => Some(check),
+				}
+			}
+			Type::Slice(item) | Type::Array(_, item) => {
+				let n = *counter;
+				*counter += 1;
+				let check = self.validate(counter, &format!("item{n}"), item, field)?;
+				Some(format!(
+					"for _, item{n} := range {expr} {{\n{}}}\n",
+					indent(&check, 1)
+				))
+			}
+			Type::Pointer(item) => {
+				// Methods of the pointed type can be called on the pointer.
+				let item_expr = match item.as_ref() {
+					Type::Named(..) => expr.to_owned(),
+					_ => format!("*{expr}"),
+				};
+				let check = self.validate(counter, &item_expr, item, field)?;
+				Some(format!("if {expr} != nil {{\n{}}}\n", indent(&check, 1)))
+			}
+			_ => None,
+		}
+	}
+}
+
+/// Writes the `Validate` method of the given type.
+fn write_validate(result: &mut String, ident: &str, body: Option<&str>) {
+	write!(
+		result,
+		"\n// Validate checks that the value conforms to its layout.\n\
+		func (v {ident}) Validate() error {{\n\
+		{}\
+		\treturn nil\n\
+		}}\n",
+		body.map(|body| indent(body, 1)).unwrap_or_default()
+	)
+	.unwrap()
+}
+
+/// Line of a struct body.
+enum Row {
+	Blank,
+	Comment(String),
+	Cells([String; 3]),
+}
+
+/// Formats the given struct body rows, aligning the cells of consecutive
+/// lines like `gofmt` does.
+fn aligned(rows: &[Row]) -> String {
+	let mut result = String::new();
+
+	for block in rows.split(|row| matches!(row, Row::Blank)) {
+		if !result.is_empty() {
+			result.push('\n');
+		}
+
+		let width = |i: usize| {
+			block
+				.iter()
+				.filter_map(|row| match row {
+					Row::Cells(cells) => Some(cells[i].chars().count()),
+					_ => None,
+				})
+				.max()
+				.unwrap_or_default()
+		};
+
+		let (name_width, type_width) = (width(0), width(1));
+
+		for row in block {
+			match row {
+				Row::Comment(comment) => result.push_str(&comment_lines(comment, "\t")),
+				Row::Cells([name, ty, tag]) => {
+					writeln!(result, "\t{name:name_width$} {ty:type_width$} {tag}").unwrap()
+				}
+				Row::Blank => (),
+			}
+		}
+	}
+
+	result
+}
+
+/// Formats the given text as a line comment with the given indentation.
+fn comment_lines(text: &str, indent: &str) -> String {
+	let mut result = String::new();
+	for line in text.lines() {
+		if line.is_empty() {
+			writeln!(result, "{indent}//").unwrap();
+		} else {
+			writeln!(result, "{indent}// {line}").unwrap();
+		}
+	}
+	result
+}
+
+/// Indents every non-empty line of the given code by `n` tabs.
+fn indent(code: &str, n: usize) -> String {
+	let mut result = String::new();
+	for line in code.lines() {
+		if !line.is_empty() {
+			for _ in 0..n {
+				result.push('\t')
+			}
+			result.push_str(line);
+		}
+		result.push('\n');
+	}
+	result
+}
+
+/// Returns the exported Go field name for the given field name.
+fn field_name(name: &str) -> String {
+	let mut result = String::new();
+
+	for part in name.split(|c: char| !c.is_ascii_alphanumeric()) {
+		if INITIALISMS.contains(&part.to_ascii_lowercase().as_str()) {
+			result.push_str(&part.to_ascii_uppercase())
+		} else {
+			let mut chars = part.chars();
+			if let Some(c) = chars.next() {
+				result.push(c.to_ascii_uppercase());
+				result.extend(chars);
+			}
+		}
+	}
+
+	if !result.starts_with(|c: char| c.is_ascii_alphabetic()) {
+		result.insert(0, 'F')
+	}
+
+	result
+}
+
+/// Checks that the given field name can be used in a `json` struct tag.
+///
+/// This follows the rules of the `encoding/json` package, that silently
+/// ignores invalid names.
+fn is_tag_name(name: &str) -> bool {
+	!name.is_empty()
+		&& name
+			.chars()
+			.all(|c| "!#$%&()*+-./:;<=>?@[]^_{|}~ ".contains(c) || c.is_alphanumeric())
+}
+
+fn string_literal(s: &str) -> String {
+	let mut result = String::with_capacity(s.len() + 2);
+	result.push('"');
+
+	for c in s.chars() {
+		match c {
+			'"' => result.push_str("\\\""),
+			'\\' => result.push_str("\\\\"),
+			'\n' => result.push_str("\\n"),
+			'\r' => result.push_str("\\r"),
+			'\t' => result.push_str("\\t"),
+			c if c.is_control() => write!(result, "\\u{:04x}", c as u32).unwrap(),
+			c => result.push(c),
+		}
+	}
+
+	result.push('"');
+	result
+}
+
+fn automaton_literal(automaton: &DetAutomaton<usize>) -> String {
+	let final_states = automaton
+		.final_states()
+		.iter()
+		.map(|q| format!("{q}: true"))
+		.collect::<Vec<_>>();
+
+	let transitions = automaton
+		.transitions()
+		.iter()
+		.map(|(q, map)| {
+			let targets = map
+				.iter()
+				.filter_map(|(range, target)| {
+					let min = range.first()?;
+					let max = range.last()?;
+					Some(format!("{{{}, {}, {target}}}", min as u32, max as u32))
+				})
+				.collect::<Vec<_>>();
+
+			format!("{q}: {{{}}}", targets.join(", "))
+		})
+		.collect::<Vec<_>>();
+
+	format!(
+		"&tldrAutomaton{{initial: {}, final: map[int]bool{{{}}}, transitions: map[int][]tldrRange{{{}}}}}",
+		automaton.initial_state(),
+		final_states.join(", "),
+		transitions.join(", ")
+	)
+}
@@ -0,0 +1,53 @@
+// tldrDecodeStrict decodes the given JSON value, rejecting unknown fields.
+func tldrDecodeStrict(data []byte, v any) error {
+	decoder := json.NewDecoder(bytes.NewReader(data))
+	decoder.DisallowUnknownFields()
+	return decoder.Decode(v)
+}
+
+type tldrRange struct {
+	min, max rune
+	target   int
+}
+
+// tldrAutomaton is a deterministic automaton recognizing a text pattern.
+type tldrAutomaton struct {
+	initial     int
+	final       map[int]bool
+	transitions map[int][]tldrRange
+}
+
+// matches checks that the given string is recognized by the automaton.
+func (a *tldrAutomaton) matches(s string) bool {
+	state := a.initial
+	for _, c := range s {
+		next := -1
+		for _, r := range a.transitions[state] {
+			if r.min <= c && c <= r.max {
+				next = r.target
+				break
+			}
+		}
+		if next < 0 {
+			return false
+		}
+		state = next
+	}
+	return a.final[state]
+}
+
+// tldrIsIri checks that the given string is an absolute IRI, starting with a
+// scheme.
+func tldrIsIri(s string) bool {
+	for i, c := range s {
+		switch {
+		case 'A' <= c && c <= 'Z', 'a' <= c && c <= 'z':
+		case i > 0 && ('0' <= c && c <= '9' || c == '+' || c == '.' || c == '-'):
+		case i > 0 && c == ':':
+			return true
+		default:
+			return false
+		}
+	}
+	return false
+}
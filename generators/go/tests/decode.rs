@@ -0,0 +1,47 @@
+//! Runs the Go tests of `tests/decode` against the generated code, when a Go
+//! toolchain is available.
+use std::{fs, path::PathBuf, process::Command};
+
+fn file_path(path: &str) -> PathBuf {
+	format!("{}/tests/{path}", env!("CARGO_MANIFEST_DIR")).into()
+}
+
+fn go_test(id: &str) {
+	if Command::new("go").arg("version").output().is_err() {
+		eprintln!("skipping Go tests: `go` not found");
+		return;
+	}
+
+	let dir = std::env::temp_dir().join(format!("treeldr-gen-go-{id}-{}", std::process::id()));
+	fs::create_dir_all(&dir).unwrap();
+	fs::write(dir.join("go.mod"), "module model\n\ngo 1.18\n").unwrap();
+	fs::copy(
+		file_path(&format!("generate/{id}-out.go")),
+		dir.join("model.go"),
+	)
+	.unwrap();
+	fs::copy(
+		file_path(&format!("decode/{id}_test.go")),
+		dir.join("model_test.go"),
+	)
+	.unwrap();
+
+	let output = Command::new("go")
+		.arg("test")
+		.current_dir(&dir)
+		.output()
+		.unwrap();
+	fs::remove_dir_all(&dir).unwrap();
+
+	assert!(
+		output.status.success(),
+		"{}{}",
+		String::from_utf8_lossy(&output.stdout),
+		String::from_utf8_lossy(&output.stderr)
+	)
+}
+
+#[test]
+fn t01() {
+	go_test("t01")
+}
@@ -0,0 +1,37 @@
+package model
+
+import "testing"
+
+func TestUnmarshalContactEmail(t *testing.T) {
+	contact, err := UnmarshalContact([]byte(`"bob@example.org"`))
+	if err != nil {
+		t.Fatal(err)
+	}
+	if _, ok := contact.(ContactEmail); !ok {
+		t.Fatalf("expected the email variant, found %T", contact)
+	}
+}
+
+func TestUnmarshalContactIri(t *testing.T) {
+	// Both variants accept an IRI, as in TreeLDR.
+	if _, err := UnmarshalContact([]byte(`"https://example.org/#bob"`)); err == nil {
+		t.Fatal("expected an ambiguity error")
+	}
+
+	var ref ContactRef
+	if err := ref.UnmarshalJSON([]byte(`"https://example.org/#bob"`)); err != nil {
+		t.Fatal(err)
+	}
+	if err := ref.Validate(); err != nil {
+		t.Fatal(err)
+	}
+}
+
+func TestRefValidate(t *testing.T) {
+	if err := Ref("bob@example.org").Validate(); err == nil {
+		t.Fatal("expected an invalid IRI error")
+	}
+	if err := Ref("https://example.org/#bob").Validate(); err != nil {
+		t.Fatal(err)
+	}
+}
@@ -0,0 +1,310 @@
+// Code generated by TreeLDR. DO NOT EDIT.
+
+package model
+
+import (
+	"bytes"
+	"encoding/json"
+	"errors"
+	"fmt"
+)
+
+// Person is generated from the `https://example.org/#Person` layout.
+//
+// A person.
+type Person struct {
+	Address     *PersonAddress `json:"address,omitempty"`
+	Age         *uint8         `json:"age,omitempty"`
+	Contact     Contact        `json:"contact"`
+	FirstFriend *Person        `json:"first-friend,omitempty"`
+
+	// Full name of the person.
+	Name             string    `json:"name"`
+	Nickname         *string   `json:"nickname,omitempty"`
+	PreviousContacts []Contact `json:"previous-contacts,omitempty"`
+	Tags             []string  `json:"tags,omitempty"`
+}
+
+// UnmarshalJSON decodes the value, checking that all the required fields
+// are present and that no unknown field is present.
+func (v *Person) UnmarshalJSON(data []byte) error {
+	var raw struct {
+		Address          json.RawMessage `json:"address"`
+		Age              json.RawMessage `json:"age"`
+		Contact          json.RawMessage `json:"contact"`
+		FirstFriend      json.RawMessage `json:"first-friend"`
+		Name             json.RawMessage `json:"name"`
+		Nickname         json.RawMessage `json:"nickname"`
+		PreviousContacts json.RawMessage `json:"previous-contacts"`
+		Tags             json.RawMessage `json:"tags"`
+	}
+	if err := tldrDecodeStrict(data, &raw); err != nil {
+		return err
+	}
+	if raw.Address != nil {
+		if err := json.Unmarshal(raw.Address, &v.Address); err != nil {
+			return err
+		}
+	}
+	if raw.Age != nil {
+		if err := json.Unmarshal(raw.Age, &v.Age); err != nil {
+			return err
+		}
+	}
+	if raw.Contact == nil {
+		return fmt.Errorf("missing required field %q", "contact")
+	}
+	value0, err := UnmarshalContact(raw.Contact)
+	if err != nil {
+		return err
+	}
+	v.Contact = value0
+	if raw.FirstFriend != nil {
+		if err := json.Unmarshal(raw.FirstFriend, &v.FirstFriend); err != nil {
+			return err
+		}
+	}
+	if raw.Name == nil {
+		return fmt.Errorf("missing required field %q", "name")
+	}
+	if err := json.Unmarshal(raw.Name, &v.Name); err != nil {
+		return err
+	}
+	if raw.Nickname != nil {
+		if err := json.Unmarshal(raw.Nickname, &v.Nickname); err != nil {
+			return err
+		}
+	}
+	if raw.PreviousContacts != nil {
+		var items1 []json.RawMessage
+		if err := json.Unmarshal(raw.PreviousContacts, &items1); err != nil {
+			return err
+		}
+		v.PreviousContacts = make([]Contact, len(items1))
+		for i1, item1 := range items1 {
+			value2, err := UnmarshalContact(item1)
+			if err != nil {
+				return err
+			}
+			v.PreviousContacts[i1] = value2
+		}
+	}
+	if raw.Tags != nil {
+		if err := json.Unmarshal(raw.Tags, &v.Tags); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// Validate checks that the value conforms to its layout.
+func (v Person) Validate() error {
+	if v.Address != nil {
+		if err := v.Address.Validate(); err != nil {
+			return fmt.Errorf("field %q: %w", "address", err)
+		}
+	}
+	if v.Contact == nil {
+		return fmt.Errorf("missing required field %q", "contact")
+	}
+	if err := v.Contact.Validate(); err != nil {
+		return fmt.Errorf("field %q: %w", "contact", err)
+	}
+	if v.FirstFriend != nil {
+		if err := v.FirstFriend.Validate(); err != nil {
+			return fmt.Errorf("field %q: %w", "first-friend", err)
+		}
+	}
+	if v.Nickname != nil {
+		if !tldrPattern0.matches(string(*v.Nickname)) {
+			return fmt.Errorf("field %q: invalid value %q", "nickname", *v.Nickname)
+		}
+	}
+	for _, item0 := range v.PreviousContacts {
+		if item0 != nil {
+			if err := item0.Validate(); err != nil {
+				return fmt.Errorf("field %q: %w", "previous-contacts", err)
+			}
+		}
+	}
+	return nil
+}
+
+// PersonAddress is generated from an anonymous layout.
+type PersonAddress struct {
+	City string `json:"city"`
+}
+
+// UnmarshalJSON decodes the value, checking that all the required fields
+// are present and that no unknown field is present.
+func (v *PersonAddress) UnmarshalJSON(data []byte) error {
+	var raw struct {
+		City json.RawMessage `json:"city"`
+	}
+	if err := tldrDecodeStrict(data, &raw); err != nil {
+		return err
+	}
+	if raw.City == nil {
+		return fmt.Errorf("missing required field %q", "city")
+	}
+	if err := json.Unmarshal(raw.City, &v.City); err != nil {
+		return err
+	}
+	return nil
+}
+
+// Validate checks that the value conforms to its layout.
+func (v PersonAddress) Validate() error {
+	return nil
+}
+
+// Contact is generated from the `https://example.org/#Contact` layout.
+type Contact interface {
+	isContact()
+	Validate() error
+}
+
+// ContactEmail is the `email` variant of Contact.
+type ContactEmail struct {
+	Value string
+}
+
+func (ContactEmail) isContact() {}
+
+// MarshalJSON encodes the variant value.
+func (v ContactEmail) MarshalJSON() ([]byte, error) {
+	return json.Marshal(v.Value)
+}
+
+// UnmarshalJSON decodes the variant value.
+func (v *ContactEmail) UnmarshalJSON(data []byte) error {
+	if err := json.Unmarshal(data, &v.Value); err != nil {
+		return err
+	}
+	return nil
+}
+
+// Validate checks that the value conforms to its layout.
+func (v ContactEmail) Validate() error {
+	return nil
+}
+
+// ContactRef is the `ref` variant of Contact.
+type ContactRef struct {
+	Value Ref
+}
+
+func (ContactRef) isContact() {}
+
+// MarshalJSON encodes the variant value.
+func (v ContactRef) MarshalJSON() ([]byte, error) {
+	return json.Marshal(v.Value)
+}
+
+// UnmarshalJSON decodes the variant value.
+func (v *ContactRef) UnmarshalJSON(data []byte) error {
+	if err := json.Unmarshal(data, &v.Value); err != nil {
+		return err
+	}
+	return nil
+}
+
+// Validate checks that the value conforms to its layout.
+func (v ContactRef) Validate() error {
+	if err := v.Value.Validate(); err != nil {
+		return err
+	}
+	return nil
+}
+
+// UnmarshalContact decodes a Contact value, selecting the only variant
+// matching the input.
+func UnmarshalContact(data []byte) (Contact, error) {
+	var candidates []Contact
+
+	var v0 ContactEmail
+	if json.Unmarshal(data, &v0) == nil && v0.Validate() == nil {
+		candidates = append(candidates, v0)
+	}
+
+	var v1 ContactRef
+	if json.Unmarshal(data, &v1) == nil && v1.Validate() == nil {
+		candidates = append(candidates, v1)
+	}
+
+	switch len(candidates) {
+	case 0:
+		return nil, errors.New("no Contact variant matches the input")
+	case 1:
+		return candidates[0], nil
+	default:
+		return nil, errors.New("more than one Contact variant matches the input")
+	}
+}
+
+// Ref is generated from the `https://example.org/#Ref` layout.
+type Ref string
+
+// Validate checks that the value conforms to its layout.
+func (v Ref) Validate() error {
+	if !tldrIsIri(string(v)) {
+		return fmt.Errorf("invalid IRI %q", v)
+	}
+	return nil
+}
+
+var tldrPattern0 = &tldrAutomaton{initial: 0, final: map[int]bool{1: true, 2: true}, transitions: map[int][]tldrRange{0: {{97, 122, 1}}, 1: {{48, 57, 2}, {97, 122, 2}}, 2: {{48, 57, 2}, {97, 122, 2}}}}
+
+// tldrDecodeStrict decodes the given JSON value, rejecting unknown fields.
+func tldrDecodeStrict(data []byte, v any) error {
+	decoder := json.NewDecoder(bytes.NewReader(data))
+	decoder.DisallowUnknownFields()
+	return decoder.Decode(v)
+}
+
+type tldrRange struct {
+	min, max rune
+	target   int
+}
+
+// tldrAutomaton is a deterministic automaton recognizing a text pattern.
+type tldrAutomaton struct {
+	initial     int
+	final       map[int]bool
+	transitions map[int][]tldrRange
+}
+
+// matches checks that the given string is recognized by the automaton.
+func (a *tldrAutomaton) matches(s string) bool {
+	state := a.initial
+	for _, c := range s {
+		next := -1
+		for _, r := range a.transitions[state] {
+			if r.min <= c && c <= r.max {
+				next = r.target
+				break
+			}
+		}
+		if next < 0 {
+			return false
+		}
+		state = next
+	}
+	return a.final[state]
+}
+
+// tldrIsIri checks that the given string is an absolute IRI, starting with a
+// scheme.
+func tldrIsIri(s string) bool {
+	for i, c := range s {
+		switch {
+		case 'A' <= c && c <= 'Z', 'a' <= c && c <= 'z':
+		case i > 0 && ('0' <= c && c <= '9' || c == '+' || c == '.' || c == '-'):
+		case i > 0 && c == ':':
+			return true
+		default:
+			return false
+		}
+	}
+	return false
+}
@@ -0,0 +1,1028 @@
+//! GraphQL schema generation from TreeLDR layouts.
+//!
+//! Generates the GraphQL type definitions (SDL) describing the tree values
+//! matched by a layout, optionally bundled in a JavaScript module along with
+//! resolvers fetching those values through hydration.
+use std::{
+	collections::{BTreeMap, HashMap, HashSet, VecDeque},
+	fmt::Write,
+	hash::Hash,
+};
+
+use rdf_types::{
+	interpretation::{ReverseIriInterpretation, ReverseLiteralInterpretation},
+	vocabulary::{IriVocabulary, LiteralVocabulary},
+	Term, RDFS_COMMENT,
+};
+use treeldr_gen_rust::utils::{type_name_from_iri, PascalCase};
+use treeldr_layouts::{
+	distill::RdfContext,
+	layout::{DataLayout, LayoutType, ListLayout, LiteralLayout},
+	Layout, LayoutRegistry, Literal, PresetLayout, Ref, Value,
+};
+use xsd_types::{
+	IntegerDatatype, LongDatatype, NonNegativeIntegerDatatype, UnsignedIntDatatype,
+	UnsignedLongDatatype,
+};
+
+/// Names of the built-in scalars, and other reserved type names.
+const RESERVED: &[&str] = &[
+	"BigInt", "Boolean", "Float", "ID", "Int", "JSON", "Query", "String",
+];
+
+/// Indentation used in the generated code.
+const INDENT: &str = "  ";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error<R = Term> {
+	#[error("missing type identifier for layout {0}")]
+	MissingTypeIdentifier(R),
+
+	#[error("missing IRI for layout {0}")]
+	MissingLayoutIri(R),
+
+	#[error("layout {0} not found")]
+	LayoutNotFound(R),
+
+	#[error("invalid field name `{0}`")]
+	InvalidFieldName(Value),
+
+	#[error("members `{1}` and `{2}` of union `{0}` cannot be told apart by resolvers")]
+	IndistinguishableMembers(String, String, String),
+}
+
+pub struct Options<R = Term> {
+	idents: HashMap<Ref<LayoutType, R>, String>,
+	resolvers: bool,
+}
+
+impl<R> Options<R> {
+	pub fn new() -> Self {
+		Self {
+			idents: HashMap::new(),
+			resolvers: false,
+		}
+	}
+
+	/// Generates a JavaScript module exporting the type definitions along
+	/// with the resolvers scaffolding, instead of the bare type definitions.
+	pub fn with_resolvers(self, resolvers: bool) -> Self {
+		Self { resolvers, ..self }
+	}
+
+	/// Sets the GraphQL name of the given layout.
+	pub fn set_ident(&mut self, layout_ref: Ref<LayoutType, R>, ident: String)
+	where
+		R: Eq + Hash,
+	{
+		self.idents.insert(layout_ref, ident);
+	}
+
+	pub fn layout_ident<V, I>(
+		&self,
+		rdf: RdfContext<V, I>,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Option<String>
+	where
+		V: IriVocabulary,
+		I: ReverseIriInterpretation<Resource = R, Iri = V::Iri>,
+		R: Eq + Hash,
+	{
+		self.idents
+			.get(layout_ref)
+			.cloned()
+			.or_else(|| default_layout_ident(rdf, layout_ref))
+	}
+}
+
+impl<R> Default for Options<R> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Checks that the given string is a valid GraphQL name.
+///
+/// Names starting with `__` are reserved by the GraphQL introspection system
+/// and are not considered valid.
+pub fn is_name(s: &str) -> bool {
+	let mut chars = s.chars();
+	match chars.next() {
+		Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+			chars.all(|c| c.is_ascii_alphanumeric() || c == '_') && !s.starts_with("__")
+		}
+		_ => false,
+	}
+}
+
+pub fn default_layout_ident<V, I>(
+	rdf: RdfContext<V, I>,
+	layout_ref: &Ref<LayoutType, I::Resource>,
+) -> Option<String>
+where
+	V: IriVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri>,
+{
+	let mut selected: Option<String> = None;
+
+	for i in rdf.interpretation.iris_of(layout_ref.id()) {
+		let iri = rdf.vocabulary.iri(i).unwrap();
+		if let Some(name) = type_name_from_iri(iri).filter(|name| is_name(name)) {
+			if !selected.as_ref().is_some_and(|s| *s < name) {
+				selected = Some(name)
+			}
+		}
+	}
+
+	selected
+}
+
+/// Generates the GraphQL schema of the given layout and all its
+/// dependencies.
+///
+/// Records are mapped to object types and sums to unions. Since union
+/// members must be object types, non-record variants are wrapped in an
+/// object type with a single `value` field. The `Query` type provides the
+/// given layout by subject identifier.
+///
+/// If the `resolvers` option is set, the output is a JavaScript module
+/// exporting the type definitions as `typeDefs`, and a `createResolvers`
+/// function building the resolvers from a `hydrate` function.
+pub fn generate<V, I>(
+	rdf: RdfContext<V, I>,
+	layouts: impl LayoutRegistry<I::Resource>,
+	layout_ref: &Ref<LayoutType, I::Resource>,
+	options: &Options<I::Resource>,
+) -> Result<String, Error<I::Resource>>
+where
+	V: IriVocabulary + LiteralVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri> + ReverseLiteralInterpretation<Literal = V::Literal>,
+	I::Resource: Clone + Ord + Hash,
+{
+	let ident = options
+		.layout_ident(rdf, layout_ref)
+		.ok_or_else(|| Error::MissingTypeIdentifier(layout_ref.id().clone()))?;
+
+	let mut generator = Generator {
+		rdf,
+		layouts: &layouts,
+		options,
+		names: HashMap::new(),
+		taken: RESERVED.iter().map(|s| s.to_string()).collect(),
+		queue: VecDeque::new(),
+		declarations: Vec::new(),
+		json_scalar: false,
+		big_int_scalar: false,
+	};
+
+	let root = generator.type_ref(layout_ref, &ident)?;
+
+	while let Some((layout_ref, ident)) = generator.queue.pop_front() {
+		generator.declaration(&layout_ref, &ident, None)?;
+	}
+
+	let query_field = field_name(&ident);
+	let mut sdl = String::new();
+	writeln!(
+		sdl,
+		"type Query {{\n{INDENT}{query_field}(id: ID!): {root}\n}}"
+	)
+	.unwrap();
+
+	if generator.json_scalar {
+		sdl.push_str("\n\"\"\"\nAny JSON value.\n\"\"\"\nscalar JSON\n");
+	}
+
+	if generator.big_int_scalar {
+		sdl.push_str(
+			"\n\"\"\"\nInteger that may not fit in the 32-bit `Int` scalar.\n\"\"\"\nscalar BigInt\n",
+		);
+	}
+
+	for declaration in &generator.declarations {
+		sdl.push('\n');
+		sdl.push_str(&declaration.sdl());
+	}
+
+	if options.resolvers {
+		for declaration in &generator.declarations {
+			if let Declaration::Union { name, members, .. } = declaration {
+				check_members(name, members)?
+			}
+		}
+
+		let layout_iri = generator
+			.iri(layout_ref.id())
+			.ok_or_else(|| Error::MissingLayoutIri(layout_ref.id().clone()))?;
+
+		Ok(generator.resolvers(&sdl, &query_field, &layout_iri, &root))
+	} else {
+		Ok(format!("# Generated by TreeLDR. Do not edit.\n\n{sdl}"))
+	}
+}
+
+/// GraphQL type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Type {
+	/// Scalar type (built-in, `JSON` or `BigInt`).
+	Scalar(&'static str),
+
+	/// Object type.
+	Object(String),
+
+	/// Union type.
+	Union(String),
+
+	/// List type, with non-null items.
+	List(Box<Self>),
+}
+
+impl std::fmt::Display for Type {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			Self::Scalar(name) => f.write_str(name),
+			Self::Object(name) | Self::Union(name) => f.write_str(name),
+			Self::List(item) => write!(f, "[{item}!]"),
+		}
+	}
+}
+
+/// Object type field.
+struct Field {
+	/// GraphQL field name.
+	name: String,
+
+	/// Key of the field in the tree value.
+	key: String,
+
+	ty: Type,
+	required: bool,
+	description: Option<String>,
+}
+
+/// Union member.
+struct Member {
+	/// Object type.
+	object: String,
+
+	/// Type of the variant value, if it is wrapped in the object type.
+	wrapped: Option<Type>,
+
+	/// Keys of the variant value, if it is a record.
+	keys: Option<RecordKeys>,
+}
+
+impl Member {
+	/// Returns the JavaScript expression checking that `value` is a value of
+	/// this member.
+	fn check(&self) -> String {
+		match (&self.wrapped, &self.keys) {
+			(Some(ty), _) => check_expr("value", ty),
+			(None, Some(RecordKeys { required, allowed })) => format!(
+				"hasKeys(value, [{}], [{}])",
+				required
+					.iter()
+					.map(|k| string_literal(k))
+					.collect::<Vec<_>>()
+					.join(", "),
+				allowed
+					.iter()
+					.map(|k| string_literal(k))
+					.collect::<Vec<_>>()
+					.join(", ")
+			),
+			(None, None) => "true".to_owned(),
+		}
+	}
+}
+
+/// Keys of a record value.
+struct RecordKeys {
+	required: Vec<String>,
+	allowed: Vec<String>,
+}
+
+enum Declaration {
+	Object {
+		name: String,
+		description: Option<String>,
+		fields: Vec<Field>,
+	},
+	Union {
+		name: String,
+		description: Option<String>,
+		members: Vec<Member>,
+	},
+}
+
+impl Declaration {
+	fn sdl(&self) -> String {
+		let mut result = String::new();
+		match self {
+			Self::Object {
+				name,
+				description,
+				fields,
+			} => {
+				if let Some(description) = description {
+					result.push_str(&block_string(description, ""))
+				}
+
+				writeln!(result, "type {name} {{").unwrap();
+				if fields.is_empty() {
+					// Object types must define at least one field.
+					result.push_str(&block_string("Placeholder for this empty record.", INDENT));
+					writeln!(result, "{INDENT}_empty: Boolean").unwrap();
+				}
+
+				for field in fields {
+					if let Some(description) = &field.description {
+						result.push_str(&block_string(description, INDENT))
+					}
+
+					let non_null = if field.required { "!" } else { "" };
+					writeln!(result, "{INDENT}{}: {}{non_null}", field.name, field.ty).unwrap();
+				}
+				result.push_str("}\n");
+			}
+			Self::Union {
+				name,
+				description,
+				members,
+			} => {
+				if let Some(description) = description {
+					result.push_str(&block_string(description, ""))
+				}
+
+				let mut names = HashSet::new();
+				let members = members
+					.iter()
+					.map(|m| m.object.as_str())
+					.filter(|name| names.insert(*name))
+					.collect::<Vec<_>>();
+				writeln!(result, "union {name} = {}", members.join(" | ")).unwrap();
+			}
+		}
+
+		result
+	}
+}
+
+struct Generator<'a, V, I: ReverseIriInterpretation, L> {
+	rdf: RdfContext<'a, V, I>,
+	layouts: &'a L,
+	options: &'a Options<I::Resource>,
+	names: HashMap<Ref<LayoutType, I::Resource>, String>,
+	taken: HashSet<String>,
+	queue: VecDeque<(Ref<LayoutType, I::Resource>, String)>,
+	declarations: Vec<Declaration>,
+	json_scalar: bool,
+	big_int_scalar: bool,
+}
+
+impl<'a, V, I, L> Generator<'a, V, I, L>
+where
+	V: IriVocabulary + LiteralVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri> + ReverseLiteralInterpretation<Literal = V::Literal>,
+	I::Resource: Clone + Ord + Hash,
+	L: LayoutRegistry<I::Resource>,
+{
+	fn get(
+		&self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Result<&'a Layout<I::Resource>, Error<I::Resource>> {
+		self.layouts
+			.get(layout_ref)
+			.ok_or_else(|| Error::LayoutNotFound(layout_ref.id().clone()))
+	}
+
+	/// Schedules the declaration of the given layout under the given name,
+	/// unless it is already declared, and returns its name.
+	fn declare(&mut self, layout_ref: &Ref<LayoutType, I::Resource>, ident: String) -> String {
+		match self.names.get(layout_ref) {
+			Some(name) => name.clone(),
+			None => {
+				let name = self.fresh_name(ident);
+				self.names.insert(layout_ref.clone(), name.clone());
+				self.queue.push_back((layout_ref.clone(), name.clone()));
+				name
+			}
+		}
+	}
+
+	/// Reserves a type name, based on the given identifier.
+	fn fresh_name(&mut self, ident: String) -> String {
+		let mut name = ident.clone();
+		let mut i = 1;
+		while self.taken.contains(&name) {
+			i += 1;
+			name = format!("{ident}{i}");
+		}
+
+		self.taken.insert(name.clone());
+		name
+	}
+
+	fn preset(&self, layout_ref: &Ref<LayoutType, I::Resource>) -> Option<PresetLayout> {
+		self.rdf
+			.interpretation
+			.iris_of(layout_ref.id())
+			.find_map(|i| PresetLayout::from_iri(self.rdf.vocabulary.iri(i).unwrap()))
+	}
+
+	fn iri(&self, resource: &I::Resource) -> Option<String> {
+		self.rdf
+			.interpretation
+			.iris_of(resource)
+			.next()
+			.map(|i| self.rdf.vocabulary.iri(i).unwrap().to_string())
+	}
+
+	fn integer_datatype(&self, datatype: &I::Resource) -> Option<IntegerDatatype> {
+		self.rdf
+			.interpretation
+			.iris_of(datatype)
+			.find_map(|i| IntegerDatatype::from_iri(self.rdf.vocabulary.iri(i).unwrap()))
+	}
+
+	/// Returns the `rdfs:comment` found in the given extra properties, if any.
+	fn comment(&self, properties: &BTreeMap<I::Resource, I::Resource>) -> Option<String> {
+		properties.iter().find_map(|(prop, value)| {
+			let is_comment = self
+				.rdf
+				.interpretation
+				.iris_of(prop)
+				.any(|i| self.rdf.vocabulary.iri(i).unwrap() == RDFS_COMMENT);
+
+			if is_comment {
+				self.rdf
+					.interpretation
+					.literals_of(value)
+					.find_map(|l| self.rdf.vocabulary.literal(l))
+					.map(|l| l.value.to_owned())
+			} else {
+				None
+			}
+		})
+	}
+
+	/// Generates the declaration of the given record or sum layout.
+	///
+	/// The `default_description` is used when the layout has neither a
+	/// comment nor an IRI.
+	fn declaration(
+		&mut self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+		ident: &str,
+		default_description: Option<String>,
+	) -> Result<(), Error<I::Resource>> {
+		let layout = self.get(layout_ref)?;
+
+		let mut description = Vec::new();
+		if let Some(comment) = self.comment(layout.extra_properties()) {
+			description.push(comment)
+		}
+		if let Some(iri) = self.iri(layout_ref.id()) {
+			description.push(format!("Layout `{iri}`."))
+		}
+		let description = if description.is_empty() {
+			default_description
+		} else {
+			Some(description.join("\n\n"))
+		};
+
+		match layout {
+			Layout::Product(layout) => {
+				let mut fields = Vec::new();
+				let mut field_names = HashSet::new();
+				for (key, field) in &layout.fields {
+					let key = match key {
+						Value::Literal(Literal::TextString(key)) => key,
+						other => return Err(Error::InvalidFieldName(other.clone())),
+					};
+
+					let mut name = field_name(key);
+					let base_len = name.len();
+					let mut i = 1;
+					while !field_names.insert(name.clone()) {
+						i += 1;
+						name.truncate(base_len);
+						write!(name, "{i}").unwrap();
+					}
+
+					let hint = format!("{ident}{}", PascalCase(key));
+					let ty = self.type_ref(&field.value.layout, &hint)?;
+
+					fields.push(Field {
+						name,
+						key: key.clone(),
+						ty,
+						required: field.required,
+						description: self.comment(&field.extra_properties),
+					})
+				}
+
+				self.declarations.push(Declaration::Object {
+					name: ident.to_owned(),
+					description,
+					fields,
+				})
+			}
+			Layout::Sum(layout) => {
+				let union_index = self.declarations.len();
+				self.declarations.push(Declaration::Union {
+					name: ident.to_owned(),
+					description,
+					members: Vec::new(),
+				});
+
+				let mut members = Vec::new();
+				for variant in &layout.variants {
+					let hint = format!("{ident}{}", PascalCase(&variant.name));
+					let variant_description = format!("Variant `{}` of `{ident}`.", variant.name);
+					let queued = self.queue.len();
+					let member = match self.type_ref(&variant.value.layout, &hint)? {
+						Type::Object(object) => {
+							// Declare newly referenced records right away, so
+							// that members follow their union in variant order.
+							if self.queue.len() > queued {
+								let (layout_ref, name) = self.queue.pop_back().unwrap();
+								self.declaration(&layout_ref, &name, Some(variant_description))?;
+							}
+
+							let keys = self.record_keys(&variant.value.layout)?;
+							Member {
+								object,
+								wrapped: None,
+								keys,
+							}
+						}
+						ty => {
+							let object = self.fresh_name(hint);
+							self.declarations.push(Declaration::Object {
+								name: object.clone(),
+								description: Some(variant_description),
+								fields: vec![Field {
+									name: "value".to_owned(),
+									key: "value".to_owned(),
+									ty: ty.clone(),
+									required: true,
+									description: None,
+								}],
+							});
+
+							Member {
+								object,
+								wrapped: Some(ty),
+								keys: None,
+							}
+						}
+					};
+
+					members.push(member)
+				}
+
+				if let Declaration::Union { members: m, .. } = &mut self.declarations[union_index] {
+					*m = members
+				}
+			}
+			_ => unreachable!(),
+		}
+
+		Ok(())
+	}
+
+	/// Returns the keys of the given record layout.
+	fn record_keys(
+		&self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Result<Option<RecordKeys>, Error<I::Resource>> {
+		match self.get(layout_ref)? {
+			Layout::Product(layout) => {
+				let mut required = Vec::new();
+				let mut allowed = Vec::new();
+				for (key, field) in &layout.fields {
+					if let Value::Literal(Literal::TextString(key)) = key {
+						if field.required {
+							required.push(key.clone())
+						}
+						allowed.push(key.clone())
+					}
+				}
+
+				Ok(Some(RecordKeys { required, allowed }))
+			}
+			_ => Ok(None),
+		}
+	}
+
+	/// Returns the type referencing the given layout.
+	fn type_ref(
+		&mut self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+		hint: &str,
+	) -> Result<Type, Error<I::Resource>> {
+		match self.preset(layout_ref) {
+			None => (),
+			Some(PresetLayout::Unit) => return Ok(self.json()),
+			Some(PresetLayout::Boolean) => return Ok(Type::Scalar("Boolean")),
+			Some(PresetLayout::Id) => return Ok(Type::Scalar("ID")),
+			Some(PresetLayout::String) => return Ok(Type::Scalar("String")),
+			Some(PresetLayout::U32 | PresetLayout::U64 | PresetLayout::I64) => {
+				return Ok(self.big_int())
+			}
+			Some(_) => return Ok(Type::Scalar("Int")),
+		}
+
+		let layout = self.get(layout_ref)?;
+		let ident = self
+			.options
+			.layout_ident(self.rdf, layout_ref)
+			.unwrap_or_else(|| hint.to_owned());
+
+		match layout {
+			Layout::Product(_) => Ok(Type::Object(self.declare(layout_ref, ident))),
+			Layout::Sum(layout) if !layout.variants.is_empty() => {
+				Ok(Type::Union(self.declare(layout_ref, ident)))
+			}
+			layout => self.type_expr(layout, hint),
+		}
+	}
+
+	/// Returns the type of the given layout, that is neither a record nor
+	/// a non-empty sum.
+	fn type_expr(
+		&mut self,
+		layout: &Layout<I::Resource>,
+		hint: &str,
+	) -> Result<Type, Error<I::Resource>> {
+		match layout {
+			Layout::Literal(LiteralLayout::Data(layout)) => match layout {
+				DataLayout::Unit(_) => Ok(self.json()),
+				DataLayout::Boolean(_) => Ok(Type::Scalar("Boolean")),
+				DataLayout::Number(layout) => match self.integer_datatype(&layout.datatype) {
+					Some(datatype) if fits_in_int(datatype) => Ok(Type::Scalar("Int")),
+					Some(_) => Ok(self.big_int()),
+					None => Ok(Type::Scalar("Float")),
+				},
+				DataLayout::ByteString(_) | DataLayout::TextString(_) => Ok(Type::Scalar("String")),
+			},
+			Layout::Literal(LiteralLayout::Id(_)) => Ok(Type::Scalar("ID")),
+			Layout::List(ListLayout::Unordered(layout)) => {
+				let item = self.type_ref(&layout.item.value.layout, &format!("{hint}Item"))?;
+				Ok(Type::List(Box::new(item)))
+			}
+			Layout::List(ListLayout::Ordered(layout)) => {
+				let item = self.type_ref(&layout.node.value.layout, &format!("{hint}Item"))?;
+				Ok(Type::List(Box::new(item)))
+			}
+			Layout::List(ListLayout::Sized(layout)) => {
+				let items = layout
+					.items
+					.iter()
+					.enumerate()
+					.map(|(i, item)| self.type_ref(&item.value.layout, &format!("{hint}Item{i}")))
+					.collect::<Result<Vec<_>, _>>()?;
+
+				match items.split_first() {
+					Some((first, rest)) if rest.iter().all(|item| item == first) => {
+						Ok(Type::List(Box::new(first.clone())))
+					}
+					_ => Ok(Type::List(Box::new(self.json()))),
+				}
+			}
+			Layout::Never | Layout::Always | Layout::Product(_) | Layout::Sum(_) => Ok(self.json()),
+		}
+	}
+
+	/// Returns the `JSON` scalar type, used for values that have no GraphQL
+	/// equivalent.
+	fn json(&mut self) -> Type {
+		self.json_scalar = true;
+		Type::Scalar("JSON")
+	}
+
+	/// Returns the `BigInt` scalar type, used for integers that may not fit in
+	/// the signed 32-bit `Int` scalar.
+	fn big_int(&mut self) -> Type {
+		self.big_int_scalar = true;
+		Type::Scalar("BigInt")
+	}
+
+	/// Generates the JavaScript module exporting the type definitions and
+	/// resolvers.
+	fn resolvers(&self, sdl: &str, query_field: &str, layout_iri: &str, root: &Type) -> String {
+		let mut result = String::new();
+		result.push_str("// Generated by TreeLDR. Do not edit.\n\n");
+		writeln!(
+			result,
+			"/** GraphQL type definitions. */\nexport const typeDefs = `\n{}`;\n",
+			template_literal_content(sdl)
+		)
+		.unwrap();
+
+		result.push_str(
+			"/**\n \
+			* Creates the resolvers of the schema.\n \
+			*\n \
+			* The `hydrate` function must return (a promise of) the tree value\n \
+			* representing `subject` with the given `layout`, for instance by\n \
+			* running `tldr hydrate` on the content of the RDF store.\n \
+			*\n \
+			* @param {(layout: string, subject: string) => unknown} hydrate\n \
+			*/\n\
+			export function createResolvers(hydrate) {\n\
+			\treturn {\n\
+			\t\tQuery: {\n",
+		);
+
+		let hydrate = format!("hydrate({}, id)", string_literal(layout_iri));
+		match wrap_expr("value", root, 0) {
+			Some(wrap) => writeln!(
+				result,
+				"\t\t\t{query_field}: async (_, {{ id }}) => {{\n\
+				\t\t\t\tconst value = await {hydrate};\n\
+				\t\t\t\treturn {wrap};\n\
+				\t\t\t}},"
+			)
+			.unwrap(),
+			None => writeln!(result, "\t\t\t{query_field}: (_, {{ id }}) => {hydrate},").unwrap(),
+		}
+		result.push_str("\t\t},\n");
+
+		let mut wrappers = String::new();
+		for declaration in &self.declarations {
+			match declaration {
+				Declaration::Object { name, fields, .. } => {
+					let resolvers = fields
+						.iter()
+						.filter_map(|field| {
+							let value = property_access("parent", &field.key);
+							let wrapped = wrap_expr(&value, &field.ty, 0);
+							if wrapped.is_none() && field.name == field.key {
+								return None;
+							}
+
+							Some(format!(
+								"\t\t\t{}: (parent) => {},\n",
+								field.name,
+								wrapped.unwrap_or(value)
+							))
+						})
+						.collect::<String>();
+
+					if !resolvers.is_empty() {
+						write!(result, "\t\t{name}: {{\n{resolvers}\t\t}},\n").unwrap();
+					}
+				}
+				Declaration::Union { name, members, .. } => {
+					writeln!(
+						result,
+						"\t\t{name}: {{\n\
+						\t\t\t__resolveType: (value) => value.__typename,\n\
+						\t\t}},"
+					)
+					.unwrap();
+
+					wrappers.push('\n');
+					wrappers.push_str(&union_wrapper(name, members));
+				}
+			}
+		}
+
+		result.push_str("\t};\n}\n");
+
+		if self.declarations.iter().any(
+			|d| matches!(d, Declaration::Union { members, .. } if members.iter().any(|m| m.keys.is_some())),
+		) {
+			result.push_str(
+				"\n\
+				/**\n \
+				* Checks that the given value is an object with all the `required` keys,\n \
+				* and only `allowed` keys.\n \
+				*/\n\
+				function hasKeys(value, required, allowed) {\n\
+				\treturn (\n\
+				\t\ttypeof value === \"object\" &&\n\
+				\t\tvalue !== null &&\n\
+				\t\t!Array.isArray(value) &&\n\
+				\t\trequired.every((key) => key in value) &&\n\
+				\t\tObject.keys(value).every((key) => allowed.includes(key))\n\
+				\t);\n\
+				}\n",
+			);
+		}
+
+		result.push_str(&wrappers);
+		result
+	}
+}
+
+/// Checks that the resolvers can tell the members of the given union
+/// apart, meaning that no two members have the same check.
+fn check_members<R>(name: &str, members: &[Member]) -> Result<(), Error<R>> {
+	let mut checks: HashMap<String, &str> = HashMap::new();
+	for member in members {
+		if let Some(other) = checks.insert(member.check(), &member.object) {
+			return Err(Error::IndistinguishableMembers(
+				name.to_owned(),
+				other.to_owned(),
+				member.object.clone(),
+			));
+		}
+	}
+
+	Ok(())
+}
+
+/// Generates the function wrapping a value of the given union into the
+/// object type of the matching variant, with its `__typename`.
+///
+/// Tree values do not say what variant they belong to, so it is selected
+/// from the shape of the value. Like when dehydrating, exactly one variant
+/// must match the value.
+fn union_wrapper(name: &str, members: &[Member]) -> String {
+	let mut result = format!(
+		"/**\n \
+		* Wraps a `{name}` value into the object type of its variant.\n \
+		*/\n\
+		function wrap{name}(value) {{\n\
+		\tif (value === null || value === undefined) {{\n\
+		\t\treturn value;\n\
+		\t}}\n\
+		\tconst variants = [];\n"
+	);
+
+	for member in members {
+		let object = match &member.wrapped {
+			Some(ty) => {
+				let inner = wrap_expr("value", ty, 0).unwrap_or_else(|| "value".to_owned());
+				let field = if inner == "value" {
+					"value".to_owned()
+				} else {
+					format!("value: {inner}")
+				};
+				format!(
+					"{{ __typename: {}, {field} }}",
+					string_literal(&member.object)
+				)
+			}
+			None => format!(
+				"{{ __typename: {}, ...value }}",
+				string_literal(&member.object)
+			),
+		};
+
+		write!(
+			result,
+			"\tif ({}) {{\n\t\tvariants.push(() => ({object}));\n\t}}\n",
+			member.check()
+		)
+		.unwrap();
+	}
+
+	write!(
+		result,
+		"\tif (variants.length === 0) {{\n\
+		\t\tthrow new Error(\"no `{name}` variant matches the value\");\n\
+		\t}}\n\
+		\tif (variants.length > 1) {{\n\
+		\t\tthrow new Error(\"more than one `{name}` variant matches the value\");\n\
+		\t}}\n\
+		\treturn variants[0]();\n\
+		}}\n"
+	)
+	.unwrap();
+	result
+}
+
+/// Returns the JavaScript expression wrapping the union values found in
+/// `expr` with their variant object type, if there is any.
+fn wrap_expr(expr: &str, ty: &Type, depth: usize) -> Option<String> {
+	match ty {
+		Type::Union(name) => Some(format!("wrap{name}({expr})")),
+		Type::List(item) => {
+			let var = format!("item{depth}");
+			let inner = wrap_expr(&var, item, depth + 1)?;
+			Some(format!("{expr}?.map(({var}) => {inner})"))
+		}
+		_ => None,
+	}
+}
+
+/// Returns the JavaScript expression checking that `expr` is a value of the
+/// given type.
+fn check_expr(expr: &str, ty: &Type) -> String {
+	match ty {
+		Type::Scalar("Int") => {
+			format!("Number.isInteger({expr}) && {expr} >= -2147483648 && {expr} <= 2147483647")
+		}
+		Type::Scalar("BigInt") => {
+			format!("(Number.isInteger({expr}) || typeof {expr} === \"bigint\")")
+		}
+		Type::Scalar("Float") => format!("typeof {expr} === \"number\""),
+		Type::Scalar("Boolean") => format!("typeof {expr} === \"boolean\""),
+		Type::Scalar("ID") => {
+			format!("typeof {expr} === \"string\" && /^[A-Za-z][A-Za-z0-9+.-]*:/.test({expr})")
+		}
+		Type::Scalar("String") => format!("typeof {expr} === \"string\""),
+		Type::List(_) => format!("Array.isArray({expr})"),
+		_ => "true".to_owned(),
+	}
+}
+
+/// Checks that the values of the given integer datatype fit in the signed
+/// 32-bit `Int` scalar.
+fn fits_in_int(datatype: IntegerDatatype) -> bool {
+	matches!(
+		datatype,
+		IntegerDatatype::Long(LongDatatype::Int(_))
+			| IntegerDatatype::NonNegativeInteger(NonNegativeIntegerDatatype::UnsignedLong(
+				UnsignedLongDatatype::UnsignedInt(UnsignedIntDatatype::UnsignedShort(_))
+			))
+	)
+}
+
+/// Returns the JavaScript expression accessing the given property.
+fn property_access(expr: &str, key: &str) -> String {
+	let is_identifier = key
+		.chars()
+		.next()
+		.is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+		&& key
+			.chars()
+			.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+
+	if is_identifier {
+		format!("{expr}.{key}")
+	} else {
+		format!("{expr}[{}]", string_literal(key))
+	}
+}
+
+/// Returns the GraphQL field name for the given tree value key, in camel
+/// case.
+fn field_name(key: &str) -> String {
+	let mut result = String::new();
+
+	for part in key.split(|c: char| !c.is_ascii_alphanumeric()) {
+		let mut chars = part.chars();
+		if let Some(c) = chars.next() {
+			if result.is_empty() {
+				result.push(c.to_ascii_lowercase())
+			} else {
+				result.push(c.to_ascii_uppercase())
+			}
+			result.extend(chars)
+		}
+	}
+
+	if !result.starts_with(|c: char| c.is_ascii_alphabetic()) {
+		result.insert(0, '_')
+	}
+
+	result
+}
+
+/// Formats the given text as a GraphQL block string (description) with the
+/// given indentation.
+fn block_string(text: &str, indent: &str) -> String {
+	let text = text.replace("\"\"\"", "\\\"\"\"");
+	let mut result = format!("{indent}\"\"\"\n");
+	for line in text.lines() {
+		if !line.is_empty() {
+			result.push_str(indent);
+			result.push_str(line);
+		}
+		result.push('\n');
+	}
+	writeln!(result, "{indent}\"\"\"").unwrap();
+	result
+}
+
+/// Escapes the given text so it can be put in a JavaScript template literal.
+fn template_literal_content(text: &str) -> String {
+	text.replace('\\', "\\\\")
+		.replace('`', "\\`")
+		.replace("${", "\\${")
+}
+
+fn string_literal(s: &str) -> String {
+	let mut result = String::with_capacity(s.len() + 2);
+	result.push('"');
+
+	for c in s.chars() {
+		match c {
+			'"' => result.push_str("\\\""),
+			'\\' => result.push_str("\\\\"),
+			'\n' => result.push_str("\\n"),
+			'\r' => result.push_str("\\r"),
+			'\t' => result.push_str("\\t"),
+			c if c.is_control() => write!(result, "\\u{:04x}", c as u32).unwrap(),
+			c => result.push(c),
+		}
+	}
+
+	result.push('"');
+	result
+}
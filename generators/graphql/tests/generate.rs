@@ -0,0 +1,79 @@
+use std::{fs, path::PathBuf};
+use treeldr_layouts::{layout::LayoutType, LayoutRegistry, Layouts, Prelude, Ref};
+
+fn file_path(id: &str, suffix: &str) -> PathBuf {
+	format!("{}/tests/generate/{id}{suffix}", env!("CARGO_MANIFEST_DIR")).into()
+}
+
+fn load_layout(id: &str) -> (Layouts, Ref<LayoutType>) {
+	let mut builder = treeldr_layouts::abs::Builder::new();
+
+	let raw_json = fs::read_to_string(file_path(id, "-layout.json")).unwrap();
+	let layout: treeldr_layouts::abs::syntax::Layout = serde_json::from_str(&raw_json).unwrap();
+	let layout_ref = layout.build(&mut builder).unwrap();
+
+	(builder.build(), layout_ref)
+}
+
+fn generate(id: &str, suffix: &str, options: treeldr_gen_graphql::Options) {
+	let (layouts, layout_ref) = load_layout(id);
+	let output = treeldr_gen_graphql::generate(
+		Default::default(),
+		layouts.with(Prelude),
+		&layout_ref,
+		&options,
+	)
+	.unwrap();
+
+	let expected = fs::read_to_string(file_path(id, suffix)).unwrap();
+	if output != expected {
+		eprintln!("output:\n{output}");
+		panic!("output differs from `{id}{suffix}`")
+	}
+}
+
+#[test]
+fn t01() {
+	generate("t01", "-out.graphql", treeldr_gen_graphql::Options::new())
+}
+
+#[test]
+fn t01_resolvers() {
+	generate(
+		"t01",
+		"-resolvers.js",
+		treeldr_gen_graphql::Options::new().with_resolvers(true),
+	)
+}
+
+#[test]
+fn indistinguishable_members() {
+	let layout: treeldr_layouts::abs::syntax::Layout = serde_json::from_value(serde_json::json!({
+		"id": "https://example.org/#Name",
+		"type": "sum",
+		"variants": {
+			"first": { "value": "https://treeldr.org/prelude#string" },
+			"last": { "value": "https://treeldr.org/prelude#string" }
+		}
+	}))
+	.unwrap();
+
+	let generate = |options: &treeldr_gen_graphql::Options| {
+		let mut builder = treeldr_layouts::abs::Builder::new();
+		let layout_ref = layout.build(&mut builder).unwrap();
+		treeldr_gen_graphql::generate(
+			Default::default(),
+			builder.build().with(Prelude),
+			&layout_ref,
+			options,
+		)
+	};
+
+	// Type definitions alone are fine, but resolvers cannot tell the
+	// variants apart.
+	assert!(generate(&treeldr_gen_graphql::Options::new()).is_ok());
+	assert!(matches!(
+		generate(&treeldr_gen_graphql::Options::new().with_resolvers(true)),
+		Err(treeldr_gen_graphql::Error::IndistinguishableMembers(..))
+	))
+}
@@ -0,0 +1,139 @@
+// Generated by TreeLDR. Do not edit.
+
+/** GraphQL type definitions. */
+export const typeDefs = `
+type Query {
+  person(id: ID!): Person
+}
+
+"""
+Integer that may not fit in the 32-bit \`Int\` scalar.
+"""
+scalar BigInt
+
+"""
+A person.
+
+Layout \`https://example.org/#Person\`.
+"""
+type Person {
+  address: PersonAddress
+  age: Int
+  contact: Contact!
+  firstFriend: Person
+  followers: BigInt
+  """
+  Full name of the person.
+  """
+  name: String!
+  nickname: String
+  previousContacts: [Contact!]
+  score: Float
+  tags: [String!]
+}
+
+type PersonAddress {
+  city: String!
+}
+
+"""
+Layout \`https://example.org/#Contact\`.
+"""
+union Contact = ContactCard | ContactEmail | ContactExtension | ContactRef
+
+"""
+Variant \`card\` of \`Contact\`.
+"""
+type ContactCard {
+  phone: String!
+}
+
+"""
+Variant \`email\` of \`Contact\`.
+"""
+type ContactEmail {
+  value: String!
+}
+
+"""
+Variant \`extension\` of \`Contact\`.
+"""
+type ContactExtension {
+  value: Int!
+}
+
+"""
+Variant \`ref\` of \`Contact\`.
+"""
+type ContactRef {
+  value: ID!
+}
+`;
+
+/**
+ * Creates the resolvers of the schema.
+ *
+ * The `hydrate` function must return (a promise of) the tree value
+ * representing `subject` with the given `layout`, for instance by
+ * running `tldr hydrate` on the content of the RDF store.
+ *
+ * @param {(layout: string, subject: string) => unknown} hydrate
+ */
+export function createResolvers(hydrate) {
+	return {
+		Query: {
+			person: (_, { id }) => hydrate("https://example.org/#Person", id),
+		},
+		Person: {
+			contact: (parent) => wrapContact(parent.contact),
+			firstFriend: (parent) => parent["first-friend"],
+			previousContacts: (parent) => parent["previous-contacts"]?.map((item0) => wrapContact(item0)),
+		},
+		Contact: {
+			__resolveType: (value) => value.__typename,
+		},
+	};
+}
+
+/**
+ * Checks that the given value is an object with all the `required` keys,
+ * and only `allowed` keys.
+ */
+function hasKeys(value, required, allowed) {
+	return (
+		typeof value === "object" &&
+		value !== null &&
+		!Array.isArray(value) &&
+		required.every((key) => key in value) &&
+		Object.keys(value).every((key) => allowed.includes(key))
+	);
+}
+
+/**
+ * Wraps a `Contact` value into the object type of its variant.
+ */
+function wrapContact(value) {
+	if (value === null || value === undefined) {
+		return value;
+	}
+	const variants = [];
+	if (hasKeys(value, ["phone"], ["phone"])) {
+		variants.push(() => ({ __typename: "ContactCard", ...value }));
+	}
+	if (typeof value === "string") {
+		variants.push(() => ({ __typename: "ContactEmail", value }));
+	}
+	if (Number.isInteger(value) && value >= -2147483648 && value <= 2147483647) {
+		variants.push(() => ({ __typename: "ContactExtension", value }));
+	}
+	if (typeof value === "string" && /^[A-Za-z][A-Za-z0-9+.-]*:/.test(value)) {
+		variants.push(() => ({ __typename: "ContactRef", value }));
+	}
+	if (variants.length === 0) {
+		throw new Error("no `Contact` variant matches the value");
+	}
+	if (variants.length > 1) {
+		throw new Error("more than one `Contact` variant matches the value");
+	}
+	return variants[0]();
+}
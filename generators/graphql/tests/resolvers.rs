@@ -0,0 +1,44 @@
+//! Runs the JavaScript tests of `tests/resolvers` against the generated
+//! resolvers, when Node.js is available.
+use std::{fs, path::PathBuf, process::Command};
+
+fn file_path(path: &str) -> PathBuf {
+	format!("{}/tests/{path}", env!("CARGO_MANIFEST_DIR")).into()
+}
+
+fn node_test(id: &str) {
+	if Command::new("node").arg("--version").output().is_err() {
+		eprintln!("skipping resolvers tests: `node` not found");
+		return;
+	}
+
+	let dir = std::env::temp_dir().join(format!("treeldr-gen-graphql-{id}-{}", std::process::id()));
+	fs::create_dir_all(&dir).unwrap();
+	fs::copy(
+		file_path(&format!("generate/{id}-resolvers.js")),
+		dir.join(format!("{id}-resolvers.mjs")),
+	)
+	.unwrap();
+	fs::copy(
+		file_path(&format!("resolvers/{id}.mjs")),
+		dir.join(format!("{id}.mjs")),
+	)
+	.unwrap();
+
+	let output = Command::new("node")
+		.arg(dir.join(format!("{id}.mjs")))
+		.output()
+		.unwrap();
+	fs::remove_dir_all(&dir).unwrap();
+
+	assert!(
+		output.status.success(),
+		"{}",
+		String::from_utf8_lossy(&output.stderr)
+	)
+}
+
+#[test]
+fn t01() {
+	node_test("t01")
+}
@@ -0,0 +1,19 @@
+import assert from "node:assert/strict";
+import { createResolvers } from "./t01-resolvers.mjs";
+
+const resolvers = createResolvers(() => null);
+const contact = (value) => resolvers.Person.contact({ contact: value });
+
+assert.deepEqual(contact("bob@example.org"), {
+	__typename: "ContactEmail",
+	value: "bob@example.org",
+});
+assert.deepEqual(contact(42), { __typename: "ContactExtension", value: 42 });
+assert.deepEqual(contact({ phone: "+33" }), {
+	__typename: "ContactCard",
+	phone: "+33",
+});
+
+// An IRI is both a string and an identifier.
+assert.throws(() => contact("https://example.org/#bob"), /more than one `Contact` variant/);
+assert.throws(() => contact(true), /no `Contact` variant/);
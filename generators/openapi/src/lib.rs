@@ -0,0 +1,550 @@
+//! OpenAPI generation from TreeLDR layouts.
+//!
+//! Generates an [OpenAPI 3.1][openapi] document whose `components.schemas`
+//! describe the JSON values produced by hydration. Since OpenAPI 3.1 schemas
+//! are [JSON Schemas (2020-12)][json-schema], the generated schemas can also
+//! be used on their own.
+//!
+//! [openapi]: <https://spec.openapis.org/oas/v3.1.0>
+//! [json-schema]: <https://json-schema.org/draft/2020-12/json-schema-core>
+use std::{
+	collections::{BTreeMap, HashMap, HashSet, VecDeque},
+	hash::Hash,
+};
+
+use json_syntax::{Object, Value as Json};
+use rdf_types::{
+	interpretation::{ReverseIriInterpretation, ReverseLiteralInterpretation},
+	vocabulary::{IriVocabulary, LiteralVocabulary},
+	Term, RDFS_COMMENT,
+};
+use treeldr_gen_rust::utils::type_name_from_iri;
+use treeldr_layouts::{
+	distill::RdfContext,
+	layout::{DataLayout, LayoutType, ListLayout, LiteralLayout, ProductLayout},
+	Layout, LayoutRegistry, Literal, PresetLayout, Ref, Value,
+};
+
+/// OpenAPI version of the generated documents.
+pub const OPENAPI_VERSION: &str = "3.1.0";
+
+/// Vendor extension giving the IRI of the layout described by a schema.
+pub const LAYOUT_EXTENSION: &str = "x-treeldr-layout";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error<R = Term> {
+	#[error("missing type identifier for layout {0}")]
+	MissingTypeIdentifier(R),
+
+	#[error("layout {0} not found")]
+	LayoutNotFound(R),
+
+	#[error("layout {0} cannot be described with JSON")]
+	Unsupported(R),
+
+	#[error("invalid field name `{0}`")]
+	InvalidFieldName(Value),
+}
+
+pub struct Options<R = Term> {
+	idents: HashMap<Ref<LayoutType, R>, String>,
+	title: String,
+	version: String,
+}
+
+impl<R> Options<R> {
+	pub fn new() -> Self {
+		Self {
+			idents: HashMap::new(),
+			title: "TreeLDR layouts".to_owned(),
+			version: "0.0.0".to_owned(),
+		}
+	}
+
+	/// Sets the title of the document (`info.title`).
+	pub fn with_title(self, title: String) -> Self {
+		Self { title, ..self }
+	}
+
+	/// Sets the version of the document (`info.version`).
+	pub fn with_version(self, version: String) -> Self {
+		Self { version, ..self }
+	}
+
+	/// Sets the schema name of the given layout.
+	pub fn set_ident(&mut self, layout_ref: Ref<LayoutType, R>, ident: String)
+	where
+		R: Eq + Hash,
+	{
+		self.idents.insert(layout_ref, ident);
+	}
+
+	pub fn layout_ident<V, I>(
+		&self,
+		rdf: RdfContext<V, I>,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Option<String>
+	where
+		V: IriVocabulary,
+		I: ReverseIriInterpretation<Resource = R, Iri = V::Iri>,
+		R: Eq + Hash,
+	{
+		self.idents
+			.get(layout_ref)
+			.cloned()
+			.or_else(|| default_layout_ident(rdf, layout_ref))
+	}
+}
+
+impl<R> Default for Options<R> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Checks that the given string is a valid component name.
+pub fn is_ident(s: &str) -> bool {
+	!s.is_empty()
+		&& s.chars()
+			.all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+}
+
+pub fn default_layout_ident<V, I>(
+	rdf: RdfContext<V, I>,
+	layout_ref: &Ref<LayoutType, I::Resource>,
+) -> Option<String>
+where
+	V: IriVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri>,
+{
+	let mut selected: Option<String> = None;
+
+	for i in rdf.interpretation.iris_of(layout_ref.id()) {
+		let iri = rdf.vocabulary.iri(i).unwrap();
+		if let Some(name) = type_name_from_iri(iri).filter(|name| is_ident(name)) {
+			if !selected.as_ref().is_some_and(|s| *s < name) {
+				selected = Some(name)
+			}
+		}
+	}
+
+	selected
+}
+
+/// Generates an OpenAPI document describing the given layouts and all their
+/// named dependencies in `components.schemas`.
+///
+/// Schemas are listed in the order of the given layouts, followed by their
+/// dependencies. Anonymous layouts (without identifier) are inlined where
+/// they are used. Text string patterns are not described.
+pub fn generate<'l, V, I>(
+	rdf: RdfContext<V, I>,
+	layouts: impl LayoutRegistry<I::Resource>,
+	layout_refs: impl IntoIterator<Item = &'l Ref<LayoutType, I::Resource>>,
+	options: &Options<I::Resource>,
+) -> Result<Json, Error<I::Resource>>
+where
+	V: IriVocabulary + LiteralVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri> + ReverseLiteralInterpretation<Literal = V::Literal>,
+	I::Resource: 'l + Clone + Ord + Hash,
+{
+	let mut generator = Generator {
+		rdf,
+		layouts: &layouts,
+		options,
+		declared: HashSet::new(),
+		queue: VecDeque::new(),
+	};
+
+	for layout_ref in layout_refs {
+		let ident = options
+			.layout_ident(rdf, layout_ref)
+			.ok_or_else(|| Error::MissingTypeIdentifier(layout_ref.id().clone()))?;
+		generator.schedule(layout_ref, ident);
+	}
+
+	let mut schemas = Object::new();
+	while let Some((layout_ref, ident)) = generator.queue.pop_front() {
+		let schema = generator.component(&layout_ref)?;
+		schemas.insert(ident.into(), schema);
+	}
+
+	let mut info = Object::new();
+	info.insert("title".into(), options.title.as_str().into());
+	info.insert("version".into(), options.version.as_str().into());
+
+	let mut components = Object::new();
+	components.insert("schemas".into(), schemas.into());
+
+	let mut document = Object::new();
+	document.insert("openapi".into(), OPENAPI_VERSION.into());
+	document.insert("info".into(), info.into());
+	document.insert("components".into(), components.into());
+
+	Ok(document.into())
+}
+
+/// Returns the reference to the named schema `ident`.
+fn schema_ref(ident: &str) -> String {
+	format!("#/components/schemas/{ident}")
+}
+
+/// Creates a schema with a single `type` keyword.
+fn typed(ty: &str) -> Object {
+	let mut schema = Object::new();
+	schema.insert("type".into(), ty.into());
+	schema
+}
+
+/// Creates an integer schema with the given bounds.
+fn integer(min: impl Into<Json>, max: Option<Json>) -> Object {
+	let mut schema = typed("integer");
+	schema.insert("minimum".into(), min.into());
+	if let Some(max) = max {
+		schema.insert("maximum".into(), max);
+	}
+	schema
+}
+
+struct Generator<'a, V, I: ReverseIriInterpretation, L> {
+	rdf: RdfContext<'a, V, I>,
+	layouts: &'a L,
+	options: &'a Options<I::Resource>,
+	declared: HashSet<Ref<LayoutType, I::Resource>>,
+	queue: VecDeque<(Ref<LayoutType, I::Resource>, String)>,
+}
+
+impl<'a, V, I, L> Generator<'a, V, I, L>
+where
+	V: IriVocabulary + LiteralVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri> + ReverseLiteralInterpretation<Literal = V::Literal>,
+	I::Resource: Clone + Ord + Hash,
+	L: LayoutRegistry<I::Resource>,
+{
+	fn get(
+		&self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Result<&'a Layout<I::Resource>, Error<I::Resource>> {
+		self.layouts
+			.get(layout_ref)
+			.ok_or_else(|| Error::LayoutNotFound(layout_ref.id().clone()))
+	}
+
+	fn schedule(&mut self, layout_ref: &Ref<LayoutType, I::Resource>, ident: String) {
+		if self.declared.insert(layout_ref.clone()) {
+			self.queue.push_back((layout_ref.clone(), ident))
+		}
+	}
+
+	fn preset(&self, layout_ref: &Ref<LayoutType, I::Resource>) -> Option<PresetLayout> {
+		self.rdf
+			.interpretation
+			.iris_of(layout_ref.id())
+			.find_map(|i| PresetLayout::from_iri(self.rdf.vocabulary.iri(i).unwrap()))
+	}
+
+	fn iri(&self, resource: &I::Resource) -> Option<String> {
+		self.rdf
+			.interpretation
+			.iris_of(resource)
+			.next()
+			.map(|i| self.rdf.vocabulary.iri(i).unwrap().to_string())
+	}
+
+	fn is_integer_datatype(&self, datatype: &I::Resource) -> bool {
+		self.rdf.interpretation.iris_of(datatype).any(|i| {
+			xsd_types::IntegerDatatype::from_iri(self.rdf.vocabulary.iri(i).unwrap()).is_some()
+		})
+	}
+
+	/// Returns the `rdfs:comment` found in the given extra properties, if any.
+	fn comment(&self, properties: &BTreeMap<I::Resource, I::Resource>) -> Option<String> {
+		properties.iter().find_map(|(prop, value)| {
+			let is_comment = self
+				.rdf
+				.interpretation
+				.iris_of(prop)
+				.any(|i| self.rdf.vocabulary.iri(i).unwrap() == RDFS_COMMENT);
+
+			if is_comment {
+				self.rdf
+					.interpretation
+					.literals_of(value)
+					.find_map(|l| self.rdf.vocabulary.literal(l))
+					.map(|l| l.value.to_owned())
+			} else {
+				None
+			}
+		})
+	}
+
+	/// Generates the schema of a named layout.
+	fn component(
+		&mut self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Result<Json, Error<I::Resource>> {
+		let layout = self.get(layout_ref)?;
+		let mut schema = self.layout_schema(layout, layout_ref)?;
+
+		if let Some(comment) = self.comment(layout.extra_properties()) {
+			schema.insert_front("description".into(), comment.into());
+		}
+
+		Ok(schema.into())
+	}
+
+	/// Returns the schema referencing the given layout.
+	///
+	/// Preset layouts are mapped to primitive schemas, named layouts are
+	/// referenced with `$ref` and anonymous layouts are inlined.
+	fn schema_ref(
+		&mut self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Result<Object, Error<I::Resource>> {
+		match self.preset(layout_ref) {
+			None => (),
+			Some(PresetLayout::Unit) => return Ok(typed("null")),
+			Some(PresetLayout::Boolean) => return Ok(typed("boolean")),
+			Some(PresetLayout::U8) => return Ok(integer(0, Some(u8::MAX.into()))),
+			Some(PresetLayout::U16) => return Ok(integer(0, Some(u16::MAX.into()))),
+			Some(PresetLayout::U32) => return Ok(integer(0, Some(u32::MAX.into()))),
+			Some(PresetLayout::U64) => return Ok(integer(0, None)),
+			Some(PresetLayout::I8) => return Ok(integer(i8::MIN, Some(i8::MAX.into()))),
+			Some(PresetLayout::I16) => return Ok(integer(i16::MIN, Some(i16::MAX.into()))),
+			Some(PresetLayout::I32) => {
+				let mut schema = typed("integer");
+				schema.insert("format".into(), "int32".into());
+				return Ok(schema);
+			}
+			Some(PresetLayout::I64) => {
+				let mut schema = typed("integer");
+				schema.insert("format".into(), "int64".into());
+				return Ok(schema);
+			}
+			Some(PresetLayout::Id) => {
+				let mut schema = typed("string");
+				schema.insert("format".into(), "uri".into());
+				return Ok(schema);
+			}
+			Some(PresetLayout::String) => return Ok(typed("string")),
+		}
+
+		match self.options.layout_ident(self.rdf, layout_ref) {
+			Some(ident) => {
+				let mut schema = Object::new();
+				schema.insert("$ref".into(), schema_ref(&ident).into());
+				self.schedule(layout_ref, ident);
+				Ok(schema)
+			}
+			None => {
+				let layout = self.get(layout_ref)?;
+				self.layout_schema(layout, layout_ref)
+			}
+		}
+	}
+
+	/// Returns the inline schema of the given layout, with its
+	/// `x-treeldr-layout` extension if the layout has an IRI.
+	fn layout_schema(
+		&mut self,
+		layout: &Layout<I::Resource>,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Result<Object, Error<I::Resource>> {
+		let mut schema = match layout {
+			Layout::Never => {
+				let mut schema = Object::new();
+				schema.insert("not".into(), Object::new().into());
+				schema
+			}
+			Layout::Always => Object::new(),
+			Layout::Literal(LiteralLayout::Data(layout)) => match layout {
+				DataLayout::Unit(layout) => {
+					let value: Json = layout
+						.const_
+						.clone()
+						.try_into()
+						.map_err(|_| Error::Unsupported(layout_ref.id().clone()))?;
+					let mut schema = Object::new();
+					schema.insert("const".into(), value);
+					schema
+				}
+				DataLayout::Boolean(_) => typed("boolean"),
+				DataLayout::Number(layout) => {
+					if self.is_integer_datatype(&layout.datatype) {
+						typed("integer")
+					} else {
+						typed("number")
+					}
+				}
+				DataLayout::ByteString(_) => {
+					return Err(Error::Unsupported(layout_ref.id().clone()))
+				}
+				DataLayout::TextString(_) => typed("string"),
+			},
+			Layout::Literal(LiteralLayout::Id(_)) => {
+				let mut schema = typed("string");
+				schema.insert("format".into(), "uri".into());
+				schema
+			}
+			Layout::Product(layout) => {
+				let mut properties = Object::new();
+				let mut required = Vec::new();
+				for (key, field) in &layout.fields {
+					let name = key
+						.as_str()
+						.ok_or_else(|| Error::InvalidFieldName(key.clone()))?;
+
+					let mut property = self.schema_ref(&field.value.layout)?;
+					if let Some(comment) = self.comment(&field.extra_properties) {
+						property.insert_front("description".into(), comment.into());
+					}
+
+					properties.insert(name.into(), property.into());
+					if field.required {
+						required.push(Json::from(name))
+					}
+				}
+
+				let mut schema = typed("object");
+				schema.insert("properties".into(), properties.into());
+				if !required.is_empty() {
+					schema.insert("required".into(), Json::Array(required));
+				}
+				schema
+			}
+			Layout::Sum(layout) => {
+				let variants = layout
+					.variants
+					.iter()
+					.map(|v| {
+						let mut schema = self.schema_ref(&v.value.layout)?;
+						if schema.get_unique("$ref").ok().flatten().is_none() {
+							schema.insert_front("title".into(), v.name.as_str().into());
+						}
+						Ok(schema.into())
+					})
+					.collect::<Result<Vec<Json>, _>>()?;
+
+				let mut schema = Object::new();
+				schema.insert("oneOf".into(), Json::Array(variants));
+				if let Some(discriminator) = self.discriminator(layout)? {
+					schema.insert("discriminator".into(), discriminator.into());
+				}
+				schema
+			}
+			Layout::List(ListLayout::Unordered(layout)) => {
+				let mut schema = typed("array");
+				schema.insert(
+					"items".into(),
+					self.schema_ref(&layout.item.value.layout)?.into(),
+				);
+				schema.insert("uniqueItems".into(), true.into());
+				schema
+			}
+			Layout::List(ListLayout::Ordered(layout)) => {
+				let mut schema = typed("array");
+				schema.insert(
+					"items".into(),
+					self.schema_ref(&layout.node.value.layout)?.into(),
+				);
+				schema
+			}
+			Layout::List(ListLayout::Sized(layout)) => {
+				let items = layout
+					.items
+					.iter()
+					.map(|item| self.schema_ref(&item.value.layout).map(Json::from))
+					.collect::<Result<Vec<_>, _>>()?;
+
+				let len = items.len() as u64;
+				let mut schema = typed("array");
+				schema.insert("prefixItems".into(), Json::Array(items));
+				schema.insert("minItems".into(), len.into());
+				schema.insert("maxItems".into(), len.into());
+				schema
+			}
+		};
+
+		if let Some(iri) = self.iri(layout_ref.id()) {
+			schema.insert(LAYOUT_EXTENSION.into(), iri.into());
+		}
+
+		Ok(schema)
+	}
+
+	/// Finds the discriminator of the given sum layout, if any.
+	///
+	/// A discriminator can only be defined if all the variants are named
+	/// records sharing a required field with a distinct constant string
+	/// value in each variant.
+	fn discriminator(
+		&self,
+		layout: &treeldr_layouts::layout::SumLayout<I::Resource>,
+	) -> Result<Option<Object>, Error<I::Resource>> {
+		let mut records = Vec::with_capacity(layout.variants.len());
+		for variant in &layout.variants {
+			let layout_ref = &variant.value.layout;
+			if self.preset(layout_ref).is_some() {
+				return Ok(None);
+			}
+
+			let Some(ident) = self.options.layout_ident(self.rdf, layout_ref) else {
+				return Ok(None);
+			};
+
+			match self.get(layout_ref)? {
+				Layout::Product(record) => records.push((ident, record)),
+				_ => return Ok(None),
+			}
+		}
+
+		let Some((_, first)) = records.first() else {
+			return Ok(None);
+		};
+
+		'candidates: for key in first.fields.keys() {
+			let Some(property) = key.as_str() else {
+				continue;
+			};
+
+			let mut mapping = Object::new();
+			let mut values = HashSet::new();
+			for (ident, record) in &records {
+				match self.constant_field(record, key)? {
+					Some(value) if values.insert(value.clone()) => {
+						mapping.insert(value.into(), schema_ref(ident).into());
+					}
+					_ => continue 'candidates,
+				}
+			}
+
+			let mut discriminator = Object::new();
+			discriminator.insert("propertyName".into(), property.into());
+			discriminator.insert("mapping".into(), mapping.into());
+			return Ok(Some(discriminator));
+		}
+
+		Ok(None)
+	}
+
+	/// Returns the constant string value of the given required field, if
+	/// any.
+	fn constant_field(
+		&self,
+		record: &ProductLayout<I::Resource>,
+		key: &Value,
+	) -> Result<Option<String>, Error<I::Resource>> {
+		match record.fields.get(key) {
+			Some(field) if field.required => match self.get(&field.value.layout)? {
+				Layout::Literal(LiteralLayout::Data(DataLayout::Unit(layout))) => {
+					match &layout.const_ {
+						Value::Literal(Literal::TextString(s)) => Ok(Some(s.clone())),
+						_ => Ok(None),
+					}
+				}
+				_ => Ok(None),
+			},
+			_ => Ok(None),
+		}
+	}
+}
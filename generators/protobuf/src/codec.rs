@@ -0,0 +1,493 @@
+//! Binary protobuf encoding of tree values.
+//!
+//! Values are encoded following the messages of a [`Schema`].
+use std::collections::BTreeMap;
+
+use treeldr_layouts::{value::NativeNumber, value::Number, Literal, Value};
+
+use crate::schema::{Body, Field, Scalar, Schema, Type};
+
+/// Varint wire type.
+const VARINT: u8 = 0;
+
+/// 64-bit wire type.
+const I64: u8 = 1;
+
+/// Length-delimited wire type.
+const LEN: u8 = 2;
+
+/// 32-bit wire type.
+const I32: u8 = 5;
+
+#[derive(Debug, thiserror::Error)]
+pub enum EncodeError {
+	#[error("expected a map for message `{0}`")]
+	ExpectedMap(String),
+
+	#[error("expected a list of {1} items for message `{0}`")]
+	ExpectedTuple(String, usize),
+
+	#[error("missing required field `{1}` of message `{0}`")]
+	MissingField(String, String),
+
+	#[error("invalid value for field `{1}` of message `{0}`")]
+	InvalidField(String, String),
+
+	#[error("no variant of message `{0}` matches the value")]
+	NoMatchingVariant(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DecodeError {
+	#[error("unexpected end of input")]
+	UnexpectedEnd,
+
+	#[error("invalid varint")]
+	InvalidVarint,
+
+	#[error("invalid field number")]
+	InvalidFieldNumber,
+
+	#[error("unsupported wire type {0}")]
+	UnsupportedWireType(u8),
+
+	#[error("invalid value for field `{1}` of message `{0}`")]
+	InvalidField(String, String),
+
+	#[error("missing variant for message `{0}`")]
+	MissingVariant(String),
+}
+
+impl Schema {
+	/// Encodes the given value as an instance of the root message.
+	pub fn encode(&self, value: &Value) -> Result<Vec<u8>, EncodeError> {
+		let mut output = Vec::new();
+		self.encode_message(self.root, value, &mut output)?;
+		Ok(output)
+	}
+
+	/// Decodes an instance of the root message.
+	pub fn decode(&self, bytes: &[u8]) -> Result<Value, DecodeError> {
+		self.decode_message(self.root, bytes)
+	}
+
+	fn encode_message(
+		&self,
+		index: usize,
+		value: &Value,
+		output: &mut Vec<u8>,
+	) -> Result<(), EncodeError> {
+		let message = &self.messages[index];
+		match &message.body {
+			Body::Record(fields) => {
+				let Value::Map(entries) = value else {
+					return Err(EncodeError::ExpectedMap(message.name.clone()));
+				};
+
+				for field in fields {
+					match entries.get(&Value::string(field.key.clone())) {
+						Some(value) => self.encode_field(&message.name, field, value, output)?,
+						None if field.required => {
+							return Err(EncodeError::MissingField(
+								message.name.clone(),
+								field.key.clone(),
+							))
+						}
+						None => (),
+					}
+				}
+
+				Ok(())
+			}
+			Body::Sum(variants) => {
+				for variant in variants {
+					let mut buffer = Vec::new();
+					if self
+						.encode_field(&message.name, variant, value, &mut buffer)
+						.is_ok()
+					{
+						output.extend(buffer);
+						return Ok(());
+					}
+				}
+
+				Err(EncodeError::NoMatchingVariant(message.name.clone()))
+			}
+			Body::Tuple(fields) => match value {
+				Value::List(items) if items.len() == fields.len() => {
+					for (field, item) in fields.iter().zip(items) {
+						self.encode_field(&message.name, field, item, output)?
+					}
+
+					Ok(())
+				}
+				_ => Err(EncodeError::ExpectedTuple(
+					message.name.clone(),
+					fields.len(),
+				)),
+			},
+			Body::Wrapper(field) => self.encode_field(&message.name, field, value, output),
+		}
+	}
+
+	fn encode_field(
+		&self,
+		message: &str,
+		field: &Field,
+		value: &Value,
+		output: &mut Vec<u8>,
+	) -> Result<(), EncodeError> {
+		let invalid = || EncodeError::InvalidField(message.to_owned(), field.key.clone());
+
+		if field.repeated {
+			let Value::List(items) = value else {
+				return Err(invalid());
+			};
+
+			match &field.ty {
+				Type::Scalar(scalar) if scalar.is_packable() => {
+					let mut packed = Vec::new();
+					for item in items {
+						encode_scalar(*scalar, item, &mut packed).ok_or_else(invalid)?;
+					}
+
+					write_tag(field.number, LEN, output);
+					write_bytes(&packed, output);
+				}
+				ty => {
+					for item in items {
+						self.encode_single(message, field, ty, item, output)?
+					}
+				}
+			}
+
+			Ok(())
+		} else {
+			self.encode_single(message, field, &field.ty, value, output)
+		}
+	}
+
+	fn encode_single(
+		&self,
+		message: &str,
+		field: &Field,
+		ty: &Type,
+		value: &Value,
+		output: &mut Vec<u8>,
+	) -> Result<(), EncodeError> {
+		let invalid = || EncodeError::InvalidField(message.to_owned(), field.key.clone());
+
+		match ty {
+			Type::Scalar(scalar) => {
+				write_tag(field.number, scalar.wire_type(), output);
+				encode_scalar(*scalar, value, output).ok_or_else(invalid)
+			}
+			Type::Unit(constant) => {
+				if value == constant {
+					write_tag(field.number, LEN, output);
+					write_bytes(&[], output);
+					Ok(())
+				} else {
+					Err(invalid())
+				}
+			}
+			Type::Message(index) => {
+				let mut buffer = Vec::new();
+				self.encode_message(*index, value, &mut buffer)?;
+				write_tag(field.number, LEN, output);
+				write_bytes(&buffer, output);
+				Ok(())
+			}
+		}
+	}
+
+	fn decode_message(&self, index: usize, bytes: &[u8]) -> Result<Value, DecodeError> {
+		let message = &self.messages[index];
+		let entries = read_entries(bytes)?;
+
+		match &message.body {
+			Body::Record(fields) => {
+				let mut map = BTreeMap::new();
+				for field in fields {
+					if let Some(value) = self.decode_field(&message.name, field, &entries)? {
+						map.insert(Value::string(field.key.clone()), value);
+					}
+				}
+
+				Ok(Value::Map(map))
+			}
+			Body::Sum(variants) => {
+				// As for any `oneof`, the last variant found wins.
+				let (variant, wire) = entries
+					.iter()
+					.rev()
+					.find_map(|(number, wire)| {
+						variants
+							.iter()
+							.find(|v| v.number == *number)
+							.map(|v| (v, wire))
+					})
+					.ok_or_else(|| DecodeError::MissingVariant(message.name.clone()))?;
+
+				self.decode_single(&message.name, variant, &variant.ty, wire)
+			}
+			Body::Tuple(fields) => {
+				let mut items = Vec::with_capacity(fields.len());
+				for field in fields {
+					items.push(self.decode_field(&message.name, field, &entries)?.unwrap())
+				}
+
+				Ok(Value::List(items))
+			}
+			Body::Wrapper(field) => Ok(self.decode_field(&message.name, field, &entries)?.unwrap()),
+		}
+	}
+
+	/// Decodes the given field from the message entries.
+	///
+	/// Absent required fields are decoded to their default value.
+	fn decode_field(
+		&self,
+		message: &str,
+		field: &Field,
+		entries: &[(u32, Wire)],
+	) -> Result<Option<Value>, DecodeError> {
+		let mut found = entries
+			.iter()
+			.filter(|(number, _)| *number == field.number)
+			.map(|(_, wire)| wire)
+			.peekable();
+
+		if field.repeated {
+			if found.peek().is_none() && !field.required {
+				return Ok(None);
+			}
+
+			let mut items = Vec::new();
+			for wire in found {
+				match (&field.ty, wire) {
+					(Type::Scalar(scalar), Wire::Len(mut packed)) if scalar.is_packable() => {
+						while !packed.is_empty() {
+							let wire = read_value(scalar.wire_type(), &mut packed)?;
+							items.push(decode_scalar(*scalar, &wire).ok_or_else(|| {
+								DecodeError::InvalidField(message.to_owned(), field.key.clone())
+							})?)
+						}
+					}
+					(ty, wire) => items.push(self.decode_single(message, field, ty, wire)?),
+				}
+			}
+
+			Ok(Some(Value::List(items)))
+		} else {
+			match found.last() {
+				Some(wire) => self
+					.decode_single(message, field, &field.ty, wire)
+					.map(Some),
+				None if field.required => match &field.ty {
+					Type::Scalar(scalar) => Ok(Some(scalar.default_value())),
+					Type::Unit(constant) => Ok(Some(constant.clone())),
+					Type::Message(index) => self.decode_message(*index, &[]).map(Some),
+				},
+				None => Ok(None),
+			}
+		}
+	}
+
+	fn decode_single(
+		&self,
+		message: &str,
+		field: &Field,
+		ty: &Type,
+		wire: &Wire,
+	) -> Result<Value, DecodeError> {
+		let invalid = || DecodeError::InvalidField(message.to_owned(), field.key.clone());
+
+		match (ty, wire) {
+			(Type::Scalar(scalar), wire) => decode_scalar(*scalar, wire).ok_or_else(invalid),
+			(Type::Unit(constant), Wire::Len(_)) => Ok(constant.clone()),
+			(Type::Message(index), Wire::Len(bytes)) => self.decode_message(*index, bytes),
+			_ => Err(invalid()),
+		}
+	}
+}
+
+impl Scalar {
+	fn wire_type(&self) -> u8 {
+		match self {
+			Self::Bool | Self::Int32 | Self::Int64 | Self::Uint32 | Self::Uint64 => VARINT,
+			Self::Double => I64,
+			Self::String | Self::Bytes => LEN,
+		}
+	}
+
+	fn default_value(&self) -> Value {
+		match self {
+			Self::Bool => Value::Literal(Literal::Boolean(false)),
+			Self::Int32 | Self::Int64 | Self::Uint32 | Self::Uint64 | Self::Double => {
+				Value::Literal(Literal::Number(0u64.into()))
+			}
+			Self::String => Value::string(String::new()),
+			Self::Bytes => Value::Literal(Literal::ByteString(Vec::new())),
+		}
+	}
+}
+
+/// Encodes the payload of a scalar value, without tag.
+fn encode_scalar(scalar: Scalar, value: &Value, output: &mut Vec<u8>) -> Option<()> {
+	match (scalar, value) {
+		(Scalar::Bool, Value::Literal(Literal::Boolean(b))) => write_varint(*b as u64, output),
+		(Scalar::Int32, Value::Literal(Literal::Number(n))) => {
+			let n = i32::try_from(as_i64(n)?).ok()?;
+			write_varint(n as i64 as u64, output)
+		}
+		(Scalar::Int64, Value::Literal(Literal::Number(n))) => {
+			write_varint(as_i64(n)? as u64, output)
+		}
+		(Scalar::Uint32, Value::Literal(Literal::Number(n))) => {
+			let n = u32::try_from(as_u64(n)?).ok()?;
+			write_varint(n as u64, output)
+		}
+		(Scalar::Uint64, Value::Literal(Literal::Number(n))) => write_varint(as_u64(n)?, output),
+		(Scalar::Double, Value::Literal(Literal::Number(n))) => {
+			output.extend(n.to_f64().to_le_bytes())
+		}
+		(Scalar::String, Value::Literal(Literal::TextString(s))) => {
+			write_bytes(s.as_bytes(), output)
+		}
+		(Scalar::Bytes, Value::Literal(Literal::ByteString(b))) => write_bytes(b, output),
+		_ => return None,
+	}
+
+	Some(())
+}
+
+fn decode_scalar(scalar: Scalar, wire: &Wire) -> Option<Value> {
+	let literal = match (scalar, wire) {
+		(Scalar::Bool, Wire::Varint(v)) => Literal::Boolean(*v != 0),
+		(Scalar::Int32, Wire::Varint(v)) => Literal::Number((*v as i32).into()),
+		(Scalar::Int64, Wire::Varint(v)) => Literal::Number((*v as i64).into()),
+		(Scalar::Uint32, Wire::Varint(v)) => Literal::Number((*v as u32).into()),
+		(Scalar::Uint64, Wire::Varint(v)) => Literal::Number((*v).into()),
+		(Scalar::Double, Wire::I64(v)) => {
+			Literal::Number(Number::try_from(f64::from_bits(*v)).ok()?)
+		}
+		(Scalar::String, Wire::Len(bytes)) => {
+			Literal::TextString(String::from_utf8(bytes.to_vec()).ok()?)
+		}
+		(Scalar::Bytes, Wire::Len(bytes)) => Literal::ByteString(bytes.to_vec()),
+		_ => return None,
+	};
+
+	Some(Value::Literal(literal))
+}
+
+fn as_i64(n: &Number) -> Option<i64> {
+	match n.as_native() {
+		NativeNumber::I64(n) => Some(n),
+		NativeNumber::U64(n) => n.try_into().ok(),
+		NativeNumber::F64(_) => None,
+	}
+}
+
+fn as_u64(n: &Number) -> Option<u64> {
+	match n.as_native() {
+		NativeNumber::U64(n) => Some(n),
+		_ => None,
+	}
+}
+
+fn write_varint(mut value: u64, output: &mut Vec<u8>) {
+	while value >= 0x80 {
+		output.push(value as u8 | 0x80);
+		value >>= 7;
+	}
+
+	output.push(value as u8)
+}
+
+fn write_tag(number: u32, wire_type: u8, output: &mut Vec<u8>) {
+	write_varint(((number as u64) << 3) | wire_type as u64, output)
+}
+
+fn write_bytes(bytes: &[u8], output: &mut Vec<u8>) {
+	write_varint(bytes.len() as u64, output);
+	output.extend_from_slice(bytes)
+}
+
+/// Wire value.
+enum Wire<'a> {
+	Varint(u64),
+	I64(u64),
+	Len(&'a [u8]),
+
+	/// 32-bit value, not used by any scalar type of the schema.
+	I32,
+}
+
+fn read_varint(input: &mut &[u8]) -> Result<u64, DecodeError> {
+	let mut value = 0u64;
+	for i in 0..10 {
+		let (&byte, rest) = input.split_first().ok_or(DecodeError::UnexpectedEnd)?;
+		*input = rest;
+
+		if i == 9 && byte > 1 {
+			return Err(DecodeError::InvalidVarint);
+		}
+
+		value |= ((byte & 0x7f) as u64) << (7 * i);
+		if byte & 0x80 == 0 {
+			return Ok(value);
+		}
+	}
+
+	Err(DecodeError::InvalidVarint)
+}
+
+fn read_fixed<const N: usize>(input: &mut &[u8]) -> Result<[u8; N], DecodeError> {
+	if input.len() < N {
+		return Err(DecodeError::UnexpectedEnd);
+	}
+
+	let (bytes, rest) = input.split_at(N);
+	*input = rest;
+	Ok(bytes.try_into().unwrap())
+}
+
+fn read_value<'a>(wire_type: u8, input: &mut &'a [u8]) -> Result<Wire<'a>, DecodeError> {
+	match wire_type {
+		VARINT => read_varint(input).map(Wire::Varint),
+		I64 => read_fixed(input).map(|b| Wire::I64(u64::from_le_bytes(b))),
+		LEN => {
+			let len = read_varint(input)?;
+			let len = usize::try_from(len).map_err(|_| DecodeError::UnexpectedEnd)?;
+			if input.len() < len {
+				return Err(DecodeError::UnexpectedEnd);
+			}
+
+			let (bytes, rest) = input.split_at(len);
+			*input = rest;
+			Ok(Wire::Len(bytes))
+		}
+		I32 => read_fixed::<4>(input).map(|_| Wire::I32),
+		other => Err(DecodeError::UnsupportedWireType(other)),
+	}
+}
+
+/// Reads all the entries of a message, in order.
+fn read_entries(mut input: &[u8]) -> Result<Vec<(u32, Wire<'_>)>, DecodeError> {
+	let mut entries = Vec::new();
+
+	while !input.is_empty() {
+		let tag = read_varint(&mut input)?;
+		let number = u32::try_from(tag >> 3).map_err(|_| DecodeError::InvalidFieldNumber)?;
+		if number == 0 {
+			return Err(DecodeError::InvalidFieldNumber);
+		}
+
+		let value = read_value((tag & 0x7) as u8, &mut input)?;
+		entries.push((number, value))
+	}
+
+	Ok(entries)
+}
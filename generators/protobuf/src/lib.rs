@@ -0,0 +1,170 @@
+//! Protocol Buffers support for TreeLDR layouts.
+//!
+//! Generates `.proto` message definitions from layouts, and encodes or
+//! decodes tree values in the binary protobuf format following those same
+//! definitions.
+//!
+//! Field numbers are assigned through the [`PROTOBUF_FIELD_IRI`] extra property,
+//! that must be given on every record field and sum variant:
+//! ```json
+//! "name": {
+//!   "value": "https://treeldr.org/prelude#string",
+//!   "extra": { "https://schema.treeldr.org/protobuf#field": 1 }
+//! }
+//! ```
+use std::{collections::HashMap, hash::Hash};
+
+use iref::Iri;
+use rdf_types::{
+	interpretation::{ReverseIriInterpretation, ReverseLiteralInterpretation},
+	vocabulary::{IriVocabulary, LiteralVocabulary},
+	Term,
+};
+use static_iref::iri;
+use treeldr_gen_rust::utils::type_name_from_iri;
+use treeldr_layouts::{distill::RdfContext, layout::LayoutType, LayoutRegistry, Ref};
+
+pub mod codec;
+mod schema;
+
+pub use codec::{DecodeError, EncodeError};
+pub use schema::Schema;
+
+/// Extra property giving the protobuf field number of a record field or sum
+/// variant.
+pub const PROTOBUF_FIELD_IRI: &Iri = iri!("https://schema.treeldr.org/protobuf#field");
+
+/// Protobuf keywords that cannot be used as message names.
+const KEYWORDS: &[&str] = &[
+	"enum", "extend", "import", "message", "oneof", "option", "package", "reserved", "service",
+	"syntax",
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error<R = Term> {
+	#[error("missing type identifier for layout {0}")]
+	MissingTypeIdentifier(R),
+
+	#[error("layout {0} not found")]
+	LayoutNotFound(R),
+
+	#[error("layout {0} cannot be represented with Protocol Buffers")]
+	Unsupported(R),
+
+	#[error("invalid field name `{0}`")]
+	InvalidFieldName(treeldr_layouts::Value),
+
+	#[error("missing field number for `{1}` in layout {0}")]
+	MissingFieldNumber(R, String),
+
+	#[error("invalid field number `{2}` for `{1}` in layout {0}")]
+	InvalidFieldNumber(R, String, String),
+
+	#[error("field number {1} is used more than once in layout {0}")]
+	DuplicateFieldNumber(R, u32),
+}
+
+pub struct Options<R = Term> {
+	idents: HashMap<Ref<LayoutType, R>, String>,
+	package: Option<String>,
+}
+
+impl<R> Options<R> {
+	pub fn new() -> Self {
+		Self {
+			idents: HashMap::new(),
+			package: None,
+		}
+	}
+
+	/// Sets the package of the generated definitions.
+	pub fn with_package(self, package: Option<String>) -> Self {
+		Self { package, ..self }
+	}
+
+	/// Sets the message name of the given layout.
+	pub fn set_ident(&mut self, layout_ref: Ref<LayoutType, R>, ident: String)
+	where
+		R: Eq + Hash,
+	{
+		self.idents.insert(layout_ref, ident);
+	}
+
+	pub fn layout_ident<V, I>(
+		&self,
+		rdf: RdfContext<V, I>,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Option<String>
+	where
+		V: IriVocabulary,
+		I: ReverseIriInterpretation<Resource = R, Iri = V::Iri>,
+		R: Eq + Hash,
+	{
+		self.idents
+			.get(layout_ref)
+			.cloned()
+			.or_else(|| default_layout_ident(rdf, layout_ref))
+	}
+}
+
+impl<R> Default for Options<R> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Checks that the given string is a valid protobuf identifier.
+pub fn is_ident(s: &str) -> bool {
+	let mut chars = s.chars();
+	match chars.next() {
+		Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+			chars.all(|c| c.is_ascii_alphanumeric() || c == '_') && !KEYWORDS.contains(&s)
+		}
+		_ => false,
+	}
+}
+
+pub fn default_layout_ident<V, I>(
+	rdf: RdfContext<V, I>,
+	layout_ref: &Ref<LayoutType, I::Resource>,
+) -> Option<String>
+where
+	V: IriVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri>,
+{
+	let mut selected: Option<String> = None;
+
+	for i in rdf.interpretation.iris_of(layout_ref.id()) {
+		let iri = rdf.vocabulary.iri(i).unwrap();
+		if let Some(name) = type_name_from_iri(iri).filter(|name| is_ident(name)) {
+			if !selected.as_ref().is_some_and(|s| *s < name) {
+				selected = Some(name)
+			}
+		}
+	}
+
+	selected
+}
+
+/// Generates the `.proto` definitions of the given layout and all its
+/// dependencies.
+///
+/// Records become messages and sums become messages with a single `oneof`.
+/// Lists become `repeated` fields. Where a repeated field is not allowed
+/// (list items, `oneof` members), lists are wrapped in a message with a
+/// single `value` field. Sized lists become messages with one field per
+/// item.
+pub fn generate<V, I>(
+	rdf: RdfContext<V, I>,
+	layouts: impl LayoutRegistry<I::Resource>,
+	layout_ref: &Ref<LayoutType, I::Resource>,
+	options: &Options<I::Resource>,
+) -> Result<String, Error<I::Resource>>
+where
+	V: IriVocabulary + LiteralVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri> + ReverseLiteralInterpretation<Literal = V::Literal>,
+	I::Resource: Clone + Ord + Hash,
+{
+	let schema = Schema::new(rdf, layouts, layout_ref, options)?;
+	Ok(schema.to_proto(options.package.as_deref()))
+}
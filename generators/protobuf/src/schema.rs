@@ -0,0 +1,639 @@
+use std::{
+	collections::{BTreeMap, HashMap, HashSet, VecDeque},
+	fmt::Write,
+	hash::Hash,
+};
+
+use rdf_types::{
+	interpretation::{ReverseIriInterpretation, ReverseLiteralInterpretation},
+	vocabulary::{IriVocabulary, LiteralVocabulary},
+	RDFS_COMMENT,
+};
+use treeldr_gen_rust::utils::PascalCase;
+use treeldr_layouts::{
+	distill::RdfContext,
+	layout::{DataLayout, LayoutType, ListLayout, LiteralLayout},
+	Layout, LayoutRegistry, Literal, PresetLayout, Ref, Value,
+};
+
+use crate::{Error, Options, PROTOBUF_FIELD_IRI};
+
+/// Name of the `oneof` of sum messages.
+const ONEOF_NAME: &str = "value";
+
+/// Indentation used in the generated definitions.
+const INDENT: &str = "  ";
+
+/// Protobuf schema derived from a layout.
+///
+/// This is the common ground between the generated `.proto` definitions and
+/// the binary codec.
+pub struct Schema {
+	pub(crate) messages: Vec<Message>,
+	pub(crate) root: usize,
+}
+
+pub(crate) struct Message {
+	pub name: String,
+	pub description: Option<String>,
+	pub body: Body,
+}
+
+pub(crate) enum Body {
+	/// Record, where each field is a tree value entry.
+	///
+	/// Fields are sorted by number.
+	Record(Vec<Field>),
+
+	/// Sum, where each field is a variant in a `oneof`.
+	///
+	/// When encoding, the first variant matching the value is selected.
+	Sum(Vec<Field>),
+
+	/// Sized list, where each field is an item.
+	Tuple(Vec<Field>),
+
+	/// Single `value` field, standing for the whole tree value.
+	Wrapper(Field),
+}
+
+pub(crate) struct Field {
+	/// Protobuf field name.
+	pub name: String,
+
+	/// Entry key in the tree value, if any.
+	pub key: String,
+
+	pub number: u32,
+	pub ty: Type,
+	pub repeated: bool,
+	pub required: bool,
+	pub description: Option<String>,
+}
+
+impl Field {
+	/// Returns the protobuf label of the field.
+	fn label(&self) -> &'static str {
+		if self.repeated {
+			"repeated "
+		} else if !self.required && matches!(self.ty, Type::Scalar(_)) {
+			"optional "
+		} else {
+			""
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Type {
+	Scalar(Scalar),
+
+	/// `google.protobuf.Empty`, standing for the given constant.
+	Unit(Value),
+
+	/// Message, given by index.
+	Message(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Scalar {
+	Bool,
+	Int32,
+	Int64,
+	Uint32,
+	Uint64,
+	Double,
+	String,
+	Bytes,
+}
+
+impl Scalar {
+	pub fn name(&self) -> &'static str {
+		match self {
+			Self::Bool => "bool",
+			Self::Int32 => "int32",
+			Self::Int64 => "int64",
+			Self::Uint32 => "uint32",
+			Self::Uint64 => "uint64",
+			Self::Double => "double",
+			Self::String => "string",
+			Self::Bytes => "bytes",
+		}
+	}
+
+	/// Checks if repeated values of this type are packed.
+	pub fn is_packable(&self) -> bool {
+		!matches!(self, Self::String | Self::Bytes)
+	}
+}
+
+impl Schema {
+	/// Builds the protobuf schema of the given layout and all its
+	/// dependencies.
+	pub fn new<V, I>(
+		rdf: RdfContext<V, I>,
+		layouts: impl LayoutRegistry<I::Resource>,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+		options: &Options<I::Resource>,
+	) -> Result<Self, Error<I::Resource>>
+	where
+		V: IriVocabulary + LiteralVocabulary,
+		I: ReverseIriInterpretation<Iri = V::Iri>
+			+ ReverseLiteralInterpretation<Literal = V::Literal>,
+		I::Resource: Clone + Ord + Hash,
+	{
+		let ident = options
+			.layout_ident(rdf, layout_ref)
+			.ok_or_else(|| Error::MissingTypeIdentifier(layout_ref.id().clone()))?;
+
+		let mut builder = Builder {
+			rdf,
+			layouts: &layouts,
+			options,
+			names: HashMap::new(),
+			taken: HashSet::new(),
+			queue: VecDeque::new(),
+			messages: Vec::new(),
+		};
+
+		let root = match builder.type_of(layout_ref, &ident)? {
+			(Type::Message(i), false) => i,
+			(ty, repeated) => builder.wrapper(ident, ty, repeated),
+		};
+
+		while let Some((layout_ref, i)) = builder.queue.pop_front() {
+			builder.declaration(&layout_ref, i)?;
+		}
+
+		Ok(Self {
+			messages: builder.messages,
+			root,
+		})
+	}
+
+	/// Returns the name of the root message.
+	pub fn root_name(&self) -> &str {
+		&self.messages[self.root].name
+	}
+
+	fn type_name(&self, ty: &Type) -> &str {
+		match ty {
+			Type::Scalar(s) => s.name(),
+			Type::Unit(_) => "google.protobuf.Empty",
+			Type::Message(i) => &self.messages[*i].name,
+		}
+	}
+
+	/// Returns the `.proto` definitions of this schema.
+	pub fn to_proto(&self, package: Option<&str>) -> String {
+		let mut output = String::new();
+		output.push_str("// Generated by TreeLDR. Do not edit.\n");
+		output.push_str("syntax = \"proto3\";\n");
+
+		if let Some(package) = package {
+			write!(output, "\npackage {package};\n").unwrap();
+		}
+
+		let uses_empty = self
+			.messages
+			.iter()
+			.any(|m| m.fields().iter().any(|f| matches!(f.ty, Type::Unit(_))));
+		if uses_empty {
+			output.push_str("\nimport \"google/protobuf/empty.proto\";\n");
+		}
+
+		for message in &self.messages {
+			output.push('\n');
+			if let Some(description) = &message.description {
+				output.push_str(&comment(description, ""));
+			}
+
+			writeln!(output, "message {} {{", message.name).unwrap();
+			match &message.body {
+				Body::Sum(variants) => {
+					writeln!(output, "{INDENT}oneof {ONEOF_NAME} {{").unwrap();
+					for variant in variants {
+						self.write_field(&mut output, variant, &format!("{INDENT}{INDENT}"));
+					}
+					writeln!(output, "{INDENT}}}").unwrap();
+				}
+				_ => {
+					for field in message.fields() {
+						self.write_field(&mut output, field, INDENT);
+					}
+				}
+			}
+			output.push_str("}\n");
+		}
+
+		output
+	}
+
+	fn write_field(&self, output: &mut String, field: &Field, indent: &str) {
+		if let Some(description) = &field.description {
+			output.push_str(&comment(description, indent));
+		}
+
+		writeln!(
+			output,
+			"{indent}{}{} {} = {};",
+			field.label(),
+			self.type_name(&field.ty),
+			field.name,
+			field.number
+		)
+		.unwrap();
+	}
+}
+
+impl Message {
+	pub fn fields(&self) -> &[Field] {
+		match &self.body {
+			Body::Record(fields) | Body::Sum(fields) | Body::Tuple(fields) => fields,
+			Body::Wrapper(field) => std::slice::from_ref(field),
+		}
+	}
+}
+
+struct Builder<'a, V, I: ReverseIriInterpretation, L> {
+	rdf: RdfContext<'a, V, I>,
+	layouts: &'a L,
+	options: &'a Options<I::Resource>,
+	names: HashMap<Ref<LayoutType, I::Resource>, usize>,
+	taken: HashSet<String>,
+	queue: VecDeque<(Ref<LayoutType, I::Resource>, usize)>,
+	messages: Vec<Message>,
+}
+
+impl<'a, V, I, L> Builder<'a, V, I, L>
+where
+	V: IriVocabulary + LiteralVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri> + ReverseLiteralInterpretation<Literal = V::Literal>,
+	I::Resource: Clone + Ord + Hash,
+	L: LayoutRegistry<I::Resource>,
+{
+	fn get(
+		&self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Result<&'a Layout<I::Resource>, Error<I::Resource>> {
+		self.layouts
+			.get(layout_ref)
+			.ok_or_else(|| Error::LayoutNotFound(layout_ref.id().clone()))
+	}
+
+	/// Adds a new message, with a fresh name based on the given identifier,
+	/// and returns its index.
+	fn add_message(&mut self, ident: String, description: Option<String>, body: Body) -> usize {
+		let mut name = ident.clone();
+		let mut i = 1;
+		while self.taken.contains(&name) {
+			i += 1;
+			name = format!("{ident}{i}");
+		}
+		self.taken.insert(name.clone());
+
+		let index = self.messages.len();
+		self.messages.push(Message {
+			name,
+			description,
+			body,
+		});
+		index
+	}
+
+	/// Schedules the declaration of the given layout as a message, unless it
+	/// is already declared, and returns the message index.
+	fn declare(&mut self, layout_ref: &Ref<LayoutType, I::Resource>, ident: String) -> usize {
+		match self.names.get(layout_ref) {
+			Some(i) => *i,
+			None => {
+				let i = self.add_message(ident, None, Body::Record(Vec::new()));
+				self.names.insert(layout_ref.clone(), i);
+				self.queue.push_back((layout_ref.clone(), i));
+				i
+			}
+		}
+	}
+
+	/// Adds a message wrapping a value of the given type.
+	fn wrapper(&mut self, ident: String, ty: Type, repeated: bool) -> usize {
+		self.add_message(
+			ident,
+			None,
+			Body::Wrapper(Field {
+				name: "value".to_owned(),
+				key: "value".to_owned(),
+				number: 1,
+				ty,
+				repeated,
+				required: true,
+				description: None,
+			}),
+		)
+	}
+
+	fn preset(&self, layout_ref: &Ref<LayoutType, I::Resource>) -> Option<PresetLayout> {
+		self.rdf
+			.interpretation
+			.iris_of(layout_ref.id())
+			.find_map(|i| PresetLayout::from_iri(self.rdf.vocabulary.iri(i).unwrap()))
+	}
+
+	fn iri(&self, resource: &I::Resource) -> Option<String> {
+		self.rdf
+			.interpretation
+			.iris_of(resource)
+			.next()
+			.map(|i| self.rdf.vocabulary.iri(i).unwrap().to_string())
+	}
+
+	fn is_integer_datatype(&self, datatype: &I::Resource) -> bool {
+		self.rdf.interpretation.iris_of(datatype).any(|i| {
+			xsd_types::IntegerDatatype::from_iri(self.rdf.vocabulary.iri(i).unwrap()).is_some()
+		})
+	}
+
+	/// Returns the lexical value of the given extra property, if any.
+	fn property(
+		&self,
+		properties: &BTreeMap<I::Resource, I::Resource>,
+		iri: &iref::Iri,
+	) -> Option<String> {
+		properties.iter().find_map(|(prop, value)| {
+			let matches = self
+				.rdf
+				.interpretation
+				.iris_of(prop)
+				.any(|i| self.rdf.vocabulary.iri(i).unwrap() == iri);
+
+			if matches {
+				self.rdf
+					.interpretation
+					.literals_of(value)
+					.find_map(|l| self.rdf.vocabulary.literal(l))
+					.map(|l| l.value.to_owned())
+			} else {
+				None
+			}
+		})
+	}
+
+	/// Returns the field number given by the extra properties of the given
+	/// record field or sum variant.
+	fn field_number(
+		&self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+		name: &str,
+		properties: &BTreeMap<I::Resource, I::Resource>,
+	) -> Result<u32, Error<I::Resource>> {
+		let value = self
+			.property(properties, PROTOBUF_FIELD_IRI)
+			.ok_or_else(|| Error::MissingFieldNumber(layout_ref.id().clone(), name.to_owned()))?;
+
+		match value.parse() {
+			Ok(n @ (1..=18999 | 20000..=536_870_911)) => Ok(n),
+			_ => Err(Error::InvalidFieldNumber(
+				layout_ref.id().clone(),
+				name.to_owned(),
+				value,
+			)),
+		}
+	}
+
+	/// Builds the body of the given message.
+	fn declaration(
+		&mut self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+		index: usize,
+	) -> Result<(), Error<I::Resource>> {
+		let layout = self.get(layout_ref)?;
+		let ident = self.messages[index].name.clone();
+
+		let mut description = Vec::new();
+		if let Some(comment) = self.property(layout.extra_properties(), RDFS_COMMENT) {
+			description.push(comment)
+		}
+		if let Some(iri) = self.iri(layout_ref.id()) {
+			description.push(format!("Layout `{iri}`."))
+		}
+
+		let mut names = HashSet::new();
+		let mut numbers = HashSet::new();
+		let mut new_field = |this: &mut Self,
+		                     key: &str,
+		                     properties: &BTreeMap<I::Resource, I::Resource>,
+		                     value_layout: &Ref<LayoutType, I::Resource>,
+		                     required: bool,
+		                     single: bool| {
+			let number = this.field_number(layout_ref, key, properties)?;
+			if !numbers.insert(number) {
+				return Err(Error::DuplicateFieldNumber(layout_ref.id().clone(), number));
+			}
+
+			let mut name = field_name(key);
+			let base_len = name.len();
+			let mut i = 1;
+			while (single && name == ONEOF_NAME) || !names.insert(name.clone()) {
+				i += 1;
+				name.truncate(base_len);
+				write!(name, "_{i}").unwrap();
+			}
+
+			let hint = format!("{ident}{}", PascalCase(key));
+			let (ty, repeated) = if single {
+				(this.single_type_of(value_layout, &hint)?, false)
+			} else {
+				this.type_of(value_layout, &hint)?
+			};
+
+			Ok(Field {
+				name,
+				key: key.to_owned(),
+				number,
+				ty,
+				repeated,
+				required,
+				description: this.property(properties, RDFS_COMMENT),
+			})
+		};
+
+		let body = match layout {
+			Layout::Product(layout) => {
+				let mut fields = Vec::new();
+				for (key, field) in &layout.fields {
+					let key = match key {
+						Value::Literal(Literal::TextString(key)) => key,
+						other => return Err(Error::InvalidFieldName(other.clone())),
+					};
+
+					fields.push(new_field(
+						self,
+						key,
+						&field.extra_properties,
+						&field.value.layout,
+						field.required,
+						false,
+					)?);
+				}
+
+				fields.sort_by_key(|f| f.number);
+				Body::Record(fields)
+			}
+			Layout::Sum(layout) => {
+				let mut variants = Vec::new();
+				for variant in &layout.variants {
+					variants.push(new_field(
+						self,
+						&variant.name,
+						&variant.extra_properties,
+						&variant.value.layout,
+						true,
+						true,
+					)?);
+				}
+
+				variants.sort_by_key(|v| v.number);
+				Body::Sum(variants)
+			}
+			_ => unreachable!(),
+		};
+
+		let message = &mut self.messages[index];
+		message.description = (!description.is_empty()).then(|| description.join("\n\n"));
+		message.body = body;
+		Ok(())
+	}
+
+	/// Returns the type of the given layout, and whether or not it is
+	/// repeated.
+	fn type_of(
+		&mut self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+		hint: &str,
+	) -> Result<(Type, bool), Error<I::Resource>> {
+		match self.preset(layout_ref) {
+			None => (),
+			Some(PresetLayout::Unit) => return Ok((Type::Unit(Value::unit()), false)),
+			Some(PresetLayout::Boolean) => return Ok((Type::Scalar(Scalar::Bool), false)),
+			Some(PresetLayout::U8 | PresetLayout::U16 | PresetLayout::U32) => {
+				return Ok((Type::Scalar(Scalar::Uint32), false))
+			}
+			Some(PresetLayout::U64) => return Ok((Type::Scalar(Scalar::Uint64), false)),
+			Some(PresetLayout::I8 | PresetLayout::I16 | PresetLayout::I32) => {
+				return Ok((Type::Scalar(Scalar::Int32), false))
+			}
+			Some(PresetLayout::I64) => return Ok((Type::Scalar(Scalar::Int64), false)),
+			Some(PresetLayout::Id | PresetLayout::String) => {
+				return Ok((Type::Scalar(Scalar::String), false))
+			}
+		}
+
+		let layout = self.get(layout_ref)?;
+		let ident = || {
+			self.options
+				.layout_ident(self.rdf, layout_ref)
+				.unwrap_or_else(|| hint.to_owned())
+		};
+
+		match layout {
+			Layout::Product(_) => {
+				let ident = ident();
+				Ok((Type::Message(self.declare(layout_ref, ident)), false))
+			}
+			Layout::Sum(layout) if !layout.variants.is_empty() => {
+				let ident = ident();
+				Ok((Type::Message(self.declare(layout_ref, ident)), false))
+			}
+			Layout::Literal(LiteralLayout::Data(layout)) => {
+				let ty = match layout {
+					DataLayout::Unit(layout) => Type::Unit(layout.const_.clone()),
+					DataLayout::Boolean(_) => Type::Scalar(Scalar::Bool),
+					DataLayout::Number(layout) => {
+						if self.is_integer_datatype(&layout.datatype) {
+							Type::Scalar(Scalar::Int64)
+						} else {
+							Type::Scalar(Scalar::Double)
+						}
+					}
+					DataLayout::ByteString(_) => Type::Scalar(Scalar::Bytes),
+					DataLayout::TextString(_) => Type::Scalar(Scalar::String),
+				};
+
+				Ok((ty, false))
+			}
+			Layout::Literal(LiteralLayout::Id(_)) => Ok((Type::Scalar(Scalar::String), false)),
+			Layout::List(ListLayout::Unordered(layout)) => {
+				let item =
+					self.single_type_of(&layout.item.value.layout, &format!("{hint}Item"))?;
+				Ok((item, true))
+			}
+			Layout::List(ListLayout::Ordered(layout)) => {
+				let item =
+					self.single_type_of(&layout.node.value.layout, &format!("{hint}Item"))?;
+				Ok((item, true))
+			}
+			Layout::List(ListLayout::Sized(layout)) => {
+				let ident = ident();
+				let mut fields = Vec::with_capacity(layout.items.len());
+				for (i, item) in layout.items.iter().enumerate() {
+					let ty = self.single_type_of(&item.value.layout, &format!("{ident}Item{i}"))?;
+					fields.push(Field {
+						name: format!("item{i}"),
+						key: i.to_string(),
+						number: i as u32 + 1,
+						ty,
+						repeated: false,
+						required: true,
+						description: None,
+					})
+				}
+
+				let index = self.add_message(ident, None, Body::Tuple(fields));
+				Ok((Type::Message(index), false))
+			}
+			Layout::Never | Layout::Always | Layout::Sum(_) => {
+				Err(Error::Unsupported(layout_ref.id().clone()))
+			}
+		}
+	}
+
+	/// Returns the type of the given layout, wrapping it in a message if it is
+	/// repeated.
+	fn single_type_of(
+		&mut self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+		hint: &str,
+	) -> Result<Type, Error<I::Resource>> {
+		match self.type_of(layout_ref, hint)? {
+			(ty, false) => Ok(ty),
+			(ty, true) => Ok(Type::Message(self.wrapper(hint.to_owned(), ty, true))),
+		}
+	}
+}
+
+/// Returns the protobuf field name for the given tree value key.
+fn field_name(key: &str) -> String {
+	let mut result: String = key
+		.chars()
+		.map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+		.collect();
+
+	if !result.starts_with(|c: char| c.is_ascii_alphabetic()) {
+		result.insert_str(0, "field_")
+	}
+
+	result
+}
+
+/// Formats the given text as a line comment with the given indentation.
+fn comment(text: &str, indent: &str) -> String {
+	let mut result = String::new();
+	for line in text.lines() {
+		if line.is_empty() {
+			writeln!(result, "{indent}//").unwrap();
+		} else {
+			writeln!(result, "{indent}// {line}").unwrap();
+		}
+	}
+	result
+}
@@ -0,0 +1,97 @@
+use std::fs;
+use treeldr_gen_protobuf::{DecodeError, Schema};
+use treeldr_layouts::{LayoutRegistry, Prelude, Value};
+
+fn schema(id: &str) -> Schema {
+	let mut builder = treeldr_layouts::abs::Builder::new();
+
+	let path = format!(
+		"{}/tests/generate/{id}-layout.json",
+		env!("CARGO_MANIFEST_DIR")
+	);
+	let raw_json = fs::read_to_string(path).unwrap();
+	let layout: treeldr_layouts::abs::syntax::Layout = serde_json::from_str(&raw_json).unwrap();
+	let layout_ref = layout.build(&mut builder).unwrap();
+	let layouts = builder.build();
+
+	Schema::new(
+		Default::default(),
+		layouts.with(Prelude),
+		&layout_ref,
+		&Default::default(),
+	)
+	.unwrap()
+}
+
+fn value(json: serde_json::Value) -> Value {
+	json.into()
+}
+
+#[test]
+fn encode() {
+	let schema = schema("t01");
+	let bytes = schema
+		.encode(&value(serde_json::json!({
+			"name": "A",
+			"contact": "e",
+			"lucky-numbers": [-1]
+		})))
+		.unwrap();
+
+	assert_eq!(
+		bytes,
+		[
+			0x0a, 0x01, b'A', // name
+			0x32, 0x03, 0x0a, 0x01, b'e', // contact
+			0x82, 0xe2, 0x09, 0x0a, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+			0x01 // lucky-numbers
+		]
+	)
+}
+
+#[test]
+fn round_trip() {
+	let schema = schema("t01");
+	let input = value(serde_json::json!({
+		"name": "Alice",
+		"age": 30,
+		"first-friend": {
+			"name": "Bob",
+			"contact": { "phone": "555-0100" },
+			"lucky-numbers": []
+		},
+		"tags": ["a", "b"],
+		"address": { "city": "Paris" },
+		"contact": [1, -2, 3],
+		"previous-contacts": ["alice@example.org", { "phone": "555-0199" }],
+		"score": 0.5,
+		"lucky-numbers": [7, 9223372036854775807_i64]
+	}));
+
+	let bytes = schema.encode(&input).unwrap();
+	assert_eq!(schema.decode(&bytes).unwrap(), input)
+}
+
+#[test]
+fn decode_defaults() {
+	let schema = schema("t01");
+
+	// Only `contact`, with an unknown field that must be ignored.
+	let bytes = [0x32, 0x03, 0x0a, 0x01, b'e', 0x48, 0x01];
+	let expected = value(serde_json::json!({
+		"name": "",
+		"contact": "e",
+		"lucky-numbers": []
+	}));
+
+	assert_eq!(schema.decode(&bytes).unwrap(), expected)
+}
+
+#[test]
+fn decode_missing_variant() {
+	let schema = schema("t01");
+	assert!(matches!(
+		schema.decode(&[]),
+		Err(DecodeError::MissingVariant(_))
+	))
+}
@@ -0,0 +1,735 @@
+//! Python code generation from TreeLDR layouts.
+//!
+//! Generates typed Python models (pydantic models or standard dataclasses)
+//! describing the tree values matched by a layout.
+use std::{
+	collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+	fmt::Write,
+	hash::Hash,
+};
+
+use rdf_types::{
+	interpretation::{ReverseIriInterpretation, ReverseLiteralInterpretation},
+	vocabulary::{IriVocabulary, LiteralVocabulary},
+	Term, RDFS_COMMENT,
+};
+use treeldr_gen_rust::utils::{type_name_from_iri, PascalCase};
+use treeldr_layouts::{
+	distill::RdfContext,
+	layout::{DataLayout, LayoutType, ListLayout, LiteralLayout},
+	Layout, LayoutRegistry, Literal, PresetLayout, Ref, Value,
+};
+
+/// Python keywords, that cannot be used as identifiers.
+const KEYWORDS: &[&str] = &[
+	"False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+	"def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+	"in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+	"with", "yield",
+];
+
+/// Indentation used in the generated code.
+const INDENT: &str = "    ";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error<R = Term> {
+	#[error("missing type identifier for layout {0}")]
+	MissingTypeIdentifier(R),
+
+	#[error("layout {0} not found")]
+	LayoutNotFound(R),
+
+	#[error("invalid field name `{0}`")]
+	InvalidFieldName(Value),
+}
+
+/// Kind of generated models.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+	/// Pydantic models (`pydantic.BaseModel`).
+	#[default]
+	Pydantic,
+
+	/// Standard library dataclasses (`dataclasses.dataclass`).
+	Dataclass,
+}
+
+impl Model {
+	pub const POSSIBLE_VALUES: &'static [&'static str] = &["pydantic", "dataclass"];
+
+	pub fn new(name: &str) -> Option<Self> {
+		match name {
+			"pydantic" => Some(Self::Pydantic),
+			"dataclass" => Some(Self::Dataclass),
+			_ => None,
+		}
+	}
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			Self::Pydantic => "pydantic",
+			Self::Dataclass => "dataclass",
+		}
+	}
+}
+
+pub struct Options<R = Term> {
+	idents: HashMap<Ref<LayoutType, R>, String>,
+	model: Model,
+}
+
+impl<R> Options<R> {
+	pub fn new() -> Self {
+		Self {
+			idents: HashMap::new(),
+			model: Model::default(),
+		}
+	}
+
+	/// Sets the kind of generated models.
+	pub fn with_model(self, model: Model) -> Self {
+		Self { model, ..self }
+	}
+
+	/// Sets the Python identifier of the given layout.
+	pub fn set_ident(&mut self, layout_ref: Ref<LayoutType, R>, ident: String)
+	where
+		R: Eq + Hash,
+	{
+		self.idents.insert(layout_ref, ident);
+	}
+
+	pub fn layout_ident<V, I>(
+		&self,
+		rdf: RdfContext<V, I>,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Option<String>
+	where
+		V: IriVocabulary,
+		I: ReverseIriInterpretation<Resource = R, Iri = V::Iri>,
+		R: Eq + Hash,
+	{
+		self.idents
+			.get(layout_ref)
+			.cloned()
+			.or_else(|| default_layout_ident(rdf, layout_ref))
+	}
+}
+
+impl<R> Default for Options<R> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Checks that the given string is a valid Python identifier.
+pub fn is_ident(s: &str) -> bool {
+	let mut chars = s.chars();
+	match chars.next() {
+		Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+			chars.all(|c| c.is_ascii_alphanumeric() || c == '_') && !KEYWORDS.contains(&s)
+		}
+		_ => false,
+	}
+}
+
+pub fn default_layout_ident<V, I>(
+	rdf: RdfContext<V, I>,
+	layout_ref: &Ref<LayoutType, I::Resource>,
+) -> Option<String>
+where
+	V: IriVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri>,
+{
+	let mut selected: Option<String> = None;
+
+	for i in rdf.interpretation.iris_of(layout_ref.id()) {
+		let iri = rdf.vocabulary.iri(i).unwrap();
+		if let Some(name) = type_name_from_iri(iri).filter(|name| is_ident(name)) {
+			if !selected.as_ref().is_some_and(|s| *s < name) {
+				selected = Some(name)
+			}
+		}
+	}
+
+	selected
+}
+
+/// Generates the Python module defining the given layout and all its
+/// dependencies.
+///
+/// Anonymous record layouts are given a name derived from where they are
+/// used. Other anonymous layouts are inlined.
+pub fn generate<V, I>(
+	rdf: RdfContext<V, I>,
+	layouts: impl LayoutRegistry<I::Resource>,
+	layout_ref: &Ref<LayoutType, I::Resource>,
+	options: &Options<I::Resource>,
+) -> Result<String, Error<I::Resource>>
+where
+	V: IriVocabulary + LiteralVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri> + ReverseLiteralInterpretation<Literal = V::Literal>,
+	I::Resource: Clone + Ord + Hash,
+{
+	let ident = options
+		.layout_ident(rdf, layout_ref)
+		.ok_or_else(|| Error::MissingTypeIdentifier(layout_ref.id().clone()))?;
+
+	let mut generator = Generator {
+		rdf,
+		layouts: &layouts,
+		options,
+		names: HashMap::new(),
+		taken: HashSet::new(),
+		queue: VecDeque::new(),
+		typing: BTreeSet::new(),
+	};
+
+	generator.declare(layout_ref, ident);
+
+	let mut declarations = Vec::new();
+	while let Some((layout_ref, ident)) = generator.queue.pop_front() {
+		declarations.push(generator.declaration(&layout_ref, &ident)?);
+	}
+
+	let mut output = String::new();
+	output.push_str("# Generated by TreeLDR. Do not edit.\n");
+	output.push_str("from __future__ import annotations\n\n");
+
+	if options.model == Model::Dataclass {
+		output.push_str("from dataclasses import dataclass, field\n");
+	}
+
+	if !generator.typing.is_empty() {
+		let names: Vec<_> = generator.typing.into_iter().collect();
+		writeln!(output, "from typing import {}", names.join(", ")).unwrap();
+	}
+
+	if options.model == Model::Pydantic {
+		output.push_str("\nfrom pydantic import BaseModel, ConfigDict, Field\n");
+	}
+
+	for declaration in declarations {
+		output.push_str("\n\n");
+		output.push_str(&declaration);
+	}
+
+	Ok(output)
+}
+
+struct Generator<'a, V, I: ReverseIriInterpretation, L> {
+	rdf: RdfContext<'a, V, I>,
+	layouts: &'a L,
+	options: &'a Options<I::Resource>,
+	names: HashMap<Ref<LayoutType, I::Resource>, String>,
+	taken: HashSet<String>,
+	queue: VecDeque<(Ref<LayoutType, I::Resource>, String)>,
+	typing: BTreeSet<&'static str>,
+}
+
+impl<'a, V, I, L> Generator<'a, V, I, L>
+where
+	V: IriVocabulary + LiteralVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri> + ReverseLiteralInterpretation<Literal = V::Literal>,
+	I::Resource: Clone + Ord + Hash,
+	L: LayoutRegistry<I::Resource>,
+{
+	fn get(
+		&self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Result<&'a Layout<I::Resource>, Error<I::Resource>> {
+		self.layouts
+			.get(layout_ref)
+			.ok_or_else(|| Error::LayoutNotFound(layout_ref.id().clone()))
+	}
+
+	/// Schedules the declaration of the given layout under the given name,
+	/// unless it is already declared, and returns its name.
+	fn declare(&mut self, layout_ref: &Ref<LayoutType, I::Resource>, ident: String) -> String {
+		match self.names.get(layout_ref) {
+			Some(name) => name.clone(),
+			None => {
+				let mut name = ident.clone();
+				let mut i = 1;
+				while self.taken.contains(&name) {
+					i += 1;
+					name = format!("{ident}{i}");
+				}
+
+				self.taken.insert(name.clone());
+				self.names.insert(layout_ref.clone(), name.clone());
+				self.queue.push_back((layout_ref.clone(), name.clone()));
+				name
+			}
+		}
+	}
+
+	fn preset(&self, layout_ref: &Ref<LayoutType, I::Resource>) -> Option<PresetLayout> {
+		self.rdf
+			.interpretation
+			.iris_of(layout_ref.id())
+			.find_map(|i| PresetLayout::from_iri(self.rdf.vocabulary.iri(i).unwrap()))
+	}
+
+	fn iri(&self, resource: &I::Resource) -> Option<String> {
+		self.rdf
+			.interpretation
+			.iris_of(resource)
+			.next()
+			.map(|i| self.rdf.vocabulary.iri(i).unwrap().to_string())
+	}
+
+	fn is_integer_datatype(&self, datatype: &I::Resource) -> bool {
+		self.rdf.interpretation.iris_of(datatype).any(|i| {
+			xsd_types::IntegerDatatype::from_iri(self.rdf.vocabulary.iri(i).unwrap()).is_some()
+		})
+	}
+
+	/// Returns the `rdfs:comment` found in the given extra properties, if any.
+	fn comment(&self, properties: &BTreeMap<I::Resource, I::Resource>) -> Option<String> {
+		properties.iter().find_map(|(prop, value)| {
+			let is_comment = self
+				.rdf
+				.interpretation
+				.iris_of(prop)
+				.any(|i| self.rdf.vocabulary.iri(i).unwrap() == RDFS_COMMENT);
+
+			if is_comment {
+				self.rdf
+					.interpretation
+					.literals_of(value)
+					.find_map(|l| self.rdf.vocabulary.literal(l))
+					.map(|l| l.value.to_owned())
+			} else {
+				None
+			}
+		})
+	}
+
+	/// Generates the declaration of the given layout.
+	fn declaration(
+		&mut self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+		ident: &str,
+	) -> Result<String, Error<I::Resource>> {
+		let layout = self.get(layout_ref)?;
+
+		let mut doc = Vec::new();
+		if let Some(comment) = self.comment(layout.extra_properties()) {
+			doc.push(comment)
+		}
+		if let Some(iri) = self.iri(layout_ref.id()) {
+			doc.push(format!("Layout `{iri}`."))
+		}
+
+		let mut result = String::new();
+		match layout {
+			Layout::Product(layout) => {
+				let model = self.options.model;
+				match model {
+					Model::Pydantic => writeln!(result, "class {ident}(BaseModel):").unwrap(),
+					Model::Dataclass => writeln!(result, "@dataclass\nclass {ident}:").unwrap(),
+				}
+
+				let mut sections = Vec::new();
+				if !doc.is_empty() {
+					sections.push(docstring(&doc.join("\n\n"), INDENT));
+				}
+
+				let mut attributes = Vec::new();
+				let mut attribute_names = HashSet::new();
+				let mut has_alias = false;
+				for (key, field) in &layout.fields {
+					let name = match key {
+						Value::Literal(Literal::TextString(name)) => name,
+						other => return Err(Error::InvalidFieldName(other.clone())),
+					};
+
+					let mut attribute_name = attribute_name(name);
+					let base_len = attribute_name.len();
+					let mut i = 1;
+					while !attribute_names.insert(attribute_name.clone()) {
+						i += 1;
+						attribute_name.truncate(base_len);
+						write!(attribute_name, "_{i}").unwrap();
+					}
+
+					let alias = (attribute_name != *name).then_some(name.as_str());
+					has_alias |= alias.is_some();
+
+					let hint = format!("{ident}{}", PascalCase(name));
+					let mut ty = self.type_ref(&field.value.layout, &hint, false)?;
+					if !field.required {
+						self.typing.insert("Optional");
+						ty = format!("Optional[{ty}]")
+					}
+
+					let description = self.comment(&field.extra_properties);
+
+					let mut args = Vec::new();
+					if !field.required {
+						args.push("default=None".to_owned())
+					}
+
+					let default = match model {
+						Model::Pydantic => {
+							if let Some(alias) = alias {
+								args.push(format!("alias={}", string_literal(alias)))
+							}
+
+							if let Some(description) = &description {
+								args.push(format!("description={}", string_literal(description)))
+							}
+
+							if alias.is_some() || description.is_some() {
+								format!(" = Field({})", args.join(", "))
+							} else if field.required {
+								String::new()
+							} else {
+								" = None".to_owned()
+							}
+						}
+						Model::Dataclass => {
+							if let Some(alias) = alias {
+								args.push(format!(
+									"metadata={{\"alias\": {}}}",
+									string_literal(alias)
+								))
+							}
+
+							if alias.is_some() {
+								format!(" = field({})", args.join(", "))
+							} else if field.required {
+								String::new()
+							} else {
+								" = None".to_owned()
+							}
+						}
+					};
+
+					let mut attribute = format!("{INDENT}{attribute_name}: {ty}{default}\n");
+					if model == Model::Dataclass {
+						if let Some(description) = description {
+							attribute.push_str(&docstring(&description, INDENT));
+						}
+					}
+
+					attributes.push((field.required, attribute));
+				}
+
+				if model == Model::Pydantic {
+					let config = if has_alias {
+						"extra=\"forbid\", populate_by_name=True"
+					} else {
+						"extra=\"forbid\""
+					};
+					sections.push(format!("{INDENT}model_config = ConfigDict({config})\n"));
+				}
+
+				if !attributes.is_empty() {
+					// Attributes without default value must come first.
+					attributes.sort_by_key(|(required, _)| !required);
+					sections.push(attributes.into_iter().map(|(_, a)| a).collect());
+				}
+
+				if sections.is_empty() {
+					sections.push(format!("{INDENT}pass\n"));
+				}
+
+				result.push_str(&sections.join("\n"));
+			}
+			Layout::Literal(LiteralLayout::Id(_)) => {
+				self.typing.insert("NewType");
+				writeln!(result, "{ident} = NewType({}, str)", string_literal(ident)).unwrap();
+				if !doc.is_empty() {
+					result.push_str(&docstring(&doc.join("\n\n"), ""))
+				}
+			}
+			Layout::Sum(layout) if !layout.variants.is_empty() => {
+				let variants = layout
+					.variants
+					.iter()
+					.map(|v| {
+						let hint = format!("{ident}{}", PascalCase(&v.name));
+						self.type_ref(&v.value.layout, &hint, true)
+					})
+					.collect::<Result<Vec<_>, _>>()?;
+
+				self.typing.insert("Literal");
+				if variants.len() == 1 {
+					writeln!(result, "{ident} = {}", variants[0]).unwrap();
+				} else {
+					self.typing.insert("Union");
+					writeln!(result, "{ident} = Union[{}]", variants.join(", ")).unwrap();
+				}
+
+				let mut doc = doc;
+				let variants_doc = layout
+					.variants
+					.iter()
+					.zip(&variants)
+					.map(|(v, ty)| format!("- `{}`: `{}`", v.name, ty.trim_matches('"')))
+					.collect::<Vec<_>>();
+				doc.push(format!("Variants:\n\n{}", variants_doc.join("\n")));
+				result.push_str(&docstring(&doc.join("\n\n"), ""));
+
+				let names = layout
+					.variants
+					.iter()
+					.map(|v| string_literal(&v.name))
+					.collect::<Vec<_>>();
+				write!(
+					result,
+					"\n{ident}Variant = Literal[{}]\n{}",
+					names.join(", "),
+					docstring(&format!("Names of the `{ident}` variants."), "")
+				)
+				.unwrap();
+			}
+			other => {
+				let ty = self.type_expr(other, ident, true)?;
+				writeln!(result, "{ident} = {ty}").unwrap();
+				if !doc.is_empty() {
+					result.push_str(&docstring(&doc.join("\n\n"), ""))
+				}
+			}
+		}
+
+		Ok(result)
+	}
+
+	/// Returns the type expression referencing the given layout.
+	///
+	/// If `quoted` is true, references to declared types are quoted so that
+	/// they can be used before their declaration.
+	fn type_ref(
+		&mut self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+		hint: &str,
+		quoted: bool,
+	) -> Result<String, Error<I::Resource>> {
+		match self.preset(layout_ref) {
+			None => (),
+			Some(PresetLayout::Unit) => return Ok("None".to_owned()),
+			Some(PresetLayout::Boolean) => return Ok("bool".to_owned()),
+			Some(PresetLayout::Id | PresetLayout::String) => return Ok("str".to_owned()),
+			Some(_) => return Ok("int".to_owned()),
+		}
+
+		let name = match self.options.layout_ident(self.rdf, layout_ref) {
+			Some(ident) => self.declare(layout_ref, ident),
+			None => {
+				let layout = self.get(layout_ref)?;
+				if matches!(layout, Layout::Product(_)) {
+					self.declare(layout_ref, hint.to_owned())
+				} else {
+					return self.type_expr(layout, hint, quoted);
+				}
+			}
+		};
+
+		if quoted {
+			Ok(format!("\"{name}\""))
+		} else {
+			Ok(name)
+		}
+	}
+
+	/// Returns the inline type expression of the given layout.
+	fn type_expr(
+		&mut self,
+		layout: &Layout<I::Resource>,
+		hint: &str,
+		quoted: bool,
+	) -> Result<String, Error<I::Resource>> {
+		match layout {
+			Layout::Never => {
+				self.typing.insert("NoReturn");
+				Ok("NoReturn".to_owned())
+			}
+			Layout::Always => {
+				self.typing.insert("Any");
+				Ok("Any".to_owned())
+			}
+			Layout::Literal(LiteralLayout::Data(layout)) => match layout {
+				DataLayout::Unit(layout) => match const_literal(&layout.const_) {
+					Some(literal) if literal == "None" => Ok(literal),
+					Some(literal) => {
+						self.typing.insert("Literal");
+						Ok(format!("Literal[{literal}]"))
+					}
+					None => {
+						self.typing.insert("Any");
+						Ok("Any".to_owned())
+					}
+				},
+				DataLayout::Boolean(_) => Ok("bool".to_owned()),
+				DataLayout::Number(layout) => {
+					if self.is_integer_datatype(&layout.datatype) {
+						Ok("int".to_owned())
+					} else {
+						Ok("float".to_owned())
+					}
+				}
+				DataLayout::ByteString(_) => Ok("bytes".to_owned()),
+				DataLayout::TextString(_) => Ok("str".to_owned()),
+			},
+			Layout::Literal(LiteralLayout::Id(_)) => Ok("str".to_owned()),
+			Layout::Product(_) => {
+				self.typing.insert("Any");
+				Ok("dict[str, Any]".to_owned())
+			}
+			Layout::Sum(layout) => match layout.variants.as_slice() {
+				[] => {
+					self.typing.insert("NoReturn");
+					Ok("NoReturn".to_owned())
+				}
+				[variant] => self.type_ref(
+					&variant.value.layout,
+					&format!("{hint}{}", PascalCase(&variant.name)),
+					quoted,
+				),
+				variants => {
+					let variants = variants
+						.iter()
+						.map(|v| {
+							let hint = format!("{hint}{}", PascalCase(&v.name));
+							self.type_ref(&v.value.layout, &hint, quoted)
+						})
+						.collect::<Result<Vec<_>, _>>()?;
+
+					self.typing.insert("Union");
+					Ok(format!("Union[{}]", variants.join(", ")))
+				}
+			},
+			Layout::List(ListLayout::Unordered(layout)) => {
+				let item_layout = &layout.item.value.layout;
+				let item = self.type_ref(item_layout, &format!("{hint}Item"), quoted)?;
+				if self.is_hashable(item_layout)? {
+					Ok(format!("set[{item}]"))
+				} else {
+					Ok(format!("list[{item}]"))
+				}
+			}
+			Layout::List(ListLayout::Ordered(layout)) => {
+				let item =
+					self.type_ref(&layout.node.value.layout, &format!("{hint}Item"), quoted)?;
+				Ok(format!("list[{item}]"))
+			}
+			Layout::List(ListLayout::Sized(layout)) => {
+				if layout.items.is_empty() {
+					return Ok("tuple[()]".to_owned());
+				}
+
+				let items = layout
+					.items
+					.iter()
+					.enumerate()
+					.map(|(i, item)| {
+						self.type_ref(&item.value.layout, &format!("{hint}Item{i}"), quoted)
+					})
+					.collect::<Result<Vec<_>, _>>()?;
+
+				Ok(format!("tuple[{}]", items.join(", ")))
+			}
+		}
+	}
+
+	/// Checks if the values of the given layout are hashable in Python, and
+	/// can hence be stored in a `set`.
+	fn is_hashable(
+		&self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Result<bool, Error<I::Resource>> {
+		match self.get(layout_ref)? {
+			Layout::Literal(LiteralLayout::Data(DataLayout::Unit(layout))) => {
+				Ok(const_literal(&layout.const_).is_some())
+			}
+			Layout::Literal(_) => Ok(true),
+			_ => Ok(false),
+		}
+	}
+}
+
+/// Returns the Python attribute name for the given field name.
+fn attribute_name(name: &str) -> String {
+	let mut result: String = name
+		.chars()
+		.map(|c| {
+			if c.is_ascii_alphanumeric() || c == '_' {
+				c
+			} else {
+				'_'
+			}
+		})
+		.collect();
+
+	if result.is_empty() || result.starts_with(|c: char| c.is_ascii_digit() || c == '_') {
+		result.insert_str(0, "field_")
+	}
+
+	if KEYWORDS.contains(&result.as_str()) {
+		result.push('_')
+	}
+
+	result
+}
+
+/// Returns the Python literal corresponding to the given constant, if it can
+/// be used in a `Literal` type.
+fn const_literal(value: &Value) -> Option<String> {
+	match value {
+		Value::Literal(Literal::Unit) => Some("None".to_owned()),
+		Value::Literal(Literal::Boolean(true)) => Some("True".to_owned()),
+		Value::Literal(Literal::Boolean(false)) => Some("False".to_owned()),
+		Value::Literal(Literal::Number(n)) => {
+			n.decimal_representation().filter(|n| !n.contains('.'))
+		}
+		Value::Literal(Literal::TextString(s)) => Some(string_literal(s)),
+		_ => None,
+	}
+}
+
+fn string_literal(s: &str) -> String {
+	let mut result = String::with_capacity(s.len() + 2);
+	result.push('"');
+
+	for c in s.chars() {
+		match c {
+			'"' => result.push_str("\\\""),
+			'\\' => result.push_str("\\\\"),
+			'\n' => result.push_str("\\n"),
+			'\r' => result.push_str("\\r"),
+			'\t' => result.push_str("\\t"),
+			c if c.is_control() => write!(result, "\\u{:04x}", c as u32).unwrap(),
+			c => result.push(c),
+		}
+	}
+
+	result.push('"');
+	result
+}
+
+/// Formats the given text as a docstring with the given indentation.
+fn docstring(text: &str, indent: &str) -> String {
+	let text = text.replace('\\', "\\\\").replace("\"\"\"", "\\\"\\\"\\\"");
+	let mut lines = text.lines();
+	let mut result = format!("{indent}\"\"\"{}", lines.next().unwrap_or_default());
+
+	let mut multiline = false;
+	for line in lines {
+		multiline = true;
+		result.push('\n');
+		if !line.is_empty() {
+			result.push_str(indent);
+			result.push_str(line);
+		}
+	}
+
+	if multiline {
+		write!(result, "\n{indent}").unwrap();
+	}
+
+	result.push_str("\"\"\"\n");
+	result
+}
@@ -0,0 +1,46 @@
+use std::{fs, path::PathBuf};
+use treeldr_gen_py::Model;
+use treeldr_layouts::{layout::LayoutType, LayoutRegistry, Layouts, Prelude, Ref};
+
+fn file_path(id: &str, suffix: &str) -> PathBuf {
+	format!("{}/tests/generate/{id}{suffix}", env!("CARGO_MANIFEST_DIR")).into()
+}
+
+fn load_layout(id: &str) -> (Layouts, Ref<LayoutType>) {
+	let mut builder = treeldr_layouts::abs::Builder::new();
+
+	let raw_json = fs::read_to_string(file_path(id, "-layout.json")).unwrap();
+	let layout: treeldr_layouts::abs::syntax::Layout = serde_json::from_str(&raw_json).unwrap();
+	let layout_ref = layout.build(&mut builder).unwrap();
+
+	(builder.build(), layout_ref)
+}
+
+fn generate(id: &str, model: Model) {
+	let (layouts, layout_ref) = load_layout(id);
+	let options = treeldr_gen_py::Options::new().with_model(model);
+	let output = treeldr_gen_py::generate(
+		Default::default(),
+		layouts.with(Prelude),
+		&layout_ref,
+		&options,
+	)
+	.unwrap();
+
+	let suffix = format!("-{}.py", model.name());
+	let expected = fs::read_to_string(file_path(id, &suffix)).unwrap();
+	if output != expected {
+		eprintln!("output:\n{output}");
+		panic!("output differs from `{id}{suffix}`")
+	}
+}
+
+#[test]
+fn t01_pydantic() {
+	generate("t01", Model::Pydantic)
+}
+
+#[test]
+fn t01_dataclass() {
+	generate("t01", Model::Dataclass)
+}
@@ -0,0 +1,43 @@
+# Generated by TreeLDR. Do not edit.
+from __future__ import annotations
+
+from dataclasses import dataclass, field
+from typing import Literal, NewType, Optional, Union
+
+
+@dataclass
+class Person:
+    """A person.
+
+    Layout `https://example.org/#Person`.
+    """
+
+    name: str
+    """Full name of the person."""
+    address: Optional[PersonAddress] = None
+    age: Optional[int] = None
+    contact: Optional[Contact] = None
+    first_friend: Optional[Person] = field(default=None, metadata={"alias": "first-friend"})
+    tags: Optional[set[str]] = None
+
+
+@dataclass
+class PersonAddress:
+    city: str
+
+
+Contact = Union[str, "Ref"]
+"""Layout `https://example.org/#Contact`.
+
+Variants:
+
+- `email`: `str`
+- `ref`: `Ref`
+"""
+
+ContactVariant = Literal["email", "ref"]
+"""Names of the `Contact` variants."""
+
+
+Ref = NewType("Ref", str)
+"""Layout `https://example.org/#Ref`."""
@@ -0,0 +1,45 @@
+# Generated by TreeLDR. Do not edit.
+from __future__ import annotations
+
+from typing import Literal, NewType, Optional, Union
+
+from pydantic import BaseModel, ConfigDict, Field
+
+
+class Person(BaseModel):
+    """A person.
+
+    Layout `https://example.org/#Person`.
+    """
+
+    model_config = ConfigDict(extra="forbid", populate_by_name=True)
+
+    name: str = Field(description="Full name of the person.")
+    address: Optional[PersonAddress] = None
+    age: Optional[int] = None
+    contact: Optional[Contact] = None
+    first_friend: Optional[Person] = Field(default=None, alias="first-friend")
+    tags: Optional[set[str]] = None
+
+
+class PersonAddress(BaseModel):
+    model_config = ConfigDict(extra="forbid")
+
+    city: str
+
+
+Contact = Union[str, "Ref"]
+"""Layout `https://example.org/#Contact`.
+
+Variants:
+
+- `email`: `str`
+- `ref`: `Ref`
+"""
+
+ContactVariant = Literal["email", "ref"]
+"""Names of the `Contact` variants."""
+
+
+Ref = NewType("Ref", str)
+"""Layout `https://example.org/#Ref`."""
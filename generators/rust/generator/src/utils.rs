@@ -1,17 +1,28 @@
 use core::fmt;
 use iref::Iri;
 
-pub fn ident_from_iri(iri: &Iri) -> Option<syn::Ident> {
-	match iri.fragment() {
-		Some(fragment) => syn::parse_str(PascalCase(fragment).to_string().as_str()).ok(),
-		None => iri
-			.path()
-			.segments()
-			.last()
-			.and_then(|segment| syn::parse_str(PascalCase(segment).to_string().as_str()).ok()),
+/// Returns the type name derived from the given IRI.
+///
+/// The name is the `PascalCase` version of the IRI fragment, or the last
+/// path segment if the IRI has no fragment. The result is not guaranteed to
+/// be a valid identifier in any particular target language.
+pub fn type_name_from_iri(iri: &Iri) -> Option<String> {
+	let name = match iri.fragment() {
+		Some(fragment) => PascalCase(fragment).to_string(),
+		None => PascalCase(iri.path().segments().next_back()?).to_string(),
+	};
+
+	if name.is_empty() {
+		None
+	} else {
+		Some(name)
 	}
 }
 
+pub fn ident_from_iri(iri: &Iri) -> Option<syn::Ident> {
+	type_name_from_iri(iri).and_then(|name| syn::parse_str(name.as_str()).ok())
+}
+
 pub struct PascalCase<T>(pub T);
 
 impl<T: AsRef<str>> fmt::Display for PascalCase<T> {
@@ -228,6 +228,7 @@ pub fn parse(input: syn::DeriveInput) -> Result<ParsedInput, Error> {
 									graph: attrs.graph.unwrap_or_default().into(),
 								}),
 								required,
+								extra: ExtraProperties::default(),
 							};
 
 							Ok((Value::string(name), field))
@@ -273,6 +274,7 @@ pub fn parse(input: syn::DeriveInput) -> Result<ParsedInput, Error> {
 											input: attrs.input.map(Into::into).unwrap_or_default(),
 											graph: attrs.graph.unwrap_or_default().into(),
 										}),
+										extra: ExtraProperties::default(),
 									};
 
 									Ok((name, field))
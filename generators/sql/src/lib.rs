@@ -0,0 +1,267 @@
+//! Relational SQL support for TreeLDR layouts.
+//!
+//! Generates `CREATE TABLE` statements from product layouts, and converts
+//! tree values to and from the rows of those tables.
+//!
+//! Each record gets its own table with an `id` primary key. Nested records
+//! are stored in their own table and referenced through a foreign key.
+//! Unordered and ordered lists are stored in join tables, with a `position`
+//! column for ordered lists. Any other value that does not fit in a single
+//! column (sums, sized lists, nested lists, etc.) is stored as JSON.
+use std::{collections::HashMap, hash::Hash};
+
+use rdf_types::{
+	interpretation::{ReverseIriInterpretation, ReverseLiteralInterpretation},
+	vocabulary::{IriVocabulary, LiteralVocabulary},
+	Term,
+};
+use treeldr_gen_rust::utils::type_name_from_iri;
+use treeldr_layouts::{distill::RdfContext, layout::LayoutType, LayoutRegistry, Ref, Value};
+
+mod rows;
+mod schema;
+
+pub use rows::{FromRowsError, RowSource, Rows, SqlValue, ToRowsError};
+pub use schema::{Column, ColumnType, Schema, Table};
+
+/// SQL reserved words that cannot be used unquoted as table or column names.
+const RESERVED: &[&str] = &[
+	"all",
+	"and",
+	"as",
+	"by",
+	"check",
+	"column",
+	"constraint",
+	"create",
+	"default",
+	"delete",
+	"distinct",
+	"drop",
+	"from",
+	"group",
+	"having",
+	"in",
+	"index",
+	"insert",
+	"into",
+	"is",
+	"join",
+	"key",
+	"limit",
+	"not",
+	"null",
+	"on",
+	"or",
+	"order",
+	"primary",
+	"references",
+	"select",
+	"set",
+	"table",
+	"to",
+	"union",
+	"unique",
+	"update",
+	"user",
+	"values",
+	"where",
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error<R = Term> {
+	#[error("missing type identifier for layout {0}")]
+	MissingTypeIdentifier(R),
+
+	#[error("layout {0} not found")]
+	LayoutNotFound(R),
+
+	#[error("layout {0} is not a record")]
+	NotARecord(R),
+
+	#[error("invalid field name `{0}`")]
+	InvalidFieldName(Value),
+}
+
+/// SQL dialect.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+	/// SQLite.
+	#[default]
+	Sqlite,
+
+	/// PostgreSQL.
+	Postgres,
+}
+
+impl Dialect {
+	pub const POSSIBLE_VALUES: &'static [&'static str] = &["sqlite", "postgres"];
+
+	pub fn new(name: &str) -> Option<Self> {
+		match name {
+			"sqlite" => Some(Self::Sqlite),
+			"postgres" => Some(Self::Postgres),
+			_ => None,
+		}
+	}
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			Self::Sqlite => "sqlite",
+			Self::Postgres => "postgres",
+		}
+	}
+
+	/// Returns the `n`-th (starting from 1) statement parameter placeholder.
+	fn placeholder(&self, n: usize) -> String {
+		match self {
+			Self::Sqlite => "?".to_owned(),
+			Self::Postgres => format!("${n}"),
+		}
+	}
+}
+
+pub struct Options<R = Term> {
+	idents: HashMap<Ref<LayoutType, R>, String>,
+	dialect: Dialect,
+}
+
+impl<R> Options<R> {
+	pub fn new() -> Self {
+		Self {
+			idents: HashMap::new(),
+			dialect: Dialect::default(),
+		}
+	}
+
+	/// Sets the SQL dialect.
+	pub fn with_dialect(self, dialect: Dialect) -> Self {
+		Self { dialect, ..self }
+	}
+
+	/// Sets the table name of the given layout.
+	pub fn set_ident(&mut self, layout_ref: Ref<LayoutType, R>, ident: String)
+	where
+		R: Eq + Hash,
+	{
+		self.idents.insert(layout_ref, ident);
+	}
+
+	pub fn layout_ident<V, I>(
+		&self,
+		rdf: RdfContext<V, I>,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Option<String>
+	where
+		V: IriVocabulary,
+		I: ReverseIriInterpretation<Resource = R, Iri = V::Iri>,
+		R: Eq + Hash,
+	{
+		self.idents
+			.get(layout_ref)
+			.cloned()
+			.or_else(|| default_layout_ident(rdf, layout_ref))
+	}
+}
+
+impl<R> Default for Options<R> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Checks that the given string can be used unquoted as a table or column
+/// name.
+pub fn is_ident(s: &str) -> bool {
+	let mut chars = s.chars();
+	match chars.next() {
+		Some(c) if c.is_ascii_lowercase() || c == '_' => {
+			chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+				&& !RESERVED.contains(&s)
+		}
+		_ => false,
+	}
+}
+
+pub fn default_layout_ident<V, I>(
+	rdf: RdfContext<V, I>,
+	layout_ref: &Ref<LayoutType, I::Resource>,
+) -> Option<String>
+where
+	V: IriVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri>,
+{
+	let mut selected: Option<String> = None;
+
+	for i in rdf.interpretation.iris_of(layout_ref.id()) {
+		let iri = rdf.vocabulary.iri(i).unwrap();
+		if let Some(name) = type_name_from_iri(iri).map(|name| snake_case(&name)) {
+			if !selected.as_ref().is_some_and(|s| *s < name) {
+				selected = Some(name)
+			}
+		}
+	}
+
+	selected
+}
+
+/// Converts the given name into `snake_case`, keeping only ASCII
+/// alphanumeric characters.
+fn snake_case(name: &str) -> String {
+	let mut result = String::with_capacity(name.len());
+	let mut prev_lower = false;
+
+	for c in name.chars() {
+		if c.is_ascii_alphanumeric() {
+			if c.is_ascii_uppercase() && prev_lower {
+				result.push('_')
+			}
+			prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+			result.push(c.to_ascii_lowercase())
+		} else {
+			if !result.is_empty() && !result.ends_with('_') {
+				result.push('_')
+			}
+			prev_lower = false
+		}
+	}
+
+	while result.ends_with('_') {
+		result.pop();
+	}
+
+	if !result.starts_with(|c: char| c.is_ascii_lowercase()) {
+		result.insert(0, '_')
+	}
+
+	result
+}
+
+/// Returns the given table or column name, quoted if necessary.
+fn quote(name: &str) -> String {
+	if is_ident(name) {
+		name.to_owned()
+	} else {
+		format!("\"{}\"", name.replace('"', "\"\""))
+	}
+}
+
+/// Generates the `CREATE TABLE` statements of the given record layout and all
+/// its dependencies.
+///
+/// Tables are ordered so that referenced tables are created first, except
+/// for cyclic references.
+pub fn generate<V, I>(
+	rdf: RdfContext<V, I>,
+	layouts: impl LayoutRegistry<I::Resource>,
+	layout_ref: &Ref<LayoutType, I::Resource>,
+	options: &Options<I::Resource>,
+) -> Result<String, Error<I::Resource>>
+where
+	V: IriVocabulary + LiteralVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri> + ReverseLiteralInterpretation<Literal = V::Literal>,
+	I::Resource: Clone + Ord + Hash,
+{
+	let schema = Schema::new(rdf, layouts, layout_ref, options)?;
+	Ok(schema.to_ddl())
+}
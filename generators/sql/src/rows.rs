@@ -0,0 +1,446 @@
+use std::collections::BTreeMap;
+
+use treeldr_layouts::{
+	value::{NativeNumber, Number},
+	Literal, Value,
+};
+
+use crate::schema::{ColumnType, Schema, Slot, Table, TableKind};
+
+/// SQL value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+	Null,
+	Boolean(bool),
+	Integer(i64),
+	Real(f64),
+	Text(String),
+	Blob(Vec<u8>),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ToRowsError {
+	#[error("expected a record for table `{0}`")]
+	ExpectedRecord(String),
+
+	#[error("expected a list for table `{0}`")]
+	ExpectedList(String),
+
+	#[error("missing required field `{1}` for table `{0}`")]
+	MissingField(String, String),
+
+	#[error("unexpected field `{1}` for table `{0}`")]
+	UnexpectedField(String, String),
+
+	#[error("invalid value for column `{1}` of table `{0}`")]
+	InvalidValue(String, String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FromRowsError<E> {
+	#[error(transparent)]
+	Source(E),
+
+	#[error("missing row {1} in table `{0}`")]
+	MissingRow(String, i64),
+
+	#[error("missing value for column `{1}` of table `{0}`")]
+	MissingValue(String, String),
+
+	#[error("invalid value for column `{1}` of table `{0}`")]
+	InvalidValue(String, String),
+}
+
+/// Rows, in insertion order.
+///
+/// Rows are ordered so that referenced rows are inserted first.
+#[derive(Debug, Default, Clone)]
+pub struct Rows(Vec<(String, Vec<SqlValue>)>);
+
+impl Rows {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn len(&self) -> usize {
+		self.0.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
+	/// Returns an iterator over the table name and values of each row.
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &[SqlValue])> {
+		self.0
+			.iter()
+			.map(|(table, values)| (table.as_str(), values.as_slice()))
+	}
+}
+
+/// Source of rows, typically a database.
+///
+/// Rows are given with the same columns as the table, in the same order.
+pub trait RowSource {
+	type Error;
+
+	/// Returns the row of the given record table with the given `id`, if any.
+	///
+	/// This is the result of the table
+	/// [select statement](Table::select_statement).
+	fn row(&mut self, table: &Table, id: i64) -> Result<Option<Vec<SqlValue>>, Self::Error>;
+
+	/// Returns the rows of the given join table owned by `owner`.
+	///
+	/// This is the result of the table
+	/// [select statement](Table::select_statement).
+	fn join_rows(&mut self, table: &Table, owner: i64) -> Result<Vec<Vec<SqlValue>>, Self::Error>;
+}
+
+impl RowSource for Rows {
+	type Error = std::convert::Infallible;
+
+	fn row(&mut self, table: &Table, id: i64) -> Result<Option<Vec<SqlValue>>, Self::Error> {
+		Ok(self
+			.iter()
+			.find(|(name, values)| *name == table.name() && values[0] == SqlValue::Integer(id))
+			.map(|(_, values)| values.to_vec()))
+	}
+
+	fn join_rows(&mut self, table: &Table, owner: i64) -> Result<Vec<Vec<SqlValue>>, Self::Error> {
+		Ok(self
+			.iter()
+			.filter(|(name, values)| *name == table.name() && values[0] == SqlValue::Integer(owner))
+			.map(|(_, values)| values.to_vec())
+			.collect())
+	}
+}
+
+impl Schema {
+	/// Returns the table with the given name.
+	pub fn table(&self, name: &str) -> Option<&Table> {
+		self.tables.iter().find(|t| t.name == name)
+	}
+
+	/// Converts the given record into rows, and returns them along with the
+	/// `id` of the root record.
+	///
+	/// Record identifiers are allocated sequentially in each table, starting
+	/// from 1.
+	pub fn to_rows(&self, value: &Value) -> Result<(i64, Rows), ToRowsError> {
+		let mut next_ids = vec![0; self.tables.len()];
+		self.to_rows_with(value, |table| {
+			let i = self
+				.tables
+				.iter()
+				.position(|t| t.name == table.name)
+				.unwrap();
+			next_ids[i] += 1;
+			next_ids[i]
+		})
+	}
+
+	/// Converts the given record into rows, using the given function to
+	/// allocate record identifiers, and returns them along with the `id` of the
+	/// root record.
+	pub fn to_rows_with(
+		&self,
+		value: &Value,
+		mut next_id: impl FnMut(&Table) -> i64,
+	) -> Result<(i64, Rows), ToRowsError> {
+		let mut rows = Rows::new();
+		let id = self.insert_record(self.root, value, &mut next_id, &mut rows)?;
+		Ok((id, rows))
+	}
+
+	fn insert_record(
+		&self,
+		index: usize,
+		value: &Value,
+		next_id: &mut impl FnMut(&Table) -> i64,
+		rows: &mut Rows,
+	) -> Result<i64, ToRowsError> {
+		let table = &self.tables[index];
+		let TableKind::Record(fields) = &table.kind else {
+			unreachable!()
+		};
+
+		let Value::Map(entries) = value else {
+			return Err(ToRowsError::ExpectedRecord(table.name.clone()));
+		};
+
+		if let Some(key) = entries
+			.keys()
+			.find(|key| !fields.iter().any(|f| key.as_str() == Some(&f.key)))
+		{
+			return Err(ToRowsError::UnexpectedField(
+				table.name.clone(),
+				key.to_string(),
+			));
+		}
+
+		let id = next_id(table);
+		let mut values = vec![SqlValue::Null; table.columns.len()];
+		values[0] = SqlValue::Integer(id);
+
+		let mut lists = Vec::new();
+		for field in fields {
+			let Some(value) = entries.get(&Value::string(field.key.clone())) else {
+				if field.required {
+					return Err(ToRowsError::MissingField(
+						table.name.clone(),
+						field.key.clone(),
+					));
+				}
+
+				continue;
+			};
+
+			match field.slot {
+				Slot::Column(c) => values[c] = self.encode(table, c, value)?,
+				Slot::Record(c, t) => {
+					let child = self.insert_record(t, value, next_id, rows)?;
+					values[c] = SqlValue::Integer(child)
+				}
+				Slot::List(t) => lists.push((t, value)),
+			}
+		}
+
+		rows.0.push((table.name.clone(), values));
+
+		for (t, value) in lists {
+			self.insert_items(t, id, value, next_id, rows)?
+		}
+
+		Ok(id)
+	}
+
+	fn insert_items(
+		&self,
+		index: usize,
+		owner: i64,
+		value: &Value,
+		next_id: &mut impl FnMut(&Table) -> i64,
+		rows: &mut Rows,
+	) -> Result<(), ToRowsError> {
+		let table = &self.tables[index];
+		let TableKind::Join { ordered, item } = &table.kind else {
+			unreachable!()
+		};
+
+		let Value::List(items) = value else {
+			return Err(ToRowsError::ExpectedList(table.name.clone()));
+		};
+
+		for (position, item_value) in items.iter().enumerate() {
+			let mut values = vec![SqlValue::Integer(owner)];
+
+			if *ordered {
+				values.push(SqlValue::Integer(position as i64))
+			}
+
+			match *item {
+				Slot::Column(c) => values.push(self.encode(table, c, item_value)?),
+				Slot::Record(_, t) => {
+					let child = self.insert_record(t, item_value, next_id, rows)?;
+					values.push(SqlValue::Integer(child))
+				}
+				Slot::List(_) => unreachable!(),
+			}
+
+			rows.0.push((table.name.clone(), values))
+		}
+
+		Ok(())
+	}
+
+	/// Encodes the value of the given column.
+	fn encode(&self, table: &Table, column: usize, value: &Value) -> Result<SqlValue, ToRowsError> {
+		let invalid =
+			|| ToRowsError::InvalidValue(table.name.clone(), table.columns[column].name.clone());
+
+		match (table.columns[column].ty, value) {
+			(ColumnType::Boolean, Value::Literal(Literal::Boolean(b))) => Ok(SqlValue::Boolean(*b)),
+			(ColumnType::Integer, Value::Literal(Literal::Number(n))) => match n.as_native() {
+				NativeNumber::I64(n) => Ok(SqlValue::Integer(n)),
+				NativeNumber::U64(n) => n.try_into().map(SqlValue::Integer).map_err(|_| invalid()),
+				NativeNumber::F64(_) => Err(invalid()),
+			},
+			(ColumnType::Real, Value::Literal(Literal::Number(n))) => {
+				Ok(SqlValue::Real(n.to_f64()))
+			}
+			(ColumnType::Text, Value::Literal(Literal::TextString(s))) => {
+				Ok(SqlValue::Text(s.clone()))
+			}
+			(ColumnType::Blob, Value::Literal(Literal::ByteString(b))) => {
+				Ok(SqlValue::Blob(b.clone()))
+			}
+			(ColumnType::Json, value) => {
+				let json: serde_json::Value = value.clone().try_into().map_err(|_| invalid())?;
+				Ok(SqlValue::Text(json.to_string()))
+			}
+			_ => Err(invalid()),
+		}
+	}
+
+	/// Rebuilds the record with the given `id` in the root table from the
+	/// given rows.
+	///
+	/// Absent optional lists cannot be distinguished from empty ones: join
+	/// tables without rows for optional fields are omitted.
+	pub fn from_rows<S: RowSource>(
+		&self,
+		id: i64,
+		source: &mut S,
+	) -> Result<Value, FromRowsError<S::Error>> {
+		self.read_record(self.root, id, source)
+	}
+
+	fn read_record<S: RowSource>(
+		&self,
+		index: usize,
+		id: i64,
+		source: &mut S,
+	) -> Result<Value, FromRowsError<S::Error>> {
+		let table = &self.tables[index];
+		let TableKind::Record(fields) = &table.kind else {
+			unreachable!()
+		};
+
+		let values = source
+			.row(table, id)
+			.map_err(FromRowsError::Source)?
+			.ok_or_else(|| FromRowsError::MissingRow(table.name.clone(), id))?;
+
+		let mut entries = BTreeMap::new();
+		for field in fields {
+			let value = match field.slot {
+				Slot::Column(c) => self.decode(table, c, &values[c])?,
+				Slot::Record(c, t) => match &values[c] {
+					SqlValue::Null => None,
+					SqlValue::Integer(child) => Some(self.read_record(t, *child, source)?),
+					_ => {
+						return Err(FromRowsError::InvalidValue(
+							table.name.clone(),
+							table.columns[c].name.clone(),
+						))
+					}
+				},
+				Slot::List(t) => self.read_items(t, id, field.required, source)?,
+			};
+
+			match value {
+				Some(value) => {
+					entries.insert(Value::string(field.key.clone()), value);
+				}
+				None if field.required => {
+					let column = match field.slot {
+						Slot::Column(c) | Slot::Record(c, _) => table.columns[c].name.clone(),
+						Slot::List(_) => unreachable!(),
+					};
+
+					return Err(FromRowsError::MissingValue(table.name.clone(), column));
+				}
+				None => (),
+			}
+		}
+
+		Ok(Value::Map(entries))
+	}
+
+	fn read_items<S: RowSource>(
+		&self,
+		index: usize,
+		owner: i64,
+		required: bool,
+		source: &mut S,
+	) -> Result<Option<Value>, FromRowsError<S::Error>> {
+		let table = &self.tables[index];
+		let TableKind::Join { ordered, item } = &table.kind else {
+			unreachable!()
+		};
+
+		let mut rows = source
+			.join_rows(table, owner)
+			.map_err(FromRowsError::Source)?;
+
+		if rows.is_empty() && !required {
+			return Ok(None);
+		}
+
+		if *ordered {
+			let mut positioned = Vec::with_capacity(rows.len());
+			for row in rows {
+				match row[1] {
+					SqlValue::Integer(position) => positioned.push((position, row)),
+					_ => {
+						return Err(FromRowsError::InvalidValue(
+							table.name.clone(),
+							table.columns[1].name.clone(),
+						))
+					}
+				}
+			}
+
+			positioned.sort_by_key(|(position, _)| *position);
+			rows = positioned.into_iter().map(|(_, row)| row).collect();
+		}
+
+		let mut items = Vec::with_capacity(rows.len());
+		for row in rows {
+			let value = match *item {
+				Slot::Column(c) => self.decode(table, c, &row[c])?,
+				Slot::Record(c, t) => match &row[c] {
+					SqlValue::Integer(child) => Some(self.read_record(t, *child, source)?),
+					_ => None,
+				},
+				Slot::List(_) => unreachable!(),
+			};
+
+			let c = match *item {
+				Slot::Column(c) | Slot::Record(c, _) => c,
+				Slot::List(_) => unreachable!(),
+			};
+
+			items.push(value.ok_or_else(|| {
+				FromRowsError::MissingValue(table.name.clone(), table.columns[c].name.clone())
+			})?)
+		}
+
+		Ok(Some(Value::List(items)))
+	}
+
+	/// Decodes the value of the given column, or returns `None` if it is
+	/// `NULL`.
+	fn decode<E>(
+		&self,
+		table: &Table,
+		column: usize,
+		value: &SqlValue,
+	) -> Result<Option<Value>, FromRowsError<E>> {
+		let invalid =
+			|| FromRowsError::InvalidValue(table.name.clone(), table.columns[column].name.clone());
+
+		let literal = match (table.columns[column].ty, value) {
+			(_, SqlValue::Null) => return Ok(None),
+			(ColumnType::Boolean, SqlValue::Boolean(b)) => Literal::Boolean(*b),
+			(ColumnType::Boolean, SqlValue::Integer(0)) => Literal::Boolean(false),
+			(ColumnType::Boolean, SqlValue::Integer(1)) => Literal::Boolean(true),
+			(ColumnType::Integer | ColumnType::Real, SqlValue::Integer(i)) => {
+				Literal::Number((*i).into())
+			}
+			(ColumnType::Real, SqlValue::Real(f)) => {
+				Literal::Number(Number::try_from(*f).map_err(|_| invalid())?)
+			}
+			(ColumnType::Text, SqlValue::Text(s)) => Literal::TextString(s.clone()),
+			(ColumnType::Blob, SqlValue::Blob(b)) => Literal::ByteString(b.clone()),
+			(ColumnType::Json, SqlValue::Text(s)) => {
+				let json: serde_json::Value = serde_json::from_str(s).map_err(|_| invalid())?;
+				return Ok(Some(json.into()));
+			}
+			_ => return Err(invalid()),
+		};
+
+		Ok(Some(Value::Literal(literal)))
+	}
+}
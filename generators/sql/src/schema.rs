@@ -0,0 +1,622 @@
+use std::{
+	collections::{BTreeMap, HashMap, HashSet},
+	fmt::Write,
+	hash::Hash,
+};
+
+use rdf_types::{
+	interpretation::{ReverseIriInterpretation, ReverseLiteralInterpretation},
+	vocabulary::{IriVocabulary, LiteralVocabulary},
+	RDFS_COMMENT,
+};
+use treeldr_layouts::{
+	distill::RdfContext,
+	layout::{DataLayout, LayoutType, ListLayout, LiteralLayout},
+	Layout, LayoutRegistry, Literal, PresetLayout, Ref, Value,
+};
+
+use crate::{quote, snake_case, Dialect, Error, Options};
+
+/// Relational schema derived from a record layout.
+///
+/// This is the common ground between the generated `CREATE TABLE`
+/// statements and the row mapping.
+pub struct Schema {
+	pub(crate) dialect: Dialect,
+	pub(crate) tables: Vec<Table>,
+	pub(crate) root: usize,
+}
+
+/// Table.
+pub struct Table {
+	pub(crate) name: String,
+	pub(crate) description: Option<String>,
+	pub(crate) columns: Vec<Column>,
+	pub(crate) kind: TableKind,
+}
+
+/// Table column.
+pub struct Column {
+	pub(crate) name: String,
+	pub(crate) ty: ColumnType,
+	pub(crate) not_null: bool,
+
+	/// Referenced table, if this is a foreign key.
+	pub(crate) references: Option<usize>,
+}
+
+/// Column type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+	Boolean,
+	Integer,
+	Real,
+	Text,
+	Blob,
+
+	/// JSON document.
+	Json,
+}
+
+impl ColumnType {
+	pub fn name(&self, dialect: Dialect) -> &'static str {
+		match (self, dialect) {
+			(Self::Boolean, _) => "BOOLEAN",
+			(Self::Integer, Dialect::Sqlite) => "INTEGER",
+			(Self::Integer, Dialect::Postgres) => "BIGINT",
+			(Self::Real, Dialect::Sqlite) => "REAL",
+			(Self::Real, Dialect::Postgres) => "DOUBLE PRECISION",
+			(Self::Text, _) => "TEXT",
+			(Self::Blob, Dialect::Sqlite) => "BLOB",
+			(Self::Blob, Dialect::Postgres) => "BYTEA",
+			(Self::Json, Dialect::Sqlite) => "TEXT",
+			(Self::Json, Dialect::Postgres) => "JSONB",
+		}
+	}
+}
+
+pub(crate) enum TableKind {
+	/// Record table, where the first column is the `id` primary key.
+	Record(Vec<Field>),
+
+	/// Join table storing the items of a list, where the first column is the
+	/// owner foreign key, followed by the `position` column if the list is
+	/// ordered, then the item column.
+	Join { ordered: bool, item: Slot },
+}
+
+pub(crate) struct Field {
+	/// Entry key in the tree value.
+	pub key: String,
+	pub required: bool,
+	pub slot: Slot,
+}
+
+/// Where a value is stored.
+#[derive(Clone, Copy)]
+pub(crate) enum Slot {
+	/// In the given column.
+	Column(usize),
+
+	/// In the given table, referenced by the given foreign key column.
+	Record(usize, usize),
+
+	/// In the given join table.
+	List(usize),
+}
+
+impl Table {
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn columns(&self) -> &[Column] {
+		&self.columns
+	}
+
+	/// Checks if this is a join table, storing the items of a list.
+	pub fn is_join_table(&self) -> bool {
+		matches!(self.kind, TableKind::Join { .. })
+	}
+
+	/// Returns the statement inserting a row in this table, taking one
+	/// parameter per column.
+	pub fn insert_statement(&self, dialect: Dialect) -> String {
+		let names = self
+			.columns
+			.iter()
+			.map(|c| quote(&c.name))
+			.collect::<Vec<_>>();
+		let params = self
+			.columns
+			.iter()
+			.enumerate()
+			.map(|(i, c)| match (c.ty, dialect) {
+				(ColumnType::Json, Dialect::Postgres) => {
+					format!("{}::jsonb", dialect.placeholder(i + 1))
+				}
+				_ => dialect.placeholder(i + 1),
+			})
+			.collect::<Vec<_>>();
+
+		format!(
+			"INSERT INTO {} ({}) VALUES ({})",
+			quote(&self.name),
+			names.join(", "),
+			params.join(", ")
+		)
+	}
+
+	/// Returns the statement selecting the rows of this table by `id`, or by
+	/// owner for join tables, taking a single parameter.
+	///
+	/// Selected columns are the same as the ones given to the
+	/// [insert statement](Self::insert_statement).
+	pub fn select_statement(&self, dialect: Dialect) -> String {
+		let names = self
+			.columns
+			.iter()
+			.map(|c| match (c.ty, dialect) {
+				(ColumnType::Json, Dialect::Postgres) => {
+					let name = quote(&c.name);
+					format!("{name}::text AS {name}")
+				}
+				_ => quote(&c.name),
+			})
+			.collect::<Vec<_>>();
+
+		let mut result = format!(
+			"SELECT {} FROM {} WHERE {} = {}",
+			names.join(", "),
+			quote(&self.name),
+			quote(&self.columns[0].name),
+			dialect.placeholder(1)
+		);
+
+		if let TableKind::Join { ordered: true, .. } = self.kind {
+			write!(result, " ORDER BY {}", quote(&self.columns[1].name)).unwrap();
+		}
+
+		result
+	}
+}
+
+impl Column {
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn ty(&self) -> ColumnType {
+		self.ty
+	}
+
+	pub fn is_not_null(&self) -> bool {
+		self.not_null
+	}
+}
+
+impl Schema {
+	/// Builds the relational schema of the given record layout and all its
+	/// dependencies.
+	pub fn new<V, I>(
+		rdf: RdfContext<V, I>,
+		layouts: impl LayoutRegistry<I::Resource>,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+		options: &Options<I::Resource>,
+	) -> Result<Self, Error<I::Resource>>
+	where
+		V: IriVocabulary + LiteralVocabulary,
+		I: ReverseIriInterpretation<Iri = V::Iri>
+			+ ReverseLiteralInterpretation<Literal = V::Literal>,
+		I::Resource: Clone + Ord + Hash,
+	{
+		let ident = options
+			.layout_ident(rdf, layout_ref)
+			.ok_or_else(|| Error::MissingTypeIdentifier(layout_ref.id().clone()))?;
+
+		let mut builder = Builder {
+			rdf,
+			layouts: &layouts,
+			options,
+			tables: HashMap::new(),
+			taken: HashSet::new(),
+			result: Vec::new(),
+		};
+
+		let root = builder.record_table(layout_ref, ident)?;
+
+		Ok(Self {
+			dialect: options.dialect,
+			tables: builder.result,
+			root,
+		})
+	}
+
+	pub fn dialect(&self) -> Dialect {
+		self.dialect
+	}
+
+	pub fn tables(&self) -> &[Table] {
+		&self.tables
+	}
+
+	/// Returns the table of the root record.
+	pub fn root_table(&self) -> &Table {
+		&self.tables[self.root]
+	}
+
+	/// Returns the indexes of the tables in creation order: referenced tables
+	/// first, except for cyclic references.
+	pub(crate) fn creation_order(&self) -> Vec<usize> {
+		fn visit(schema: &Schema, i: usize, visited: &mut Vec<bool>, result: &mut Vec<usize>) {
+			if !visited[i] {
+				visited[i] = true;
+				for column in &schema.tables[i].columns {
+					if let Some(j) = column.references {
+						visit(schema, j, visited, result)
+					}
+				}
+				result.push(i)
+			}
+		}
+
+		let mut visited = vec![false; self.tables.len()];
+		let mut result = Vec::with_capacity(self.tables.len());
+		for i in 0..self.tables.len() {
+			visit(self, i, &mut visited, &mut result)
+		}
+
+		result
+	}
+
+	/// Returns the `CREATE TABLE` statements of this schema.
+	pub fn to_ddl(&self) -> String {
+		let mut output = String::new();
+		output.push_str("-- Generated by TreeLDR. Do not edit.\n");
+
+		for i in self.creation_order() {
+			let table = &self.tables[i];
+			output.push('\n');
+
+			if let Some(description) = &table.description {
+				for line in description.lines() {
+					if line.is_empty() {
+						output.push_str("--\n")
+					} else {
+						writeln!(output, "-- {line}").unwrap()
+					}
+				}
+			}
+
+			writeln!(output, "CREATE TABLE {} (", quote(&table.name)).unwrap();
+
+			let mut definitions = Vec::new();
+			for (i, column) in table.columns.iter().enumerate() {
+				let mut def = format!("{} {}", quote(&column.name), column.ty.name(self.dialect));
+
+				if i == 0 && !table.is_join_table() {
+					def.push_str(" PRIMARY KEY")
+				} else if column.not_null {
+					def.push_str(" NOT NULL")
+				}
+
+				if let Some(j) = column.references {
+					write!(def, " REFERENCES {} (id)", quote(&self.tables[j].name)).unwrap();
+				}
+
+				definitions.push(def)
+			}
+
+			if table.is_join_table() {
+				let key = &table.columns[..2];
+				definitions.push(format!(
+					"PRIMARY KEY ({}, {})",
+					quote(&key[0].name),
+					quote(&key[1].name)
+				))
+			}
+
+			for (i, def) in definitions.iter().enumerate() {
+				let sep = if i + 1 < definitions.len() { "," } else { "" };
+				writeln!(output, "  {def}{sep}").unwrap();
+			}
+
+			output.push_str(");\n");
+		}
+
+		output
+	}
+}
+
+struct Builder<'a, V, I: ReverseIriInterpretation, L> {
+	rdf: RdfContext<'a, V, I>,
+	layouts: &'a L,
+	options: &'a Options<I::Resource>,
+	tables: HashMap<Ref<LayoutType, I::Resource>, usize>,
+	taken: HashSet<String>,
+	result: Vec<Table>,
+}
+
+impl<'a, V, I, L> Builder<'a, V, I, L>
+where
+	V: IriVocabulary + LiteralVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri> + ReverseLiteralInterpretation<Literal = V::Literal>,
+	I::Resource: Clone + Ord + Hash,
+	L: LayoutRegistry<I::Resource>,
+{
+	fn get(
+		&self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Result<&'a Layout<I::Resource>, Error<I::Resource>> {
+		self.layouts
+			.get(layout_ref)
+			.ok_or_else(|| Error::LayoutNotFound(layout_ref.id().clone()))
+	}
+
+	/// Adds a new table, with a fresh name based on the given identifier,
+	/// and returns its index.
+	fn add_table(&mut self, ident: &str, kind: TableKind) -> usize {
+		let mut name = ident.to_owned();
+		let mut i = 1;
+		while self.taken.contains(&name) {
+			i += 1;
+			name = format!("{ident}_{i}");
+		}
+		self.taken.insert(name.clone());
+
+		let index = self.result.len();
+		self.result.push(Table {
+			name,
+			description: None,
+			columns: Vec::new(),
+			kind,
+		});
+		index
+	}
+
+	fn preset(&self, layout_ref: &Ref<LayoutType, I::Resource>) -> Option<PresetLayout> {
+		self.rdf
+			.interpretation
+			.iris_of(layout_ref.id())
+			.find_map(|i| PresetLayout::from_iri(self.rdf.vocabulary.iri(i).unwrap()))
+	}
+
+	fn iri(&self, resource: &I::Resource) -> Option<String> {
+		self.rdf
+			.interpretation
+			.iris_of(resource)
+			.next()
+			.map(|i| self.rdf.vocabulary.iri(i).unwrap().to_string())
+	}
+
+	fn is_integer_datatype(&self, datatype: &I::Resource) -> bool {
+		self.rdf.interpretation.iris_of(datatype).any(|i| {
+			xsd_types::IntegerDatatype::from_iri(self.rdf.vocabulary.iri(i).unwrap()).is_some()
+		})
+	}
+
+	/// Returns the `rdfs:comment` found in the given extra properties, if any.
+	fn comment(&self, properties: &BTreeMap<I::Resource, I::Resource>) -> Option<String> {
+		properties.iter().find_map(|(prop, value)| {
+			let is_comment = self
+				.rdf
+				.interpretation
+				.iris_of(prop)
+				.any(|i| self.rdf.vocabulary.iri(i).unwrap() == RDFS_COMMENT);
+
+			if is_comment {
+				self.rdf
+					.interpretation
+					.literals_of(value)
+					.find_map(|l| self.rdf.vocabulary.literal(l))
+					.map(|l| l.value.to_owned())
+			} else {
+				None
+			}
+		})
+	}
+
+	/// Returns the table of the given record layout, declaring it first if
+	/// necessary.
+	fn record_table(
+		&mut self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+		hint: String,
+	) -> Result<usize, Error<I::Resource>> {
+		if let Some(i) = self.tables.get(layout_ref) {
+			return Ok(*i);
+		}
+
+		let Layout::Product(layout) = self.get(layout_ref)? else {
+			return Err(Error::NotARecord(layout_ref.id().clone()));
+		};
+
+		let ident = self
+			.options
+			.layout_ident(self.rdf, layout_ref)
+			.unwrap_or(hint);
+		let index = self.add_table(&ident, TableKind::Record(Vec::new()));
+		self.tables.insert(layout_ref.clone(), index);
+
+		let mut description = Vec::new();
+		if let Some(comment) = self.comment(&layout.extra_properties) {
+			description.push(comment)
+		}
+		if let Some(iri) = self.iri(layout_ref.id()) {
+			description.push(format!("Layout `{iri}`."))
+		}
+
+		let mut columns = vec![Column {
+			name: "id".to_owned(),
+			ty: ColumnType::Integer,
+			not_null: true,
+			references: None,
+		}];
+		let mut column_names: HashSet<String> = HashSet::from(["id".to_owned()]);
+		let mut fresh_column = |base: String| {
+			let mut name = base.clone();
+			let mut i = 1;
+			while !column_names.insert(name.clone()) {
+				i += 1;
+				name = format!("{base}_{i}");
+			}
+			name
+		};
+
+		let table_name = self.result[index].name.clone();
+		let mut fields = Vec::new();
+		for (key, field) in &layout.fields {
+			let key = match key {
+				Value::Literal(Literal::TextString(key)) => key,
+				other => return Err(Error::InvalidFieldName(other.clone())),
+			};
+
+			let field_layout = &field.value.layout;
+			let base = snake_case(key);
+			let slot = match self.get(field_layout)? {
+				Layout::Product(_) if self.preset(field_layout).is_none() => {
+					let table = self.record_table(field_layout, format!("{table_name}_{base}"))?;
+					columns.push(Column {
+						name: fresh_column(format!("{base}_id")),
+						ty: ColumnType::Integer,
+						not_null: field.required,
+						references: Some(table),
+					});
+					Slot::Record(columns.len() - 1, table)
+				}
+				Layout::List(ListLayout::Unordered(list)) => {
+					let item = &list.item.value.layout;
+					Slot::List(self.join_table(
+						index,
+						format!("{table_name}_{base}"),
+						item,
+						false,
+					)?)
+				}
+				Layout::List(ListLayout::Ordered(list)) => {
+					let item = &list.node.value.layout;
+					Slot::List(self.join_table(
+						index,
+						format!("{table_name}_{base}"),
+						item,
+						true,
+					)?)
+				}
+				_ => {
+					columns.push(Column {
+						name: fresh_column(base),
+						ty: self.column_type(field_layout)?,
+						not_null: field.required,
+						references: None,
+					});
+					Slot::Column(columns.len() - 1)
+				}
+			};
+
+			fields.push(Field {
+				key: key.clone(),
+				required: field.required,
+				slot,
+			})
+		}
+
+		let table = &mut self.result[index];
+		table.description = (!description.is_empty()).then(|| description.join("\n\n"));
+		table.columns = columns;
+		table.kind = TableKind::Record(fields);
+		Ok(index)
+	}
+
+	/// Declares the join table storing the items of a list owned by the given
+	/// table.
+	fn join_table(
+		&mut self,
+		owner: usize,
+		ident: String,
+		item_layout: &Ref<LayoutType, I::Resource>,
+		ordered: bool,
+	) -> Result<usize, Error<I::Resource>> {
+		let index = self.add_table(
+			&ident,
+			TableKind::Join {
+				ordered,
+				item: Slot::Column(0),
+			},
+		);
+
+		let mut columns = vec![Column {
+			name: "owner_id".to_owned(),
+			ty: ColumnType::Integer,
+			not_null: true,
+			references: Some(owner),
+		}];
+
+		if ordered {
+			columns.push(Column {
+				name: "position".to_owned(),
+				ty: ColumnType::Integer,
+				not_null: true,
+				references: None,
+			})
+		}
+
+		let item = match self.get(item_layout)? {
+			Layout::Product(_) if self.preset(item_layout).is_none() => {
+				let table = self.record_table(item_layout, format!("{ident}_item"))?;
+				columns.push(Column {
+					name: "value_id".to_owned(),
+					ty: ColumnType::Integer,
+					not_null: true,
+					references: Some(table),
+				});
+				Slot::Record(columns.len() - 1, table)
+			}
+			_ => {
+				columns.push(Column {
+					name: "value".to_owned(),
+					ty: self.column_type(item_layout)?,
+					not_null: true,
+					references: None,
+				});
+				Slot::Column(columns.len() - 1)
+			}
+		};
+
+		let table = &mut self.result[index];
+		table.columns = columns;
+		table.kind = TableKind::Join { ordered, item };
+		Ok(index)
+	}
+
+	/// Returns the type of the column storing a value of the given layout.
+	fn column_type(
+		&self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Result<ColumnType, Error<I::Resource>> {
+		match self.preset(layout_ref) {
+			None => (),
+			Some(PresetLayout::Unit) => return Ok(ColumnType::Json),
+			Some(PresetLayout::Boolean) => return Ok(ColumnType::Boolean),
+			Some(PresetLayout::Id | PresetLayout::String) => return Ok(ColumnType::Text),
+			Some(_) => return Ok(ColumnType::Integer),
+		}
+
+		match self.get(layout_ref)? {
+			Layout::Literal(LiteralLayout::Data(layout)) => match layout {
+				DataLayout::Unit(_) => Ok(ColumnType::Json),
+				DataLayout::Boolean(_) => Ok(ColumnType::Boolean),
+				DataLayout::Number(layout) => {
+					if self.is_integer_datatype(&layout.datatype) {
+						Ok(ColumnType::Integer)
+					} else {
+						Ok(ColumnType::Real)
+					}
+				}
+				DataLayout::ByteString(_) => Ok(ColumnType::Blob),
+				DataLayout::TextString(_) => Ok(ColumnType::Text),
+			},
+			Layout::Literal(LiteralLayout::Id(_)) => Ok(ColumnType::Text),
+			_ => Ok(ColumnType::Json),
+		}
+	}
+}
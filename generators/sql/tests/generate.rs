@@ -0,0 +1,48 @@
+use std::{fs, path::PathBuf};
+use treeldr_gen_sql::Dialect;
+use treeldr_layouts::{layout::LayoutType, LayoutRegistry, Layouts, Prelude, Ref};
+
+fn file_path(id: &str, suffix: &str) -> PathBuf {
+	format!("{}/tests/generate/{id}{suffix}", env!("CARGO_MANIFEST_DIR")).into()
+}
+
+fn load_layout(id: &str) -> (Layouts, Ref<LayoutType>) {
+	let mut builder = treeldr_layouts::abs::Builder::new();
+
+	let raw_json = fs::read_to_string(file_path(id, "-layout.json")).unwrap();
+	let layout: treeldr_layouts::abs::syntax::Layout = serde_json::from_str(&raw_json).unwrap();
+	let layout_ref = layout.build(&mut builder).unwrap();
+
+	(builder.build(), layout_ref)
+}
+
+fn generate(id: &str, suffix: &str, options: treeldr_gen_sql::Options) {
+	let (layouts, layout_ref) = load_layout(id);
+	let output = treeldr_gen_sql::generate(
+		Default::default(),
+		layouts.with(Prelude),
+		&layout_ref,
+		&options,
+	)
+	.unwrap();
+
+	let expected = fs::read_to_string(file_path(id, suffix)).unwrap();
+	if output != expected {
+		eprintln!("output:\n{output}");
+		panic!("output differs from `{id}{suffix}`")
+	}
+}
+
+#[test]
+fn t01_sqlite() {
+	generate("t01", "-sqlite.sql", treeldr_gen_sql::Options::new())
+}
+
+#[test]
+fn t01_postgres() {
+	generate(
+		"t01",
+		"-postgres.sql",
+		treeldr_gen_sql::Options::new().with_dialect(Dialect::Postgres),
+	)
+}
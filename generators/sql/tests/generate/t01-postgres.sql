@@ -0,0 +1,33 @@
+-- Generated by TreeLDR. Do not edit.
+
+CREATE TABLE person_address (
+  id BIGINT PRIMARY KEY,
+  city TEXT NOT NULL
+);
+
+-- A person.
+--
+-- Layout `https://example.org/#Person`.
+CREATE TABLE person (
+  id BIGINT PRIMARY KEY,
+  address_id BIGINT REFERENCES person_address (id),
+  age BIGINT,
+  contact JSONB,
+  first_friend_id BIGINT REFERENCES person (id),
+  name TEXT NOT NULL,
+  photo BYTEA,
+  score DOUBLE PRECISION
+);
+
+CREATE TABLE person_children (
+  owner_id BIGINT NOT NULL REFERENCES person (id),
+  position BIGINT NOT NULL,
+  value_id BIGINT NOT NULL REFERENCES person (id),
+  PRIMARY KEY (owner_id, position)
+);
+
+CREATE TABLE person_tags (
+  owner_id BIGINT NOT NULL REFERENCES person (id),
+  value TEXT NOT NULL,
+  PRIMARY KEY (owner_id, value)
+);
@@ -0,0 +1,33 @@
+-- Generated by TreeLDR. Do not edit.
+
+CREATE TABLE person_address (
+  id INTEGER PRIMARY KEY,
+  city TEXT NOT NULL
+);
+
+-- A person.
+--
+-- Layout `https://example.org/#Person`.
+CREATE TABLE person (
+  id INTEGER PRIMARY KEY,
+  address_id INTEGER REFERENCES person_address (id),
+  age INTEGER,
+  contact TEXT,
+  first_friend_id INTEGER REFERENCES person (id),
+  name TEXT NOT NULL,
+  photo BLOB,
+  score REAL
+);
+
+CREATE TABLE person_children (
+  owner_id INTEGER NOT NULL REFERENCES person (id),
+  position INTEGER NOT NULL,
+  value_id INTEGER NOT NULL REFERENCES person (id),
+  PRIMARY KEY (owner_id, position)
+);
+
+CREATE TABLE person_tags (
+  owner_id INTEGER NOT NULL REFERENCES person (id),
+  value TEXT NOT NULL,
+  PRIMARY KEY (owner_id, value)
+);
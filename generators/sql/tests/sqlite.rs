@@ -0,0 +1,133 @@
+use std::fs;
+use treeldr_gen_sql::{RowSource, Schema, SqlValue, Table};
+use treeldr_layouts::{LayoutRegistry, Prelude, Value};
+
+fn load_schema(id: &str) -> Schema {
+	let mut builder = treeldr_layouts::abs::Builder::new();
+
+	let raw_json = fs::read_to_string(format!(
+		"{}/tests/generate/{id}-layout.json",
+		env!("CARGO_MANIFEST_DIR")
+	))
+	.unwrap();
+	let layout: treeldr_layouts::abs::syntax::Layout = serde_json::from_str(&raw_json).unwrap();
+	let layout_ref = layout.build(&mut builder).unwrap();
+	let layouts = builder.build();
+
+	Schema::new(
+		Default::default(),
+		layouts.with(Prelude),
+		&layout_ref,
+		&Default::default(),
+	)
+	.unwrap()
+}
+
+fn person() -> Value {
+	serde_json::json!({
+		"name": "Alice",
+		"age": 30,
+		"score": 4.5,
+		"tags": ["a", "b"],
+		"address": { "city": "Paris" },
+		"contact": { "card": { "phone": "0123" } },
+		"first-friend": {
+			"name": "Bob",
+			"children": []
+		},
+		"children": [
+			{ "name": "Carol", "contact": { "email": "carol@example.org" }, "children": [] },
+			{ "name": "Dave", "children": [] }
+		]
+	})
+	.into()
+}
+
+fn to_sql(value: SqlValue) -> rusqlite::types::Value {
+	match value {
+		SqlValue::Null => rusqlite::types::Value::Null,
+		SqlValue::Boolean(b) => rusqlite::types::Value::Integer(b as i64),
+		SqlValue::Integer(i) => rusqlite::types::Value::Integer(i),
+		SqlValue::Real(f) => rusqlite::types::Value::Real(f),
+		SqlValue::Text(s) => rusqlite::types::Value::Text(s),
+		SqlValue::Blob(b) => rusqlite::types::Value::Blob(b),
+	}
+}
+
+fn from_sql(value: rusqlite::types::Value) -> SqlValue {
+	match value {
+		rusqlite::types::Value::Null => SqlValue::Null,
+		rusqlite::types::Value::Integer(i) => SqlValue::Integer(i),
+		rusqlite::types::Value::Real(f) => SqlValue::Real(f),
+		rusqlite::types::Value::Text(s) => SqlValue::Text(s),
+		rusqlite::types::Value::Blob(b) => SqlValue::Blob(b),
+	}
+}
+
+struct Sqlite<'a>(&'a rusqlite::Connection);
+
+impl<'a> Sqlite<'a> {
+	fn select(&self, table: &Table, key: i64) -> rusqlite::Result<Vec<Vec<SqlValue>>> {
+		let mut stmt = self
+			.0
+			.prepare(&table.select_statement(Default::default()))?;
+		let len = table.columns().len();
+		let rows = stmt.query_map([key], |row| {
+			(0..len)
+				.map(|i| row.get::<_, rusqlite::types::Value>(i).map(from_sql))
+				.collect()
+		})?;
+		rows.collect()
+	}
+}
+
+impl<'a> RowSource for Sqlite<'a> {
+	type Error = rusqlite::Error;
+
+	fn row(&mut self, table: &Table, id: i64) -> Result<Option<Vec<SqlValue>>, Self::Error> {
+		Ok(self.select(table, id)?.into_iter().next())
+	}
+
+	fn join_rows(&mut self, table: &Table, owner: i64) -> Result<Vec<Vec<SqlValue>>, Self::Error> {
+		self.select(table, owner)
+	}
+}
+
+#[test]
+fn in_memory_round_trip() {
+	let schema = load_schema("t01");
+	let value = person();
+
+	let (id, mut rows) = schema.to_rows(&value).unwrap();
+	assert_eq!(schema.from_rows(id, &mut rows).unwrap(), value)
+}
+
+#[test]
+fn sqlite_round_trip() {
+	let schema = load_schema("t01");
+	let value = person();
+
+	let connection = rusqlite::Connection::open_in_memory().unwrap();
+	connection.execute_batch(&schema.to_ddl()).unwrap();
+
+	let (id, rows) = schema.to_rows(&value).unwrap();
+	for (table_name, row) in rows.iter() {
+		let table = schema.table(table_name).unwrap();
+		connection
+			.execute(
+				&table.insert_statement(schema.dialect()),
+				rusqlite::params_from_iter(row.iter().cloned().map(to_sql)),
+			)
+			.unwrap();
+	}
+
+	let count: i64 = connection
+		.query_row("SELECT COUNT(*) FROM person", [], |row| row.get(0))
+		.unwrap();
+	assert_eq!(count, 4);
+
+	assert_eq!(
+		schema.from_rows(id, &mut Sqlite(&connection)).unwrap(),
+		value
+	)
+}
@@ -0,0 +1,680 @@
+//! TypeScript code generation from TreeLDR layouts.
+//!
+//! Generates TypeScript type declarations describing the tree values
+//! matched by a layout and, optionally, runtime validators checking that an
+//! arbitrary value conforms to those declarations.
+use std::{
+	collections::{HashMap, HashSet, VecDeque},
+	fmt::Write,
+	hash::Hash,
+};
+
+use rdf_types::{interpretation::ReverseIriInterpretation, vocabulary::IriVocabulary, Term};
+use treeldr_gen_rust::utils::type_name_from_iri;
+use treeldr_layouts::{
+	distill::RdfContext,
+	layout::{DataLayout, LayoutType, ListLayout, LiteralLayout},
+	utils::DetAutomaton,
+	Layout, LayoutRegistry, Literal, PresetLayout, Ref, Value,
+};
+
+/// Validators runtime, included in the output when validators are enabled.
+const RUNTIME: &str = include_str!("runtime.ts");
+
+/// TypeScript reserved words, that cannot be used as type identifiers.
+const RESERVED: &[&str] = &[
+	"any",
+	"boolean",
+	"break",
+	"case",
+	"catch",
+	"class",
+	"const",
+	"continue",
+	"debugger",
+	"default",
+	"delete",
+	"do",
+	"else",
+	"enum",
+	"export",
+	"extends",
+	"false",
+	"finally",
+	"for",
+	"function",
+	"if",
+	"import",
+	"in",
+	"instanceof",
+	"never",
+	"new",
+	"null",
+	"number",
+	"object",
+	"return",
+	"string",
+	"super",
+	"switch",
+	"symbol",
+	"this",
+	"throw",
+	"true",
+	"try",
+	"typeof",
+	"undefined",
+	"unknown",
+	"var",
+	"void",
+	"while",
+	"with",
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error<R = Term> {
+	#[error("missing type identifier for layout {0}")]
+	MissingTypeIdentifier(R),
+
+	#[error("layout {0} not found")]
+	LayoutNotFound(R),
+
+	#[error("invalid field name `{0}`")]
+	InvalidFieldName(Value),
+}
+
+pub struct Options<R = Term> {
+	idents: HashMap<Ref<LayoutType, R>, String>,
+	validators: bool,
+}
+
+impl<R> Options<R> {
+	pub fn new() -> Self {
+		Self {
+			idents: HashMap::new(),
+			validators: false,
+		}
+	}
+
+	/// Enables or disables the generation of runtime validators.
+	pub fn with_validators(self, validators: bool) -> Self {
+		Self { validators, ..self }
+	}
+
+	/// Sets the TypeScript identifier of the given layout.
+	pub fn set_ident(&mut self, layout_ref: Ref<LayoutType, R>, ident: String)
+	where
+		R: Eq + Hash,
+	{
+		self.idents.insert(layout_ref, ident);
+	}
+
+	pub fn layout_ident<V, I>(
+		&self,
+		rdf: RdfContext<V, I>,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Option<String>
+	where
+		V: IriVocabulary,
+		I: ReverseIriInterpretation<Resource = R, Iri = V::Iri>,
+		R: Eq + Hash,
+	{
+		self.idents
+			.get(layout_ref)
+			.cloned()
+			.or_else(|| default_layout_ident(rdf, layout_ref))
+	}
+}
+
+impl<R> Default for Options<R> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Checks that the given string is a valid TypeScript type identifier.
+pub fn is_ident(s: &str) -> bool {
+	is_identifier_name(s) && !RESERVED.contains(&s)
+}
+
+/// Checks that the given string is a valid identifier name, that can be
+/// used unquoted as a property name.
+fn is_identifier_name(s: &str) -> bool {
+	let mut chars = s.chars();
+	match chars.next() {
+		Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$' => {
+			chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+		}
+		_ => false,
+	}
+}
+
+pub fn default_layout_ident<V, I>(
+	rdf: RdfContext<V, I>,
+	layout_ref: &Ref<LayoutType, I::Resource>,
+) -> Option<String>
+where
+	V: IriVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri>,
+{
+	let mut selected: Option<String> = None;
+
+	for i in rdf.interpretation.iris_of(layout_ref.id()) {
+		let iri = rdf.vocabulary.iri(i).unwrap();
+		if let Some(name) = type_name_from_iri(iri).filter(|name| is_ident(name)) {
+			if !selected.as_ref().is_some_and(|s| *s < name) {
+				selected = Some(name)
+			}
+		}
+	}
+
+	selected
+}
+
+/// Generates the TypeScript declarations for the given layout and all its
+/// named dependencies.
+///
+/// Anonymous layouts (without identifier) are inlined where they are used.
+pub fn generate<V, I>(
+	rdf: RdfContext<V, I>,
+	layouts: impl LayoutRegistry<I::Resource>,
+	layout_ref: &Ref<LayoutType, I::Resource>,
+	options: &Options<I::Resource>,
+) -> Result<String, Error<I::Resource>>
+where
+	V: IriVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri>,
+	I::Resource: Clone + Ord + Hash,
+{
+	let ident = options
+		.layout_ident(rdf, layout_ref)
+		.ok_or_else(|| Error::MissingTypeIdentifier(layout_ref.id().clone()))?;
+
+	let mut generator = Generator {
+		rdf,
+		layouts: &layouts,
+		options,
+		declared: HashSet::new(),
+		queue: VecDeque::new(),
+		patterns: Vec::new(),
+	};
+
+	generator.schedule(layout_ref, ident);
+
+	let mut declarations = Vec::new();
+	while let Some((layout_ref, ident)) = generator.queue.pop_front() {
+		declarations.push(generator.declaration(&layout_ref, &ident)?);
+	}
+
+	let mut output = String::new();
+	output.push_str("// Generated by TreeLDR. Do not edit.\n");
+
+	if options.validators {
+		output.push('\n');
+		output.push_str(RUNTIME);
+
+		for (i, pattern) in generator.patterns.iter().enumerate() {
+			write!(output, "\nconst $pattern{i}: $Automaton = {pattern};\n").unwrap();
+		}
+	}
+
+	for declaration in declarations {
+		output.push('\n');
+		output.push_str(&declaration);
+	}
+
+	Ok(output)
+}
+
+struct Generator<'a, V, I: ReverseIriInterpretation, L> {
+	rdf: RdfContext<'a, V, I>,
+	layouts: &'a L,
+	options: &'a Options<I::Resource>,
+	declared: HashSet<Ref<LayoutType, I::Resource>>,
+	queue: VecDeque<(Ref<LayoutType, I::Resource>, String)>,
+	patterns: Vec<String>,
+}
+
+impl<'a, V, I, L> Generator<'a, V, I, L>
+where
+	V: IriVocabulary,
+	I: ReverseIriInterpretation<Iri = V::Iri>,
+	I::Resource: Clone + Ord + Hash,
+	L: LayoutRegistry<I::Resource>,
+{
+	fn get(
+		&self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Result<&'a Layout<I::Resource>, Error<I::Resource>> {
+		self.layouts
+			.get(layout_ref)
+			.ok_or_else(|| Error::LayoutNotFound(layout_ref.id().clone()))
+	}
+
+	fn schedule(&mut self, layout_ref: &Ref<LayoutType, I::Resource>, ident: String) {
+		if self.declared.insert(layout_ref.clone()) {
+			self.queue.push_back((layout_ref.clone(), ident))
+		}
+	}
+
+	fn preset(&self, layout_ref: &Ref<LayoutType, I::Resource>) -> Option<PresetLayout> {
+		self.rdf
+			.interpretation
+			.iris_of(layout_ref.id())
+			.find_map(|i| PresetLayout::from_iri(self.rdf.vocabulary.iri(i).unwrap()))
+	}
+
+	fn iri(&self, resource: &I::Resource) -> Option<String> {
+		self.rdf
+			.interpretation
+			.iris_of(resource)
+			.next()
+			.map(|i| self.rdf.vocabulary.iri(i).unwrap().to_string())
+	}
+
+	fn is_integer_datatype(&self, datatype: &I::Resource) -> bool {
+		self.rdf.interpretation.iris_of(datatype).any(|i| {
+			xsd_types::IntegerDatatype::from_iri(self.rdf.vocabulary.iri(i).unwrap()).is_some()
+		})
+	}
+
+	/// Generates the declaration of a named layout.
+	fn declaration(
+		&mut self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+		ident: &str,
+	) -> Result<String, Error<I::Resource>> {
+		let layout = self.get(layout_ref)?;
+		let mut result = String::new();
+
+		if let Some(iri) = self.iri(layout_ref.id()) {
+			writeln!(result, "/**\n * Layout `{iri}`.\n */").unwrap();
+		}
+
+		match layout {
+			Layout::Product(layout) => {
+				writeln!(result, "export interface {ident} {{").unwrap();
+				for (key, field) in &layout.fields {
+					let name = field_name(key)?;
+					let optional = if field.required { "" } else { "?" };
+					let ty = self.type_ref(&field.value.layout)?;
+					writeln!(result, "\t{}{optional}: {ty};", property_name(name)).unwrap();
+				}
+				result.push_str("}\n");
+			}
+			Layout::Literal(LiteralLayout::Id(_)) => {
+				writeln!(
+					result,
+					"export type {ident} = string & {{ readonly __brand: \"{ident}\" }};"
+				)
+				.unwrap();
+			}
+			Layout::Sum(layout) if !layout.variants.is_empty() => {
+				writeln!(result, "export type {ident} =").unwrap();
+				for (i, variant) in layout.variants.iter().enumerate() {
+					let ty = self.type_ref(&variant.value.layout)?;
+					let end = if i + 1 == layout.variants.len() {
+						";"
+					} else {
+						""
+					};
+					writeln!(result, "\t// Variant `{}`.\n\t| {ty}{end}", variant.name).unwrap();
+				}
+			}
+			other => {
+				let ty = self.type_expr(other)?;
+				writeln!(result, "export type {ident} = {ty};").unwrap();
+			}
+		}
+
+		if self.options.validators {
+			let check = self.check_expr(layout)?;
+			write!(
+				result,
+				"\nconst ${ident}: $Check = {check};\n\
+				\nexport function is{ident}(value: unknown): value is {ident} {{\n\
+				\treturn ${ident}(value);\n\
+				}}\n"
+			)
+			.unwrap();
+
+			if let Layout::Sum(layout) = layout {
+				if !layout.variants.is_empty() {
+					let names = layout
+						.variants
+						.iter()
+						.map(|v| string_literal(&v.name))
+						.collect::<Vec<_>>();
+					let checks = layout
+						.variants
+						.iter()
+						.map(|v| {
+							Ok(format!(
+								"[{}, {}]",
+								string_literal(&v.name),
+								self.check_ref(&v.value.layout)?
+							))
+						})
+						.collect::<Result<Vec<_>, _>>()?;
+
+					write!(
+						result,
+						"\nexport function variantOf{ident}(value: unknown): {} | undefined {{\n\
+						\treturn $variant([{}], value);\n\
+						}}\n",
+						names.join(" | "),
+						checks.join(", ")
+					)
+					.unwrap();
+				}
+			}
+		}
+
+		Ok(result)
+	}
+
+	/// Returns the type expression referencing the given layout.
+	///
+	/// Preset layouts are mapped to TypeScript primitive types, named layouts
+	/// are referenced by identifier and anonymous layouts are inlined.
+	fn type_ref(
+		&mut self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Result<String, Error<I::Resource>> {
+		match self.preset(layout_ref) {
+			Some(PresetLayout::Id) | None => (),
+			Some(PresetLayout::Unit) => return Ok("null".to_owned()),
+			Some(PresetLayout::Boolean) => return Ok("boolean".to_owned()),
+			Some(PresetLayout::String) => return Ok("string".to_owned()),
+			Some(_) => return Ok("number".to_owned()),
+		}
+
+		match self.options.layout_ident(self.rdf, layout_ref) {
+			Some(ident) => {
+				self.schedule(layout_ref, ident.clone());
+				Ok(ident)
+			}
+			None => {
+				let layout = self.get(layout_ref)?;
+				self.type_expr(layout)
+			}
+		}
+	}
+
+	/// Returns the inline type expression of the given layout.
+	fn type_expr(&mut self, layout: &Layout<I::Resource>) -> Result<String, Error<I::Resource>> {
+		match layout {
+			Layout::Never => Ok("never".to_owned()),
+			Layout::Always => Ok("unknown".to_owned()),
+			Layout::Literal(LiteralLayout::Data(layout)) => match layout {
+				DataLayout::Unit(layout) => Ok(value_literal(&layout.const_, true)),
+				DataLayout::Boolean(_) => Ok("boolean".to_owned()),
+				DataLayout::Number(_) => Ok("number".to_owned()),
+				DataLayout::ByteString(_) => Ok("Uint8Array".to_owned()),
+				DataLayout::TextString(_) => Ok("string".to_owned()),
+			},
+			Layout::Literal(LiteralLayout::Id(_)) => Ok("string".to_owned()),
+			Layout::Product(layout) => {
+				if layout.fields.is_empty() {
+					return Ok("Record<string, never>".to_owned());
+				}
+
+				let fields = layout
+					.fields
+					.iter()
+					.map(|(key, field)| {
+						let name = field_name(key)?;
+						let optional = if field.required { "" } else { "?" };
+						let ty = self.type_ref(&field.value.layout)?;
+						Ok(format!("{}{optional}: {ty}", property_name(name)))
+					})
+					.collect::<Result<Vec<_>, _>>()?;
+
+				Ok(format!("{{ {} }}", fields.join("; ")))
+			}
+			Layout::Sum(layout) => {
+				if layout.variants.is_empty() {
+					return Ok("never".to_owned());
+				}
+
+				let variants = layout
+					.variants
+					.iter()
+					.map(|v| self.type_ref(&v.value.layout))
+					.collect::<Result<Vec<_>, _>>()?;
+
+				Ok(format!("({})", variants.join(" | ")))
+			}
+			Layout::List(ListLayout::Unordered(layout)) => {
+				let item = self.type_ref(&layout.item.value.layout)?;
+				Ok(format!("Array<{item}>"))
+			}
+			Layout::List(ListLayout::Ordered(layout)) => {
+				let item = self.type_ref(&layout.node.value.layout)?;
+				Ok(format!("Array<{item}>"))
+			}
+			Layout::List(ListLayout::Sized(layout)) => {
+				let items = layout
+					.items
+					.iter()
+					.map(|item| self.type_ref(&item.value.layout))
+					.collect::<Result<Vec<_>, _>>()?;
+
+				Ok(format!("[{}]", items.join(", ")))
+			}
+		}
+	}
+
+	/// Returns the validator expression for the given layout reference.
+	///
+	/// Named layouts are referenced through a closure so that validators can
+	/// be declared in any order.
+	fn check_ref(
+		&mut self,
+		layout_ref: &Ref<LayoutType, I::Resource>,
+	) -> Result<String, Error<I::Resource>> {
+		if self.preset(layout_ref).is_none() {
+			if let Some(ident) = self.options.layout_ident(self.rdf, layout_ref) {
+				self.schedule(layout_ref, ident.clone());
+				return Ok(format!("(v) => ${ident}(v)"));
+			}
+		}
+
+		let layout = self.get(layout_ref)?;
+		self.check_expr(layout)
+	}
+
+	/// Returns the inline validator expression of the given layout.
+	fn check_expr(&mut self, layout: &Layout<I::Resource>) -> Result<String, Error<I::Resource>> {
+		match layout {
+			Layout::Never => Ok("$never".to_owned()),
+			Layout::Always => Ok("$always".to_owned()),
+			Layout::Literal(LiteralLayout::Data(layout)) => match layout {
+				DataLayout::Unit(layout) => {
+					Ok(format!("$const({})", value_literal(&layout.const_, false)))
+				}
+				DataLayout::Boolean(_) => Ok("$boolean".to_owned()),
+				DataLayout::Number(layout) => {
+					if self.is_integer_datatype(&layout.datatype) {
+						Ok("$integer".to_owned())
+					} else {
+						Ok("$number".to_owned())
+					}
+				}
+				DataLayout::ByteString(_) => Ok("$bytes".to_owned()),
+				DataLayout::TextString(layout) => Ok(format!(
+					"$string({})",
+					self.pattern(layout.pattern.as_ref())
+				)),
+			},
+			Layout::Literal(LiteralLayout::Id(layout)) => {
+				Ok(format!("$id({})", self.pattern(layout.pattern.as_ref())))
+			}
+			Layout::Product(layout) => {
+				let fields = layout
+					.fields
+					.iter()
+					.map(|(key, field)| {
+						let name = field_name(key)?;
+						let check = self.check_ref(&field.value.layout)?;
+						Ok(format!(
+							"{}: [{check}, {}]",
+							string_literal(name),
+							field.required
+						))
+					})
+					.collect::<Result<Vec<_>, _>>()?;
+
+				Ok(format!("$record({{ {} }})", fields.join(", ")))
+			}
+			Layout::Sum(layout) => {
+				let variants = layout
+					.variants
+					.iter()
+					.map(|v| self.check_ref(&v.value.layout))
+					.collect::<Result<Vec<_>, _>>()?;
+
+				Ok(format!("$sum([{}])", variants.join(", ")))
+			}
+			Layout::List(ListLayout::Unordered(layout)) => {
+				let item = self.check_ref(&layout.item.value.layout)?;
+				Ok(format!("$list({item})"))
+			}
+			Layout::List(ListLayout::Ordered(layout)) => {
+				let item = self.check_ref(&layout.node.value.layout)?;
+				Ok(format!("$list({item})"))
+			}
+			Layout::List(ListLayout::Sized(layout)) => {
+				let items = layout
+					.items
+					.iter()
+					.map(|item| self.check_ref(&item.value.layout))
+					.collect::<Result<Vec<_>, _>>()?;
+
+				Ok(format!("$tuple([{}])", items.join(", ")))
+			}
+		}
+	}
+
+	/// Declares the given pattern automaton and returns the name of its
+	/// constant, or an empty string if there is no pattern.
+	fn pattern(&mut self, automaton: Option<&DetAutomaton<usize>>) -> String {
+		match automaton {
+			Some(automaton) => {
+				let i = self.patterns.len();
+				self.patterns.push(automaton_literal(automaton));
+				format!("$pattern{i}")
+			}
+			None => String::new(),
+		}
+	}
+}
+
+fn field_name<R>(key: &Value) -> Result<&str, Error<R>> {
+	match key {
+		Value::Literal(Literal::TextString(name)) => Ok(name),
+		other => Err(Error::InvalidFieldName(other.clone())),
+	}
+}
+
+fn property_name(name: &str) -> String {
+	if is_identifier_name(name) {
+		name.to_owned()
+	} else {
+		string_literal(name)
+	}
+}
+
+fn string_literal(s: &str) -> String {
+	let mut result = String::with_capacity(s.len() + 2);
+	result.push('"');
+
+	for c in s.chars() {
+		match c {
+			'"' => result.push_str("\\\""),
+			'\\' => result.push_str("\\\\"),
+			'\n' => result.push_str("\\n"),
+			'\r' => result.push_str("\\r"),
+			'\t' => result.push_str("\\t"),
+			c if c.is_control() => write!(result, "\\u{:04x}", c as u32).unwrap(),
+			c => result.push(c),
+		}
+	}
+
+	result.push('"');
+	result
+}
+
+/// Returns the TypeScript literal representing the given value, either as a
+/// type (`as_type`) or as an expression.
+fn value_literal(value: &Value, as_type: bool) -> String {
+	match value {
+		Value::Literal(Literal::Unit) => "null".to_owned(),
+		Value::Literal(Literal::Boolean(b)) => b.to_string(),
+		Value::Literal(Literal::Number(n)) => n
+			.decimal_representation()
+			.unwrap_or_else(|| n.to_f64().to_string()),
+		Value::Literal(Literal::TextString(s)) => string_literal(s),
+		Value::Literal(Literal::ByteString(bytes)) => {
+			if as_type {
+				"Uint8Array".to_owned()
+			} else {
+				let bytes = bytes.iter().map(u8::to_string).collect::<Vec<_>>();
+				format!("new Uint8Array([{}])", bytes.join(", "))
+			}
+		}
+		Value::Map(map) => {
+			let entries = map
+				.iter()
+				.map(|(key, value)| {
+					let key = match key {
+						Value::Literal(Literal::TextString(s)) => string_literal(s),
+						other => string_literal(&other.to_string()),
+					};
+					format!("{key}: {}", value_literal(value, as_type))
+				})
+				.collect::<Vec<_>>();
+
+			format!("{{ {} }}", entries.join(", "))
+		}
+		Value::List(items) => {
+			let items = items
+				.iter()
+				.map(|item| value_literal(item, as_type))
+				.collect::<Vec<_>>();
+
+			format!("[{}]", items.join(", "))
+		}
+	}
+}
+
+fn automaton_literal(automaton: &DetAutomaton<usize>) -> String {
+	let final_states = automaton
+		.final_states()
+		.iter()
+		.map(usize::to_string)
+		.collect::<Vec<_>>();
+
+	let transitions = automaton
+		.transitions()
+		.iter()
+		.map(|(q, map)| {
+			let targets = map
+				.iter()
+				.filter_map(|(range, target)| {
+					let min = range.first()?;
+					let max = range.last()?;
+					Some(format!("[{}, {}, {target}]", min as u32, max as u32))
+				})
+				.collect::<Vec<_>>();
+
+			format!("{q}: [{}]", targets.join(", "))
+		})
+		.collect::<Vec<_>>();
+
+	format!(
+		"{{ initial: {}, final: [{}], transitions: {{ {} }} }}",
+		automaton.initial_state(),
+		final_states.join(", "),
+		transitions.join(", ")
+	)
+}
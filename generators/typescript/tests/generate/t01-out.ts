@@ -0,0 +1,26 @@
+// Generated by TreeLDR. Do not edit.
+
+/**
+ * Layout `https://example.org/#Person`.
+ */
+export interface Person {
+	age?: number;
+	contact?: Contact;
+	"first-friend"?: Person;
+	name: string;
+	tags?: Array<string>;
+}
+
+/**
+ * Layout `https://example.org/#Contact`.
+ */
+export type Contact =
+	// Variant `email`.
+	| string
+	// Variant `ref`.
+	| Ref;
+
+/**
+ * Layout `https://example.org/#Ref`.
+ */
+export type Ref = string & { readonly __brand: "Ref" };
@@ -0,0 +1,150 @@
+// Generated by TreeLDR. Do not edit.
+
+type $Check = (value: unknown) => boolean;
+
+interface $Automaton {
+	initial: number;
+	final: number[];
+	transitions: Record<number, [number, number, number][]>;
+}
+
+function $matches(automaton: $Automaton, value: string): boolean {
+	let state = automaton.initial;
+	for (const c of value) {
+		const code = c.codePointAt(0) as number;
+		const transition = (automaton.transitions[state] ?? []).find(
+			([min, max]) => min <= code && code <= max
+		);
+		if (transition === undefined) {
+			return false;
+		}
+		state = transition[2];
+	}
+	return automaton.final.includes(state);
+}
+
+function $equals(a: unknown, b: unknown): boolean {
+	if (a === b) {
+		return true;
+	}
+	if (Array.isArray(a)) {
+		return (
+			Array.isArray(b) &&
+			a.length === b.length &&
+			a.every((item, i) => $equals(item, b[i]))
+		);
+	}
+	if (
+		typeof a === "object" &&
+		a !== null &&
+		typeof b === "object" &&
+		b !== null &&
+		!Array.isArray(b)
+	) {
+		const x = a as Record<string, unknown>;
+		const y = b as Record<string, unknown>;
+		const keys = Object.keys(x);
+		return (
+			keys.length === Object.keys(y).length &&
+			keys.every((key) => Object.prototype.hasOwnProperty.call(y, key) && $equals(x[key], y[key]))
+		);
+	}
+	return false;
+}
+
+const $never: $Check = () => false;
+
+const $always: $Check = () => true;
+
+const $boolean: $Check = (value) => typeof value === "boolean";
+
+const $number: $Check = (value) => typeof value === "number";
+
+const $integer: $Check = (value) => Number.isInteger(value);
+
+const $bytes: $Check = (value) => value instanceof Uint8Array;
+
+function $const(expected: unknown): $Check {
+	return (value) => $equals(expected, value);
+}
+
+function $string(pattern?: $Automaton): $Check {
+	return (value) =>
+		typeof value === "string" && (pattern === undefined || $matches(pattern, value));
+}
+
+function $id(pattern?: $Automaton): $Check {
+	return (value) =>
+		typeof value === "string" &&
+		/^[A-Za-z][A-Za-z0-9+.-]*:/.test(value) &&
+		(pattern === undefined || $matches(pattern, value));
+}
+
+function $record(fields: Record<string, [$Check, boolean]>): $Check {
+	return (value) => {
+		if (typeof value !== "object" || value === null || Array.isArray(value)) {
+			return false;
+		}
+		const record = value as Record<string, unknown>;
+		for (const key of Object.keys(record)) {
+			if (!Object.prototype.hasOwnProperty.call(fields, key)) {
+				return false;
+			}
+		}
+		return Object.entries(fields).every(([key, [check, required]]) =>
+			record[key] === undefined ? !required : check(record[key])
+		);
+	};
+}
+
+function $list(item: $Check): $Check {
+	return (value) => Array.isArray(value) && value.every((v) => item(v));
+}
+
+function $tuple(items: $Check[]): $Check {
+	return (value) =>
+		Array.isArray(value) &&
+		value.length === items.length &&
+		items.every((check, i) => check(value[i]));
+}
+
+function $sum(variants: $Check[]): $Check {
+	return (value) => variants.filter((check) => check(value)).length === 1;
+}
+
+function $variant<T extends string>(variants: [T, $Check][], value: unknown): T | undefined {
+	const matching = variants.filter(([, check]) => check(value));
+	return matching.length === 1 ? matching[0][0] : undefined;
+}
+
+const $pattern0: $Automaton = { initial: 0, final: [1, 2], transitions: { 0: [[65, 90, 1]], 1: [[65, 90, 2]], 2: [[65, 90, 2]] } };
+
+/**
+ * Layout `https://example.org/#Status`.
+ */
+export type Status =
+	// Variant `code`.
+	| Code
+	// Variant `flags`.
+	| [boolean, number];
+
+const $Status: $Check = $sum([(v) => $Code(v), $tuple([$boolean, $integer])]);
+
+export function isStatus(value: unknown): value is Status {
+	return $Status(value);
+}
+
+export function variantOfStatus(value: unknown): "code" | "flags" | undefined {
+	return $variant([["code", (v) => $Code(v)], ["flags", $tuple([$boolean, $integer])]], value);
+}
+
+/**
+ * Layout `https://example.org/#Code`.
+ */
+export type Code = string;
+
+const $Code: $Check = $string($pattern0);
+
+export function isCode(value: unknown): value is Code {
+	return $Code(value);
+}
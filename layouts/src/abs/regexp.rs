@@ -54,12 +54,8 @@ impl RegExp {
 	pub fn push(&mut self, e: Self) {
 		let this = match unsafe { std::ptr::read(self) } {
 			Self::Sequence(mut seq) => {
-				if seq.is_empty() {
-					e
-				} else {
-					seq.push(e);
-					Self::Sequence(seq)
-				}
+				seq.push(e);
+				Self::Sequence(seq)
 			}
 			Self::Union(items) if items.is_empty() => e,
 			item => Self::Sequence(vec![item, e]),
@@ -68,9 +64,21 @@ impl RegExp {
 		unsafe { std::ptr::write(self, this) }
 	}
 
+	/// Repeat the last item of this regexp between `min` and `max` times.
+	///
+	/// For instance if `self` is `/ab/` then the result is `/ab{min,max}/`.
 	pub fn repeat(&mut self, min: u32, max: u32) {
-		let this = Self::Repeat(Box::new(unsafe { std::ptr::read(self) }), min, max);
-		unsafe { std::ptr::write(self, this) }
+		match self {
+			Self::Sequence(seq) if !seq.is_empty() => {
+				let last = seq.last_mut().unwrap();
+				let this = Self::Repeat(Box::new(unsafe { std::ptr::read(last) }), min, max);
+				unsafe { std::ptr::write(last, this) }
+			}
+			_ => {
+				let this = Self::Repeat(Box::new(unsafe { std::ptr::read(self) }), min, max);
+				unsafe { std::ptr::write(self, this) }
+			}
+		}
 	}
 
 	pub fn simplified(self) -> Self {
@@ -112,7 +120,11 @@ impl RegExp {
 	}
 
 	pub fn is_simple(&self) -> bool {
-		matches!(self, Self::Any | Self::Set(_) | Self::Sequence(_))
+		match self {
+			Self::Any | Self::Set(_) => true,
+			Self::Sequence(seq) => seq.len() <= 1 && seq.iter().all(Self::is_simple),
+			_ => false,
+		}
 	}
 
 	/// Checks if this regular expression matches only one value.
@@ -268,16 +280,17 @@ impl RegExp {
 			}
 			Self::Repeat(exp, min, max) => exp.build_repeat_into(new_state, automaton, *min, *max),
 			Self::Sequence(exps) => {
-				let mut a = new_state();
+				let a = new_state();
 
+				let mut current = a;
 				for e in exps {
 					let (ea, eb) = e.build_into(new_state, automaton);
-					automaton.add(a, None, ea);
-					a = eb;
+					automaton.add(current, None, ea);
+					current = eb;
 				}
 
 				let b = new_state();
-				automaton.add(a, None, b);
+				automaton.add(current, None, b);
 
 				(a, b)
 			}
@@ -489,7 +502,7 @@ impl fmt::Display for RegExp {
 			}
 			Self::Sequence(seq) => {
 				for item in seq {
-					if seq.len() > 1 {
+					if seq.len() > 1 && matches!(item, Self::Union(_)) {
 						item.display_sub().fmt(f)?
 					} else {
 						item.fmt(f)?
@@ -516,7 +529,11 @@ impl fmt::Display for RegExp {
 						write!(f, "|")?
 					}
 
-					item.display_sub().fmt(f)?
+					if matches!(item, Self::Union(_)) {
+						item.display_sub().fmt(f)?
+					} else {
+						item.fmt(f)?
+					}
 				}
 
 				Ok(())
@@ -632,6 +649,9 @@ mod tests {
 		("(a|b)?", "(a|b)?"),
 		("[A-Za-z0-89]", "[0-9A-Za-z]"),
 		("[a|b]", "[ab\\|]"),
+		("[A-Z][a-z]*", "[A-Z][a-z]*"),
+		("(ab)*c", "(ab)*c"),
+		("a(b|c)+", "a(b|c)+"),
 	];
 
 	#[test]
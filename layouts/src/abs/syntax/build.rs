@@ -36,6 +36,112 @@ pub enum BuildError {
 
 	#[error("no property object")]
 	NoPropertyObject,
+
+	#[error("undefined layout `{0}`")]
+	UndefinedLayout(String),
+
+	/// Error caused by a nested fragment of the layout definition, with the
+	/// path leading to this fragment.
+	#[error("{1}")]
+	At(Vec<PathSegment>, Box<Self>),
+}
+
+impl BuildError {
+	/// Prepends the given segment to the path of the layout fragment causing
+	/// this error.
+	pub fn at(self, segment: impl Into<PathSegment>) -> Self {
+		match self {
+			Self::At(mut path, e) => {
+				path.insert(0, segment.into());
+				Self::At(path, e)
+			}
+			e => Self::At(vec![segment.into()], Box::new(e)),
+		}
+	}
+
+	/// Returns the path, from the root of the layout definition, to the
+	/// fragment causing this error.
+	pub fn path(&self) -> &[PathSegment] {
+		match self {
+			Self::At(path, _) => path,
+			_ => &[],
+		}
+	}
+
+	/// Returns the error without its location.
+	pub fn cause(&self) -> &Self {
+		match self {
+			Self::At(_, e) => e,
+			e => e,
+		}
+	}
+
+	/// Returns the position (code map offset) of the JSON value causing this
+	/// error in the given layout definition.
+	///
+	/// The path is followed as far as it goes in `json`. Some fragments are
+	/// implicit (such as the default `value` input), in which case the
+	/// position of the closest enclosing value is returned.
+	pub fn position(&self, json: &json_syntax::Value, code_map: &json_syntax::CodeMap) -> usize {
+		use json_syntax::array::JsonArray;
+		let mut json = json;
+		let mut offset = 0;
+
+		for segment in self.path() {
+			let next = match (json, segment) {
+				(json_syntax::Value::Object(object), PathSegment::Key(key)) => object
+					.iter_mapped(code_map, offset)
+					.find(|e| e.value.key.value.as_str() == key)
+					.map(|e| (e.value.value.value, e.value.value.offset)),
+				(json_syntax::Value::Array(array), PathSegment::Index(i)) => array
+					.iter_mapped(code_map, offset)
+					.nth(*i)
+					.map(|item| (item.value, item.offset)),
+				_ => None,
+			};
+
+			match next {
+				Some((value, value_offset)) => {
+					json = value;
+					offset = value_offset
+				}
+				None => break,
+			}
+		}
+
+		offset
+	}
+}
+
+/// Segment of a path in a JSON layout definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+	/// Object entry.
+	Key(String),
+
+	/// Array item.
+	Index(usize),
+}
+
+impl From<&str> for PathSegment {
+	fn from(value: &str) -> Self {
+		Self::Key(value.to_owned())
+	}
+}
+
+impl From<usize> for PathSegment {
+	fn from(value: usize) -> Self {
+		Self::Index(value)
+	}
+}
+
+impl std::fmt::Display for PathSegment {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			Self::Key(key) => write!(f, ".{key}"),
+			Self::Index(i) => write!(f, "[{i}]"),
+		}
+	}
 }
 
 pub trait Context {
@@ -52,6 +158,14 @@ pub trait Context {
 	fn literal_resource(&mut self, value: &str, type_: LexicalLiteralTypeRef) -> Self::Resource;
 
 	fn anonymous_resource(&mut self) -> Self::Resource;
+
+	/// Checks that the given layout reference is defined.
+	///
+	/// Layouts may be referenced before being defined (or defined in another
+	/// document), hence by default every reference is accepted.
+	fn check_layout_ref(&mut self, _id: &Self::Resource) -> Result<(), BuildError> {
+		Ok(())
+	}
 }
 
 impl<G> Context for abs::BuilderWithGeneratorMut<'_, G>
@@ -171,13 +285,14 @@ impl Scope {
 		let mut result = self.clone();
 
 		if let Some(base_iri) = &header.base {
-			result.base_iri = Some(base_iri.resolve(self)?)
+			result.base_iri = Some(base_iri.resolve(self).map_err(|e| e.at("base"))?)
 		}
 
 		for (name, prefix) in &header.prefixes {
-			result
-				.iri_prefixes
-				.insert(name.clone(), prefix.resolve(self)?);
+			let prefix = prefix
+				.resolve(self)
+				.map_err(|e| e.at(name.as_str()).at("prefixes"))?;
+			result.iri_prefixes.insert(name.clone(), prefix);
 		}
 
 		for name in header
@@ -230,6 +345,14 @@ impl Scope {
 		self.iri_prefixes.get(prefix).map(IriBuf::as_iri)
 	}
 
+	/// Returns an iterator over the current IRI prefixes, in no particular
+	/// order.
+	pub fn iri_prefixes(&self) -> impl Iterator<Item = (&str, &Iri)> {
+		self.iri_prefixes
+			.iter()
+			.map(|(name, prefix)| (name.as_str(), prefix.as_iri()))
+	}
+
 	/// Defines a new variable.
 	///
 	/// The new variable will be assigned a new unique index.
@@ -247,3 +370,75 @@ impl Scope {
 			.ok_or_else(|| BuildError::UndeclaredVariable(name.to_owned()))
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use json_syntax::{Parse, TryFromJson};
+
+	use crate::abs::{syntax::Layout, Builder};
+
+	use super::*;
+
+	/// Builds the given layout definition and returns the error with the
+	/// source text it points to.
+	fn build_error(source: &str) -> (BuildError, &str) {
+		let (json, code_map) = json_syntax::Value::parse_str(source).unwrap();
+		let layout = Layout::try_from_json(&json, &code_map).unwrap();
+		let e = layout.build(&mut Builder::new()).unwrap_err();
+		let span = code_map.get(e.position(&json, &code_map)).unwrap().span;
+		(e, &source[span.range()])
+	}
+
+	#[test]
+	fn undeclared_variable_position() {
+		let (e, text) = build_error(
+			r#"{
+				"type": "record",
+				"fields": {
+					"a": {
+						"value": "https://treeldr.org/prelude#string",
+						"dataset": [["_:self", "https://example.org/a", "_:other"]]
+					}
+				}
+			}"#,
+		);
+
+		assert!(matches!(e.cause(), BuildError::UndeclaredVariable(x) if x == "other"));
+		assert_eq!(
+			e.path(),
+			[
+				"fields".into(),
+				"a".into(),
+				"dataset".into(),
+				0.into(),
+				2.into()
+			]
+		);
+		assert_eq!(text, r#""_:other""#)
+	}
+
+	#[test]
+	fn no_base_iri_position() {
+		let (e, text) = build_error(
+			r#"{
+				"type": "sum",
+				"variants": {
+					"a": { "value": { "type": "tuple", "items": [{ "value": "relative" }] } }
+				}
+			}"#,
+		);
+
+		assert!(matches!(e.cause(), BuildError::NoBaseIri(_)));
+		assert_eq!(text, r#""relative""#)
+	}
+
+	#[test]
+	fn implicit_fragment_position() {
+		let (e, text) = build_error(
+			r#"{ "type": "record", "fields": { "a": { "intro": [], "value": "https://treeldr.org/prelude#string" } } }"#,
+		);
+
+		assert!(matches!(e.cause(), BuildError::UndeclaredVariable(x) if x == "value"));
+		assert_eq!(text, r#""https://treeldr.org/prelude#string""#)
+	}
+}
@@ -1,7 +1,7 @@
 use json_syntax::TryFromJson;
 use serde::{Deserialize, Serialize};
 
-use super::{expect_array, Build, BuildError, Context, Error, Pattern, Scope};
+use super::{expect_array, Build, BuildError, Context, Error, Normalize, Pattern, Scope};
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -11,6 +11,24 @@ impl Dataset {
 	pub fn is_empty(&self) -> bool {
 		self.0.is_empty()
 	}
+
+	/// Removes the first quad of the form `subject predicate object` from
+	/// this dataset, in the default graph, and returns its predicate.
+	///
+	/// The `subject` and `object` are given as variable indexes in `scope`.
+	/// This is the quad a record field or list item `property` stands for.
+	pub fn take_property(&mut self, scope: &Scope, subject: u32, object: u32) -> Option<Pattern> {
+		let is_variable = |pattern: &Pattern, i: u32| match pattern {
+			Pattern::Var(name) => scope.variable(name).ok() == Some(i),
+			_ => false,
+		};
+
+		let i = self.0.iter().position(|quad| {
+			quad.3.is_none() && is_variable(&quad.0, subject) && is_variable(&quad.2, object)
+		})?;
+
+		Some(self.0.remove(i).1)
+	}
 }
 
 impl From<Vec<Quad>> for Dataset {
@@ -39,14 +57,24 @@ where
 
 	fn build(&self, context: &mut C, scope: &Scope) -> Result<Self::Target, BuildError> {
 		let mut dataset = crate::Dataset::new();
-		for quad in &self.0 {
-			dataset.insert(quad.build(context, scope)?);
+		for (i, quad) in self.0.iter().enumerate() {
+			dataset.insert(quad.build(context, scope).map_err(|e| e.at(i))?);
 		}
 
 		Ok(dataset)
 	}
 }
 
+impl Normalize for Dataset {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		for quad in &mut self.0 {
+			quad.normalize(scope)?
+		}
+
+		Ok(())
+	}
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Quad(
 	pub Pattern,
@@ -100,7 +128,13 @@ impl<C: Context> Build<C> for Pattern {
 				Ok(crate::Pattern::Resource(context.iri_resource(&iri)))
 			}
 			Self::Literal(l) => Ok(crate::Pattern::Resource(
-				context.literal_resource(&l.value, l.type_.resolve(scope)?.as_lexical_type_ref()),
+				context.literal_resource(
+					&l.value,
+					l.type_
+						.resolve(scope)
+						.map_err(|e| e.at("type"))?
+						.as_lexical_type_ref(),
+				),
 			)),
 		}
 	}
@@ -116,13 +150,23 @@ impl<C: Context> Build<C> for Quad {
 
 	fn build(&self, context: &mut C, scope: &Scope) -> Result<Self::Target, BuildError> {
 		Ok(rdf_types::Quad(
-			self.0.build(context, scope)?,
-			self.1.build(context, scope)?,
-			self.2.build(context, scope)?,
+			self.0.build(context, scope).map_err(|e| e.at(0))?,
+			self.1.build(context, scope).map_err(|e| e.at(1))?,
+			self.2.build(context, scope).map_err(|e| e.at(2))?,
 			self.3
 				.as_ref()
 				.map(|g| g.build(context, scope))
-				.transpose()?,
+				.transpose()
+				.map_err(|e| e.at(3))?,
 		))
 	}
 }
+
+impl Normalize for Quad {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		self.0.normalize(scope)?;
+		self.1.normalize(scope)?;
+		self.2.normalize(scope)?;
+		self.3.normalize(scope)
+	}
+}
@@ -6,8 +6,8 @@ use crate::abs::{
 	self,
 	syntax::{
 		check_type, expect_object, get_entry, require_entry, require_type, Build, BuildError,
-		CompactIri, Context, Dataset, Error, ExpectedType, ObjectUnusedEntries, Pattern, Scope,
-		ValueFormatOrLayout, ValueIntro,
+		CompactIri, Context, Dataset, Error, ExpectedType, Normalize, ObjectUnusedEntries, Pattern,
+		Scope, ValueFormatOrLayout, ValueIntro,
 	},
 };
 
@@ -31,6 +31,14 @@ impl ListLayout {
 			Self::Sized(l) => l.header.id.as_ref(),
 		}
 	}
+
+	pub fn header(&self) -> &LayoutHeader {
+		match self {
+			Self::Ordered(l) => &l.header,
+			Self::Unordered(l) => &l.header,
+			Self::Sized(l) => &l.header,
+		}
+	}
 }
 
 impl TryFromJsonObject for ListLayout {
@@ -86,6 +94,33 @@ where
 	}
 }
 
+impl Normalize for ListLayout {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		match self {
+			Self::Ordered(l) => {
+				let scope = l.header.normalize(scope)?;
+				l.node.normalize(&scope)?;
+				l.head.normalize(&scope)?;
+				l.tail.normalize(&scope)
+			}
+			Self::Unordered(l) => {
+				let subject = (!l.header.input.is_empty()).then_some(0);
+				let scope = l.header.normalize(scope)?;
+				l.item.normalize(&scope, subject)
+			}
+			Self::Sized(l) => {
+				let subject = (!l.header.input.is_empty()).then_some(0);
+				let scope = l.header.normalize(scope)?;
+				for item in &mut l.items {
+					item.normalize(&scope, subject)?
+				}
+
+				Ok(())
+			}
+		}
+	}
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct OrderedListLayout {
@@ -259,6 +294,24 @@ pub struct ListNode {
 	pub dataset: Option<Dataset>,
 }
 
+impl Normalize for ListNodeOrLayout {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		match self {
+			Self::ListNode(node) => {
+				let scope = scope.with_intro(
+					[&node.head, &node.rest]
+						.into_iter()
+						.chain(node.intro.as_slice()),
+				)?;
+				node.intro.normalize(&scope)?;
+				node.value.normalize(&scope)?;
+				node.dataset.normalize(&scope)
+			}
+			Self::Layout(layout) => layout.normalize(scope),
+		}
+	}
+}
+
 impl ListNode {
 	pub fn default_head() -> String {
 		"head".to_string()
@@ -312,9 +365,9 @@ where
 		Ok(abs::layout::OrderedListLayout {
 			input: header.input,
 			intro: header.intro,
-			node: self.node.build(context, &scope)?,
-			head: self.head.build(context, &scope)?,
-			tail: self.tail.build(context, &scope)?,
+			node: self.node.build(context, &scope).map_err(|e| e.at("node"))?,
+			head: self.head.build(context, &scope).map_err(|e| e.at("head"))?,
+			tail: self.tail.build(context, &scope).map_err(|e| e.at("tail"))?,
 			dataset: header.dataset,
 			extra_properties: header.properties,
 		})
@@ -337,20 +390,32 @@ where
 		)?;
 		Ok(abs::layout::list::ordered::NodeLayout {
 			intro: self.intro.len() as u32,
-			value: self.value.build(context, &scope)?,
+			value: self
+				.value
+				.build(context, &scope)
+				.map_err(|e| e.at("value"))?,
 			dataset: match &self.dataset {
-				Some(dataset) => dataset.build(context, &scope)?,
+				Some(dataset) => dataset
+					.build(context, &scope)
+					.map_err(|e| e.at("dataset"))?,
 				None => match &self.value {
 					ValueFormatOrLayout::Format(f) => {
 						if f.input.len() == 1 {
-							let first = f.input.first().unwrap().build(context, &scope)?;
+							let first = f
+								.input
+								.first()
+								.unwrap()
+								.build(context, &scope)
+								.map_err(|e| e.at(0).at("input").at("value"))?;
 							default_list_dataset(context, head, rest, first)
 						} else {
 							BTreeDataset::new()
 						}
 					}
 					ValueFormatOrLayout::Layout(_) => {
-						let first = crate::Pattern::Var(scope.variable("value")?);
+						let first = crate::Pattern::Var(
+							scope.variable("value").map_err(|e| e.at("value"))?,
+						);
 						default_list_dataset(context, head, rest, first)
 					}
 				},
@@ -470,7 +535,10 @@ where
 		Ok(abs::layout::UnorderedListLayout {
 			input: header.input,
 			intro: header.intro,
-			item: self.item.build(context, &scope, subject)?,
+			item: self
+				.item
+				.build(context, &scope, subject)
+				.map_err(|e| e.at("item"))?,
 			dataset: header.dataset,
 			extra_properties: header.properties,
 		})
@@ -478,6 +546,26 @@ where
 }
 
 impl ListItem {
+	/// Rewrites this item into its normal form.
+	///
+	/// See [`Normalize`] for the performed rewrites.
+	fn normalize(&mut self, scope: &Scope, subject: Option<u32>) -> Result<(), BuildError> {
+		let object = scope.variable_count();
+		let scope = scope.with_intro(self.intro.as_slice())?;
+		self.intro.normalize(&scope)?;
+		self.value.normalize(&scope)?;
+		self.dataset.normalize(&scope)?;
+		self.property.normalize(&scope)?;
+
+		if let Some(subject) = subject {
+			if self.property.is_none() && !self.intro.is_empty() {
+				self.property = self.dataset.take_property(&scope, subject, object)
+			}
+		}
+
+		Ok(())
+	}
+
 	fn build<C: Context>(
 		&self,
 		context: &mut C,
@@ -495,12 +583,15 @@ impl ListItem {
 
 		let scope = scope.with_intro(self.intro.as_slice())?;
 
-		let mut dataset = self.dataset.build(context, &scope)?;
+		let mut dataset = self
+			.dataset
+			.build(context, &scope)
+			.map_err(|e| e.at("dataset"))?;
 		if let Some(prop) = &self.property {
 			match subject {
 				Some(subject) => match object {
 					Some(object) => {
-						let prop = prop.build(context, &scope)?;
+						let prop = prop.build(context, &scope).map_err(|e| e.at("property"))?;
 						dataset.insert(rdf_types::Quad(
 							crate::Pattern::Var(subject),
 							prop,
@@ -508,15 +599,18 @@ impl ListItem {
 							None,
 						));
 					}
-					None => return Err(BuildError::NoPropertyObject),
+					None => return Err(BuildError::NoPropertyObject.at("property")),
 				},
-				None => return Err(BuildError::NoPropertySubject),
+				None => return Err(BuildError::NoPropertySubject.at("property")),
 			}
 		}
 
 		Ok(abs::layout::list::ItemLayout {
 			intro: self.intro.len() as u32,
-			value: self.value.build(context, &scope)?,
+			value: self
+				.value
+				.build(context, &scope)
+				.map_err(|e| e.at("value"))?,
 			dataset,
 		})
 	}
@@ -583,8 +677,11 @@ where
 		let (header, scope) = self.header.build(context, scope)?;
 
 		let mut items = Vec::with_capacity(self.items.len());
-		for item in &self.items {
-			items.push(item.build(context, &scope, subject)?)
+		for (i, item) in self.items.iter().enumerate() {
+			items.push(
+				item.build(context, &scope, subject)
+					.map_err(|e| e.at(i).at("items"))?,
+			)
 		}
 
 		Ok(abs::layout::SizedListLayout {
@@ -6,7 +6,8 @@ use crate::{
 		self,
 		syntax::{
 			check_type, expect_object, get_entry, require_entry, require_type, Build, BuildError,
-			CompactIri, Context, Error, ExpectedType, ObjectUnusedEntries, Pattern, Scope,
+			CompactIri, Context, Error, ExpectedType, Normalize, ObjectUnusedEntries, Pattern,
+			Scope,
 		},
 		RegExp,
 	},
@@ -32,6 +33,13 @@ impl LiteralLayout {
 			Self::Id(l) => l.header.id.as_ref(),
 		}
 	}
+
+	pub fn header(&self) -> &LayoutHeader {
+		match self {
+			Self::Data(l) => l.header(),
+			Self::Id(l) => &l.header,
+		}
+	}
 }
 
 impl TryFromJsonObject for LiteralLayout {
@@ -84,6 +92,31 @@ where
 	}
 }
 
+impl Normalize for LiteralLayout {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		let (header, mut resource, mut datatype) = match self {
+			Self::Data(DataLayout::Unit(l)) => (&mut l.header, None, None),
+			Self::Data(DataLayout::Boolean(l)) => {
+				(&mut l.header, Some(&mut l.resource), l.datatype.as_mut())
+			}
+			Self::Data(DataLayout::Number(l)) => {
+				(&mut l.header, Some(&mut l.resource), Some(&mut l.datatype))
+			}
+			Self::Data(DataLayout::ByteString(l)) => {
+				(&mut l.header, Some(&mut l.resource), Some(&mut l.datatype))
+			}
+			Self::Data(DataLayout::TextString(l)) => {
+				(&mut l.header, Some(&mut l.resource), l.datatype.as_mut())
+			}
+			Self::Id(l) => (&mut l.header, Some(&mut l.resource), None),
+		};
+
+		let scope = header.normalize(scope)?;
+		resource.normalize(&scope)?;
+		datatype.normalize(&scope)
+	}
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum DataLayout {
@@ -104,6 +137,16 @@ impl DataLayout {
 			Self::TextString(l) => l.header.id.as_ref(),
 		}
 	}
+
+	pub fn header(&self) -> &LayoutHeader {
+		match self {
+			Self::Unit(l) => &l.header,
+			Self::Boolean(l) => &l.header,
+			Self::Number(l) => &l.header,
+			Self::ByteString(l) => &l.header,
+			Self::TextString(l) => &l.header,
+		}
+	}
 }
 
 impl TryFromJson for DataLayout {
@@ -253,6 +296,7 @@ pub struct BooleanLayout {
 	#[serde(flatten)]
 	pub header: LayoutHeader,
 
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub resource: Option<Pattern>,
 
 	#[serde(default, skip_serializing_if = "Option::is_none")]
@@ -266,10 +310,10 @@ fn literal_resource<C: Context>(
 	resource: Option<&Pattern>,
 ) -> Result<crate::Pattern<C::Resource>, BuildError> {
 	match resource {
-		Some(r) => r.build(context, scope),
+		Some(r) => r.build(context, scope).map_err(|e| e.at("resource")),
 		None => {
 			if input.is_empty() {
-				Err(BuildError::MissingLiteralTargetResource)
+				Err(BuildError::MissingLiteralTargetResource.at("input"))
 			} else {
 				Ok(crate::Pattern::Var(0))
 			}
@@ -300,7 +344,8 @@ where
 				.datatype
 				.as_ref()
 				.map(|i| i.build(context, &scope))
-				.transpose()?
+				.transpose()
+				.map_err(|e| e.at("datatype"))?
 				.unwrap_or_else(|| context.iri_resource(xsd_types::XSD_BOOLEAN)),
 			extra_properties: header.properties,
 		})
@@ -346,6 +391,7 @@ pub struct NumberLayout {
 	#[serde(flatten)]
 	pub header: LayoutHeader,
 
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub resource: Option<Pattern>,
 
 	pub datatype: CompactIri,
@@ -370,7 +416,10 @@ where
 				&self.header.input,
 				self.resource.as_ref(),
 			)?,
-			datatype: self.datatype.build(context, &scope)?,
+			datatype: self
+				.datatype
+				.build(context, &scope)
+				.map_err(|e| e.at("datatype"))?,
 			extra_properties: header.properties,
 		})
 	}
@@ -415,6 +464,7 @@ pub struct ByteStringLayout {
 	#[serde(flatten)]
 	pub header: LayoutHeader,
 
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub resource: Option<Pattern>,
 
 	pub datatype: CompactIri,
@@ -439,7 +489,10 @@ where
 				&self.header.input,
 				self.resource.as_ref(),
 			)?,
-			datatype: self.datatype.build(context, &scope)?,
+			datatype: self
+				.datatype
+				.build(context, &scope)
+				.map_err(|e| e.at("datatype"))?,
 			extra_properties: header.properties,
 		})
 	}
@@ -487,6 +540,7 @@ pub struct TextStringLayout {
 	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub pattern: Option<RegExp>,
 
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub resource: Option<Pattern>,
 
 	#[serde(default, skip_serializing_if = "Option::is_none")]
@@ -549,7 +603,8 @@ where
 				.datatype
 				.as_ref()
 				.map(|i| i.build(context, &scope))
-				.transpose()?
+				.transpose()
+				.map_err(|e| e.at("datatype"))?
 				.unwrap_or_else(|| context.iri_resource(xsd_types::XSD_STRING)),
 			properties: header.properties,
 		})
@@ -568,6 +623,7 @@ pub struct IdLayout {
 	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub pattern: Option<RegExp>,
 
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub resource: Option<Pattern>,
 }
 
@@ -31,8 +31,8 @@ use crate::{
 
 use super::{
 	get_entry, require_type, Build, BuildError, CompactIri, Context, Dataset, Error, ExpectedType,
-	InvalidCompactIri, ObjectUnusedEntries, OneOrMany, Pattern, Resource, Scope, ValueFormat,
-	VariableName,
+	InvalidCompactIri, Normalize, ObjectUnusedEntries, OneOrMany, Pattern, Resource, Scope,
+	ValueFormat, VariableName,
 };
 
 /// Abstract syntax layout.
@@ -112,6 +112,19 @@ impl Layout {
 		}
 	}
 
+	/// Returns the header of this layout, if any.
+	pub fn header(&self) -> Option<&LayoutHeader> {
+		match self {
+			Self::Literal(l) => Some(l.header()),
+			Self::Product(l) => Some(&l.header),
+			Self::Sum(l) => Some(&l.header),
+			Self::List(l) => Some(l.header()),
+			Self::Boolean(_) => None,
+			Self::Union(l) => Some(&l.header),
+			Self::Intersection(l) => Some(&l.header),
+		}
+	}
+
 	pub fn build(&self, builder: &mut Builder) -> Result<Ref<LayoutType>, BuildError> {
 		let mut context = builder.with_generator_mut(generator::Blank::new());
 		self.build_with_context(&mut context)
@@ -144,6 +157,14 @@ impl Layout {
 		let scope = Scope::default();
 		Build::build(self, context, &scope)
 	}
+
+	/// Rewrites this layout into its normal form.
+	///
+	/// See [`Normalize`] for the performed rewrites.
+	pub fn normalize(&mut self) -> Result<(), BuildError> {
+		let scope = Scope::default();
+		Normalize::normalize(self, &scope)
+	}
 }
 
 impl TryFromJson for Layout {
@@ -236,7 +257,7 @@ where
 	fn build(&self, context: &mut C, scope: &Scope) -> Result<Self::Target, BuildError> {
 		let id = match self.id() {
 			Some(id) => {
-				let iri = id.resolve(scope)?;
+				let iri = id.resolve(scope).map_err(|e| e.at("id"))?;
 				context.iri_resource(&iri)
 			}
 			None => context.anonymous_resource(),
@@ -256,13 +277,27 @@ where
 		let (layout_ref, old_layout) = context.insert_layout(id, layout);
 
 		if old_layout.is_some() {
-			Err(BuildError::LayoutRedefinition)
+			Err(BuildError::LayoutRedefinition.at("id"))
 		} else {
 			Ok(layout_ref)
 		}
 	}
 }
 
+impl Normalize for Layout {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		match self {
+			Self::Literal(l) => l.normalize(scope),
+			Self::Product(l) => l.normalize(scope),
+			Self::Sum(l) => l.normalize(scope),
+			Self::List(l) => l.normalize(scope),
+			Self::Boolean(_) => Ok(()),
+			Self::Union(l) => l.header.normalize(scope).map(|_| ()),
+			Self::Intersection(l) => l.header.normalize(scope).map(|_| ()),
+		}
+	}
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum LayoutRef {
@@ -327,12 +362,26 @@ where
 	fn build(&self, context: &mut C, scope: &Scope) -> Result<Self::Target, BuildError> {
 		let scope = scope.without_variables();
 		match self {
-			Self::Ref(r) => r.build(context, &scope).map(Ref::new),
+			Self::Ref(r) => {
+				let id = r.build(context, &scope)?;
+				context.check_layout_ref(&id)?;
+				Ok(Ref::new(id))
+			}
 			Self::Layout(l) => l.build(context, &scope),
 		}
 	}
 }
 
+impl Normalize for LayoutRef {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		let scope = scope.without_variables();
+		match self {
+			Self::Ref(r) => r.normalize(&scope),
+			Self::Layout(l) => Normalize::normalize(l, &scope),
+		}
+	}
+}
+
 impl From<IriBuf> for LayoutRef {
 	fn from(value: IriBuf) -> Self {
 		Self::Ref(value.into())
@@ -435,6 +484,25 @@ impl LayoutHeader {
 	}
 }
 
+impl LayoutHeader {
+	/// Rewrites this header into its normal form, and returns the scope of
+	/// the layout it belongs to.
+	///
+	/// The layout identifier is compacted using the enclosing `scope`, in
+	/// which it is resolved.
+	pub fn normalize(&mut self, scope: &Scope) -> Result<Scope, BuildError> {
+		self.id.normalize(scope)?;
+
+		let scope = scope.with_header(self)?;
+		self.input.0.unwrap_single();
+		self.intro.unwrap_single();
+		self.dataset.normalize(&scope)?;
+		self.extra.normalize(&scope)?;
+
+		Ok(scope)
+	}
+}
+
 impl<C: Context> Build<C> for LayoutHeader
 where
 	C::Resource: Clone,
@@ -447,8 +515,14 @@ where
 		let header = BuiltLayoutHeader {
 			input: self.input.len() as u32,
 			intro: self.intro.len() as u32,
-			dataset: self.dataset.build(context, &scope)?,
-			properties: self.extra.build(context, &scope)?,
+			dataset: self
+				.dataset
+				.build(context, &scope)
+				.map_err(|e| e.at("dataset"))?,
+			properties: self
+				.extra
+				.build(context, &scope)
+				.map_err(|e| e.at("extra"))?,
 		};
 
 		Ok((header, scope))
@@ -495,8 +569,9 @@ impl<C: Context> Build<C> for ExtraProperties {
 		let mut result = BTreeMap::new();
 
 		for (prop, value) in &self.0 {
-			let prop = prop.build(context, scope)?;
-			let value = value.build(context, scope)?;
+			let at = |e: BuildError| e.at(prop.0.as_str());
+			let prop = prop.build(context, scope).map_err(at)?;
+			let value = value.build(context, scope).map_err(at)?;
 			result.insert(prop, value);
 		}
 
@@ -504,6 +579,21 @@ impl<C: Context> Build<C> for ExtraProperties {
 	}
 }
 
+impl Normalize for ExtraProperties {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		let mut result = BTreeMap::new();
+
+		for (mut prop, mut value) in std::mem::take(&mut self.0) {
+			prop.normalize(scope)?;
+			value.normalize(scope)?;
+			result.insert(prop, value);
+		}
+
+		self.0 = result;
+		Ok(())
+	}
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct ValueInput(OneOrMany<Pattern>);
@@ -555,6 +645,12 @@ impl TryFromJson for ValueInput {
 	}
 }
 
+impl Normalize for ValueInput {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		self.0.normalize(scope)
+	}
+}
+
 impl<C: Context> Build<C> for ValueFormat
 where
 	C::Resource: Clone,
@@ -563,22 +659,38 @@ where
 
 	fn build(&self, context: &mut C, scope: &Scope) -> Result<Self::Target, BuildError> {
 		let mut inputs = Vec::with_capacity(self.input.len());
-		for i in self.input.as_slice() {
-			inputs.push(i.build(context, scope)?);
+		for (i, input) in self.input.as_slice().iter().enumerate() {
+			inputs.push(
+				input
+					.build(context, scope)
+					.map_err(|e| e.at(i).at("input"))?,
+			);
 		}
 
 		Ok(crate::ValueFormat {
-			layout: self.layout.build(context, scope)?,
+			layout: self
+				.layout
+				.build(context, scope)
+				.map_err(|e| e.at("layout"))?,
 			input: inputs,
 			graph: self
 				.graph
 				.as_ref()
 				.map(|g| g.as_ref().map(|g| g.build(context, scope)).transpose())
-				.transpose()?,
+				.transpose()
+				.map_err(|e| e.at("graph"))?,
 		})
 	}
 }
 
+impl Normalize for ValueFormat {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		self.layout.normalize(scope)?;
+		self.input.normalize(scope)?;
+		self.graph.normalize(scope)
+	}
+}
+
 macro_rules! type_markers {
 	($($id:ident: $value:literal),*) => {
 		$(
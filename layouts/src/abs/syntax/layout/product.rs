@@ -8,13 +8,14 @@ use crate::{
 		self,
 		syntax::{
 			check_type, expect_object, get_entry, require_entry, Build, BuildError, Context,
-			Dataset, Error, ObjectUnusedEntries, Pattern, Scope, ValueFormatOrLayout, ValueIntro,
+			Dataset, Error, Normalize, ObjectUnusedEntries, Pattern, Scope, ValueFormatOrLayout,
+			ValueIntro,
 		},
 	},
 	Value,
 };
 
-use super::{LayoutHeader, ProductLayoutType};
+use super::{ExtraProperties, LayoutHeader, ProductLayoutType};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -90,22 +91,28 @@ where
 		let mut fields = BTreeMap::new();
 
 		for (name, field) in &self.fields {
+			let at = |key: &'static str| {
+				move |e: BuildError| e.at(key).at(name.as_str().unwrap_or_default()).at("fields")
+			};
 			let scope = scope.with_intro(field.intro.as_slice())?;
 
-			let mut dataset = field.dataset.build(context, &scope)?;
+			let mut dataset = field
+				.dataset
+				.build(context, &scope)
+				.map_err(at("dataset"))?;
 
 			if let Some(property) = &field.property {
 				if self.header.input.is_empty() {
-					return Err(BuildError::NoPropertySubject);
+					return Err(at("property")(BuildError::NoPropertySubject));
 				} else {
 					let subject = crate::Pattern::Var(0);
 					if field.intro.is_empty() {
-						return Err(BuildError::NoPropertyObject);
+						return Err(at("property")(BuildError::NoPropertyObject));
 					} else {
 						let object = crate::Pattern::Var(
 							(self.header.input.len() + self.header.intro.len()) as u32,
 						);
-						let predicate = property.build(context, &scope)?;
+						let predicate = property.build(context, &scope).map_err(at("property"))?;
 						dataset.insert(rdf_types::Quad(subject, predicate, object, None));
 					}
 				}
@@ -115,9 +122,10 @@ where
 				name.to_owned(),
 				crate::layout::product::Field {
 					intro: field.intro.len() as u32,
-					value: field.value.build(context, &scope)?,
+					value: field.value.build(context, &scope).map_err(at("value"))?,
 					dataset,
 					required: field.required,
+					extra_properties: field.extra.build(context, &scope).map_err(at("extra"))?,
 				},
 			);
 		}
@@ -132,6 +140,29 @@ where
 	}
 }
 
+impl Normalize for ProductLayout {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		let scope = self.header.normalize(scope)?;
+		let object = (self.header.input.len() + self.header.intro.len()) as u32;
+
+		for field in self.fields.values_mut() {
+			let scope = scope.with_intro(field.intro.as_slice())?;
+			field.intro.normalize(&scope)?;
+			field.value.normalize(&scope)?;
+			field.dataset.normalize(&scope)?;
+			field.property.normalize(&scope)?;
+			field.extra.normalize(&scope)?;
+
+			if field.property.is_none() && !self.header.input.is_empty() && !field.intro.is_empty()
+			{
+				field.property = field.dataset.take_property(&scope, 0, object)
+			}
+		}
+
+		Ok(())
+	}
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Field {
@@ -148,6 +179,9 @@ pub struct Field {
 
 	#[serde(default, skip_serializing_if = "crate::abs::is_false")]
 	pub required: bool,
+
+	#[serde(default, skip_serializing_if = "ExtraProperties::is_empty")]
+	pub extra: ExtraProperties,
 }
 
 impl TryFromJson for Field {
@@ -181,6 +215,8 @@ impl TryFromJsonObject for Field {
 			property: get_entry(object, "property", &mut unused_entries, code_map, offset)?,
 			required: get_entry(object, "required", &mut unused_entries, code_map, offset)?
 				.unwrap_or_default(),
+			extra: get_entry(object, "extra", &mut unused_entries, code_map, offset)?
+				.unwrap_or_default(),
 		};
 		unused_entries.check()?;
 		Ok(result)
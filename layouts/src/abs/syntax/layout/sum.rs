@@ -7,11 +7,11 @@ use crate::abs::{
 	self,
 	syntax::{
 		check_type, expect_object, get_entry, require_entry, Build, BuildError, Context, Dataset,
-		Error, ObjectUnusedEntries, OneOrMany, Pattern, Scope, VariableName,
+		Error, Normalize, ObjectUnusedEntries, OneOrMany, Pattern, Scope, VariableName,
 	},
 };
 
-use super::{LayoutHeader, LayoutRef, SumLayoutType};
+use super::{ExtraProperties, LayoutHeader, LayoutRef, SumLayoutType};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -81,6 +81,9 @@ pub struct Variant {
 
 	#[serde(default, skip_serializing_if = "Dataset::is_empty")]
 	pub dataset: Dataset,
+
+	#[serde(default, skip_serializing_if = "ExtraProperties::is_empty")]
+	pub extra: ExtraProperties,
 }
 
 impl TryFromJson for Variant {
@@ -111,6 +114,8 @@ impl TryFromJsonObject for Variant {
 			value: require_entry(object, "value", &mut unused_entries, code_map, offset)?,
 			dataset: get_entry(object, "dataset", &mut unused_entries, code_map, offset)?
 				.unwrap_or_default(),
+			extra: get_entry(object, "extra", &mut unused_entries, code_map, offset)?
+				.unwrap_or_default(),
 		};
 		unused_entries.check()?;
 		Ok(result)
@@ -129,12 +134,18 @@ where
 		let mut variants = Vec::with_capacity(self.variants.len());
 
 		for (name, variant) in &self.variants {
+			let at =
+				|key: &'static str| move |e: BuildError| e.at(key).at(name.as_str()).at("variants");
 			let scope = scope.with_intro(variant.intro.as_slice())?;
 			variants.push(crate::layout::sum::Variant {
 				name: name.to_owned(),
 				intro: variant.intro.len() as u32,
-				value: variant.value.build(context, &scope)?,
-				dataset: variant.dataset.build(context, &scope)?,
+				value: variant.value.build(context, &scope).map_err(at("value"))?,
+				dataset: variant
+					.dataset
+					.build(context, &scope)
+					.map_err(at("dataset"))?,
+				extra_properties: variant.extra.build(context, &scope).map_err(at("extra"))?,
 			})
 		}
 
@@ -148,6 +159,22 @@ where
 	}
 }
 
+impl Normalize for SumLayout {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		let scope = self.header.normalize(scope)?;
+
+		for variant in self.variants.values_mut() {
+			let scope = scope.with_intro(variant.intro.as_slice())?;
+			variant.intro.unwrap_single();
+			variant.value.normalize(&scope)?;
+			variant.dataset.normalize(&scope)?;
+			variant.extra.normalize(&scope)?;
+		}
+
+		Ok(())
+	}
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum VariantFormatOrLayout {
@@ -201,6 +228,22 @@ where
 	}
 }
 
+impl Normalize for VariantFormatOrLayout {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		match self {
+			Self::Format(f) => {
+				f.normalize(scope)?;
+				if f.input.is_default() && f.graph.is_none() {
+					*self = Self::Layout(f.layout.clone())
+				}
+
+				Ok(())
+			}
+			Self::Layout(layout) => layout.normalize(scope),
+		}
+	}
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct VariantFormat {
@@ -255,22 +298,38 @@ where
 
 	fn build(&self, context: &mut C, scope: &Scope) -> Result<Self::Target, BuildError> {
 		let mut inputs = Vec::with_capacity(self.input.len());
-		for i in self.input.as_slice() {
-			inputs.push(i.build(context, scope)?);
+		for (i, input) in self.input.as_slice().iter().enumerate() {
+			inputs.push(
+				input
+					.build(context, scope)
+					.map_err(|e| e.at(i).at("input"))?,
+			);
 		}
 
 		Ok(crate::ValueFormat {
-			layout: self.layout.build(context, scope)?,
+			layout: self
+				.layout
+				.build(context, scope)
+				.map_err(|e| e.at("layout"))?,
 			input: inputs,
 			graph: self
 				.graph
 				.as_ref()
 				.map(|g| g.as_ref().map(|g| g.build(context, scope)).transpose())
-				.transpose()?,
+				.transpose()
+				.map_err(|e| e.at("graph"))?,
 		})
 	}
 }
 
+impl Normalize for VariantFormat {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		self.layout.normalize(scope)?;
+		self.input.0.normalize(scope)?;
+		self.graph.normalize(scope)
+	}
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct VariantInput(OneOrMany<Pattern>);
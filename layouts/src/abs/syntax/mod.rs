@@ -6,12 +6,14 @@ use serde::{Deserialize, Serialize};
 mod build;
 mod dataset;
 mod layout;
+mod normalize;
 mod pattern;
 mod resource;
 
 pub use build::*;
 pub use dataset::*;
 pub use layout::*;
+pub use normalize::*;
 pub use pattern::*;
 pub use resource::*;
 
@@ -404,6 +406,22 @@ impl<T> OneOrMany<T> {
 			Self::Many(v) => v.as_slice(),
 		}
 	}
+
+	pub fn as_mut_slice(&mut self) -> &mut [T] {
+		match self {
+			Self::One(t) => std::slice::from_mut(t),
+			Self::Many(v) => v.as_mut_slice(),
+		}
+	}
+
+	/// Replaces a single item array by the item itself.
+	pub fn unwrap_single(&mut self) {
+		if let Self::Many(v) = self {
+			if v.len() == 1 {
+				*self = Self::One(v.pop().unwrap())
+			}
+		}
+	}
 }
 
 impl<T> Default for OneOrMany<T> {
@@ -495,6 +513,22 @@ where
 	}
 }
 
+impl Normalize for ValueFormatOrLayout {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		match self {
+			Self::Format(f) => {
+				f.normalize(scope)?;
+				if f.input.is_default() && f.graph.is_none() {
+					*self = Self::Layout(f.layout.clone())
+				}
+
+				Ok(())
+			}
+			Self::Layout(layout) => layout.normalize(scope),
+		}
+	}
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ValueFormat {
@@ -575,3 +609,10 @@ impl TryFromJson for ValueIntro {
 		Ok(Self(OneOrMany::try_from_json_at(json, code_map, offset)?))
 	}
 }
+
+impl Normalize for ValueIntro {
+	fn normalize(&mut self, _scope: &Scope) -> Result<(), BuildError> {
+		self.0.unwrap_single();
+		Ok(())
+	}
+}
@@ -0,0 +1,50 @@
+use super::{BuildError, OneOrMany, Scope};
+
+/// Layout fragment normalization.
+///
+/// Rewrites a layout fragment into its normal form without changing the
+/// layout it builds to:
+///   - full IRIs are compacted using the IRI prefixes in scope;
+///   - single item arrays are replaced by the item itself;
+///   - value formats using the default input are replaced by their layout;
+///   - dataset quads relating the layout input to the value of a record field
+///     or list item are replaced by the equivalent `property`.
+///
+/// Entries set to their default value are not serialized, and entries are
+/// serialized in a fixed order, so that serializing a normalized layout gives
+/// its canonical representation.
+pub trait Normalize {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError>;
+}
+
+impl<T: Normalize> Normalize for &mut T {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		T::normalize(self, scope)
+	}
+}
+
+impl<T: Normalize> Normalize for Box<T> {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		T::normalize(self, scope)
+	}
+}
+
+impl<T: Normalize> Normalize for Option<T> {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		match self {
+			Some(t) => t.normalize(scope),
+			None => Ok(()),
+		}
+	}
+}
+
+impl<T: Normalize> Normalize for OneOrMany<T> {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		for item in self.as_mut_slice() {
+			item.normalize(scope)?
+		}
+
+		self.unwrap_single();
+		Ok(())
+	}
+}
@@ -5,7 +5,7 @@ use json_syntax::TryFromJson;
 use serde::{Deserialize, Serialize};
 use xsd_types::XSD_STRING;
 
-use crate::abs::syntax::{expect_string, Build, BuildError, Context, Error, Scope};
+use crate::abs::syntax::{expect_string, Build, BuildError, Context, Error, Normalize, Scope};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -84,3 +84,44 @@ impl<C: Context> Build<C> for CompactIri {
 		Ok(context.iri_resource(&iri))
 	}
 }
+
+impl Normalize for CompactIri {
+	/// Compacts this IRI using the longest matching IRI prefix in `scope`.
+	///
+	/// Relative IRI references and IRIs that are already compact are left
+	/// unchanged.
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		let Some(iri) = self.0.as_iri() else {
+			return Ok(());
+		};
+
+		if scope.iri_prefix(iri.scheme().as_str()).is_some() {
+			return Ok(());
+		}
+
+		let compact = scope
+			.iri_prefixes()
+			.filter_map(|(name, prefix)| {
+				let suffix = iri.as_str().strip_prefix(prefix.as_str())?;
+				if suffix.is_empty() {
+					return None;
+				}
+
+				let compact = Self(IriRefBuf::new(format!("{name}:{suffix}")).ok()?);
+				(compact.resolve(scope).ok()? == iri).then_some((
+					prefix.as_str().len(),
+					name,
+					compact,
+				))
+			})
+			.max_by(|(a_len, a_name, _), (b_len, b_name, _)| {
+				a_len.cmp(b_len).then_with(|| b_name.cmp(a_name))
+			});
+
+		if let Some((_, _, compact)) = compact {
+			*self = compact
+		}
+
+		Ok(())
+	}
+}
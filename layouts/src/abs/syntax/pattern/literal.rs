@@ -2,7 +2,9 @@ use iref::IriBuf;
 use langtag::LangTagBuf;
 use rdf_types::XSD_STRING;
 
-use crate::abs::syntax::{get_entry, require_entry, BuildError, Error, ObjectUnusedEntries, Scope};
+use crate::abs::syntax::{
+	get_entry, require_entry, BuildError, Error, Normalize, ObjectUnusedEntries, Scope,
+};
 
 use super::CompactIri;
 
@@ -29,11 +31,12 @@ impl LiteralValue {
 				LiteralType::Iri(LiteralTypeIri { type_: ty })
 			}
 			None => {
-				let language: String =
-					require_entry(object, "language", &mut unused_entries, code_map, offset)?;
-				match LangTagBuf::new(language) {
-					Ok(language) => LiteralType::Language(LiteralTypeLanguage { language }),
-					Err(e) => return Err(Error::InvalidLangTag(offset, e.0)),
+				let language: Option<String> =
+					get_entry(object, "language", &mut unused_entries, code_map, offset)?;
+				match language.map(LangTagBuf::new) {
+					Some(Ok(language)) => LiteralType::Language(LiteralTypeLanguage { language }),
+					Some(Err(e)) => return Err(Error::InvalidLangTag(offset, e.0)),
+					None => LiteralType::default(),
 				}
 			}
 		};
@@ -80,6 +83,22 @@ impl LiteralType {
 	}
 }
 
+impl Normalize for LiteralType {
+	/// Compacts the literal type IRI, unless it is `xsd:string`, which is
+	/// expanded so that it can be omitted.
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		if let Self::Iri(iri) = self {
+			if iri.resolve(scope)? == XSD_STRING {
+				iri.type_ = CompactIri::xsd_string()
+			} else {
+				iri.type_.normalize(scope)?
+			}
+		}
+
+		Ok(())
+	}
+}
+
 impl From<rdf_types::LiteralType> for LiteralType {
 	fn from(value: rdf_types::LiteralType) -> Self {
 		match value {
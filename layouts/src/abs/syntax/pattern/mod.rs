@@ -12,7 +12,7 @@ use rdf_types::{BlankId, BlankIdBuf, Id, Term, RDF_NIL};
 use serde::{Deserialize, Serialize};
 pub use variable::*;
 
-use super::{BuildError, Error, Scope};
+use super::{BuildError, Error, Normalize, Scope};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Pattern {
@@ -71,6 +71,16 @@ impl Pattern {
 	}
 }
 
+impl Normalize for Pattern {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		match self {
+			Self::Var(_) => Ok(()),
+			Self::Iri(compact_iri) => compact_iri.normalize(scope),
+			Self::Literal(l) => l.type_.normalize(scope),
+		}
+	}
+}
+
 impl TryFromJson for Pattern {
 	type Error = Error;
 
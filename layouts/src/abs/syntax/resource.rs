@@ -4,7 +4,8 @@ use serde::{Deserialize, Serialize};
 use xsd_types::{XSD_BOOLEAN, XSD_STRING};
 
 use super::{
-	require_entry, Build, BuildError, CompactIri, Context, Error, ObjectUnusedEntries, Scope,
+	require_entry, Build, BuildError, CompactIri, Context, Error, Normalize, ObjectUnusedEntries,
+	Scope,
 };
 
 /// RDF Resource description.
@@ -87,6 +88,15 @@ impl<C: Context> Build<C> for Resource {
 	}
 }
 
+impl Normalize for Resource {
+	fn normalize(&mut self, scope: &Scope) -> Result<(), BuildError> {
+		match self {
+			Self::TypedString(t) => t.type_.normalize(scope),
+			_ => Ok(()),
+		}
+	}
+}
+
 /// Typed string literal.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct TypedString {
@@ -118,7 +128,7 @@ impl<C: Context> Build<C> for TypedString {
 	type Target = C::Resource;
 
 	fn build(&self, context: &mut C, scope: &Scope) -> Result<Self::Target, BuildError> {
-		let type_ = self.type_.resolve(scope)?;
+		let type_ = self.type_.resolve(scope).map_err(|e| e.at("type"))?;
 		Ok(context.literal_resource(&self.value, LexicalLiteralTypeRef::Any(&type_)))
 	}
 }
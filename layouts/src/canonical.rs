@@ -0,0 +1,497 @@
+//! RDF Dataset Canonicalization ([RDFC-1.0]).
+//!
+//! Canonicalization relabels the blank nodes of a dataset in a deterministic
+//! way, such that two isomorphic datasets have the same canonical form. The
+//! canonical N-Quads serialization of a dataset can then be hashed or signed.
+//!
+//! [RDFC-1.0]: <https://www.w3.org/TR/rdf-canon/>
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+
+use iref::{Iri, IriBuf};
+use rdf_types::{BlankId, BlankIdBuf, Id, Literal, LiteralType, Quad, Term, XSD_STRING};
+use sha2::{Digest, Sha256};
+
+/// Default maximum number of calls to the Hash N-Degree Quads algorithm.
+///
+/// Some datasets (with many indistinguishable blank nodes) require an
+/// exponential amount of work to be canonicalized. This limit is used to
+/// reject such datasets.
+pub const DEFAULT_MAX_N_DEGREE_CALLS: usize = 4000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	#[error("dataset is too complex to canonicalize (more than {0} Hash N-Degree Quads calls)")]
+	TooComplex(usize),
+}
+
+/// Canonicalizes the given dataset.
+///
+/// Returns the quads of the dataset with their canonical blank node
+/// identifiers (`_:c14n0`, `_:c14n1`, etc.), sorted in the order of their
+/// canonical N-Quads serialization. Duplicate quads are removed.
+pub fn canonicalize(dataset: impl IntoIterator<Item = Quad>) -> Result<Vec<Quad>, Error> {
+	canonicalize_with(dataset, DEFAULT_MAX_N_DEGREE_CALLS)
+}
+
+/// Canonicalizes the given dataset, with the given maximum number of calls to
+/// the Hash N-Degree Quads algorithm.
+///
+/// See [`canonicalize`].
+pub fn canonicalize_with(
+	dataset: impl IntoIterator<Item = Quad>,
+	max_n_degree_calls: usize,
+) -> Result<Vec<Quad>, Error> {
+	let quads: Vec<Quad> = dataset
+		.into_iter()
+		.collect::<BTreeSet<_>>()
+		.into_iter()
+		.collect();
+	let state = State::new(&quads, max_n_degree_calls);
+	let canonical_issuer = state.issue_canonical_identifiers()?;
+
+	let relabel = |term: &Term| match term {
+		Term::Id(Id::Blank(id)) => Term::blank(
+			BlankIdBuf::new(format!("_:{}", canonical_issuer.get(id).unwrap())).unwrap(),
+		),
+		term => term.clone(),
+	};
+
+	let mut result: Vec<(String, Quad)> = quads
+		.iter()
+		.map(|Quad(s, p, o, g)| {
+			let quad = Quad(relabel(s), relabel(p), relabel(o), g.as_ref().map(relabel));
+			let mut line = String::new();
+			write_nquad(&mut line, &quad, |id| id.suffix());
+			(line, quad)
+		})
+		.collect();
+
+	result.sort_by(|a, b| a.0.cmp(&b.0));
+	Ok(result.into_iter().map(|(_, quad)| quad).collect())
+}
+
+/// Returns the canonical N-Quads serialization of the given dataset.
+///
+/// See [`canonicalize`].
+pub fn canonical_nquads(dataset: impl IntoIterator<Item = Quad>) -> Result<String, Error> {
+	let mut result = String::new();
+	for quad in canonicalize(dataset)? {
+		write_nquad(&mut result, &quad, BlankId::suffix);
+	}
+
+	Ok(result)
+}
+
+/// Replaces every blank node of the given dataset by an IRI derived from the
+/// dataset content.
+///
+/// The IRI of each blank node is the given `prefix` followed by the
+/// hexadecimal SHA-256 hash of the canonical N-Quads serialization of the
+/// dataset followed by the canonical identifier of the blank node. The same
+/// (isomorphic) dataset hence always gets the same IRIs, no matter how its
+/// blank nodes are labeled.
+///
+/// Returns the quads of the dataset (without duplicates) along with the IRI
+/// given to each blank node.
+pub fn hash_blank_nodes(
+	dataset: impl IntoIterator<Item = Quad>,
+	prefix: &Iri,
+) -> Result<(Vec<Quad>, HashMap<BlankIdBuf, IriBuf>), Error> {
+	let quads: Vec<Quad> = dataset
+		.into_iter()
+		.collect::<BTreeSet<_>>()
+		.into_iter()
+		.collect();
+	let state = State::new(&quads, DEFAULT_MAX_N_DEGREE_CALLS);
+	let canonical_issuer = state.issue_canonical_identifiers()?;
+
+	let mut lines: Vec<String> = quads
+		.iter()
+		.map(|quad| {
+			let mut line = String::new();
+			write_nquad(&mut line, quad, |id| canonical_issuer.get(id).unwrap());
+			line
+		})
+		.collect();
+	lines.sort();
+	let document = lines.concat();
+
+	let map: HashMap<BlankIdBuf, IriBuf> = canonical_issuer
+		.issued
+		.iter()
+		.map(|id| {
+			let label = canonical_issuer.get(id).unwrap();
+			let iri = format!("{prefix}{}", hash(&format!("{document}_:{label}")));
+			((*id).to_owned(), IriBuf::new(iri).unwrap())
+		})
+		.collect();
+
+	let relabel = |term: &Term| match term {
+		Term::Id(Id::Blank(id)) => Term::iri(map[id].clone()),
+		term => term.clone(),
+	};
+
+	let result = quads
+		.iter()
+		.map(|Quad(s, p, o, g)| Quad(relabel(s), relabel(p), relabel(o), g.as_ref().map(relabel)))
+		.collect();
+
+	Ok((result, map))
+}
+
+/// Writes the canonical N-Quads serialization of the given quad, including
+/// the final new line.
+///
+/// Blank node identifiers are written using the given `label` function,
+/// returning the identifier without the `_:` prefix.
+pub fn write_nquad<'a>(
+	output: &mut String,
+	quad: &'a Quad,
+	label: impl Fn(&'a BlankId) -> &'a str,
+) {
+	let Quad(s, p, o, g) = quad;
+	write_term(output, s, &label);
+	output.push(' ');
+	write_term(output, p, &label);
+	output.push(' ');
+	write_term(output, o, &label);
+	if let Some(g) = g {
+		output.push(' ');
+		write_term(output, g, &label);
+	}
+	output.push_str(" .\n")
+}
+
+fn write_term<'a>(output: &mut String, term: &'a Term, label: &impl Fn(&'a BlankId) -> &'a str) {
+	match term {
+		Term::Id(Id::Iri(iri)) => write!(output, "<{iri}>").unwrap(),
+		Term::Id(Id::Blank(id)) => write!(output, "_:{}", label(id)).unwrap(),
+		Term::Literal(literal) => write_literal(output, literal),
+	}
+}
+
+fn write_literal(output: &mut String, literal: &Literal) {
+	output.push('"');
+	for c in literal.value.chars() {
+		match c {
+			'"' => output.push_str("\\\""),
+			'\\' => output.push_str("\\\\"),
+			'\u{8}' => output.push_str("\\b"),
+			'\t' => output.push_str("\\t"),
+			'\n' => output.push_str("\\n"),
+			'\u{c}' => output.push_str("\\f"),
+			'\r' => output.push_str("\\r"),
+			'\u{0}'..='\u{1f}' | '\u{7f}' => write!(output, "\\u{:04X}", c as u32).unwrap(),
+			c => output.push(c),
+		}
+	}
+	output.push('"');
+
+	match &literal.type_ {
+		LiteralType::Any(iri) if *iri == XSD_STRING => (),
+		LiteralType::Any(iri) => write!(output, "^^<{iri}>").unwrap(),
+		LiteralType::LangString(tag) => write!(output, "@{tag}").unwrap(),
+	}
+}
+
+fn hash(data: &str) -> String {
+	let mut result = String::with_capacity(64);
+	for byte in Sha256::digest(data.as_bytes()) {
+		write!(result, "{byte:02x}").unwrap();
+	}
+
+	result
+}
+
+/// Blank node identifier issuer.
+#[derive(Clone)]
+struct IdentifierIssuer<'a> {
+	prefix: &'static str,
+
+	/// Issued identifiers, in issuance order.
+	issued: Vec<&'a BlankId>,
+
+	map: HashMap<&'a BlankId, String>,
+}
+
+impl<'a> IdentifierIssuer<'a> {
+	fn new(prefix: &'static str) -> Self {
+		Self {
+			prefix,
+			issued: Vec::new(),
+			map: HashMap::new(),
+		}
+	}
+
+	fn get(&self, id: &BlankId) -> Option<&str> {
+		self.map.get(id).map(String::as_str)
+	}
+
+	fn issue(&mut self, id: &'a BlankId) -> &str {
+		let count = self.issued.len();
+		self.map.entry(id).or_insert_with(|| {
+			self.issued.push(id);
+			format!("{}{count}", self.prefix)
+		})
+	}
+}
+
+/// Canonicalization state.
+struct State<'a> {
+	/// Quads mentioning each blank node.
+	blank_node_quads: BTreeMap<&'a BlankId, Vec<&'a Quad>>,
+
+	canonical_issuer: IdentifierIssuer<'a>,
+
+	max_n_degree_calls: usize,
+
+	n_degree_calls: usize,
+}
+
+impl<'a> State<'a> {
+	fn new(quads: &'a [Quad], max_n_degree_calls: usize) -> Self {
+		let mut blank_node_quads: BTreeMap<&BlankId, Vec<&Quad>> = BTreeMap::new();
+		for quad in quads {
+			let Quad(s, p, o, g) = quad;
+			for term in [Some(s), Some(p), Some(o), g.as_ref()]
+				.into_iter()
+				.flatten()
+			{
+				if let Term::Id(Id::Blank(id)) = term {
+					let quads = blank_node_quads.entry(id).or_default();
+					if quads.last() != Some(&quad) {
+						quads.push(quad)
+					}
+				}
+			}
+		}
+
+		Self {
+			blank_node_quads,
+			canonical_issuer: IdentifierIssuer::new("c14n"),
+			max_n_degree_calls,
+			n_degree_calls: 0,
+		}
+	}
+
+	/// Issues the canonical identifier of every blank node, and returns the
+	/// canonical issuer.
+	fn issue_canonical_identifiers(mut self) -> Result<IdentifierIssuer<'a>, Error> {
+		let mut hash_to_blank_nodes: BTreeMap<String, Vec<&'a BlankId>> = BTreeMap::new();
+		for &id in self.blank_node_quads.keys() {
+			hash_to_blank_nodes
+				.entry(self.hash_first_degree_quads(id))
+				.or_default()
+				.push(id)
+		}
+
+		for ids in hash_to_blank_nodes.values() {
+			if let [id] = ids.as_slice() {
+				self.canonical_issuer.issue(id);
+			}
+		}
+
+		for ids in hash_to_blank_nodes.values() {
+			if ids.len() == 1 {
+				continue;
+			}
+
+			let mut hash_path_list = Vec::new();
+			for &id in ids {
+				if self.canonical_issuer.get(id).is_some() {
+					continue;
+				}
+
+				let mut issuer = IdentifierIssuer::new("b");
+				issuer.issue(id);
+				hash_path_list.push(self.hash_n_degree_quads(id, issuer)?);
+			}
+
+			hash_path_list.sort_by(|a, b| a.0.cmp(&b.0));
+			for (_, issuer) in hash_path_list {
+				for id in issuer.issued {
+					self.canonical_issuer.issue(id);
+				}
+			}
+		}
+
+		Ok(self.canonical_issuer)
+	}
+
+	/// Hash First Degree Quads algorithm.
+	fn hash_first_degree_quads(&self, id: &BlankId) -> String {
+		let mut lines: Vec<String> = self.blank_node_quads[id]
+			.iter()
+			.map(|quad| {
+				let mut line = String::new();
+				write_nquad(&mut line, quad, |other| if other == id { "a" } else { "z" });
+				line
+			})
+			.collect();
+
+		lines.sort_unstable();
+		hash(&lines.concat())
+	}
+
+	/// Hash Related Blank Node algorithm.
+	fn hash_related_blank_node(
+		&self,
+		related: &BlankId,
+		quad: &Quad,
+		issuer: &IdentifierIssuer,
+		position: char,
+	) -> String {
+		let mut input = position.to_string();
+		if position != 'g' {
+			write_term(&mut input, &quad.1, &BlankId::suffix);
+		}
+
+		match self
+			.canonical_issuer
+			.get(related)
+			.or_else(|| issuer.get(related))
+		{
+			Some(id) => write!(input, "_:{id}").unwrap(),
+			None => input.push_str(&self.hash_first_degree_quads(related)),
+		}
+
+		hash(&input)
+	}
+
+	/// Hash N-Degree Quads algorithm.
+	fn hash_n_degree_quads(
+		&mut self,
+		id: &'a BlankId,
+		mut issuer: IdentifierIssuer<'a>,
+	) -> Result<(String, IdentifierIssuer<'a>), Error> {
+		self.n_degree_calls += 1;
+		if self.n_degree_calls > self.max_n_degree_calls {
+			return Err(Error::TooComplex(self.max_n_degree_calls));
+		}
+
+		let mut hash_to_related: BTreeMap<String, Vec<&'a BlankId>> = BTreeMap::new();
+		for &quad in &self.blank_node_quads[id] {
+			let Quad(s, _, o, g) = quad;
+			for (term, position) in [(Some(s), 's'), (Some(o), 'o'), (g.as_ref(), 'g')] {
+				if let Some(Term::Id(Id::Blank(related))) = term {
+					if related != id {
+						let hash = self.hash_related_blank_node(related, quad, &issuer, position);
+						hash_to_related.entry(hash).or_default().push(related)
+					}
+				}
+			}
+		}
+
+		let mut data_to_hash = String::new();
+		for (related_hash, blank_nodes) in hash_to_related {
+			data_to_hash.push_str(&related_hash);
+			let mut chosen: Option<(String, IdentifierIssuer)> = None;
+
+			let mut permutations = Permutations::new(blank_nodes);
+			while let Some(permutation) = permutations.next() {
+				let mut issuer_copy = issuer.clone();
+				let mut path = String::new();
+				let mut recursion_list = Vec::new();
+
+				let is_worse = |path: &str, chosen: &Option<(String, IdentifierIssuer)>| {
+					chosen.as_ref().is_some_and(|(chosen_path, _)| {
+						path.len() >= chosen_path.len() && path > chosen_path.as_str()
+					})
+				};
+
+				let mut skip = false;
+				for &related in permutation {
+					match self.canonical_issuer.get(related) {
+						Some(id) => write!(path, "_:{id}").unwrap(),
+						None => {
+							if issuer_copy.get(related).is_none() {
+								recursion_list.push(related)
+							}
+
+							write!(path, "_:{}", issuer_copy.issue(related)).unwrap()
+						}
+					}
+
+					if is_worse(&path, &chosen) {
+						skip = true;
+						break;
+					}
+				}
+
+				if skip {
+					continue;
+				}
+
+				for related in recursion_list {
+					let (hash, result_issuer) = self.hash_n_degree_quads(related, issuer_copy)?;
+					issuer_copy = result_issuer;
+					write!(path, "_:{}<{hash}>", issuer_copy.issue(related)).unwrap();
+
+					if is_worse(&path, &chosen) {
+						skip = true;
+						break;
+					}
+				}
+
+				if skip {
+					continue;
+				}
+
+				if chosen
+					.as_ref()
+					.map_or(true, |(chosen_path, _)| path < *chosen_path)
+				{
+					chosen = Some((path, issuer_copy))
+				}
+			}
+
+			let (chosen_path, chosen_issuer) = chosen.unwrap();
+			data_to_hash.push_str(&chosen_path);
+			issuer = chosen_issuer;
+		}
+
+		Ok((hash(&data_to_hash), issuer))
+	}
+}
+
+/// Permutations of a list of items, in lexicographic order of their indexes.
+struct Permutations<T> {
+	items: Vec<T>,
+	indexes: Vec<usize>,
+	permutation: Vec<T>,
+	started: bool,
+}
+
+impl<T: Copy> Permutations<T> {
+	fn new(items: Vec<T>) -> Self {
+		Self {
+			indexes: (0..items.len()).collect(),
+			permutation: items.clone(),
+			items,
+			started: false,
+		}
+	}
+
+	/// Returns the next permutation.
+	fn next(&mut self) -> Option<&[T]> {
+		if self.started {
+			// Find the last index smaller than its successor.
+			let i = self.indexes.windows(2).rposition(|w| w[0] < w[1])?;
+			let j = self
+				.indexes
+				.iter()
+				.rposition(|&j| j > self.indexes[i])
+				.unwrap();
+			self.indexes.swap(i, j);
+			self.indexes[i + 1..].reverse();
+
+			for (item, &k) in self.permutation.iter_mut().zip(&self.indexes) {
+				*item = self.items[k]
+			}
+		}
+
+		self.started = true;
+		Some(&self.permutation)
+	}
+}
@@ -0,0 +1,114 @@
+//! Node identifier generators.
+//!
+//! These generators can be given to the [`Options`](super::Options) of the
+//! dehydration algorithm to assign globally unique IRIs to anonymous
+//! resources instead of blank node identifiers.
+use iref::{Iri, IriBuf, IriRef};
+use rdf_types::{
+	vocabulary::{BlankIdVocabulary, IriVocabularyMut},
+	Generator, Id,
+};
+
+/// Generates `urn:uuid:{uuid}` IRIs, where `{uuid}` is a random (version 4)
+/// UUID.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Uuid;
+
+impl<V: IriVocabularyMut + BlankIdVocabulary> Generator<V> for Uuid {
+	fn next(&mut self, vocabulary: &mut V) -> Id<V::Iri, V::BlankId> {
+		let iri = IriBuf::new(format!("urn:uuid:{}", random_uuid())).unwrap();
+		Id::Iri(vocabulary.insert(&iri))
+	}
+}
+
+/// Generates [Skolem IRIs] of the form
+/// `{authority}/.well-known/genid/{uuid}` where `{authority}` is the
+/// scheme and authority of a base IRI and `{uuid}` is a random (version 4)
+/// UUID.
+///
+/// [Skolem IRIs]: <https://www.w3.org/TR/rdf11-concepts/#section-skolemization>
+#[derive(Debug, Clone)]
+pub struct Genid {
+	prefix: IriBuf,
+}
+
+impl Genid {
+	/// Creates a new generator minting Skolem IRIs under the authority of the
+	/// given base IRI.
+	pub fn new(base: &Iri) -> Self {
+		Self {
+			prefix: IriRef::new("/.well-known/genid/").unwrap().resolved(base),
+		}
+	}
+
+	/// Returns the prefix of the generated IRIs.
+	pub fn prefix(&self) -> &Iri {
+		&self.prefix
+	}
+}
+
+impl<V: IriVocabularyMut + BlankIdVocabulary> Generator<V> for Genid {
+	fn next(&mut self, vocabulary: &mut V) -> Id<V::Iri, V::BlankId> {
+		let iri = IriBuf::new(format!("{}{}", self.prefix, random_uuid())).unwrap();
+		Id::Iri(vocabulary.insert(&iri))
+	}
+}
+
+/// Generates a random (version 4) UUID, in its hyphenated lowercase form.
+fn random_uuid() -> String {
+	let mut bytes = [0u8; 16];
+	getrandom::getrandom(&mut bytes).expect("unable to generate random bytes");
+
+	// Version 4, RFC 4122 variant.
+	bytes[6] = (bytes[6] & 0x0f) | 0x40;
+	bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+	let mut result = String::with_capacity(36);
+	for (i, b) in bytes.iter().enumerate() {
+		if matches!(i, 4 | 6 | 8 | 10) {
+			result.push('-')
+		}
+
+		result.push_str(&format!("{b:02x}"))
+	}
+
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn uuid() {
+		let Id::Iri(iri) = Generator::<()>::next(&mut Uuid, &mut ()) else {
+			panic!("expected IRI")
+		};
+
+		let uuid = iri.as_str().strip_prefix("urn:uuid:").unwrap();
+		assert_eq!(uuid.len(), 36);
+		assert_eq!(&uuid[14..15], "4");
+		assert!(matches!(&uuid[19..20], "8" | "9" | "a" | "b"))
+	}
+
+	#[test]
+	fn genid() {
+		let base = Iri::new("https://example.org/foo/bar?q").unwrap();
+		let mut generator = Genid::new(base);
+		assert_eq!(
+			generator.prefix().as_str(),
+			"https://example.org/.well-known/genid/"
+		);
+
+		let Id::Iri(a) = Generator::<()>::next(&mut generator, &mut ()) else {
+			panic!("expected IRI")
+		};
+
+		let Id::Iri(b) = Generator::<()>::next(&mut generator, &mut ()) else {
+			panic!("expected IRI")
+		};
+
+		assert!(a.as_str().starts_with(generator.prefix().as_str()));
+		assert_ne!(a, b)
+	}
+}
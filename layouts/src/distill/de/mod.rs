@@ -22,6 +22,7 @@ use rdf_types::{
 use super::RdfContextMut;
 
 mod data;
+pub mod id;
 
 pub type RdfLiteral<V> = rdf_types::Literal<<V as IriVocabulary>::Iri>;
 
@@ -115,6 +116,12 @@ pub struct Options<G = generator::Blank> {
 	/// identifier `_:input{i}` where `{i}` is replaced by the input index.
 	input_term_generator: fn(usize) -> Term,
 
+	/// Use the `generator` for input resources too, instead of the
+	/// `input_term_generator`.
+	///
+	/// By default this is `false`.
+	generate_inputs: bool,
+
 	/// Resource id generator for non-input resources.
 	///
 	/// By default the [`generator::Blank`] generator is used, creating a new
@@ -122,6 +129,12 @@ pub struct Options<G = generator::Blank> {
 	/// where `{i}` is replaced by a unique number from `0` to `n` where `n` is
 	/// the number of anonymous non-input resources.
 	generator: G,
+
+	/// Variant selection of the sum layouts used to deserialize the value.
+	///
+	/// By default every variant is tried, and the value must match exactly one
+	/// of them.
+	selection: Selection,
 }
 
 impl Default for Options {
@@ -129,7 +142,9 @@ impl Default for Options {
 		Self {
 			input_count: None,
 			input_term_generator: |i| Term::blank(BlankIdBuf::new(format!("_:input{i}")).unwrap()),
+			generate_inputs: false,
 			generator: generator::Blank::new(),
+			selection: Selection::Any,
 		}
 	}
 }
@@ -160,6 +175,19 @@ impl<G> Options<G> {
 		}
 	}
 
+	/// Uses the resource id generator (see [`Self::with_generator`]) to
+	/// generate the terms of input resources not defined by the input tree
+	/// value, instead of the input term generator.
+	///
+	/// This is useful when input resources must be given globally unique
+	/// identifiers, for instance with the generators of the [`id`] module.
+	pub fn with_generated_inputs(self) -> Self {
+		Self {
+			generate_inputs: true,
+			..self
+		}
+	}
+
 	/// Changes the generator used to generate non-input anonymous resources
 	/// terms.
 	///
@@ -171,7 +199,58 @@ impl<G> Options<G> {
 		Options {
 			input_count: self.input_count,
 			input_term_generator: self.input_term_generator,
+			generate_inputs: self.generate_inputs,
 			generator,
+			selection: self.selection,
+		}
+	}
+
+	/// Selects the variant of the sum layouts used to deserialize the value.
+	///
+	/// By default every variant is tried, and the value must match exactly
+	/// one of them.
+	pub fn with_selection(self, selection: Selection) -> Self {
+		Self { selection, ..self }
+	}
+}
+
+/// Sum layout variant selection.
+///
+/// Mirrors the structure of the deserialized value to select what variant of
+/// a sum layout must be used, instead of trying every variant. This is useful
+/// when the tree value format carries this information (for instance with
+/// CBOR tags).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum Selection {
+	/// Any matching variant is selected.
+	#[default]
+	Any,
+
+	/// Selects the given variant of a sum layout, with the given selection for
+	/// the variant value.
+	Variant(u32, Box<Self>),
+
+	/// Selection for each entry of a map value.
+	Map(BTreeMap<Value, Self>),
+
+	/// Selection for each item of a list value.
+	List(Vec<Self>),
+}
+
+impl Selection {
+	/// Returns the selection of the given map entry.
+	pub fn entry(&self, key: &Value) -> &Self {
+		match self {
+			Self::Map(entries) => entries.get(key).unwrap_or(&Self::Any),
+			_ => &Self::Any,
+		}
+	}
+
+	/// Returns the selection of the given list item.
+	pub fn item(&self, i: usize) -> &Self {
+		match self {
+			Self::List(items) => items.get(i).unwrap_or(&Self::Any),
+			_ => &Self::Any,
 		}
 	}
 }
@@ -432,10 +511,11 @@ pub fn dehydrate<G: Generator>(
 		interpretation: &mut interpretation,
 	};
 
-	dehydrate_with(
+	dehydrate_with_ref(
 		&mut rdf,
-		layouts,
+		&layouts,
 		value,
+		&options.selection,
 		None,
 		layout_ref,
 		&inputs,
@@ -478,7 +558,13 @@ pub fn dehydrate<G: Generator>(
 			InputResource::Term(t) => t,
 			InputResource::Input(i) => map
 				.entry(InputResource::Input(i))
-				.or_insert_with(|| (options.input_term_generator)(i))
+				.or_insert_with(|| {
+					if options.generate_inputs {
+						Term::Id(options.generator.next(&mut ()))
+					} else {
+						(options.input_term_generator)(i)
+					}
+				})
 				.clone(),
 			InputResource::Anonymous(i) => map
 				.entry(InputResource::Anonymous(i))
@@ -531,6 +617,7 @@ where
 		rdf,
 		&layouts,
 		value,
+		&Selection::Any,
 		current_graph,
 		layout_ref,
 		inputs,
@@ -538,10 +625,12 @@ where
 	)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn dehydrate_with_ref<V, I, Q, D>(
 	rdf: &mut RdfContextMut<V, I>,
 	layouts: &impl LayoutRegistry<Q>,
 	value: &Value,
+	selection: &Selection,
 	current_graph: Option<&I::Resource>,
 	layout_ref: &Ref<LayoutType, Q>,
 	inputs: &[I::Resource],
@@ -670,8 +759,17 @@ where
 			let env = env.intro(rdf, layout.intro);
 			env.instantiate_dataset(&layout.dataset, output)?;
 
-			let mut selection = None;
-			for variant in &layout.variants {
+			let (selected, variant_selection) = match selection {
+				Selection::Variant(i, inner) => (Some(*i as usize), inner.as_ref()),
+				other => (None, other),
+			};
+
+			let mut matching = None;
+			for (i, variant) in layout.variants.iter().enumerate() {
+				if selected.is_some_and(|j| i != j) {
+					continue;
+				}
+
 				let mut variant_dataset = BTreeDataset::new();
 
 				let env = env.intro(rdf, variant.intro);
@@ -681,18 +779,19 @@ where
 					rdf,
 					layouts,
 					value,
+					variant_selection,
 					current_graph,
 					&variant.value,
 					&env,
 					output,
 				)
-				.is_ok() && selection.replace(variant_dataset).is_some()
+				.is_ok() && matching.replace(variant_dataset).is_some()
 				{
 					return Err(Error::DataAmbiguity);
 				}
 			}
 
-			match selection {
+			match matching {
 				Some(variant_dataset) => {
 					for quad in variant_dataset {
 						output.insert(quad);
@@ -717,6 +816,7 @@ where
 								rdf,
 								layouts,
 								value,
+								selection.entry(key),
 								current_graph,
 								&field.value,
 								&env,
@@ -747,13 +847,14 @@ where
 					let env = env.intro(rdf, layout.intro);
 					env.instantiate_dataset(&layout.dataset, output)?;
 
-					for item in value {
+					for (i, item) in value.iter().enumerate() {
 						let env = env.intro(rdf, layout.item.intro);
 						env.instantiate_dataset(&layout.item.dataset, output)?;
 						dehydrate_sub_value(
 							rdf,
 							layouts,
 							item,
+							selection.item(i),
 							current_graph,
 							&layout.item.value,
 							&env,
@@ -785,6 +886,7 @@ where
 							rdf,
 							layouts,
 							item,
+							selection.item(i),
 							current_graph,
 							&layout.node.value,
 							&env,
@@ -800,18 +902,19 @@ where
 					let env = env.intro(rdf, layout.intro);
 					env.instantiate_dataset(&layout.dataset, output)?;
 
-					let mut items = value.iter();
+					let mut items = value.iter().enumerate();
 					let mut item_layouts = layout.items.iter();
 
 					loop {
 						match (items.next(), item_layouts.next()) {
-							(Some(item), Some(item_layout)) => {
+							(Some((i, item)), Some(item_layout)) => {
 								let env = env.intro(rdf, item_layout.intro);
 								env.instantiate_dataset(&item_layout.dataset, output)?;
 								dehydrate_sub_value(
 									rdf,
 									layouts,
 									item,
+									selection.item(i),
 									current_graph,
 									&item_layout.value,
 									&env,
@@ -832,10 +935,12 @@ where
 	}
 }
 
+#[allow(clippy::too_many_arguments)]
 fn dehydrate_sub_value<V, I, Q, D>(
 	rdf: &mut RdfContextMut<V, I>,
 	layouts: &impl LayoutRegistry<Q>,
 	value: &Value,
+	selection: &Selection,
 	current_graph: Option<&I::Resource>,
 	format: &ValueFormat<Q>,
 	env: &Environment<I::Resource>,
@@ -862,6 +967,7 @@ where
 		rdf,
 		layouts,
 		value,
+		selection,
 		graph.as_ref(),
 		&format.layout,
 		&inputs,
@@ -67,6 +67,10 @@ pub struct Field<R> {
 
 	/// Whether or not the field is required.
 	pub required: bool,
+
+	/// Additional properties.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub extra_properties: BTreeMap<R, R>,
 }
 
 impl<R: Ord> PartialOrd for Field<R> {
@@ -74,3 +78,36 @@ impl<R: Ord> PartialOrd for Field<R> {
 		Some(self.cmp(other))
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use rdf_types::Term;
+	use static_iref::iri;
+
+	use crate::Pattern;
+
+	use super::*;
+
+	#[test]
+	fn field_without_extra_properties() {
+		let field: Field<Term> = Field {
+			intro: 1,
+			value: ValueFormat {
+				layout: Ref::new(Term::iri(
+					iri!("https://treeldr.org/prelude#string").to_owned(),
+				)),
+				input: vec![Pattern::Var(1)],
+				graph: None,
+			},
+			dataset: Dataset::new(),
+			required: true,
+			extra_properties: BTreeMap::new(),
+		};
+
+		// Fields serialized before `extra_properties` was introduced must
+		// still deserialize.
+		let json = serde_json::to_value(&field).unwrap();
+		assert!(json.get("extra_properties").is_none());
+		assert_eq!(serde_json::from_value::<Field<Term>>(json).unwrap(), field)
+	}
+}
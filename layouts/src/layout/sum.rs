@@ -65,6 +65,10 @@ pub struct Variant<R> {
 
 	/// Graph.
 	pub dataset: Dataset<R>,
+
+	/// Additional properties.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub extra_properties: BTreeMap<R, R>,
 }
 
 impl<R: Ord> PartialOrd for Variant<R> {
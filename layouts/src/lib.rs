@@ -88,6 +88,7 @@
 //!     [`Layouts`](crate::Layouts) collection.
 #![allow(rustdoc::redundant_explicit_links)]
 pub mod abs;
+pub mod canonical;
 pub mod distill;
 pub mod format;
 pub mod graph;
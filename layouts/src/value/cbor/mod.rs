@@ -5,7 +5,7 @@ use rdf_types::{
 };
 use static_iref::iri;
 
-use crate::{layout::LayoutType, LayoutRegistry, Ref};
+use crate::{layout::LayoutType, LayoutRegistry, Ref, Value};
 
 #[cfg(feature = "serde_cbor")]
 mod serde_cbor;
@@ -29,6 +29,121 @@ pub enum InvalidTag {
 	Type(IriBuf),
 }
 
+/// Error type returned when converting a tagged CBOR value into a TreeLDR
+/// value.
+#[derive(Debug, thiserror::Error)]
+pub enum TagError {
+	#[error(transparent)]
+	InvalidTag(#[from] InvalidTag),
+
+	#[error("layout `{0}` is undefined")]
+	LayoutNotFound(Ref<LayoutType>),
+
+	/// The value is tagged, but not with the expected tag, or with none of
+	/// the tags of the sum layout variants.
+	#[error("expected tag {}, found tag {found}", Tags(expected))]
+	Mismatch { expected: Vec<u64>, found: u64 },
+
+	#[error("unexpected tag {0}")]
+	Unexpected(u64),
+
+	#[error("non finite float value `{0}`")]
+	NonFiniteFloat(f64),
+
+	/// Error caused by a nested value, with the path leading to this value.
+	#[error("at `{}`: {1}", TagPath(.0))]
+	At(Vec<TagPathSegment>, Box<Self>),
+}
+
+impl TagError {
+	/// Prepends the given segment to the path of the value causing this
+	/// error.
+	pub fn at(self, segment: TagPathSegment) -> Self {
+		match self {
+			Self::At(mut path, e) => {
+				path.insert(0, segment);
+				Self::At(path, e)
+			}
+			e => Self::At(vec![segment], Box::new(e)),
+		}
+	}
+
+	/// Returns the path, from the root value, to the value causing this
+	/// error.
+	pub fn path(&self) -> &[TagPathSegment] {
+		match self {
+			Self::At(path, _) => path,
+			_ => &[],
+		}
+	}
+
+	/// Returns the error without its location.
+	pub fn cause(&self) -> &Self {
+		match self {
+			Self::At(_, e) => e,
+			e => e,
+		}
+	}
+}
+
+/// Segment of the path to a nested CBOR value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagPathSegment {
+	/// Map entry.
+	Key(Value),
+
+	/// Array item.
+	Index(usize),
+
+	/// Sum layout variant, by name.
+	Variant(String),
+}
+
+impl std::fmt::Display for TagPathSegment {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			Self::Key(key) => match key.as_str() {
+				Some(key) => write!(f, ".{key}"),
+				None => write!(f, "[{key}]"),
+			},
+			Self::Index(i) => write!(f, "[{i}]"),
+			Self::Variant(name) => write!(f, "({name})"),
+		}
+	}
+}
+
+struct TagPath<'a>(&'a [TagPathSegment]);
+
+impl std::fmt::Display for TagPath<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		for segment in self.0 {
+			segment.fmt(f)?
+		}
+
+		Ok(())
+	}
+}
+
+struct Tags<'a>(&'a [u64]);
+
+impl std::fmt::Display for Tags<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		for (i, tag) in self.0.iter().enumerate() {
+			if i > 0 {
+				if i + 1 == self.0.len() {
+					f.write_str(" or ")?
+				} else {
+					f.write_str(", ")?
+				}
+			}
+
+			tag.fmt(f)?
+		}
+
+		Ok(())
+	}
+}
+
 /// Returns the CBOR tag of a given layout (reference).
 pub fn get_layout_tag<V, I>(
 	vocabulary: &V,
@@ -1,12 +1,27 @@
+use std::collections::BTreeMap;
+
+use num_bigint::{BigInt, Sign};
+use num_rational::BigRational;
 use num_traits::{Signed, ToBytes};
 use rdf_types::{
 	interpretation::{IriInterpretation, ReverseLiteralInterpretation},
-	Interpretation, Vocabulary,
+	Interpretation, Term, Vocabulary,
+};
+
+use crate::{
+	distill::de::Selection,
+	layout::{LayoutType, ListLayout, SumLayout},
+	value::Number,
+	Layout, LayoutRegistry, Literal, Ref, TypedLiteral, TypedValue, Value,
 };
 
-use crate::{value::Number, LayoutRegistry, Literal, TypedLiteral, TypedValue, Value};
+use super::{get_layout_tag, InvalidTag, TagError, TagPathSegment};
 
-use super::{get_layout_tag, InvalidTag};
+/// Positive bignum tag.
+const POSITIVE_BIGNUM_TAG: u64 = 2;
+
+/// Negative bignum tag.
+const NEGATIVE_BIGNUM_TAG: u64 = 3;
 
 impl TypedValue {
 	/// Tries to convert this TreeLDR value into a CBOR value, using CBOR tags
@@ -157,6 +172,231 @@ impl From<Number> for serde_cbor::Value {
 	}
 }
 
+impl Value {
+	/// Converts a tagged CBOR value into a TreeLDR value, following the given
+	/// layout.
+	///
+	/// Each tag is checked against the layout property
+	/// <https://schema.treeldr.org/cbor#tag> of the layout used at its
+	/// position, and removed. Untagged values are accepted even if the layout
+	/// has a tag. Tags are also used to select sum layout variants, which are
+	/// returned as a [`Selection`] to be given to the dehydration algorithm.
+	///
+	/// This is the inverse of [`TypedValue::try_into_tagged_serde_cbor`].
+	pub fn try_from_tagged_serde_cbor(
+		value: serde_cbor::Value,
+		layouts: impl LayoutRegistry,
+		layout_ref: &Ref<LayoutType>,
+	) -> Result<(Self, Selection), TagError> {
+		untag(&layouts, value, layout_ref)
+	}
+}
+
+fn untag(
+	layouts: &impl LayoutRegistry,
+	value: serde_cbor::Value,
+	layout_ref: &Ref<LayoutType>,
+) -> Result<(Value, Selection), TagError> {
+	let layout = layouts
+		.get(layout_ref)
+		.ok_or_else(|| TagError::LayoutNotFound(layout_ref.clone()))?;
+	let expected = get_layout_tag(&(), &(), layouts, layout_ref)?;
+	let value = match value {
+		serde_cbor::Value::Tag(tag, inner) if Some(tag) == expected => *inner,
+		value => value,
+	};
+
+	match (layout, value) {
+		(Layout::Sum(layout), value) => untag_variant(layouts, layout, value),
+		(Layout::Product(layout), serde_cbor::Value::Map(entries)) => {
+			let mut map = BTreeMap::new();
+			let mut selection = BTreeMap::new();
+
+			for (key, value) in entries {
+				let key = untag_untyped(key, None)?;
+				let (value, s) = match layout.fields.get(&key) {
+					Some(field) => untag(layouts, value, &field.value.layout),
+					None => untag_untyped(value, None).map(|value| (value, Selection::Any)),
+				}
+				.map_err(|e| e.at(TagPathSegment::Key(key.clone())))?;
+
+				selection.insert(key.clone(), s);
+				map.insert(key, value);
+			}
+
+			Ok((Value::Map(map), Selection::Map(selection)))
+		}
+		(Layout::List(layout), serde_cbor::Value::Array(items)) => {
+			let mut list = Vec::with_capacity(items.len());
+			let mut selection = Vec::with_capacity(items.len());
+
+			for (i, item) in items.into_iter().enumerate() {
+				let item_layout = match layout {
+					ListLayout::Unordered(layout) => Some(&layout.item.value.layout),
+					ListLayout::Ordered(layout) => Some(&layout.node.value.layout),
+					ListLayout::Sized(layout) => layout.items.get(i).map(|item| &item.value.layout),
+				};
+
+				let (item, s) = match item_layout {
+					Some(item_layout) => untag(layouts, item, item_layout),
+					None => untag_untyped(item, None).map(|item| (item, Selection::Any)),
+				}
+				.map_err(|e| e.at(TagPathSegment::Index(i)))?;
+
+				list.push(item);
+				selection.push(s);
+			}
+
+			Ok((Value::List(list), Selection::List(selection)))
+		}
+		(_, value) => Ok((untag_untyped(value, expected)?, Selection::Any)),
+	}
+}
+
+/// Selects the variant of the given sum layout matching the given value.
+///
+/// If the value is tagged, the variant with the same tag is selected.
+/// Otherwise, the variant is selected only if it is the only one consistent
+/// with the tags found inside the value.
+///
+/// If no variant matches because of the tag of the value itself, the error
+/// lists the tags expected by the variants, including nested sum layouts
+/// variants. Otherwise the error of the first variant failing deeper inside
+/// the value is returned, with its path.
+fn untag_variant(
+	layouts: &impl LayoutRegistry,
+	layout: &SumLayout<Term>,
+	value: serde_cbor::Value,
+) -> Result<(Value, Selection), TagError> {
+	let mut variant_tags = Vec::new();
+	for variant in &layout.variants {
+		variant_tags.push(get_layout_tag(&(), &(), layouts, &variant.value.layout)?)
+	}
+
+	if let serde_cbor::Value::Tag(tag, _) = &value {
+		let mut tagged = variant_tags
+			.iter()
+			.enumerate()
+			.filter(|(_, t)| **t == Some(*tag));
+
+		if let (Some((i, _)), None) = (tagged.next(), tagged.next()) {
+			let variant = &layout.variants[i];
+			let (value, selection) = untag(layouts, value, &variant.value.layout)
+				.map_err(|e| e.at(TagPathSegment::Variant(variant.name.clone())))?;
+			return Ok((value, Selection::Variant(i as u32, Box::new(selection))));
+		}
+	}
+
+	let mut selected = None;
+	let mut ambiguous = false;
+	let mut errors = Vec::new();
+	for (i, variant) in layout.variants.iter().enumerate() {
+		match untag(layouts, value.clone(), &variant.value.layout) {
+			Ok(result) => {
+				if selected.is_none() {
+					selected = Some((i, result))
+				} else {
+					ambiguous = true
+				}
+			}
+			Err(e) => errors.push(e.at(TagPathSegment::Variant(variant.name.clone()))),
+		}
+	}
+
+	match selected {
+		Some((_, (value, _))) if ambiguous => Ok((value, Selection::Any)),
+		Some((i, (value, selection))) => {
+			Ok((value, Selection::Variant(i as u32, Box::new(selection))))
+		}
+		None => {
+			let mut expected = Vec::new();
+			let mut found = None;
+			let mut deeper_error = None;
+			for e in errors {
+				let on_value = e
+					.path()
+					.iter()
+					.all(|s| matches!(s, TagPathSegment::Variant(_)));
+
+				match e.cause() {
+					TagError::Mismatch {
+						expected: tags,
+						found: tag,
+					} if on_value => {
+						for tag in tags {
+							if !expected.contains(tag) {
+								expected.push(*tag)
+							}
+						}
+
+						found = Some(*tag)
+					}
+					_ => {
+						deeper_error.get_or_insert(e);
+					}
+				}
+			}
+
+			match (deeper_error, found) {
+				(Some(e), _) => Err(e),
+				(None, Some(found)) => Err(TagError::Mismatch { expected, found }),
+				(None, None) => Ok((untag_untyped(value, None)?, Selection::Any)),
+			}
+		}
+	}
+}
+
+/// Converts a CBOR value without layout information.
+///
+/// Bignums are converted into numbers. Any other tag is an error, reported
+/// as a mismatch when the `expected` tag is given.
+fn untag_untyped(value: serde_cbor::Value, expected: Option<u64>) -> Result<Value, TagError> {
+	match value {
+		serde_cbor::Value::Null => Ok(Value::Literal(Literal::Unit)),
+		serde_cbor::Value::Bool(b) => Ok(Value::Literal(Literal::Boolean(b))),
+		serde_cbor::Value::Integer(i) => Ok(Value::Literal(Literal::Number(Number::new(
+			BigRational::from_integer(i.into()),
+		)))),
+		serde_cbor::Value::Float(f) => Number::try_from(f)
+			.map(|n| Value::Literal(Literal::Number(n)))
+			.map_err(|_| TagError::NonFiniteFloat(f)),
+		serde_cbor::Value::Bytes(bytes) => Ok(Value::Literal(Literal::ByteString(bytes))),
+		serde_cbor::Value::Text(string) => Ok(Value::Literal(Literal::TextString(string))),
+		serde_cbor::Value::Array(items) => items
+			.into_iter()
+			.map(|item| untag_untyped(item, None))
+			.collect::<Result<_, _>>()
+			.map(Value::List),
+		serde_cbor::Value::Map(entries) => entries
+			.into_iter()
+			.map(|(key, value)| Ok((untag_untyped(key, None)?, untag_untyped(value, None)?)))
+			.collect::<Result<_, _>>()
+			.map(Value::Map),
+		serde_cbor::Value::Tag(tag, inner) => match (tag, *inner) {
+			(POSITIVE_BIGNUM_TAG, serde_cbor::Value::Bytes(bytes)) => {
+				let n = BigInt::from_bytes_be(Sign::Plus, &bytes);
+				Ok(Value::Literal(Literal::Number(Number::new(
+					BigRational::from_integer(n),
+				))))
+			}
+			(NEGATIVE_BIGNUM_TAG, serde_cbor::Value::Bytes(bytes)) => {
+				let n = BigInt::from(-1) - BigInt::from_bytes_be(Sign::Plus, &bytes);
+				Ok(Value::Literal(Literal::Number(Number::new(
+					BigRational::from_integer(n),
+				))))
+			}
+			_ => match expected {
+				Some(expected) => Err(TagError::Mismatch {
+					expected: vec![expected],
+					found: tag,
+				}),
+				None => Err(TagError::Unexpected(tag)),
+			},
+		},
+		serde_cbor::Value::__Hidden => unreachable!(),
+	}
+}
+
 impl From<Value> for serde_cbor::Value {
 	fn from(value: Value) -> Self {
 		match value {
@@ -179,9 +419,16 @@ impl From<Value> for serde_cbor::Value {
 
 #[cfg(test)]
 mod tests {
+	use super::TagPathSegment;
 	use crate::{
 		abs::{self, syntax},
+		distill::{
+			de::{Options, Selection},
+			dehydrate,
+		},
 		hydrate,
+		layout::LayoutType,
+		Layouts, Ref,
 	};
 	use rdf_types::{dataset::IndexedBTreeDataset, BlankIdBuf, Literal, LiteralType, Quad, Term};
 	use serde_json::json;
@@ -241,4 +488,305 @@ mod tests {
 
 		assert_eq!(output, expected)
 	}
+
+	#[test]
+	fn from_tagged_cbor() {
+		let layout: syntax::Layout = serde_json::from_value(json!(
+			{
+				"prefixes": {
+					"cbor": "https://schema.treeldr.org/cbor#"
+				},
+				"type": "record",
+				"fields": {
+					"value": {
+						"value": {
+							"type": "sum",
+							"variants": {
+								"a": {
+									"value": {
+										"type": "string",
+										"datatype": "https://example.org/A",
+										"extra": { "cbor:tag": 10 }
+									}
+								},
+								"b": {
+									"value": {
+										"type": "string",
+										"datatype": "https://example.org/B",
+										"extra": { "cbor:tag": 11 }
+									}
+								}
+							}
+						},
+						"property": "https://schema.org/value"
+					}
+				}
+			}
+		))
+		.unwrap();
+
+		let mut builder = abs::Builder::new();
+		let layout_ref = layout.build(&mut builder).unwrap();
+		let layouts = builder.build();
+
+		let subject = Term::blank(BlankIdBuf::from_suffix("input0").unwrap());
+		let mut dataset = IndexedBTreeDataset::new();
+		dataset.insert(Quad(
+			subject.clone(),
+			Term::iri(iri!("https://schema.org/value").to_owned()),
+			Term::Literal(Literal::new(
+				"value".to_owned(),
+				LiteralType::Any(iri!("https://example.org/B").to_owned()),
+			)),
+			None,
+		));
+
+		let value = hydrate(&layouts, &dataset, &layout_ref, &[subject]).unwrap();
+		let cbor = value.try_into_tagged_serde_cbor(&layouts).unwrap();
+
+		let (value, selection) =
+			crate::Value::try_from_tagged_serde_cbor(cbor, &layouts, &layout_ref).unwrap();
+
+		// Without the tags, the value is ambiguous.
+		assert!(dehydrate(&layouts, &value, &layout_ref, Options::default()).is_err());
+
+		let (output, _) = dehydrate(
+			&layouts,
+			&value,
+			&layout_ref,
+			Options::default().with_selection(selection),
+		)
+		.unwrap();
+
+		assert_eq!(
+			output.into_iter().collect::<Vec<_>>(),
+			dataset.into_iter().collect::<Vec<_>>()
+		);
+
+		let wrong_tag = serde_cbor::Value::Map(
+			[(
+				"value".to_owned().into(),
+				serde_cbor::Value::Tag(12, Box::new("value".to_owned().into())),
+			)]
+			.into_iter()
+			.collect(),
+		);
+
+		assert_eq!(
+			crate::Value::try_from_tagged_serde_cbor(wrong_tag, &layouts, &layout_ref)
+				.unwrap_err()
+				.to_string(),
+			"at `.value`: expected tag 10 or 11, found tag 12"
+		)
+	}
+
+	fn build(layout: serde_json::Value) -> (Layouts, Ref<LayoutType>) {
+		let layout: syntax::Layout = serde_json::from_value(layout).unwrap();
+		let mut builder = abs::Builder::new();
+		let layout_ref = layout.build(&mut builder).unwrap();
+		(builder.build(), layout_ref)
+	}
+
+	fn tagged_string(tag: u64, value: &str) -> serde_cbor::Value {
+		serde_cbor::Value::Tag(tag, Box::new(value.to_owned().into()))
+	}
+
+	/// Returns the datatypes of the literals of the given dataset.
+	fn datatypes(dataset: impl IntoIterator<Item = Quad<Term>>) -> Vec<String> {
+		let mut result: Vec<_> = dataset
+			.into_iter()
+			.filter_map(|Quad(_, _, o, _)| match o {
+				Term::Literal(Literal {
+					type_: LiteralType::Any(ty),
+					..
+				}) => Some(ty.into_string()),
+				_ => None,
+			})
+			.collect();
+		result.sort();
+		result
+	}
+
+	fn nested_sums() -> (Layouts, Ref<LayoutType>) {
+		build(json!(
+			{
+				"prefixes": {
+					"cbor": "https://schema.treeldr.org/cbor#"
+				},
+				"type": "record",
+				"fields": {
+					"value": {
+						"value": {
+							"type": "sum",
+							"variants": {
+								"x": {
+									"value": {
+										"type": "sum",
+										"variants": {
+											"a": {
+												"value": {
+													"type": "string",
+													"datatype": "https://example.org/A",
+													"extra": { "cbor:tag": 10 }
+												}
+											},
+											"b": {
+												"value": {
+													"type": "string",
+													"datatype": "https://example.org/B",
+													"extra": { "cbor:tag": 11 }
+												}
+											}
+										}
+									}
+								},
+								"y": {
+									"value": {
+										"type": "string",
+										"datatype": "https://example.org/C",
+										"extra": { "cbor:tag": 12 }
+									}
+								}
+							}
+						},
+						"property": "https://schema.org/value"
+					}
+				}
+			}
+		))
+	}
+
+	#[test]
+	fn from_tagged_cbor_nested_sums() {
+		let (layouts, layout_ref) = nested_sums();
+		let key = crate::Value::string("value".to_owned());
+
+		for (tag, expected_selection, expected_datatype) in [
+			(
+				10,
+				Selection::Variant(0, Box::new(Selection::Variant(0, Box::new(Selection::Any)))),
+				"https://example.org/A",
+			),
+			(
+				11,
+				Selection::Variant(0, Box::new(Selection::Variant(1, Box::new(Selection::Any)))),
+				"https://example.org/B",
+			),
+			(
+				12,
+				Selection::Variant(1, Box::new(Selection::Any)),
+				"https://example.org/C",
+			),
+		] {
+			let cbor = serde_cbor::Value::Map(
+				[("value".to_owned().into(), tagged_string(tag, "v"))]
+					.into_iter()
+					.collect(),
+			);
+
+			let (value, selection) =
+				crate::Value::try_from_tagged_serde_cbor(cbor, &layouts, &layout_ref).unwrap();
+			assert_eq!(*selection.entry(&key), expected_selection);
+
+			let (output, _) = dehydrate(
+				&layouts,
+				&value,
+				&layout_ref,
+				Options::default().with_selection(selection),
+			)
+			.unwrap();
+
+			assert_eq!(datatypes(output), [expected_datatype])
+		}
+	}
+
+	#[test]
+	fn from_tagged_cbor_nested_sums_mismatch() {
+		let (layouts, layout_ref) = nested_sums();
+		let cbor = serde_cbor::Value::Map(
+			[("value".to_owned().into(), tagged_string(13, "v"))]
+				.into_iter()
+				.collect(),
+		);
+
+		let e = crate::Value::try_from_tagged_serde_cbor(cbor, &layouts, &layout_ref).unwrap_err();
+		assert_eq!(
+			e.path(),
+			[TagPathSegment::Key(crate::Value::string(
+				"value".to_owned()
+			))]
+		);
+		assert_eq!(
+			e.to_string(),
+			"at `.value`: expected tag 10, 11 or 12, found tag 13"
+		)
+	}
+
+	#[test]
+	fn from_tagged_cbor_list() {
+		let (layouts, layout_ref) = build(json!(
+			{
+				"prefixes": {
+					"cbor": "https://schema.treeldr.org/cbor#"
+				},
+				"type": "set",
+				"item": {
+					"value": {
+						"type": "sum",
+						"variants": {
+							"a": {
+								"value": {
+									"type": "string",
+									"datatype": "https://example.org/A",
+									"extra": { "cbor:tag": 10 }
+								}
+							},
+							"b": {
+								"value": {
+									"type": "string",
+									"datatype": "https://example.org/B",
+									"extra": { "cbor:tag": 11 }
+								}
+							}
+						}
+					},
+					"property": "https://schema.org/value"
+				}
+			}
+		));
+
+		let cbor = serde_cbor::Value::Array(vec![tagged_string(10, "x"), tagged_string(11, "y")]);
+		let (value, selection) =
+			crate::Value::try_from_tagged_serde_cbor(cbor, &layouts, &layout_ref).unwrap();
+		assert_eq!(
+			*selection.item(0),
+			Selection::Variant(0, Box::new(Selection::Any))
+		);
+		assert_eq!(
+			*selection.item(1),
+			Selection::Variant(1, Box::new(Selection::Any))
+		);
+
+		let (output, _) = dehydrate(
+			&layouts,
+			&value,
+			&layout_ref,
+			Options::default().with_selection(selection),
+		)
+		.unwrap();
+
+		assert_eq!(
+			datatypes(output),
+			["https://example.org/A", "https://example.org/B"]
+		);
+
+		let wrong_tag =
+			serde_cbor::Value::Array(vec![tagged_string(10, "x"), tagged_string(12, "y")]);
+		assert_eq!(
+			crate::Value::try_from_tagged_serde_cbor(wrong_tag, &layouts, &layout_ref)
+				.unwrap_err()
+				.to_string(),
+			"at `[1]`: expected tag 10 or 11, found tag 12"
+		)
+	}
 }
@@ -6,7 +6,6 @@ use lazy_static::lazy_static;
 use num_bigint::{BigInt, Sign};
 use num_rational::BigRational;
 use num_traits::{Signed, ToPrimitive, Zero};
-use xsd_types::ParseXsd;
 
 use crate::{
 	layout::{
@@ -22,6 +21,12 @@ pub mod ser;
 #[cfg(feature = "cbor")]
 pub mod cbor;
 
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+
+#[cfg(feature = "toml")]
+pub mod toml;
+
 lazy_static! {
 	static ref TEN: BigInt = 10u32.into();
 }
@@ -91,13 +96,8 @@ impl Number {
 		let mut fraction = String::new();
 		let mut map = std::collections::HashMap::new();
 
-		let mut rem = if self.0.is_negative() {
-			-self.0.numer()
-		} else {
-			self.0.numer().clone()
-		};
-
-		rem %= self.0.denom();
+		let numer = self.0.numer().abs();
+		let mut rem = &numer % self.0.denom();
 		while !rem.is_zero() && !map.contains_key(&rem) {
 			map.insert(rem.clone(), fraction.len());
 			rem *= TEN.clone();
@@ -111,7 +111,7 @@ impl Number {
 			String::new()
 		};
 
-		output.push_str(&(self.0.numer() / self.0.denom()).to_string());
+		output.push_str(&(numer / self.0.denom()).to_string());
 
 		if rem.is_zero() {
 			if !fraction.is_empty() {
@@ -187,34 +187,39 @@ impl fmt::Display for Number {
 
 impl From<serde_json::Number> for Number {
 	fn from(value: serde_json::Number) -> Self {
-		Self(
-			xsd_types::Decimal::parse_xsd(&value.to_string())
-				.ok()
-				.unwrap()
-				.into(),
-		)
+		Self(parse_json_number(&value.to_string()))
 	}
 }
 
 impl From<json_syntax::NumberBuf> for Number {
 	fn from(value: json_syntax::NumberBuf) -> Self {
-		Self(
-			xsd_types::Decimal::parse_xsd(value.as_str())
-				.ok()
-				.unwrap()
-				.into(),
-		)
+		Self(parse_json_number(value.as_str()))
 	}
 }
 
 impl<'a> From<&'a json_syntax::Number> for Number {
 	fn from(value: &'a json_syntax::Number) -> Self {
-		Self(
-			xsd_types::Decimal::parse_xsd(value.as_str())
-				.ok()
-				.unwrap()
-				.into(),
-		)
+		Self(parse_json_number(value.as_str()))
+	}
+}
+
+/// Parses the exact value of a JSON number, including numbers in exponent
+/// notation.
+fn parse_json_number(s: &str) -> BigRational {
+	let (mantissa, exponent) = match s.find(['e', 'E']) {
+		Some(i) => (&s[..i], s[i + 1..].parse::<i32>().unwrap()),
+		None => (s, 0),
+	};
+
+	let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+	let digits: BigInt = format!("{integer}{fraction}").parse().unwrap();
+	let exponent = exponent - fraction.len() as i32;
+
+	let scale = TEN.pow(exponent.unsigned_abs());
+	if exponent < 0 {
+		BigRational::new(digits, scale)
+	} else {
+		BigRational::from_integer(digits * scale)
 	}
 }
 
@@ -692,6 +697,9 @@ mod tests {
 			((1, 7), None),
 			((1, 8), Some("0.125")),
 			((1, 9), None),
+			((-3, 1), Some("-3")),
+			((-5, 2), Some("-2.5")),
+			((-1, 3), None),
 		];
 
 		for ((p, q), expected) in vectors {
@@ -699,4 +707,23 @@ mod tests {
 			assert_eq!(number.decimal_representation().as_deref(), expected)
 		}
 	}
+
+	#[test]
+	fn from_json_number() {
+		let vectors = [
+			("0", (0, 1)),
+			("-0", (0, 1)),
+			("12", (12, 1)),
+			("-2.5", (-5, 2)),
+			("0.125", (1, 8)),
+			("1e3", (1000, 1)),
+			("1.5E+2", (150, 1)),
+			("-25e-2", (-1, 4)),
+		];
+
+		for (json, (p, q)) in vectors {
+			let number: Number = json.parse::<json_syntax::NumberBuf>().unwrap().into();
+			assert_eq!(number, Number::new(Ratio::new(p.into(), q.into())))
+		}
+	}
 }
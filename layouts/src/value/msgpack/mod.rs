@@ -0,0 +1,74 @@
+use iref::{Iri, IriBuf};
+use rdf_types::{
+	interpretation::{IriInterpretation, ReverseLiteralInterpretation},
+	Vocabulary,
+};
+use static_iref::iri;
+
+use crate::{layout::LayoutType, LayoutRegistry, Ref};
+
+#[cfg(feature = "rmpv")]
+mod rmpv;
+
+#[cfg(feature = "rmpv")]
+pub use self::rmpv::InvalidMsgpackValue;
+
+/// MessagePack extension `ext` property.
+///
+/// Allows one to specifies the MessagePack extension type of a TreeLDR
+/// layout. Values of this layout are MessagePack-encoded and wrapped in an
+/// extension value of the given type.
+pub const MSGPACK_EXT_IRI: &Iri = iri!("https://schema.treeldr.org/msgpack#ext");
+
+/// Error type returned by the [`get_layout_ext_type`] function when the value
+/// of the [MessagePack extension `ext` property](MSGPACK_EXT_IRI) is invalid.
+#[derive(Debug, thiserror::Error)]
+pub enum InvalidExtType {
+	#[error("non literal extension type value")]
+	NonLiteral,
+
+	#[error("invalid extension type value: {0}")]
+	Value(String),
+
+	#[error("invalid extension type type: {0}")]
+	Type(IriBuf),
+}
+
+/// Returns the MessagePack extension type of a given layout (reference).
+pub fn get_layout_ext_type<V, I>(
+	vocabulary: &V,
+	interpretation: &I,
+	layouts: &impl LayoutRegistry<I::Resource>,
+	layout_ref: &Ref<LayoutType, I::Resource>,
+) -> Result<Option<i8>, InvalidExtType>
+where
+	V: Vocabulary,
+	I: IriInterpretation<V::Iri> + ReverseLiteralInterpretation<Literal = V::Literal>,
+	I::Resource: Ord,
+{
+	let layout = layouts.get(layout_ref).expect("missing layout definition");
+	match interpretation.lexical_iri_interpretation(vocabulary, MSGPACK_EXT_IRI) {
+		Some(prop) => match layout.extra_properties().get(&prop) {
+			Some(value) => {
+				for l in interpretation.literals_of(value) {
+					if let Some(literal) = vocabulary.literal(l) {
+						if let rdf_types::LiteralTypeRef::Any(ty) = literal.type_ {
+							if let Some(ty_iri) = vocabulary.iri(ty) {
+								return match xsd_types::IntegerDatatype::from_iri(ty_iri) {
+									Some(_) => literal.value.parse().map(Some).map_err(|_| {
+										InvalidExtType::Value(literal.value.to_owned())
+									}),
+									None => Err(InvalidExtType::Type(ty_iri.to_owned())),
+								};
+							}
+						}
+					}
+				}
+
+				Err(InvalidExtType::NonLiteral)
+			}
+			None => Ok(None),
+		},
+		None => Ok(None),
+	}
+}
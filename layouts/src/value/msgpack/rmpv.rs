@@ -0,0 +1,277 @@
+use rdf_types::{
+	interpretation::{IriInterpretation, ReverseLiteralInterpretation},
+	Interpretation, Vocabulary,
+};
+use xsd_types::ParseXsd;
+
+use crate::{
+	value::{NativeNumber, Number},
+	LayoutRegistry, Literal, TypedLiteral, TypedValue, Value,
+};
+
+use super::{get_layout_ext_type, InvalidExtType};
+
+/// Error raised when trying to convert a MessagePack value that is not
+/// compatible with the TreeLDR data model.
+#[derive(Debug, thiserror::Error)]
+pub enum InvalidMsgpackValue {
+	/// Infinite or NaN float.
+	#[error("non finite float value `{0}`")]
+	NonFiniteFloat(f64),
+
+	/// String that is not valid UTF-8.
+	#[error("invalid UTF-8 string")]
+	InvalidUtf8(Vec<u8>),
+
+	/// Extension value whose data is not a MessagePack value.
+	#[error("invalid extension value of type {0}")]
+	Ext(i8),
+}
+
+impl TypedValue {
+	/// Tries to convert this TreeLDR value into a MessagePack value, using
+	/// extension types as specified by the layout property
+	/// <https://schema.treeldr.org/msgpack#ext>.
+	pub fn try_into_tagged_rmpv(
+		self,
+		layouts: impl LayoutRegistry,
+	) -> Result<rmpv::Value, InvalidExtType> {
+		self.try_into_tagged_rmpv_with(&(), &(), layouts)
+	}
+}
+
+impl<R> TypedValue<R> {
+	/// Tries to convert this TreeLDR value into a MessagePack value, using
+	/// extension types as specified by the layout property
+	/// <https://schema.treeldr.org/msgpack#ext>.
+	pub fn try_into_tagged_rmpv_with<V, I>(
+		self,
+		vocabulary: &V,
+		interpretation: &I,
+		layouts: impl LayoutRegistry<R>,
+	) -> Result<rmpv::Value, InvalidExtType>
+	where
+		V: Vocabulary,
+		I: Interpretation<Resource = R>
+			+ IriInterpretation<V::Iri>
+			+ ReverseLiteralInterpretation<Literal = V::Literal>,
+		R: Ord,
+	{
+		self.try_into_tagged_rmpv_with_ref(vocabulary, interpretation, &layouts)
+	}
+
+	fn try_into_tagged_rmpv_with_ref<V, I>(
+		self,
+		vocabulary: &V,
+		interpretation: &I,
+		layouts: &impl LayoutRegistry<R>,
+	) -> Result<rmpv::Value, InvalidExtType>
+	where
+		V: Vocabulary,
+		I: Interpretation<Resource = R>
+			+ IriInterpretation<V::Iri>
+			+ ReverseLiteralInterpretation<Literal = V::Literal>,
+		R: Ord,
+	{
+		let (value, ty) = match self {
+			Self::Always(value) => (value.into(), None),
+			Self::Literal(TypedLiteral::Unit(_, ty)) => (rmpv::Value::Nil, Some(ty.cast())),
+			Self::Literal(TypedLiteral::Boolean(b, ty)) => {
+				(rmpv::Value::Boolean(b), Some(ty.cast()))
+			}
+			Self::Literal(TypedLiteral::Number(n, ty)) => (n.into(), Some(ty.cast())),
+			Self::Literal(TypedLiteral::ByteString(b, ty)) => {
+				(rmpv::Value::Binary(b), Some(ty.cast()))
+			}
+			Self::Literal(TypedLiteral::TextString(s, ty)) => (s.into(), Some(ty.cast())),
+			Self::Literal(TypedLiteral::Id(s, ty)) => (s.into(), Some(ty.cast())),
+			Self::Variant(inner, ty, _) => (
+				inner.try_into_tagged_rmpv_with_ref(vocabulary, interpretation, layouts)?,
+				Some(ty.cast()),
+			),
+			Self::Map(map, ty) => (
+				rmpv::Value::Map(
+					map.into_iter()
+						.map(|(key, value)| {
+							Ok((
+								key.into(),
+								value.try_into_tagged_rmpv_with_ref(
+									vocabulary,
+									interpretation,
+									layouts,
+								)?,
+							))
+						})
+						.collect::<Result<_, _>>()?,
+				),
+				Some(ty.cast()),
+			),
+			Self::List(items, ty) => (
+				rmpv::Value::Array(
+					items
+						.into_iter()
+						.map(|t| {
+							t.try_into_tagged_rmpv_with_ref(vocabulary, interpretation, layouts)
+						})
+						.collect::<Result<_, _>>()?,
+				),
+				Some(ty.cast()),
+			),
+		};
+
+		if let Some(ty) = ty {
+			if let Some(ext_type) = get_layout_ext_type(vocabulary, interpretation, layouts, &ty)? {
+				let mut data = Vec::new();
+				rmpv::encode::write_value(&mut data, &value).unwrap();
+				return Ok(rmpv::Value::Ext(ext_type, data));
+			}
+		}
+
+		Ok(value)
+	}
+}
+
+impl From<Number> for rmpv::Value {
+	fn from(value: Number) -> Self {
+		match value.as_native() {
+			NativeNumber::U64(u) => rmpv::Value::from(u),
+			NativeNumber::I64(i) => rmpv::Value::from(i),
+			NativeNumber::F64(f) => rmpv::Value::F64(f),
+		}
+	}
+}
+
+impl From<Value> for rmpv::Value {
+	fn from(value: Value) -> Self {
+		match value {
+			Value::Literal(Literal::Unit) => rmpv::Value::Nil,
+			Value::Literal(Literal::Boolean(b)) => rmpv::Value::Boolean(b),
+			Value::Literal(Literal::Number(n)) => n.into(),
+			Value::Literal(Literal::ByteString(bytes)) => rmpv::Value::Binary(bytes),
+			Value::Literal(Literal::TextString(string)) => string.into(),
+			Value::Map(map) => rmpv::Value::Map(
+				map.into_iter()
+					.map(|(key, value)| (key.into(), value.into()))
+					.collect(),
+			),
+			Value::List(items) => rmpv::Value::Array(items.into_iter().map(Into::into).collect()),
+		}
+	}
+}
+
+impl TryFrom<rmpv::Value> for Value {
+	type Error = InvalidMsgpackValue;
+
+	/// Converts a MessagePack value.
+	///
+	/// Extension values are replaced by the MessagePack value encoded in
+	/// their data.
+	fn try_from(value: rmpv::Value) -> Result<Self, Self::Error> {
+		match value {
+			rmpv::Value::Nil => Ok(Value::Literal(Literal::Unit)),
+			rmpv::Value::Boolean(b) => Ok(Value::Literal(Literal::Boolean(b))),
+			rmpv::Value::Integer(i) => match i.as_u64() {
+				Some(u) => Ok(Value::Literal(Literal::Number(u.into()))),
+				None => Ok(Value::Literal(Literal::Number(i.as_i64().unwrap().into()))),
+			},
+			rmpv::Value::F32(f) => float_value(f.into()),
+			rmpv::Value::F64(f) => float_value(f),
+			rmpv::Value::String(s) => {
+				if s.is_str() {
+					Ok(Value::Literal(Literal::TextString(s.into_str().unwrap())))
+				} else {
+					Err(InvalidMsgpackValue::InvalidUtf8(s.into_bytes()))
+				}
+			}
+			rmpv::Value::Binary(bytes) => Ok(Value::Literal(Literal::ByteString(bytes))),
+			rmpv::Value::Array(items) => items
+				.into_iter()
+				.map(TryInto::try_into)
+				.collect::<Result<_, _>>()
+				.map(Value::List),
+			rmpv::Value::Map(entries) => entries
+				.into_iter()
+				.map(|(key, value)| Ok((key.try_into()?, value.try_into()?)))
+				.collect::<Result<_, _>>()
+				.map(Value::Map),
+			rmpv::Value::Ext(ty, data) => {
+				let mut bytes = data.as_slice();
+				match rmpv::decode::read_value(&mut bytes) {
+					Ok(inner) if bytes.is_empty() => inner.try_into(),
+					_ => Err(InvalidMsgpackValue::Ext(ty)),
+				}
+			}
+		}
+	}
+}
+
+/// Converts a float into a number, using its shortest decimal representation.
+fn float_value(f: f64) -> Result<Value, InvalidMsgpackValue> {
+	match xsd_types::Decimal::parse_xsd(&f.to_string()) {
+		Ok(d) => Ok(Value::Literal(Literal::Number(Number::new(d.into())))),
+		Err(_) => Err(InvalidMsgpackValue::NonFiniteFloat(f)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		abs::{self, syntax},
+		hydrate, Value,
+	};
+	use rdf_types::{dataset::IndexedBTreeDataset, BlankIdBuf, Literal, LiteralType, Quad, Term};
+	use serde_json::json;
+	use static_iref::iri;
+	use xsd_types::XSD_STRING;
+
+	#[test]
+	fn into_tagged_rmpv() {
+		let layout: syntax::Layout = serde_json::from_value(json!(
+			{
+				"prefixes": {
+					"msgpack": "https://schema.treeldr.org/msgpack#"
+				},
+				"type": "record",
+				"fields": {
+					"name": {
+						"value": {
+							"type": "string",
+							"extra": {
+								"msgpack:ext": 42
+							}
+						},
+						"property": "https://schema.org/name"
+					}
+				}
+			}
+		))
+		.unwrap();
+
+		let mut builder = abs::Builder::new();
+		let layout_ref = layout.build(&mut builder).unwrap();
+		let layouts = builder.build();
+
+		let mut dataset = IndexedBTreeDataset::new();
+		let subject = Term::blank(BlankIdBuf::from_suffix("subject").unwrap());
+		dataset.insert(Quad(
+			subject.clone(),
+			Term::iri(iri!("https://schema.org/name").to_owned()),
+			Term::Literal(Literal::new(
+				"Bob L'éponge".to_owned(),
+				LiteralType::Any(XSD_STRING.to_owned()),
+			)),
+			None,
+		));
+
+		let value = hydrate(&layouts, &dataset, &layout_ref, &[subject]).unwrap();
+		let untyped = value.clone().into_untyped();
+		let output = value.try_into_tagged_rmpv(&layouts).unwrap();
+
+		let mut data = Vec::new();
+		rmpv::encode::write_value(&mut data, &"Bob L'éponge".into()).unwrap();
+		let expected = rmpv::Value::Map(vec![("name".into(), rmpv::Value::Ext(42, data))]);
+
+		assert_eq!(output, expected);
+		assert_eq!(Value::try_from(output).unwrap(), untyped)
+	}
+}
@@ -0,0 +1,172 @@
+//! Conversions between [`Value`] and [`toml::Value`].
+use std::str::FromStr;
+
+use num_traits::ToPrimitive;
+use xsd_types::ParseXsd;
+
+use super::{Literal, Number, Value};
+
+/// Error raised when trying to convert a value to TOML that is not compatible
+/// with the TOML data model.
+#[derive(Debug, thiserror::Error)]
+pub enum NonTomlValue {
+	/// Unit value, not supported by TOML.
+	#[error("unit value cannot be converted to TOML")]
+	Unit,
+
+	/// Number cannot be represented as TOML (non-decimal number, or integer
+	/// that does not fit in 64 bits).
+	#[error("not a TOML number: {0}")]
+	Number(Number),
+
+	/// Byte string value, not supported by TOML.
+	#[error("byte string cannot be converted to TOML")]
+	ByteString(Vec<u8>),
+
+	/// Non-string key, not supported by TOML.
+	#[error("non-string key")]
+	NonStringKey(Value),
+
+	/// TOML documents must be tables.
+	#[error("TOML document must be a table, found `{0}`")]
+	NonTableDocument(Value),
+}
+
+impl From<toml::Value> for Value {
+	/// Converts a TOML value.
+	///
+	/// Date-time values and non-finite floats are converted into text
+	/// strings.
+	fn from(value: toml::Value) -> Self {
+		match value {
+			toml::Value::Boolean(b) => Self::Literal(Literal::Boolean(b)),
+			toml::Value::Integer(i) => Self::Literal(Literal::Number(i.into())),
+			toml::Value::Float(f) => match xsd_types::Decimal::parse_xsd(&f.to_string()) {
+				Ok(d) => Self::Literal(Literal::Number(Number::new(d.into()))),
+				Err(_) => Self::Literal(Literal::TextString(f.to_string())),
+			},
+			toml::Value::String(s) => Self::Literal(Literal::TextString(s)),
+			toml::Value::Datetime(d) => Self::Literal(Literal::TextString(d.to_string())),
+			toml::Value::Array(items) => Self::List(items.into_iter().map(Into::into).collect()),
+			toml::Value::Table(table) => Self::Map(
+				table
+					.into_iter()
+					.map(|(key, value)| (Value::string(key), value.into()))
+					.collect(),
+			),
+		}
+	}
+}
+
+impl TryFrom<Number> for toml::Value {
+	type Error = NonTomlValue;
+
+	fn try_from(value: Number) -> Result<Self, Self::Error> {
+		match value.as_integer() {
+			Some(i) => match i.to_i64() {
+				Some(i) => Ok(toml::Value::Integer(i)),
+				None => Err(NonTomlValue::Number(value)),
+			},
+			None => match value.decimal_representation() {
+				Some(decimal) => Ok(toml::Value::Float(f64::from_str(&decimal).unwrap())),
+				None => Err(NonTomlValue::Number(value)),
+			},
+		}
+	}
+}
+
+impl TryFrom<Literal> for toml::Value {
+	type Error = NonTomlValue;
+
+	fn try_from(value: Literal) -> Result<Self, Self::Error> {
+		match value {
+			Literal::Unit => Err(NonTomlValue::Unit),
+			Literal::Boolean(b) => Ok(toml::Value::Boolean(b)),
+			Literal::Number(n) => n.try_into(),
+			Literal::TextString(s) => Ok(toml::Value::String(s)),
+			Literal::ByteString(s) => Err(NonTomlValue::ByteString(s)),
+		}
+	}
+}
+
+impl TryFrom<Value> for toml::Value {
+	type Error = NonTomlValue;
+
+	fn try_from(value: Value) -> Result<Self, Self::Error> {
+		match value {
+			Value::Literal(l) => l.try_into(),
+			Value::Map(r) => {
+				let mut table = toml::Table::new();
+
+				for (key, value) in r {
+					match key {
+						Value::Literal(Literal::TextString(key)) => {
+							table.insert(key, value.try_into()?);
+						}
+						other => return Err(NonTomlValue::NonStringKey(other)),
+					}
+				}
+
+				Ok(toml::Value::Table(table))
+			}
+			Value::List(list) => list
+				.into_iter()
+				.map(TryInto::try_into)
+				.collect::<Result<Vec<_>, _>>()
+				.map(toml::Value::Array),
+		}
+	}
+}
+
+impl Value {
+	/// Converts this value into a TOML document.
+	pub fn try_into_toml_table(self) -> Result<toml::Table, NonTomlValue> {
+		match self {
+			Self::Map(_) => match self.try_into()? {
+				toml::Value::Table(table) => Ok(table),
+				_ => unreachable!(),
+			},
+			other => Err(NonTomlValue::NonTableDocument(other)),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn round_trip() {
+		let document: toml::Table = toml::from_str(
+			r#"
+			name = "treeldr"
+			version = 2
+			ratio = 0.5
+			tags = ["a", 1, true]
+
+			[nested]
+			key = "value"
+			"#,
+		)
+		.unwrap();
+
+		let value: Value = toml::Value::Table(document.clone()).into();
+		let back = value.try_into_toml_table().unwrap();
+		assert_eq!(back, document)
+	}
+
+	#[test]
+	fn non_toml_values() {
+		let unit = Value::Map([(Value::string("a".to_owned()), Value::unit())].into());
+		assert!(matches!(
+			unit.try_into_toml_table(),
+			Err(NonTomlValue::Unit)
+		));
+
+		let list = Value::List(Vec::new());
+		assert!(matches!(
+			list.try_into_toml_table(),
+			Err(NonTomlValue::NonTableDocument(_))
+		));
+	}
+}
@@ -0,0 +1,140 @@
+//! Tests from the RDFC-1.0 test suite.
+//!
+//! See <https://w3c.github.io/rdf-canon/tests/>.
+use nquads_syntax::Parse;
+use paste::paste;
+use rdf_types::Quad;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+use treeldr_layouts::utils::strip_rdf_quad;
+
+fn file_path(id: &str, suffix: &str) -> PathBuf {
+	format!(
+		"{}/tests/canonical/{id}{suffix}",
+		env!("CARGO_MANIFEST_DIR")
+	)
+	.into()
+}
+
+fn load_input(id: &str) -> Vec<Quad> {
+	nquads_syntax::Document::parse_str(&fs::read_to_string(file_path(id, "-in.nq")).unwrap())
+		.unwrap()
+		.into_value()
+		.into_iter()
+		.map(strip_rdf_quad)
+		.collect()
+}
+
+fn canonicalize(id: &str) {
+	let expected = fs::read_to_string(file_path(id, "-rdfc10.nq")).unwrap();
+	let output = treeldr_layouts::canonical::canonical_nquads(load_input(id)).unwrap();
+	assert_eq!(output, expected)
+}
+
+macro_rules! test {
+	($(#[$meta:meta])* $name:ident) => {
+		paste! {
+			$(#[$meta])*
+			#[test]
+			fn [<canonicalize_ $name>] () {
+				canonicalize(stringify!($name))
+			}
+		}
+	};
+}
+
+test! {
+	/// Simple id.
+	test001
+}
+
+test! {
+	/// Blank node diamond.
+	test020
+}
+
+test! {
+	/// Blank node double circle of 2.
+	test022
+}
+
+test! {
+	/// Blank node double circle of 3.
+	test029
+}
+
+test! {
+	/// Reordered 6 blank nodes.
+	test040
+}
+
+test! {
+	/// Poison dataset that is still within the call limit.
+	test044
+}
+
+test! {
+	/// Deep difference.
+	test047
+}
+
+test! {
+	/// Blank node graph names.
+	test054
+}
+
+test! {
+	/// N-Quads escaping.
+	test060
+}
+
+test! {
+	/// Blank node diamond with `_:b` labels.
+	test063
+}
+
+test! {
+	/// Blank nodes shared between graphs.
+	test072
+}
+
+test! {
+	/// Blank node graph name referenced from the default graph.
+	test073
+}
+
+test! {
+	/// Duplicate quad with blank nodes in input.
+	test077
+}
+
+/// Clique graph, exceeding the call limit.
+#[test]
+fn canonicalize_test074() {
+	assert!(treeldr_layouts::canonical::canonical_nquads(load_input("test074")).is_err())
+}
+
+/// Isomorphic datasets get the same content-derived IRIs.
+#[test]
+fn hash_blank_nodes() {
+	let prefix = iref::Iri::new("https://example.org/id/").unwrap();
+	let (a, a_map) =
+		treeldr_layouts::canonical::hash_blank_nodes(load_input("test020"), prefix).unwrap();
+	let (b, b_map) =
+		treeldr_layouts::canonical::hash_blank_nodes(load_input("test063"), prefix).unwrap();
+
+	assert_eq!(a_map.len(), 3);
+	assert!(a_map.values().all(|iri| iri.starts_with(prefix.as_str())));
+	assert!(a
+		.iter()
+		.all(|Quad(s, _, o, _)| !s.is_blank() && !o.is_blank()));
+	assert_eq!(
+		a.into_iter().collect::<BTreeSet<_>>(),
+		b.into_iter().collect::<BTreeSet<_>>()
+	);
+	assert_eq!(
+		a_map.values().collect::<BTreeSet<_>>(),
+		b_map.values().collect::<BTreeSet<_>>()
+	)
+}
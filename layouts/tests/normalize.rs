@@ -0,0 +1,89 @@
+use json_syntax::{Parse, TryFromJson};
+use std::fs;
+use treeldr_layouts::abs::{self, syntax::Layout};
+
+fn parse(json: &str) -> Layout {
+	let (json, code_map) = json_syntax::Value::parse_str(json).unwrap();
+	Layout::try_from_json(&json, &code_map).unwrap()
+}
+
+fn build(layout: &Layout) -> treeldr_layouts::Layouts {
+	let mut builder = abs::Builder::new();
+	layout.build(&mut builder).unwrap();
+	builder.build()
+}
+
+/// Normalizes the given layout, checking that the normalized layout builds to
+/// the same layouts and that its serialization parses back to itself.
+fn normalize(json: &str) -> Layout {
+	let layout = parse(json);
+	let mut normalized = layout.clone();
+	normalized.normalize().unwrap();
+
+	assert!(build(&layout).iter().eq(build(&normalized).iter()));
+
+	let serialized = serde_json::to_string(&normalized).unwrap();
+	assert_eq!(parse(&serialized), normalized);
+
+	normalized
+}
+
+#[test]
+fn normalize_distill_layouts() {
+	let dir = format!("{}/tests/distill", env!("CARGO_MANIFEST_DIR"));
+	for entry in fs::read_dir(dir).unwrap() {
+		let path = entry.unwrap().path();
+		if path.to_string_lossy().ends_with("-layout.json") {
+			normalize(&fs::read_to_string(path).unwrap());
+		}
+	}
+}
+
+#[test]
+fn normalize_record() {
+	let normalized = normalize(
+		r#"{
+			"type": "record",
+			"prefixes": { "ex": "https://example.org/#" },
+			"input": ["self"],
+			"fields": {
+				"name": {
+					"intro": ["value"],
+					"value": { "layout": "https://treeldr.org/prelude#string", "input": "_:value" },
+					"dataset": [
+						["_:self", "https://example.org/#name", "_:value"]
+					]
+				},
+				"id": {
+					"intro": [],
+					"value": {
+						"layout": "https://treeldr.org/prelude#id",
+						"input": ["_:self"]
+					}
+				}
+			}
+		}"#,
+	);
+
+	let expected = parse(
+		r#"{
+			"type": "record",
+			"prefixes": { "ex": "https://example.org/#" },
+			"fields": {
+				"name": {
+					"value": "https://treeldr.org/prelude#string",
+					"property": "ex:name"
+				},
+				"id": {
+					"intro": [],
+					"value": {
+						"layout": "https://treeldr.org/prelude#id",
+						"input": "_:self"
+					}
+				}
+			}
+		}"#,
+	);
+
+	assert_eq!(normalized, expected)
+}
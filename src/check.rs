@@ -0,0 +1,119 @@
+use std::{fs, path::PathBuf};
+
+use codespan_reporting::files::SimpleFiles;
+use rdf_types::{generator, Generator, LexicalLiteralTypeRef, Term};
+use treeldr_layouts::{
+	abs::{
+		self,
+		syntax::{BuildError, Context},
+		InsertResult,
+	},
+	LayoutRegistry, Layouts, Prelude, Ref,
+};
+
+use crate::{format, load_layout, parse_layout, report, Error};
+
+/// Loads and builds every given layout file, then checks that every layout
+/// they reference is defined.
+///
+/// Every error is reported, instead of stopping at the first one.
+pub fn check(
+	files: &mut SimpleFiles<String, String>,
+	paths: impl IntoIterator<Item = PathBuf>,
+	prelude: Option<Prelude>,
+) -> Result<(), Error> {
+	let mut layouts = Layouts::new();
+	let mut generator = generator::Blank::new();
+	let mut prefixes = format::rdf::Prefixes::default();
+	let mut loaded = Vec::new();
+	let mut count = 0;
+	let mut failures = 0;
+
+	for path in paths {
+		count += 1;
+		let result = fs::read_to_string(&path)
+			.map_err(Error::IO)
+			.and_then(|content| {
+				let file_id = files.add(path.to_string_lossy().into_owned(), content);
+				load_layout(files, file_id, &mut layouts, &mut generator, &mut prefixes)?;
+				Ok(file_id)
+			});
+
+		match result {
+			Ok(file_id) => loaded.push(file_id),
+			Err(e) => {
+				report(files, e.into_diagnostic());
+				failures += 1
+			}
+		}
+	}
+
+	let layouts = layouts.with(prelude);
+	for file_id in loaded {
+		if let Err(e) = check_references(files, file_id, &layouts) {
+			report(files, e.into_diagnostic());
+			failures += 1
+		}
+	}
+
+	if failures == 0 {
+		Ok(())
+	} else {
+		Err(Error::Check(failures, count))
+	}
+}
+
+/// Builds the given layout file again, checking that every layout it
+/// references is defined in `layouts`.
+fn check_references(
+	files: &SimpleFiles<String, String>,
+	file_id: usize,
+	layouts: &impl LayoutRegistry,
+) -> Result<(), Error> {
+	let (json, code_map, layout) = parse_layout(files, file_id)?;
+	let mut builder = abs::Builder::new();
+	let mut context = CheckContext {
+		inner: builder.with_generator_mut(generator::Blank::new()),
+		layouts,
+	};
+
+	layout
+		.build_with_context(&mut context)
+		.map_err(|e| Error::layout_build(file_id, &json, &code_map, e))?;
+
+	Ok(())
+}
+
+/// Layout build context rejecting references to undefined layouts.
+struct CheckContext<'a, G, L> {
+	inner: abs::BuilderWithGeneratorMut<'a, G>,
+	layouts: &'a L,
+}
+
+impl<G: Generator, L: LayoutRegistry> Context for CheckContext<'_, G, L> {
+	type Resource = Term;
+
+	fn insert_layout(&mut self, id: Term, layout: abs::Layout<Term>) -> InsertResult<Term> {
+		self.inner.insert_layout(id, layout)
+	}
+
+	fn iri_resource(&mut self, iri: &iref::Iri) -> Term {
+		self.inner.iri_resource(iri)
+	}
+
+	fn literal_resource(&mut self, value: &str, type_: LexicalLiteralTypeRef) -> Term {
+		self.inner.literal_resource(value, type_)
+	}
+
+	fn anonymous_resource(&mut self) -> Term {
+		self.inner.anonymous_resource()
+	}
+
+	fn check_layout_ref(&mut self, id: &Term) -> Result<(), BuildError> {
+		if self.layouts.contains(&Ref::new(id.clone())) {
+			Ok(())
+		} else {
+			Err(BuildError::UndefinedLayout(id.to_string()))
+		}
+	}
+}
@@ -0,0 +1,78 @@
+use std::{fs, path::PathBuf};
+
+use codespan_reporting::files::SimpleFiles;
+use json_syntax::{
+	print::{Indent, Limit},
+	Print,
+};
+
+use crate::{parse_layout, report, Error};
+
+/// Formats every given layout file.
+///
+/// Each layout is normalized and serialized in its canonical form. If `check`
+/// is set, files are left untouched and the path of every file that is not
+/// formatted is printed instead.
+pub fn fmt(
+	files: &mut SimpleFiles<String, String>,
+	paths: impl IntoIterator<Item = PathBuf>,
+	check: bool,
+) -> Result<(), Error> {
+	let mut count = 0;
+	let mut unformatted = 0;
+	let mut failures = 0;
+
+	for path in paths {
+		count += 1;
+		let result = fs::read_to_string(&path)
+			.map_err(Error::IO)
+			.and_then(|content| {
+				let file_id = files.add(path.to_string_lossy().into_owned(), content);
+				let formatted = format_layout(files, file_id)?;
+				Ok((file_id, formatted))
+			});
+
+		match result {
+			Ok((file_id, formatted)) => {
+				if *files.get(file_id).unwrap().source() != formatted {
+					if check {
+						println!("{}", path.display());
+						unformatted += 1
+					} else {
+						fs::write(&path, formatted).map_err(Error::IO)?
+					}
+				}
+			}
+			Err(e) => {
+				report(files, e.into_diagnostic());
+				failures += 1
+			}
+		}
+	}
+
+	if failures > 0 {
+		Err(Error::Check(failures, count))
+	} else if unformatted > 0 {
+		Err(Error::Unformatted(unformatted, count))
+	} else {
+		Ok(())
+	}
+}
+
+/// Returns the canonical representation of the given layout file.
+fn format_layout(files: &SimpleFiles<String, String>, file_id: usize) -> Result<String, Error> {
+	let (json, code_map, mut layout) = parse_layout(files, file_id)?;
+	layout
+		.normalize()
+		.map_err(|e| Error::layout_build(file_id, &json, &code_map, e))?;
+
+	let value = json_syntax::to_value(&layout).map_err(Error::SerializeLayout)?;
+	let mut options = json_syntax::print::Options::pretty();
+	options.indent = Indent::Tabs(1);
+	// Keep short arrays, such as quads, on a single line.
+	options.array_begin = 0;
+	options.array_end = 0;
+	options.array_limit = Some(Limit::Width(80));
+
+	Ok(format!("{}\n", value.print_with(options)))
+}
@@ -0,0 +1,439 @@
+//! JSON-LD support.
+use std::{
+	collections::BTreeMap,
+	fs,
+	io::{self, BufRead},
+	path::PathBuf,
+};
+
+use iref::{Iri, IriBuf};
+use json_ld::{JsonLdProcessor, Loader, LoadingResult, RemoteDocument};
+use json_syntax::{Object, Parse, Value as Json};
+use rdf_types::{
+	dataset::BTreeDataset, generator, Id, Literal, LiteralType, Quad, Term, RDF_TYPE, XSD_STRING,
+};
+
+use super::{LoadError, RDFFormat, WriteError};
+
+/// Local JSON-LD document loader.
+///
+/// Remote documents (such as contexts) are loaded from local directories
+/// only, never from the network: either from a directory mounted on a prefix
+/// of the document IRI, or from the cache directory where the document
+/// `https://host/path` is stored at `host/path`. Documents whose IRI has a
+/// query, or a path that cannot be mapped safely to a relative file path, are
+/// never loaded from the cache.
+#[derive(Default)]
+pub struct ContextLoader {
+	mounts: json_ld::FsLoader,
+	cache: Option<PathBuf>,
+}
+
+impl ContextLoader {
+	/// Loads documents whose IRI starts with `prefix` from the given
+	/// directory.
+	pub fn mount(&mut self, prefix: IriBuf, path: PathBuf) {
+		self.mounts.mount(prefix, path)
+	}
+
+	/// Sets the cache directory.
+	pub fn set_cache(&mut self, path: PathBuf) {
+		self.cache = Some(path)
+	}
+
+	/// Returns the path of the given document in the cache directory, if
+	/// any.
+	///
+	/// The query is not part of the path, so documents with a query are not
+	/// cached. Empty, `.` and `..` segments are rejected so that the path
+	/// cannot escape the cache directory.
+	fn cached(&self, url: &Iri) -> Option<PathBuf> {
+		if url.query().is_some() {
+			return None;
+		}
+
+		let mut path = self.cache.clone()?;
+		let host = url.authority()?.host().as_str();
+		if !is_safe_path_component(host) {
+			return None;
+		}
+		path.push(host);
+
+		for segment in url.path().segments() {
+			if !is_safe_path_component(segment.as_str()) {
+				return None;
+			}
+			path.push(segment.as_str())
+		}
+
+		Some(path)
+	}
+}
+
+impl Loader for ContextLoader {
+	async fn load(&self, url: &Iri) -> LoadingResult<IriBuf> {
+		if self.mounts.filepath(url).is_some() {
+			return self.mounts.load(url).await;
+		}
+
+		match self.cached(url) {
+			Some(path) => {
+				let content = fs::read_to_string(path).map_err(|e| {
+					json_ld::LoadError::new(url.to_owned(), json_ld::loader::fs::Error::IO(e))
+				})?;
+				let (document, _) = Json::parse_str(&content).map_err(|e| {
+					json_ld::LoadError::new(url.to_owned(), json_ld::loader::fs::Error::Parse(e))
+				})?;
+				Ok(RemoteDocument::new(Some(url.to_owned()), None, document))
+			}
+			None => Err(json_ld::LoadError::new(
+				url.to_owned(),
+				json_ld::loader::fs::Error::NoMountPoint,
+			)),
+		}
+	}
+}
+
+/// Checks that the given IRI component can be used as a file name in the
+/// cache directory.
+fn is_safe_path_component(s: &str) -> bool {
+	!matches!(s, "" | "." | "..") && !s.contains(['/', '\\'])
+}
+
+/// Parses a `<IRI>=<DIR>` mount point.
+pub fn parse_mount_point(s: &str) -> Result<(IriBuf, PathBuf), String> {
+	let (prefix, path) = s
+		.split_once('=')
+		.ok_or_else(|| "expected `<IRI>=<DIR>`".to_owned())?;
+	let prefix = IriBuf::new(prefix.to_owned()).map_err(|e| format!("invalid IRI `{}`", e.0))?;
+	Ok((prefix, path.into()))
+}
+
+/// Expands the given JSON-LD document and converts it into an RDF dataset.
+pub fn load(input: impl BufRead, loader: &ContextLoader) -> Result<BTreeDataset, LoadError> {
+	let content = io::read_to_string(input).map_err(LoadError::IO)?;
+	let (json, _) = Json::parse_str(&content).map_err(LoadError::Json)?;
+	let document = RemoteDocument::new(None, None, json);
+
+	let mut rdf = futures::executor::block_on(
+		document.to_rdf(generator::Blank::new_with_prefix("b".to_owned()), loader),
+	)
+	.map_err(LoadError::JsonLd)?;
+
+	Ok(rdf
+		.cloned_quads()
+		.map(|Quad(s, p, o, g)| Quad(Term::Id(s), Term::Id(p), o, g.map(Term::Id)))
+		.collect())
+}
+
+/// Serializes the given dataset into an expanded JSON-LD document, or a
+/// flattened document if `format` is [`RDFFormat::JsonLdFlattened`].
+///
+/// Each subject is given its own node object, with `rdf:type` objects listed
+/// in `@type`. Named graphs are described by graph objects. Lists and native
+/// types are not converted.
+///
+/// The flattened document lists its nodes in a top-level `@graph`, where
+/// every node referenced in a graph is given its own node object, as done
+/// by the JSON-LD flattening algorithm.
+pub fn serialize(
+	format: &RDFFormat,
+	dataset: impl IntoIterator<Item = Quad>,
+) -> Result<Json, WriteError> {
+	let flatten = matches!(format, RDFFormat::JsonLdFlattened);
+	let mut graphs: BTreeMap<Option<String>, BTreeMap<String, Node>> = BTreeMap::new();
+
+	for Quad(s, p, o, g) in dataset {
+		let graph = match g {
+			Some(g) => Some(term_id(format, g)?),
+			None => None,
+		};
+		let p = match p {
+			Term::Id(Id::Iri(p)) => p,
+			_ => return Err(WriteError::Generalized(format.clone())),
+		};
+
+		let nodes = graphs.entry(graph).or_default();
+		let node = nodes.entry(term_id(format, s)?).or_default();
+		match (p, o) {
+			(p, Term::Id(o)) if p == RDF_TYPE => node.types.push(id_string(&o).into()),
+			(p, o) => {
+				let referenced = match &o {
+					Term::Id(id) if flatten => Some(id_string(id)),
+					_ => None,
+				};
+
+				node.properties
+					.entry(p.into_string())
+					.or_default()
+					.push(object_value(o));
+
+				if let Some(id) = referenced {
+					nodes.entry(id).or_default();
+				}
+			}
+		}
+	}
+
+	let mut default_graph = graphs.remove(&None).unwrap_or_default();
+	for (graph, nodes) in graphs {
+		let graph = graph.unwrap();
+		let nodes = nodes
+			.into_iter()
+			.map(|(id, node)| node.into_json(id))
+			.collect();
+		default_graph.entry(graph).or_default().graph = Some(nodes);
+	}
+
+	let nodes: Vec<Json> = default_graph
+		.into_iter()
+		.map(|(id, node)| node.into_json(id))
+		.collect();
+
+	if flatten {
+		let mut document = Object::new();
+		document.insert("@graph".into(), Json::Array(nodes));
+		Ok(document.into())
+	} else {
+		Ok(Json::Array(nodes))
+	}
+}
+
+#[derive(Default)]
+struct Node {
+	types: Vec<Json>,
+	properties: BTreeMap<String, Vec<Json>>,
+	graph: Option<Vec<Json>>,
+}
+
+impl Node {
+	fn into_json(self, id: String) -> Json {
+		let mut object = Object::new();
+		object.insert("@id".into(), id.into());
+
+		if !self.types.is_empty() {
+			object.insert("@type".into(), Json::Array(self.types));
+		}
+
+		for (property, values) in self.properties {
+			object.insert(property.into(), Json::Array(values));
+		}
+
+		if let Some(graph) = self.graph {
+			object.insert("@graph".into(), Json::Array(graph));
+		}
+
+		object.into()
+	}
+}
+
+fn id_string(id: &Id) -> String {
+	match id {
+		Id::Iri(iri) => iri.to_string(),
+		Id::Blank(b) => b.to_string(),
+	}
+}
+
+/// Returns the identifier of the given subject or graph name.
+///
+/// Literals cannot be used there in JSON-LD.
+fn term_id(format: &RDFFormat, term: Term) -> Result<String, WriteError> {
+	match term {
+		Term::Id(id) => Ok(id_string(&id)),
+		Term::Literal(_) => Err(WriteError::Generalized(format.clone())),
+	}
+}
+
+fn object_value(term: Term) -> Json {
+	let mut object = Object::new();
+	match term {
+		Term::Id(id) => {
+			object.insert("@id".into(), id_string(&id).into());
+		}
+		Term::Literal(Literal { value, type_ }) => {
+			object.insert("@value".into(), value.into());
+			match type_ {
+				LiteralType::Any(ty) if ty == XSD_STRING => (),
+				LiteralType::Any(ty) => {
+					object.insert("@type".into(), ty.to_string().into());
+				}
+				LiteralType::LangString(tag) => {
+					object.insert("@language".into(), tag.to_string().into());
+				}
+			}
+		}
+	}
+
+	object.into()
+}
+
+#[cfg(test)]
+mod tests {
+	use json_syntax::Print;
+	use static_iref::iri;
+
+	use super::*;
+	use crate::format::rdf::Options;
+
+	const NQUADS: &str = r#"<https://example.org/#alice> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://schema.org/Person> .
+<https://example.org/#alice> <https://schema.org/name> "Alice" .
+<https://example.org/#alice> <https://schema.org/description> "Une personne"@fr .
+<https://example.org/#alice> <https://schema.org/age> "42"^^<http://www.w3.org/2001/XMLSchema#integer> .
+<https://example.org/#alice> <https://schema.org/knows> _:bob .
+_:bob <https://schema.org/knows> <https://example.org/#carol> <https://example.org/#g> .
+"#;
+
+	fn load_nquads(input: &str) -> BTreeDataset {
+		RDFFormat::NQuads
+			.load(input.as_bytes(), &Options::default())
+			.unwrap()
+	}
+
+	fn canonical(dataset: BTreeDataset) -> Vec<Quad> {
+		treeldr_layouts::canonical::canonicalize(dataset).unwrap()
+	}
+
+	/// Serializes the given dataset in the given format, and checks that
+	/// loading it back gives an isomorphic dataset.
+	fn round_trip(format: RDFFormat, dataset: BTreeDataset) -> Json {
+		let json = serialize(&format, dataset.clone()).unwrap();
+		let loaded = load(
+			json.pretty_print().to_string().as_bytes(),
+			&Default::default(),
+		)
+		.unwrap();
+		assert_eq!(canonical(loaded), canonical(dataset));
+		json
+	}
+
+	#[test]
+	fn load_compacted() {
+		let input = r#"{
+			"@context": { "@vocab": "https://schema.org/", "@language": "fr" },
+			"@id": "https://example.org/#alice",
+			"@type": "Person",
+			"description": "Une personne"
+		}"#;
+
+		let expected = load_nquads(
+			r#"<https://example.org/#alice> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://schema.org/Person> .
+<https://example.org/#alice> <https://schema.org/description> "Une personne"@fr .
+"#,
+		);
+
+		assert_eq!(
+			load(input.as_bytes(), &Default::default()).unwrap(),
+			expected
+		)
+	}
+
+	#[test]
+	fn expanded_round_trip() {
+		let json = round_trip(RDFFormat::JsonLd, load_nquads(NQUADS));
+		assert!(json.is_array())
+	}
+
+	#[test]
+	fn flattened_round_trip() {
+		let json = round_trip(RDFFormat::JsonLdFlattened, load_nquads(NQUADS));
+		let nodes = json
+			.as_object()
+			.unwrap()
+			.get_unique("@graph")
+			.unwrap()
+			.unwrap();
+		let ids: Vec<_> = nodes
+			.as_array()
+			.unwrap()
+			.iter()
+			.map(|node| {
+				let node = node.as_object().unwrap();
+				node.get_unique("@id").unwrap().unwrap().as_str().unwrap()
+			})
+			.collect();
+
+		// Referenced nodes, and the named graph, have their own node object.
+		assert_eq!(
+			ids,
+			[
+				"_:bob",
+				"https://example.org/#alice",
+				"https://example.org/#g"
+			]
+		);
+
+		let graph = json
+			.as_object()
+			.unwrap()
+			.get_unique("@graph")
+			.unwrap()
+			.unwrap();
+		let named = graph.as_array().unwrap()[2].as_object().unwrap();
+		assert_eq!(
+			named
+				.get_unique("@graph")
+				.unwrap()
+				.unwrap()
+				.as_array()
+				.unwrap()
+				.len(),
+			2
+		)
+	}
+
+	#[test]
+	fn lang_string_without_language() {
+		round_trip(
+			RDFFormat::JsonLd,
+			load_nquads(
+				"<https://example.org/#a> <https://example.org/#b> \"c\"^^<http://www.w3.org/1999/02/22-rdf-syntax-ns#langString> .\n",
+			),
+		);
+	}
+
+	#[test]
+	fn generalized() {
+		let literal = Term::Literal(Literal::new(
+			"a".to_owned(),
+			LiteralType::Any(XSD_STRING.to_owned()),
+		));
+		let iri = Term::Id(Id::Iri(iri!("https://example.org/#a").to_owned()));
+
+		for quad in [
+			Quad(literal.clone(), iri.clone(), iri.clone(), None),
+			Quad(iri.clone(), literal.clone(), iri.clone(), None),
+			Quad(iri.clone(), iri.clone(), iri.clone(), Some(literal)),
+		] {
+			assert!(matches!(
+				serialize(&RDFFormat::JsonLd, [quad]),
+				Err(WriteError::Generalized(_))
+			))
+		}
+	}
+
+	#[test]
+	fn cached_path() {
+		let mut loader = ContextLoader::default();
+		assert_eq!(
+			loader.cached(iri!("https://example.org/context.jsonld")),
+			None
+		);
+
+		loader.set_cache("cache".into());
+		assert_eq!(
+			loader.cached(iri!("https://example.org/ns/context.jsonld")),
+			Some(PathBuf::from("cache/example.org/ns/context.jsonld"))
+		);
+
+		for url in [
+			iri!("https://example.org/../context.jsonld"),
+			iri!("https://example.org/ns/./context.jsonld"),
+			iri!("https://example.org/ns//context.jsonld"),
+			iri!("https://example.org/ns/"),
+			iri!("https://../context.jsonld"),
+			iri!("https://example.org/context.jsonld?version=2"),
+		] {
+			assert_eq!(loader.cached(url), None, "{url}")
+		}
+	}
+}
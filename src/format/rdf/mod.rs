@@ -0,0 +1,522 @@
+use core::fmt;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+
+use clap::builder::TypedValueParser;
+use iref::IriBuf;
+use json_syntax::Print;
+use locspan::Span;
+use nquads_syntax::Parse;
+use rdf_types::dataset::BTreeDataset;
+use rdf_types::{BlankId, BlankIdBuf, Id, Literal, LiteralType, Quad, Term, Triple};
+
+mod json_ld;
+
+pub use json_ld::{parse_mount_point, ContextLoader};
+
+/// IRI prefixes used to compact IRIs, indexed by prefix name.
+pub type Prefixes = BTreeMap<String, IriBuf>;
+
+/// RDF loading and writing options.
+#[derive(Default)]
+pub struct Options {
+	/// IRI prefixes used to compact IRIs in formats that support it (Turtle,
+	/// TriG and RDF/XML).
+	pub prefixes: Prefixes,
+
+	/// Loader used to resolve remote JSON-LD contexts.
+	pub contexts: ContextLoader,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LoadError {
+	#[error(transparent)]
+	IO(io::Error),
+
+	#[error("N-Quads parse error: {0}")]
+	NQuads(
+		#[from] nquads_syntax::parsing::MetaError<nquads_syntax::lexing::Error<io::Error>, Span>,
+	),
+
+	#[error("{0} parse error: {1}")]
+	Turtle(RDFFormat, oxttl::TurtleParseError),
+
+	#[error("RDF/XML parse error: {0}")]
+	RdfXml(oxrdfxml::RdfXmlParseError),
+
+	#[error("invalid language tag `{0}`")]
+	InvalidLangTag(String),
+
+	#[error("JSON parse error: {0}")]
+	Json(json_syntax::parse::Error),
+
+	#[error("JSON-LD error: {0}")]
+	JsonLd(::json_ld::ToRdfError),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WriteError {
+	#[error(transparent)]
+	IO(#[from] io::Error),
+
+	#[error("named graphs cannot be written in {0}")]
+	NamedGraph(RDFFormat),
+
+	#[error("generalized RDF triples cannot be written in {0}")]
+	Generalized(RDFFormat),
+
+	#[error("invalid IRI prefix `{0}`")]
+	InvalidPrefix(String),
+
+	#[error("canonical datasets cannot be written in {0}")]
+	Canonical(RDFFormat),
+
+	#[error(transparent)]
+	Canonicalization(#[from] treeldr_layouts::canonical::Error),
+}
+
+#[derive(Debug, Clone)]
+pub enum RDFFormat {
+	NQuads,
+	NTriples,
+	Turtle,
+	TriG,
+	RdfXml,
+	JsonLd,
+	JsonLdFlattened,
+}
+
+impl RDFFormat {
+	pub const POSSIBLE_VALUES: &'static [&'static str] = &[
+		"application/n-quads",
+		"n-quads",
+		"nquads",
+		"nq",
+		"application/n-triples",
+		"n-triples",
+		"ntriples",
+		"nt",
+		"text/turtle",
+		"turtle",
+		"ttl",
+		"application/trig",
+		"trig",
+		"application/rdf+xml",
+		"rdf-xml",
+		"rdfxml",
+		"application/ld+json",
+		"json-ld",
+		"jsonld",
+		"application/ld+json;profile=http://www.w3.org/ns/json-ld#flattened",
+		"json-ld-flattened",
+	];
+
+	pub fn parser(
+	) -> clap::builder::MapValueParser<clap::builder::PossibleValuesParser, fn(String) -> Self> {
+		clap::builder::PossibleValuesParser::new(Self::POSSIBLE_VALUES)
+			.map(|s| Self::new(&s).unwrap())
+	}
+
+	pub fn new(name: &str) -> Option<Self> {
+		match name {
+			"nq" | "nquads" | "n-quads" | "application/n-quads" => Some(Self::NQuads),
+			"nt" | "ntriples" | "n-triples" | "application/n-triples" => Some(Self::NTriples),
+			"ttl" | "turtle" | "text/turtle" => Some(Self::Turtle),
+			"trig" | "application/trig" => Some(Self::TriG),
+			"rdfxml" | "rdf-xml" | "application/rdf+xml" => Some(Self::RdfXml),
+			"jsonld" | "json-ld" | "application/ld+json" => Some(Self::JsonLd),
+			"json-ld-flattened"
+			| "application/ld+json;profile=http://www.w3.org/ns/json-ld#flattened" => {
+				Some(Self::JsonLdFlattened)
+			}
+			_ => None,
+		}
+	}
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::NQuads => "application/n-quads",
+			Self::NTriples => "application/n-triples",
+			Self::Turtle => "text/turtle",
+			Self::TriG => "application/trig",
+			Self::RdfXml => "application/rdf+xml",
+			Self::JsonLd => "application/ld+json",
+			Self::JsonLdFlattened => {
+				"application/ld+json;profile=http://www.w3.org/ns/json-ld#flattened"
+			}
+		}
+	}
+
+	/// Checks if the concatenation of datasets written in this format is a
+	/// valid document, as required by batch mode.
+	pub fn is_line_based(&self) -> bool {
+		matches!(self, Self::NQuads | Self::NTriples)
+	}
+
+	/// Loads a dataset.
+	///
+	/// JSON-LD documents are expanded and converted to RDF, resolving remote
+	/// contexts with the loader given in `options`.
+	pub fn load(&self, input: impl BufRead, options: &Options) -> Result<BTreeDataset, LoadError> {
+		match self {
+			Self::NQuads => {
+				let utf8_input = utf8_decode::UnsafeDecoder::new(input.bytes());
+				let document = nquads_syntax::GrdfDocument::parse_utf8(utf8_input)
+					.map_err(LoadError::NQuads)?
+					.into_value();
+				Ok(document
+					.into_iter()
+					.map(|q| nquads_syntax::strip_quad(q.into_value()))
+					.collect())
+			}
+			Self::NTriples => oxttl::NTriplesParser::new()
+				.for_reader(input)
+				.map(|t| {
+					let t = t.map_err(|e| LoadError::Turtle(self.clone(), e))?;
+					quad_from_ox(t.in_graph(oxrdf::GraphName::DefaultGraph))
+				})
+				.collect(),
+			Self::Turtle => oxttl::TurtleParser::new()
+				.for_reader(input)
+				.map(|t| {
+					let t = t.map_err(|e| LoadError::Turtle(self.clone(), e))?;
+					quad_from_ox(t.in_graph(oxrdf::GraphName::DefaultGraph))
+				})
+				.collect(),
+			Self::TriG => oxttl::TriGParser::new()
+				.for_reader(input)
+				.map(|q| quad_from_ox(q.map_err(|e| LoadError::Turtle(self.clone(), e))?))
+				.collect(),
+			Self::RdfXml => oxrdfxml::RdfXmlParser::new()
+				.for_reader(input)
+				.map(|t| {
+					let t = t.map_err(LoadError::RdfXml)?;
+					quad_from_ox(t.in_graph(oxrdf::GraphName::DefaultGraph))
+				})
+				.collect(),
+			Self::JsonLd | Self::JsonLdFlattened => json_ld::load(input, &options.contexts),
+		}
+	}
+
+	/// Writes the given dataset.
+	///
+	/// The prefixes given in `options` are used to compact IRIs in formats
+	/// that support it (Turtle, TriG and RDF/XML).
+	pub fn write(
+		&self,
+		dataset: impl IntoIterator<Item = Quad>,
+		options: &Options,
+		mut output: impl Write,
+	) -> Result<(), WriteError> {
+		match self {
+			Self::NQuads => {
+				for quad in dataset {
+					writeln!(output, "{} .", quad)?;
+				}
+
+				Ok(())
+			}
+			Self::NTriples => {
+				for Quad(s, p, o, g) in dataset {
+					if g.is_some() {
+						return Err(WriteError::NamedGraph(self.clone()));
+					}
+
+					writeln!(output, "{} .", Triple(s, p, o))?;
+				}
+
+				Ok(())
+			}
+			Self::Turtle => {
+				let mut serializer = oxttl::TurtleSerializer::new();
+				for (name, iri) in &options.prefixes {
+					serializer = serializer
+						.with_prefix(name, iri.as_str())
+						.map_err(|_| WriteError::InvalidPrefix(name.clone()))?;
+				}
+
+				let mut writer = serializer.for_writer(output);
+				for quad in dataset {
+					if quad.3.is_some() {
+						return Err(WriteError::NamedGraph(self.clone()));
+					}
+
+					writer.serialize_triple(&oxrdf::Triple::from(quad_into_ox(self, quad)?))?;
+				}
+
+				writer.finish()?;
+				Ok(())
+			}
+			Self::TriG => {
+				let mut serializer = oxttl::TriGSerializer::new();
+				for (name, iri) in &options.prefixes {
+					serializer = serializer
+						.with_prefix(name, iri.as_str())
+						.map_err(|_| WriteError::InvalidPrefix(name.clone()))?;
+				}
+
+				let mut writer = serializer.for_writer(output);
+				for quad in dataset {
+					writer.serialize_quad(&quad_into_ox(self, quad)?)?;
+				}
+
+				writer.finish()?;
+				Ok(())
+			}
+			Self::RdfXml => {
+				let mut serializer = oxrdfxml::RdfXmlSerializer::new();
+				for (name, iri) in &options.prefixes {
+					serializer = serializer
+						.with_prefix(name, iri.as_str())
+						.map_err(|_| WriteError::InvalidPrefix(name.clone()))?;
+				}
+
+				let mut writer = serializer.for_writer(output);
+				for quad in dataset {
+					if quad.3.is_some() {
+						return Err(WriteError::NamedGraph(self.clone()));
+					}
+
+					writer.serialize_triple(&oxrdf::Triple::from(quad_into_ox(self, quad)?))?;
+				}
+
+				writer.finish()?;
+				Ok(())
+			}
+			Self::JsonLd | Self::JsonLdFlattened => {
+				let document = json_ld::serialize(self, dataset)?;
+				writeln!(output, "{}", document.pretty_print())?;
+				Ok(())
+			}
+		}
+	}
+
+	/// Writes the canonical form of the given dataset, following the RDF
+	/// Dataset Canonicalization algorithm (RDFC-1.0).
+	///
+	/// The output is the canonical N-Quads serialization of the dataset, which
+	/// is byte-identical for isomorphic datasets. Only N-Quads and N-Triples
+	/// (without named graphs) are supported.
+	pub fn write_canonical(
+		&self,
+		dataset: impl IntoIterator<Item = Quad>,
+		mut output: impl Write,
+	) -> Result<(), WriteError> {
+		match self {
+			Self::NQuads | Self::NTriples => {
+				let quads = treeldr_layouts::canonical::canonicalize(dataset)?;
+				let mut line = String::new();
+				for quad in &quads {
+					if quad.3.is_some() && matches!(self, Self::NTriples) {
+						return Err(WriteError::NamedGraph(self.clone()));
+					}
+
+					line.clear();
+					treeldr_layouts::canonical::write_nquad(&mut line, quad, BlankId::suffix);
+					output.write_all(line.as_bytes())?;
+				}
+
+				Ok(())
+			}
+			_ => Err(WriteError::Canonical(self.clone())),
+		}
+	}
+}
+
+impl fmt::Display for RDFFormat {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
+fn quad_from_ox(quad: oxrdf::Quad) -> Result<Quad, LoadError> {
+	let subject = match quad.subject {
+		oxrdf::Subject::NamedNode(n) => iri_from_ox(n),
+		oxrdf::Subject::BlankNode(b) => blank_from_ox(b),
+	};
+
+	let object = match quad.object {
+		oxrdf::Term::NamedNode(n) => Term::Id(iri_from_ox(n)),
+		oxrdf::Term::BlankNode(b) => Term::Id(blank_from_ox(b)),
+		oxrdf::Term::Literal(l) => {
+			let (value, datatype, language) = l.destruct();
+			let type_ = match language {
+				Some(tag) => LiteralType::LangString(
+					langtag::LangTagBuf::new(tag).map_err(|e| LoadError::InvalidLangTag(e.0))?,
+				),
+				None => LiteralType::Any(match datatype {
+					Some(datatype) => IriBuf::new(datatype.into_string()).unwrap(),
+					None => IriBuf::new(oxrdf::vocab::xsd::STRING.as_str().to_owned()).unwrap(),
+				}),
+			};
+
+			Term::Literal(Literal::new(value, type_))
+		}
+	};
+
+	let graph = match quad.graph_name {
+		oxrdf::GraphName::NamedNode(n) => Some(Term::Id(iri_from_ox(n))),
+		oxrdf::GraphName::BlankNode(b) => Some(Term::Id(blank_from_ox(b))),
+		oxrdf::GraphName::DefaultGraph => None,
+	};
+
+	Ok(Quad(
+		Term::Id(subject),
+		Term::Id(iri_from_ox(quad.predicate)),
+		object,
+		graph,
+	))
+}
+
+fn iri_from_ox(node: oxrdf::NamedNode) -> Id {
+	Id::Iri(IriBuf::new(node.into_string()).unwrap())
+}
+
+fn blank_from_ox(node: oxrdf::BlankNode) -> Id {
+	Id::Blank(BlankIdBuf::new(format!("_:{}", node.as_str())).unwrap())
+}
+
+fn quad_into_ox(format: &RDFFormat, Quad(s, p, o, g): Quad) -> Result<oxrdf::Quad, WriteError> {
+	let subject: oxrdf::Subject = match s {
+		Term::Id(Id::Iri(iri)) => oxrdf::NamedNode::new_unchecked(iri.into_string()).into(),
+		Term::Id(Id::Blank(b)) => blank_into_ox(format, b).into(),
+		Term::Literal(_) => return Err(WriteError::Generalized(format.clone())),
+	};
+
+	let predicate = match p {
+		Term::Id(Id::Iri(iri)) => oxrdf::NamedNode::new_unchecked(iri.into_string()),
+		_ => return Err(WriteError::Generalized(format.clone())),
+	};
+
+	let object: oxrdf::Term = match o {
+		Term::Id(Id::Iri(iri)) => oxrdf::NamedNode::new_unchecked(iri.into_string()).into(),
+		Term::Id(Id::Blank(b)) => blank_into_ox(format, b).into(),
+		Term::Literal(l) => match l.type_ {
+			LiteralType::Any(datatype) => oxrdf::Literal::new_typed_literal(
+				l.value,
+				oxrdf::NamedNode::new_unchecked(datatype.into_string()),
+			)
+			.into(),
+			LiteralType::LangString(tag) => {
+				oxrdf::Literal::new_language_tagged_literal_unchecked(l.value, tag.to_string())
+					.into()
+			}
+		},
+	};
+
+	let graph: oxrdf::GraphName = match g {
+		None => oxrdf::GraphName::DefaultGraph,
+		Some(Term::Id(Id::Iri(iri))) => oxrdf::NamedNode::new_unchecked(iri.into_string()).into(),
+		Some(Term::Id(Id::Blank(b))) => blank_into_ox(format, b).into(),
+		Some(Term::Literal(_)) => return Err(WriteError::Generalized(format.clone())),
+	};
+
+	Ok(oxrdf::Quad::new(subject, predicate, object, graph))
+}
+
+/// Converts a blank node identifier.
+///
+/// RDF/XML writes blank node labels in `rdf:nodeID` attributes, which must
+/// be XML names, unlike Turtle labels that may start with a digit. Labels
+/// are prefixed with `b` in RDF/XML so that they always are.
+fn blank_into_ox(format: &RDFFormat, id: BlankIdBuf) -> oxrdf::BlankNode {
+	match format {
+		RDFFormat::RdfXml => oxrdf::BlankNode::new_unchecked(format!("b{}", id.suffix())),
+		_ => oxrdf::BlankNode::new_unchecked(id.suffix()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TRIPLES: &str = r#"<https://example.org/#alice> <https://schema.org/name> "Alice" .
+<https://example.org/#alice> <https://schema.org/description> "Une personne"@fr .
+<https://example.org/#alice> <https://schema.org/age> "42"^^<http://www.w3.org/2001/XMLSchema#integer> .
+<https://example.org/#alice> <https://schema.org/knows> _:bob .
+_:bob <https://schema.org/name> "Bob \"the\" builder\n" .
+"#;
+
+	fn load(format: RDFFormat, input: &str) -> BTreeDataset {
+		format.load(input.as_bytes(), &Options::default()).unwrap()
+	}
+
+	fn write(format: RDFFormat, dataset: &BTreeDataset, options: &Options) -> String {
+		let mut output = Vec::new();
+		format.write(dataset.clone(), options, &mut output).unwrap();
+		String::from_utf8(output).unwrap()
+	}
+
+	/// Writes the given N-Quads dataset in the given format, and checks that
+	/// loading it back gives the same dataset.
+	fn round_trip(format: RDFFormat, nquads: &str, options: &Options) -> String {
+		let dataset = load(RDFFormat::NQuads, nquads);
+		let output = write(format.clone(), &dataset, options);
+		assert_eq!(load(format, &output), dataset);
+		output
+	}
+
+	#[test]
+	fn turtle_round_trip() {
+		round_trip(RDFFormat::Turtle, TRIPLES, &Options::default());
+	}
+
+	#[test]
+	fn turtle_prefixes() {
+		let options = Options {
+			prefixes: [(
+				"schema".to_owned(),
+				IriBuf::new("https://schema.org/".to_owned()).unwrap(),
+			)]
+			.into_iter()
+			.collect(),
+			..Default::default()
+		};
+
+		let output = round_trip(RDFFormat::Turtle, TRIPLES, &options);
+		assert!(output.contains("schema:name"))
+	}
+
+	#[test]
+	fn n_triples_round_trip() {
+		round_trip(RDFFormat::NTriples, TRIPLES, &Options::default());
+	}
+
+	#[test]
+	fn trig_round_trip() {
+		let nquads = format!(
+			"{TRIPLES}\
+			<https://example.org/#alice> <https://schema.org/name> \"Alice\" <https://example.org/#g> .\n\
+			_:bob <https://schema.org/knows> _:carol _:g .\n"
+		);
+
+		let output = round_trip(RDFFormat::TriG, &nquads, &Options::default());
+		assert!(output.contains("<https://example.org/#g> {"))
+	}
+
+	#[test]
+	fn rdf_xml_round_trip() {
+		let nquads = format!("{TRIPLES}_:0 <https://schema.org/knows> _:bob .\n");
+		let dataset = load(RDFFormat::NQuads, &nquads);
+		let output = write(RDFFormat::RdfXml, &dataset, &Options::default());
+		assert!(output.contains("rdf:nodeID=\"b0\""));
+
+		let loaded = load(RDFFormat::RdfXml, &output);
+		assert_eq!(
+			treeldr_layouts::canonical::canonicalize(loaded).unwrap(),
+			treeldr_layouts::canonical::canonicalize(dataset).unwrap()
+		)
+	}
+
+	#[test]
+	fn turtle_named_graph() {
+		let dataset = load(
+			RDFFormat::NQuads,
+			"<https://example.org/#a> <https://example.org/#b> <https://example.org/#c> <https://example.org/#g> .",
+		);
+
+		let mut output = Vec::new();
+		let result = RDFFormat::Turtle.write(dataset, &Options::default(), &mut output);
+		assert!(matches!(result, Err(WriteError::NamedGraph(_))))
+	}
+}
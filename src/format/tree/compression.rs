@@ -0,0 +1,787 @@
+//! Semantic compression of CBOR values, in the style of CBOR-LD.
+//!
+//! Identifiers (values of identifier layouts) found in the codebook are
+//! replaced by their integer code. Since the layout tells where identifiers
+//! are, decompression replaces integers found at those positions back with
+//! the corresponding IRI.
+//!
+//! The codebook is either explicit, or derived from the layouts. A derived
+//! codebook assigns each IRI used by the layouts a code computed from the IRI
+//! alone, so that codes stay the same when the layouts change.
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fs,
+	path::Path,
+};
+
+use iref::{Iri, IriBuf};
+use rdf_types::{Id, Term};
+use treeldr_layouts::{
+	distill::de::Selection,
+	layout::{DataLayout, LayoutType, ListLayout, LiteralLayout},
+	value::{
+		cbor::{get_layout_tag, InvalidTag},
+		Number,
+	},
+	Dataset, Layout, LayoutRegistry, Literal, Pattern, Ref, TypedLiteral, TypedValue, Value,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum CodebookError {
+	#[error(transparent)]
+	IO(#[from] std::io::Error),
+
+	#[error("invalid codebook: {0}")]
+	Json(#[from] json_syntax::parse::Error),
+
+	#[error("invalid codebook: expected an object")]
+	NotAnObject,
+
+	#[error("invalid code for `{0}`: expected a non-negative integer")]
+	InvalidCode(String),
+
+	#[error("invalid codebook IRI `{0}`")]
+	InvalidIri(String),
+
+	#[error("code {0} is assigned to both `{1}` and `{2}`")]
+	DuplicateCode(u64, IriBuf, IriBuf),
+}
+
+/// Bidirectional mapping between IRIs and integer codes.
+#[derive(Debug, Default)]
+pub struct Codebook {
+	codes: BTreeMap<IriBuf, u64>,
+	iris: BTreeMap<u64, IriBuf>,
+}
+
+impl Codebook {
+	/// Assigns the given code to the given IRI.
+	pub fn insert(&mut self, iri: IriBuf, code: u64) -> Result<(), CodebookError> {
+		if let Some(other) = self.iris.get(&code) {
+			if *other != iri {
+				return Err(CodebookError::DuplicateCode(code, other.clone(), iri));
+			}
+		}
+
+		if let Some(old_code) = self.codes.insert(iri.clone(), code) {
+			self.iris.remove(&old_code);
+		}
+
+		self.iris.insert(code, iri);
+		Ok(())
+	}
+
+	/// Loads a codebook from the given file, containing a JSON object mapping
+	/// each IRI to its code.
+	pub fn load(path: impl AsRef<Path>) -> Result<Self, CodebookError> {
+		Self::parse(&fs::read_to_string(path)?)
+	}
+
+	/// Parses a codebook, given as a JSON object mapping each IRI to its code.
+	pub fn parse(content: &str) -> Result<Self, CodebookError> {
+		use json_syntax::Parse;
+		let (json, _) = json_syntax::Value::parse_str(content)?;
+		let entries = json.as_object().ok_or(CodebookError::NotAnObject)?;
+
+		let mut result = Self::default();
+		for entry in entries {
+			let key = entry.key.as_str();
+			let code = entry
+				.value
+				.as_number()
+				.and_then(|n| n.as_u64())
+				.ok_or_else(|| CodebookError::InvalidCode(key.to_owned()))?;
+			let iri = IriBuf::new(key.to_owned()).map_err(|e| CodebookError::InvalidIri(e.0))?;
+			result.insert(iri, code)?;
+		}
+
+		Ok(result)
+	}
+
+	/// Derives a codebook from the layouts reachable from the given layouts.
+	///
+	/// Every IRI used by those layouts (properties, datatypes, constant
+	/// resources, etc.) is assigned the 32-bit FNV-1a hash of the IRI as code,
+	/// which does not depend on the other IRIs. Two IRIs with the same hash
+	/// are reported as a [`CodebookError::DuplicateCode`], in which case an
+	/// explicit codebook must be used.
+	pub fn from_layouts<'a>(
+		layouts: &'a impl LayoutRegistry,
+		layout_refs: impl IntoIterator<Item = &'a Ref<LayoutType>>,
+	) -> Result<Self, CodebookError> {
+		let mut visited = BTreeSet::new();
+		let mut stack: Vec<_> = layout_refs.into_iter().collect();
+		let mut iris = BTreeSet::new();
+
+		while let Some(layout_ref) = stack.pop() {
+			if !visited.insert(layout_ref) {
+				continue;
+			}
+
+			if let Some(layout) = layouts.get(layout_ref) {
+				collect_iris(layout, &mut iris);
+				layout.visit_dependencies(|r| stack.push(r));
+			}
+		}
+
+		let mut result = Self::default();
+		for iri in iris {
+			let code = fnv1a(iri.as_str()).into();
+			result.insert(iri, code)?
+		}
+
+		Ok(result)
+	}
+
+	pub fn code(&self, iri: &str) -> Option<u64> {
+		let iri = Iri::new(iri).ok()?;
+		self.codes.get(iri).copied()
+	}
+
+	pub fn iri(&self, code: u64) -> Option<&Iri> {
+		self.iris.get(&code).map(IriBuf::as_iri)
+	}
+
+	/// Compresses the given typed value, and its tagged CBOR representation
+	/// `cbor` (as returned by [`TypedValue::try_into_tagged_serde_cbor`]).
+	pub fn compress(
+		&self,
+		layouts: &impl LayoutRegistry,
+		value: &TypedValue,
+		cbor: serde_cbor::Value,
+	) -> Result<serde_cbor::Value, InvalidTag> {
+		let tag = match value.type_() {
+			Some(layout_ref) if layouts.get(layout_ref).is_some() => {
+				get_layout_tag(&(), &(), layouts, layout_ref)?
+			}
+			_ => None,
+		};
+
+		match cbor {
+			serde_cbor::Value::Tag(t, inner) if Some(t) == tag => Ok(serde_cbor::Value::Tag(
+				t,
+				Box::new(self.compress_untagged(layouts, value, *inner)?),
+			)),
+			cbor => self.compress_untagged(layouts, value, cbor),
+		}
+	}
+
+	fn compress_untagged(
+		&self,
+		layouts: &impl LayoutRegistry,
+		value: &TypedValue,
+		cbor: serde_cbor::Value,
+	) -> Result<serde_cbor::Value, InvalidTag> {
+		match (value, cbor) {
+			(TypedValue::Literal(TypedLiteral::Id(id, _)), cbor) => match self.code(id) {
+				Some(code) => Ok(serde_cbor::Value::Integer(code as i128)),
+				None => Ok(cbor),
+			},
+			(TypedValue::Variant(inner, _, _), cbor) => self.compress(layouts, inner, cbor),
+			(TypedValue::Map(entries, _), serde_cbor::Value::Map(mut cbor_entries)) => {
+				for (key, value) in entries {
+					let key: serde_cbor::Value = key.clone().into();
+					if let Some(cbor) = cbor_entries.remove(&key) {
+						cbor_entries.insert(key, self.compress(layouts, value, cbor)?);
+					}
+				}
+
+				Ok(serde_cbor::Value::Map(cbor_entries))
+			}
+			(TypedValue::List(items, _), serde_cbor::Value::Array(cbor_items)) => items
+				.iter()
+				.zip(cbor_items)
+				.map(|(item, cbor)| self.compress(layouts, item, cbor))
+				.collect::<Result<_, _>>()
+				.map(serde_cbor::Value::Array),
+			(_, cbor) => Ok(cbor),
+		}
+	}
+
+	/// Decompresses the given value, expected to match the given layout.
+	///
+	/// Integers found at the position of an identifier layout are replaced by
+	/// the IRI of the same code. The `selection` gives the variant of sum
+	/// layouts. When no variant is selected, every variant accepting the
+	/// value must decompress it the same way, otherwise the value is
+	/// ambiguous (for instance when an integer may be either a code or a
+	/// number).
+	pub fn decompress(
+		&self,
+		layouts: &impl LayoutRegistry,
+		layout_ref: &Ref<LayoutType>,
+		selection: &Selection,
+		value: Value,
+	) -> Result<Value, DecompressError> {
+		self.try_decompress(layouts, layout_ref, selection, value)
+			.map(Decompressed::into_value)
+	}
+
+	fn try_decompress(
+		&self,
+		layouts: &impl LayoutRegistry,
+		layout_ref: &Ref<LayoutType>,
+		selection: &Selection,
+		value: Value,
+	) -> Result<Decompressed, DecompressError> {
+		let layout = match layouts.get(layout_ref) {
+			Some(layout) => layout,
+			None => return Ok(Decompressed::Unchanged(value)),
+		};
+
+		match (layout, value) {
+			(Layout::Literal(LiteralLayout::Id(_)), Value::Literal(Literal::Number(n))) => {
+				match code_of(&n).and_then(|code| self.iri(code)) {
+					Some(iri) => Ok(Decompressed::Changed(Value::string(iri.to_string()))),
+					None => Ok(Decompressed::Unchanged(Value::Literal(Literal::Number(n)))),
+				}
+			}
+			(Layout::Sum(layout), value) => match selection {
+				Selection::Variant(i, inner) => match layout.variants.get(*i as usize) {
+					Some(variant) => {
+						self.try_decompress(layouts, &variant.value.layout, inner, value)
+					}
+					None => Ok(Decompressed::Unchanged(value)),
+				},
+				selection => {
+					let mut decompressed: Option<(&str, Decompressed)> = None;
+					for variant in &layout.variants {
+						if !self.accepts(layouts, &variant.value.layout, &value) {
+							continue;
+						}
+
+						let result = self.try_decompress(
+							layouts,
+							&variant.value.layout,
+							selection,
+							value.clone(),
+						)?;
+
+						match &decompressed {
+							Some((other, d)) => {
+								if d.value() != result.value() {
+									return Err(DecompressError::Ambiguous(
+										other.to_string(),
+										variant.name.clone(),
+									));
+								}
+							}
+							None => decompressed = Some((&variant.name, result)),
+						}
+					}
+
+					Ok(decompressed
+						.map(|(_, d)| d)
+						.unwrap_or(Decompressed::Unchanged(value)))
+				}
+			},
+			(Layout::Product(layout), Value::Map(entries)) => {
+				let mut changed = false;
+				let entries = entries
+					.into_iter()
+					.map(|(key, value)| {
+						let value = match layout.fields.get(&key) {
+							Some(field) => self
+								.try_decompress(
+									layouts,
+									&field.value.layout,
+									selection.entry(&key),
+									value,
+								)?
+								.into_value_with(&mut changed),
+							None => value,
+						};
+
+						Ok((key, value))
+					})
+					.collect::<Result<_, _>>()?;
+
+				Ok(Decompressed::new(Value::Map(entries), changed))
+			}
+			(Layout::List(layout), Value::List(items)) => {
+				let mut changed = false;
+				let items = items
+					.into_iter()
+					.enumerate()
+					.map(|(i, item)| match list_item_layout(layout, i) {
+						Some(item_layout) => Ok(self
+							.try_decompress(layouts, item_layout, selection.item(i), item)?
+							.into_value_with(&mut changed)),
+						None => Ok(item),
+					})
+					.collect::<Result<_, _>>()?;
+
+				Ok(Decompressed::new(Value::List(items), changed))
+			}
+			(_, value) => Ok(Decompressed::Unchanged(value)),
+		}
+	}
+
+	/// Checks if the given (possibly compressed) value has the shape of the
+	/// given layout.
+	///
+	/// Identifier layouts accept both text strings and integer codes of this
+	/// codebook.
+	fn accepts(
+		&self,
+		layouts: &impl LayoutRegistry,
+		layout_ref: &Ref<LayoutType>,
+		value: &Value,
+	) -> bool {
+		let layout = match layouts.get(layout_ref) {
+			Some(layout) => layout,
+			None => return false,
+		};
+
+		match (layout, value) {
+			(Layout::Never, _) => false,
+			(Layout::Always, _) => true,
+			(Layout::Literal(LiteralLayout::Id(_)), Value::Literal(Literal::TextString(_))) => true,
+			(Layout::Literal(LiteralLayout::Id(_)), Value::Literal(Literal::Number(n))) => {
+				code_of(n).and_then(|code| self.iri(code)).is_some()
+			}
+			(Layout::Literal(LiteralLayout::Data(layout)), Value::Literal(literal)) => matches!(
+				(layout, literal),
+				(DataLayout::Unit(_), Literal::Unit)
+					| (DataLayout::Boolean(_), Literal::Boolean(_))
+					| (DataLayout::Number(_), Literal::Number(_))
+					| (DataLayout::ByteString(_), Literal::ByteString(_))
+					| (DataLayout::TextString(_), Literal::TextString(_))
+			),
+			(Layout::Sum(layout), value) => layout
+				.variants
+				.iter()
+				.any(|variant| self.accepts(layouts, &variant.value.layout, value)),
+			(Layout::Product(layout), Value::Map(entries)) => {
+				entries
+					.iter()
+					.all(|(key, value)| match layout.fields.get(key) {
+						Some(field) => self.accepts(layouts, &field.value.layout, value),
+						None => false,
+					})
+			}
+			(Layout::List(layout), Value::List(items)) => {
+				let sized = match layout {
+					ListLayout::Sized(layout) => layout.items.len() == items.len(),
+					_ => true,
+				};
+
+				sized
+					&& items
+						.iter()
+						.enumerate()
+						.all(|(i, item)| match list_item_layout(layout, i) {
+							Some(item_layout) => self.accepts(layouts, item_layout, item),
+							None => false,
+						})
+			}
+			_ => false,
+		}
+	}
+}
+
+/// Decompression error.
+#[derive(Debug, thiserror::Error)]
+pub enum DecompressError {
+	#[error("ambiguous compressed value: it may be decompressed as variant `{0}` or `{1}`")]
+	Ambiguous(String, String),
+}
+
+/// Result of the decompression of a value.
+enum Decompressed {
+	/// At least one integer code was replaced.
+	Changed(Value),
+
+	/// The value is unchanged.
+	Unchanged(Value),
+}
+
+impl Decompressed {
+	fn new(value: Value, changed: bool) -> Self {
+		if changed {
+			Self::Changed(value)
+		} else {
+			Self::Unchanged(value)
+		}
+	}
+
+	fn value(&self) -> &Value {
+		match self {
+			Self::Changed(value) | Self::Unchanged(value) => value,
+		}
+	}
+
+	fn into_value(self) -> Value {
+		match self {
+			Self::Changed(value) | Self::Unchanged(value) => value,
+		}
+	}
+
+	/// Returns the value, setting `changed` if it was changed.
+	fn into_value_with(self, changed: &mut bool) -> Value {
+		if let Self::Changed(_) = &self {
+			*changed = true
+		}
+
+		self.into_value()
+	}
+}
+
+fn list_item_layout(layout: &ListLayout<Term>, i: usize) -> Option<&Ref<LayoutType>> {
+	match layout {
+		ListLayout::Unordered(layout) => Some(&layout.item.value.layout),
+		ListLayout::Ordered(layout) => Some(&layout.node.value.layout),
+		ListLayout::Sized(layout) => layout.items.get(i).map(|item| &item.value.layout),
+	}
+}
+
+fn code_of(n: &Number) -> Option<u64> {
+	n.as_integer().and_then(|i| u64::try_from(i).ok())
+}
+
+/// Computes the 32-bit FNV-1a hash of the given string.
+fn fnv1a(s: &str) -> u32 {
+	s.bytes().fold(0x811c9dc5, |hash, b| {
+		(hash ^ u32::from(b)).wrapping_mul(0x01000193)
+	})
+}
+
+/// Collects the IRIs used by the given layout definition.
+fn collect_iris<'a>(layout: &'a Layout, iris: &mut BTreeSet<IriBuf>) {
+	let mut datasets: Vec<&'a Dataset> = Vec::new();
+	let mut resources: Vec<&'a Term> = Vec::new();
+
+	match layout {
+		Layout::Never | Layout::Always => (),
+		Layout::Literal(LiteralLayout::Id(layout)) => datasets.push(&layout.dataset),
+		Layout::Literal(LiteralLayout::Data(layout)) => match layout {
+			DataLayout::Unit(layout) => datasets.push(&layout.dataset),
+			DataLayout::Boolean(layout) => {
+				datasets.push(&layout.dataset);
+				resources.push(&layout.datatype)
+			}
+			DataLayout::Number(layout) => {
+				datasets.push(&layout.dataset);
+				resources.push(&layout.datatype)
+			}
+			DataLayout::ByteString(layout) => {
+				datasets.push(&layout.dataset);
+				resources.push(&layout.datatype)
+			}
+			DataLayout::TextString(layout) => {
+				datasets.push(&layout.dataset);
+				resources.push(&layout.datatype)
+			}
+		},
+		Layout::Product(layout) => {
+			datasets.push(&layout.dataset);
+			datasets.extend(layout.fields.values().map(|f| &f.dataset))
+		}
+		Layout::Sum(layout) => {
+			datasets.push(&layout.dataset);
+			datasets.extend(layout.variants.iter().map(|v| &v.dataset))
+		}
+		Layout::List(ListLayout::Unordered(layout)) => {
+			datasets.push(&layout.dataset);
+			datasets.push(&layout.item.dataset)
+		}
+		Layout::List(ListLayout::Ordered(layout)) => {
+			datasets.push(&layout.dataset);
+			datasets.push(&layout.node.dataset)
+		}
+		Layout::List(ListLayout::Sized(layout)) => {
+			datasets.push(&layout.dataset);
+			datasets.extend(layout.items.iter().map(|i| &i.dataset))
+		}
+	}
+
+	for dataset in datasets {
+		for quad in dataset {
+			for pattern in [quad.0, quad.1, quad.2].into_iter().chain(quad.3) {
+				if let Pattern::Resource(term) = pattern {
+					resources.push(term)
+				}
+			}
+		}
+	}
+
+	for term in resources {
+		if let Term::Id(Id::Iri(iri)) = term {
+			iris.insert(iri.clone());
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::format::tree::tests::{build_layout, typed};
+	use treeldr_layouts::Prelude;
+
+	const PERSON: &str = r#"{
+		"type": "record",
+		"prefixes": {
+			"tldr": "https://treeldr.org/prelude#",
+			"cbor": "https://schema.treeldr.org/cbor#"
+		},
+		"fields": {
+			"id": {
+				"intro": [],
+				"value": { "layout": "tldr:id", "input": ["_:self"] }
+			},
+			"name": { "value": "tldr:string", "property": "https://schema.org/name" },
+			"knows": { "value": "tldr:id", "property": "https://schema.org/knows" },
+			"contact": {
+				"value": {
+					"type": "sum",
+					"variants": {
+						"ref": { "value": { "type": "id", "extra": { "cbor:tag": 40 } } },
+						"number": {
+							"value": {
+								"type": "number",
+								"datatype": "http://www.w3.org/2001/XMLSchema#integer",
+								"extra": { "cbor:tag": 41 }
+							}
+						}
+					}
+				},
+				"property": "https://schema.org/contactPoint"
+			},
+			"homepage": {
+				"value": {
+					"type": "sum",
+					"variants": {
+						"ref": { "value": "tldr:id" },
+						"text": { "value": "tldr:string" }
+					}
+				},
+				"property": "https://schema.org/url"
+			}
+		}
+	}"#;
+
+	const CODEBOOK: &str = r#"{
+		"https://example.org/#alice": 0,
+		"https://example.org/#bob": 1
+	}"#;
+
+	fn json(input: &str) -> Value {
+		crate::format::tree::TreeFormat::Json
+			.load(input.as_bytes())
+			.unwrap()
+	}
+
+	/// Compresses the given value, returning the compressed CBOR value.
+	fn compress(
+		layouts: &impl LayoutRegistry,
+		layout_ref: &Ref<LayoutType>,
+		codebook: &Codebook,
+		value: &Value,
+	) -> serde_cbor::Value {
+		let value = typed(layouts, layout_ref, value);
+		let cbor = value.clone().try_into_tagged_serde_cbor(layouts).unwrap();
+		codebook.compress(layouts, &value, cbor).unwrap()
+	}
+
+	/// Decompresses the given CBOR value, using its tags to select sum
+	/// layouts variants.
+	fn decompress(
+		layouts: &impl LayoutRegistry,
+		layout_ref: &Ref<LayoutType>,
+		codebook: &Codebook,
+		cbor: serde_cbor::Value,
+	) -> Result<Value, DecompressError> {
+		let (value, selection) =
+			Value::try_from_tagged_serde_cbor(cbor, layouts, layout_ref).unwrap();
+		codebook.decompress(layouts, layout_ref, &selection, value)
+	}
+
+	fn entry<'a>(cbor: &'a serde_cbor::Value, key: &str) -> &'a serde_cbor::Value {
+		match cbor {
+			serde_cbor::Value::Map(entries) => &entries[&serde_cbor::Value::Text(key.to_owned())],
+			_ => panic!("expected a map"),
+		}
+	}
+
+	#[test]
+	fn round_trip() {
+		let (layouts, layout_ref) = build_layout(PERSON);
+		let layouts = layouts.with(Prelude);
+		let codebook = Codebook::parse(CODEBOOK).unwrap();
+
+		let value = json(
+			r#"{
+				"id": "https://example.org/#alice",
+				"name": "Alice",
+				"knows": "https://example.org/#bob",
+				"contact": "https://example.org/#bob"
+			}"#,
+		);
+
+		let cbor = compress(&layouts, &layout_ref, &codebook, &value);
+		assert_eq!(*entry(&cbor, "id"), serde_cbor::Value::Integer(0));
+		assert_eq!(*entry(&cbor, "knows"), serde_cbor::Value::Integer(1));
+		assert_eq!(
+			*entry(&cbor, "contact"),
+			serde_cbor::Value::Tag(40, Box::new(serde_cbor::Value::Integer(1)))
+		);
+		assert_eq!(
+			*entry(&cbor, "name"),
+			serde_cbor::Value::Text("Alice".to_owned())
+		);
+
+		assert_eq!(
+			decompress(&layouts, &layout_ref, &codebook, cbor).unwrap(),
+			value
+		)
+	}
+
+	#[test]
+	fn unknown_iris_are_kept() {
+		let (layouts, layout_ref) = build_layout(PERSON);
+		let layouts = layouts.with(Prelude);
+		let codebook = Codebook::parse(CODEBOOK).unwrap();
+
+		let value = json(
+			r#"{
+				"id": "https://example.org/#carol",
+				"name": "https://example.org/#alice",
+				"knows": "https://example.org/#alice"
+			}"#,
+		);
+
+		let cbor = compress(&layouts, &layout_ref, &codebook, &value);
+		assert_eq!(
+			*entry(&cbor, "id"),
+			serde_cbor::Value::Text("https://example.org/#carol".to_owned())
+		);
+		// Only identifiers are compressed.
+		assert_eq!(
+			*entry(&cbor, "name"),
+			serde_cbor::Value::Text("https://example.org/#alice".to_owned())
+		);
+		assert_eq!(*entry(&cbor, "knows"), serde_cbor::Value::Integer(0));
+
+		assert_eq!(
+			decompress(&layouts, &layout_ref, &codebook, cbor).unwrap(),
+			value
+		)
+	}
+
+	#[test]
+	fn sum_ambiguity() {
+		let (layouts, layout_ref) = build_layout(PERSON);
+		let layouts = layouts.with(Prelude);
+		let codebook = Codebook::parse(CODEBOOK).unwrap();
+
+		// The code may also be a number.
+		let compressed = json(r#"{ "id": 0, "contact": 1 }"#);
+		assert!(matches!(
+			codebook.decompress(&layouts, &layout_ref, &Selection::Any, compressed),
+			Err(DecompressError::Ambiguous(_, _))
+		));
+
+		// Only one variant accepts an integer.
+		let compressed = json(r#"{ "id": 0, "homepage": 1 }"#);
+		assert_eq!(
+			codebook
+				.decompress(&layouts, &layout_ref, &Selection::Any, compressed)
+				.unwrap(),
+			json(
+				r#"{ "id": "https://example.org/#alice", "homepage": "https://example.org/#bob" }"#
+			)
+		);
+
+		// Integers that are not codes are not identifiers.
+		let compressed = json(r#"{ "id": 0, "contact": 2 }"#);
+		assert_eq!(
+			codebook
+				.decompress(&layouts, &layout_ref, &Selection::Any, compressed)
+				.unwrap(),
+			json(r#"{ "id": "https://example.org/#alice", "contact": 2 }"#)
+		);
+	}
+
+	#[test]
+	fn load_codebook() {
+		let codebook = Codebook::parse(CODEBOOK).unwrap();
+		assert_eq!(codebook.code("https://example.org/#bob"), Some(1));
+		assert_eq!(
+			codebook.iri(0).map(Iri::as_str),
+			Some("https://example.org/#alice")
+		);
+		assert_eq!(codebook.code("https://example.org/#carol"), None);
+
+		assert!(matches!(
+			Codebook::parse("[]"),
+			Err(CodebookError::NotAnObject)
+		));
+		assert!(matches!(
+			Codebook::parse(r#"{ "https://example.org/#alice": -1 }"#),
+			Err(CodebookError::InvalidCode(_))
+		));
+		assert!(matches!(
+			Codebook::parse(r#"{ "alice": 0 }"#),
+			Err(CodebookError::InvalidIri(_))
+		));
+		assert!(matches!(
+			Codebook::parse(
+				r#"{ "https://example.org/#alice": 0, "https://example.org/#bob": 0 }"#
+			),
+			Err(CodebookError::DuplicateCode(0, _, _))
+		));
+		assert!(matches!(Codebook::parse("{"), Err(CodebookError::Json(_))));
+	}
+
+	#[test]
+	fn derived_codebook() {
+		assert_eq!(fnv1a(""), 0x811c9dc5);
+		assert_eq!(fnv1a("a"), 0xe40c292c);
+
+		let (layouts, layout_ref) = build_layout(PERSON);
+		let layouts = layouts.with(Prelude);
+		let codebook = Codebook::from_layouts(&layouts, [&layout_ref]).unwrap();
+
+		// Properties and datatypes of reachable layouts are included.
+		let name = u64::from(fnv1a("https://schema.org/name"));
+		assert_eq!(codebook.code("https://schema.org/name"), Some(name));
+		assert!(codebook
+			.code("http://www.w3.org/2001/XMLSchema#integer")
+			.is_some());
+		assert_eq!(codebook.code("https://example.org/#alice"), None);
+
+		// Codes do not depend on the other IRIs of the layouts.
+		let (other_layouts, other_ref) = build_layout(
+			r#"{
+				"type": "record",
+				"fields": {
+					"name": {
+						"value": "https://treeldr.org/prelude#string",
+						"property": "https://schema.org/name"
+					}
+				}
+			}"#,
+		);
+		let other_layouts = other_layouts.with(Prelude);
+		let other = Codebook::from_layouts(&other_layouts, [&other_ref]).unwrap();
+		assert_eq!(other.code("https://schema.org/name"), Some(name));
+		assert_eq!(other.code("https://schema.org/knows"), None);
+
+		let value = json(
+			r#"{
+				"id": "https://example.org/#alice",
+				"knows": "https://schema.org/name"
+			}"#,
+		);
+
+		let cbor = compress(&layouts, &layout_ref, &codebook, &value);
+		assert_eq!(
+			*entry(&cbor, "knows"),
+			serde_cbor::Value::Integer(name.into())
+		);
+		assert_eq!(
+			decompress(&layouts, &layout_ref, &codebook, cbor).unwrap(),
+			value
+		)
+	}
+}
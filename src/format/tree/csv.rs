@@ -0,0 +1,508 @@
+//! CSV and TSV support.
+//!
+//! Each row is a record value whose fields are given by the header. Only flat
+//! record layouts, where every field is a literal, can be read or written.
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	io::{BufRead, Write},
+	str::FromStr,
+};
+
+use treeldr_layouts::{
+	layout::{DataLayout, LayoutType, ListLayout, LiteralLayout},
+	Layout, LayoutRegistry, Literal, PresetLayout, Ref, Value,
+};
+
+/// Error raised when a layout or value is not flat.
+#[derive(Debug, thiserror::Error)]
+pub enum NotFlat {
+	#[error("expected a record or list of records layout")]
+	Layout,
+
+	#[error("field `{0}` is not a literal")]
+	Field(String),
+
+	#[error("non-string field key")]
+	NonStringKey,
+
+	#[error("layout `{0}` not found")]
+	LayoutNotFound(String),
+
+	#[error("expected a record or list of records")]
+	Value,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LoadError {
+	#[error(transparent)]
+	Csv(::csv::Error),
+
+	#[error(transparent)]
+	NotFlat(NotFlat),
+
+	#[error("row {row}, column `{column}`: invalid {expected} `{value}`")]
+	InvalidCell {
+		row: usize,
+		column: String,
+		value: String,
+		expected: CellType,
+	},
+
+	#[error("row {row}: unknown column `{column}`")]
+	UnknownColumn { row: usize, column: String },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WriteError {
+	#[error(transparent)]
+	Csv(#[from] ::csv::Error),
+
+	#[error(transparent)]
+	NotFlat(NotFlat),
+
+	#[error("column `{0}`: byte strings cannot be written")]
+	ByteString(String),
+
+	#[error("column `{0}`: not a decimal number: {1}")]
+	Number(String, treeldr_layouts::value::Number),
+}
+
+/// Type of a cell, determined by the layout of its column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellType {
+	Unit,
+	Boolean,
+	Number,
+	Text,
+}
+
+impl std::fmt::Display for CellType {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Unit => f.write_str("unit"),
+			Self::Boolean => f.write_str("boolean"),
+			Self::Number => f.write_str("number"),
+			Self::Text => f.write_str("text"),
+		}
+	}
+}
+
+/// Shape of a flat layout.
+struct Shape {
+	/// Whether the layout is a list of records, or a single record.
+	list: bool,
+
+	/// Columns.
+	columns: BTreeMap<String, CellType>,
+}
+
+impl Shape {
+	fn new(layouts: &impl LayoutRegistry, layout_ref: &Ref<LayoutType>) -> Result<Self, NotFlat> {
+		match get(layouts, layout_ref)? {
+			Layout::Product(_) => Ok(Self {
+				list: false,
+				columns: columns(layouts, layout_ref)?,
+			}),
+			Layout::List(list) => {
+				let item = match list {
+					ListLayout::Unordered(l) => &l.item.value.layout,
+					ListLayout::Ordered(l) => &l.node.value.layout,
+					ListLayout::Sized(_) => return Err(NotFlat::Layout),
+				};
+
+				Ok(Self {
+					list: true,
+					columns: columns(layouts, item)?,
+				})
+			}
+			_ => Err(NotFlat::Layout),
+		}
+	}
+}
+
+fn get<'a>(
+	layouts: &'a impl LayoutRegistry,
+	layout_ref: &Ref<LayoutType>,
+) -> Result<&'a Layout, NotFlat> {
+	layouts
+		.get(layout_ref)
+		.ok_or_else(|| NotFlat::LayoutNotFound(layout_ref.id().to_string()))
+}
+
+/// Returns the columns of the given flat record layout.
+fn columns(
+	layouts: &impl LayoutRegistry,
+	layout_ref: &Ref<LayoutType>,
+) -> Result<BTreeMap<String, CellType>, NotFlat> {
+	match get(layouts, layout_ref)? {
+		Layout::Product(product) => {
+			let mut columns = BTreeMap::new();
+			for (key, field) in &product.fields {
+				let key = key.as_str().ok_or(NotFlat::NonStringKey)?;
+				let ty = cell_type(layouts, &field.value.layout)?
+					.ok_or_else(|| NotFlat::Field(key.to_owned()))?;
+				columns.insert(key.to_owned(), ty);
+			}
+
+			Ok(columns)
+		}
+		_ => Err(NotFlat::Layout),
+	}
+}
+
+fn cell_type(
+	layouts: &impl LayoutRegistry,
+	layout_ref: &Ref<LayoutType>,
+) -> Result<Option<CellType>, NotFlat> {
+	if let Some(preset) = layout_ref
+		.id()
+		.as_iri()
+		.and_then(|iri| PresetLayout::from_iri(iri))
+	{
+		return Ok(Some(match preset {
+			PresetLayout::Unit => CellType::Unit,
+			PresetLayout::Boolean => CellType::Boolean,
+			PresetLayout::Id | PresetLayout::String => CellType::Text,
+			_ => CellType::Number,
+		}));
+	}
+
+	match get(layouts, layout_ref)? {
+		Layout::Literal(LiteralLayout::Data(layout)) => match layout {
+			DataLayout::Unit(_) => Ok(Some(CellType::Unit)),
+			DataLayout::Boolean(_) => Ok(Some(CellType::Boolean)),
+			DataLayout::Number(_) => Ok(Some(CellType::Number)),
+			DataLayout::ByteString(_) => Ok(None),
+			DataLayout::TextString(_) => Ok(Some(CellType::Text)),
+		},
+		Layout::Literal(LiteralLayout::Id(_)) => Ok(Some(CellType::Text)),
+		_ => Ok(None),
+	}
+}
+
+/// Reads the rows of the input, typed according to the given layout.
+///
+/// If the layout is a list of records, a single list value is returned.
+/// Otherwise each row is returned as a separate record value. Empty cells
+/// are treated as absent fields, including text fields, matching how
+/// [`write`] writes absent fields.
+pub fn load_typed(
+	layouts: &impl LayoutRegistry,
+	layout_ref: &Ref<LayoutType>,
+	delimiter: u8,
+	input: impl BufRead,
+) -> Result<Vec<Value>, LoadError> {
+	let shape = Shape::new(layouts, layout_ref).map_err(LoadError::NotFlat)?;
+
+	let mut reader = ::csv::ReaderBuilder::new()
+		.delimiter(delimiter)
+		.from_reader(input);
+	let headers = reader.headers().map_err(LoadError::Csv)?.clone();
+
+	let mut rows = Vec::new();
+	for (i, record) in reader.records().enumerate() {
+		let row = i + 1;
+		let record = record.map_err(LoadError::Csv)?;
+		let mut map = BTreeMap::new();
+		for (column, cell) in headers.iter().zip(&record) {
+			let ty = *shape
+				.columns
+				.get(column)
+				.ok_or_else(|| LoadError::UnknownColumn {
+					row,
+					column: column.to_owned(),
+				})?;
+
+			if cell.is_empty() {
+				continue;
+			}
+
+			let value = parse_cell(ty, cell).ok_or_else(|| LoadError::InvalidCell {
+				row,
+				column: column.to_owned(),
+				value: cell.to_owned(),
+				expected: ty,
+			})?;
+
+			map.insert(Value::string(column.to_owned()), value);
+		}
+
+		rows.push(Value::Map(map));
+	}
+
+	if shape.list {
+		Ok(vec![Value::List(rows)])
+	} else {
+		Ok(rows)
+	}
+}
+
+fn parse_cell(ty: CellType, cell: &str) -> Option<Value> {
+	let literal = match ty {
+		CellType::Unit => return None,
+		CellType::Boolean => match cell {
+			"true" | "1" => Literal::Boolean(true),
+			"false" | "0" => Literal::Boolean(false),
+			_ => return None,
+		},
+		CellType::Number => {
+			Literal::Number(json_syntax::NumberBuf::from_str(cell.trim()).ok()?.into())
+		}
+		CellType::Text => Literal::TextString(cell.to_owned()),
+	};
+
+	Some(Value::Literal(literal))
+}
+
+/// Returns the columns of the given record, or list of records, layout.
+pub fn layout_columns(
+	layouts: &impl LayoutRegistry,
+	layout_ref: &Ref<LayoutType>,
+) -> Result<Vec<String>, NotFlat> {
+	Shape::new(layouts, layout_ref).map(|shape| shape.columns.into_keys().collect())
+}
+
+/// Writes the given record, or list of records, as rows.
+///
+/// The given `columns` are always written, even if no record has a value for
+/// them. Other columns are added for each field found in the records.
+pub fn write(
+	value: Value,
+	columns: Vec<String>,
+	delimiter: u8,
+	output: impl Write,
+) -> Result<(), WriteError> {
+	let rows = match value {
+		Value::Map(map) => vec![map],
+		Value::List(items) => items
+			.into_iter()
+			.map(|item| match item {
+				Value::Map(map) => Ok(map),
+				_ => Err(WriteError::NotFlat(NotFlat::Value)),
+			})
+			.collect::<Result<_, _>>()?,
+		_ => return Err(WriteError::NotFlat(NotFlat::Value)),
+	};
+
+	let mut columns: BTreeSet<String> = columns.into_iter().collect();
+
+	for row in &rows {
+		for key in row.keys() {
+			let key = key
+				.as_str()
+				.ok_or(WriteError::NotFlat(NotFlat::NonStringKey))?;
+			columns.insert(key.to_owned());
+		}
+	}
+
+	let mut writer = ::csv::WriterBuilder::new()
+		.delimiter(delimiter)
+		.from_writer(output);
+	writer.write_record(&columns)?;
+
+	for row in rows {
+		let mut record = Vec::with_capacity(columns.len());
+		for column in &columns {
+			let cell = match row.get(&Value::string(column.clone())) {
+				None | Some(Value::Literal(Literal::Unit)) => String::new(),
+				Some(Value::Literal(Literal::Boolean(b))) => b.to_string(),
+				Some(Value::Literal(Literal::Number(n))) => n
+					.decimal_representation()
+					.ok_or_else(|| WriteError::Number(column.clone(), n.clone()))?,
+				Some(Value::Literal(Literal::TextString(s))) => s.clone(),
+				Some(Value::Literal(Literal::ByteString(_))) => {
+					return Err(WriteError::ByteString(column.clone()))
+				}
+				Some(_) => return Err(WriteError::NotFlat(NotFlat::Field(column.clone()))),
+			};
+
+			record.push(cell);
+		}
+
+		writer.write_record(&record)?;
+	}
+
+	writer.flush().map_err(|e| WriteError::Csv(e.into()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::format::tree::{tests::build_layout, Style, TreeFormat};
+
+	const PERSON: &str = r#"{
+		"type": "record",
+		"fields": {
+			"name": { "value": "https://treeldr.org/prelude#string", "property": "https://schema.org/name" },
+			"age": { "value": "https://treeldr.org/prelude#u32", "property": "https://schema.org/age" },
+			"score": {
+				"value": { "type": "number", "datatype": "http://www.w3.org/2001/XMLSchema#decimal" },
+				"property": "https://example.org/#score"
+			},
+			"active": { "value": "https://treeldr.org/prelude#boolean", "property": "https://example.org/#active" }
+		}
+	}"#;
+
+	fn load(layout: &str, delimiter: u8, input: &str) -> Result<Vec<Value>, LoadError> {
+		let (layouts, layout_ref) = build_layout(layout);
+		load_typed(&layouts, &layout_ref, delimiter, input.as_bytes())
+	}
+
+	fn record(fields: &[(&str, Literal)]) -> Value {
+		Value::Map(
+			fields
+				.iter()
+				.map(|(key, value)| {
+					(
+						Value::string((*key).to_owned()),
+						Value::Literal(value.clone()),
+					)
+				})
+				.collect(),
+		)
+	}
+
+	fn number(n: &str) -> Literal {
+		Literal::Number(json_syntax::NumberBuf::from_str(n).unwrap().into())
+	}
+
+	#[test]
+	fn typed_cells() {
+		let rows = load(
+			PERSON,
+			b',',
+			"name,age,score,active\nBob,42,-1.5,true\n007,,0,0\n",
+		)
+		.unwrap();
+
+		assert_eq!(
+			rows,
+			[
+				record(&[
+					("name", Literal::TextString("Bob".to_owned())),
+					("age", number("42")),
+					("score", number("-1.5")),
+					("active", Literal::Boolean(true)),
+				]),
+				record(&[
+					("name", Literal::TextString("007".to_owned())),
+					("score", number("0")),
+					("active", Literal::Boolean(false)),
+				]),
+			]
+		)
+	}
+
+	#[test]
+	fn list_of_records() {
+		let layout = format!(r#"{{ "type": "list", "node": {{ "value": {PERSON} }} }}"#);
+		let rows = load(&layout, b',', "name\nBob\nAlice\n").unwrap();
+		assert_eq!(
+			rows,
+			[Value::List(vec![
+				record(&[("name", Literal::TextString("Bob".to_owned()))]),
+				record(&[("name", Literal::TextString("Alice".to_owned()))]),
+			])]
+		)
+	}
+
+	#[test]
+	fn invalid_number() {
+		let error = load(PERSON, b',', "name,age\nBob,42\nAlice,old\n").unwrap_err();
+		assert_eq!(
+			error.to_string(),
+			"row 2, column `age`: invalid number `old`"
+		)
+	}
+
+	#[test]
+	fn unknown_column() {
+		let error = load(PERSON, b',', "name,email\nBob,bob@example.org\n").unwrap_err();
+		assert_eq!(error.to_string(), "row 1: unknown column `email`")
+	}
+
+	#[test]
+	fn non_flat_layout() {
+		let layout = r#"{
+			"type": "record",
+			"fields": {
+				"name": { "value": "https://treeldr.org/prelude#string", "property": "https://schema.org/name" },
+				"address": {
+					"value": {
+						"type": "record",
+						"fields": {
+							"city": { "value": "https://treeldr.org/prelude#string", "property": "https://schema.org/addressLocality" }
+						}
+					},
+					"property": "https://schema.org/address"
+				}
+			}
+		}"#;
+
+		let error = load(layout, b',', "name\nBob\n").unwrap_err();
+		assert_eq!(error.to_string(), "field `address` is not a literal");
+
+		let (layouts, layout_ref) = build_layout(layout);
+		assert!(matches!(
+			layout_columns(&layouts, &layout_ref),
+			Err(NotFlat::Field(field)) if field == "address"
+		))
+	}
+
+	#[test]
+	fn tsv() {
+		let (layouts, layout_ref) = build_layout(PERSON);
+		let rows = vec![
+			record(&[
+				("name", Literal::TextString("Bob, Jr.".to_owned())),
+				("age", number("42")),
+			]),
+			record(&[("name", Literal::TextString("Alice".to_owned()))]),
+		];
+
+		let mut output = Vec::new();
+		TreeFormat::Tsv
+			.write_untyped_all(rows.clone(), Style::Compact, &mut output)
+			.unwrap();
+		assert_eq!(
+			String::from_utf8(output.clone()).unwrap(),
+			"age\tname\n42\tBob, Jr.\n\tAlice\n"
+		);
+
+		let loaded = TreeFormat::Tsv
+			.load_typed_all(&layouts, &layout_ref, output.as_slice())
+			.unwrap();
+		assert_eq!(
+			loaded
+				.into_iter()
+				.map(|(value, _)| value)
+				.collect::<Vec<_>>(),
+			rows
+		)
+	}
+	#[test]
+	fn absent_text_field() {
+		let (layouts, layout_ref) = build_layout(PERSON);
+		let rows = vec![record(&[("age", number("42"))])];
+
+		let mut output = Vec::new();
+		TreeFormat::Csv
+			.write_untyped_all(rows.clone(), Style::Compact, &mut output)
+			.unwrap();
+		assert_eq!(String::from_utf8(output.clone()).unwrap(), "age\n42\n");
+
+		let loaded = load(PERSON, b',', "age,name\n42,\n").unwrap();
+		assert_eq!(loaded, rows);
+
+		let loaded = TreeFormat::Csv
+			.load_typed_all(&layouts, &layout_ref, output.as_slice())
+			.unwrap();
+		assert_eq!(
+			loaded
+				.into_iter()
+				.map(|(value, _)| value)
+				.collect::<Vec<_>>(),
+			rows
+		)
+	}
+}
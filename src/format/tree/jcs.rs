@@ -0,0 +1,168 @@
+//! JSON Canonicalization Scheme ([RFC 8785]).
+//!
+//! [RFC 8785]: <https://www.rfc-editor.org/rfc/rfc8785>
+use std::io::Write;
+
+use treeldr_layouts::{
+	value::{NonJsonValue, Number},
+	Literal, Value,
+};
+
+use super::WriteError;
+
+/// Writes the canonical JSON serialization of the given value.
+///
+/// Numbers are serialized as IEEE 754 double precision values, and must be
+/// exactly represented by their serialization: numbers with no finite decimal
+/// representation, too large numbers, or numbers with more significant digits
+/// than a double can hold, are rejected.
+pub fn write(value: &Value, output: &mut impl Write) -> Result<(), WriteError> {
+	let mut buffer = String::new();
+	write_value(value, &mut buffer)?;
+	output.write_all(buffer.as_bytes()).map_err(WriteError::IO)
+}
+
+fn write_value(value: &Value, output: &mut String) -> Result<(), WriteError> {
+	match value {
+		Value::Literal(Literal::Unit) => output.push_str("null"),
+		Value::Literal(Literal::Boolean(true)) => output.push_str("true"),
+		Value::Literal(Literal::Boolean(false)) => output.push_str("false"),
+		Value::Literal(Literal::Number(n)) => output.push_str(&number(n)?),
+		Value::Literal(Literal::TextString(s)) => write_string(s, output),
+		Value::Literal(Literal::ByteString(s)) => {
+			return Err(WriteError::NonJsonValue(NonJsonValue::ByteString(
+				s.clone(),
+			)))
+		}
+		Value::List(items) => {
+			output.push('[');
+			for (i, item) in items.iter().enumerate() {
+				if i > 0 {
+					output.push(',')
+				}
+
+				write_value(item, output)?
+			}
+			output.push(']')
+		}
+		Value::Map(entries) => {
+			let mut sorted = Vec::with_capacity(entries.len());
+			for (key, value) in entries {
+				match key {
+					Value::Literal(Literal::TextString(key)) => {
+						sorted.push((key.encode_utf16().collect::<Vec<_>>(), key, value))
+					}
+					other => {
+						return Err(WriteError::NonJsonValue(NonJsonValue::NonStringKey(
+							other.clone(),
+						)))
+					}
+				}
+			}
+
+			// Properties are sorted by their UTF-16 code units.
+			sorted.sort_by(|a, b| a.0.cmp(&b.0));
+
+			output.push('{');
+			for (i, (_, key, value)) in sorted.into_iter().enumerate() {
+				if i > 0 {
+					output.push(',')
+				}
+
+				write_string(key, output);
+				output.push(':');
+				write_value(value, output)?
+			}
+			output.push('}')
+		}
+	}
+
+	Ok(())
+}
+
+fn write_string(s: &str, output: &mut String) {
+	output.push('"');
+	for c in s.chars() {
+		match c {
+			'"' => output.push_str("\\\""),
+			'\\' => output.push_str("\\\\"),
+			'\u{8}' => output.push_str("\\b"),
+			'\t' => output.push_str("\\t"),
+			'\n' => output.push_str("\\n"),
+			'\u{c}' => output.push_str("\\f"),
+			'\r' => output.push_str("\\r"),
+			'\u{0}'..='\u{1f}' => output.push_str(&format!("\\u{:04x}", c as u32)),
+			c => output.push(c),
+		}
+	}
+	output.push('"')
+}
+
+/// Returns the canonical serialization of the given number, following the
+/// ECMAScript `Number.prototype.toString` algorithm.
+fn number(n: &Number) -> Result<String, WriteError> {
+	let inexact = || WriteError::InexactNumber(n.clone());
+	let f: f64 = n
+		.decimal_representation()
+		.ok_or_else(inexact)?
+		.parse()
+		.unwrap();
+
+	if !f.is_finite() {
+		return Err(inexact());
+	}
+
+	let result = format_f64(f);
+	let exact: Number = result.parse::<json_syntax::NumberBuf>().unwrap().into();
+
+	if exact == *n {
+		Ok(result)
+	} else {
+		Err(inexact())
+	}
+}
+
+fn format_f64(f: f64) -> String {
+	if f == 0.0 {
+		return "0".to_owned();
+	}
+
+	// Shortest digits `d` and exponent `n` such that `f = 0.d × 10^n`.
+	let exp = format!("{:e}", f.abs());
+	let (mantissa, e) = exp.split_once('e').unwrap();
+	let digits = mantissa.replace('.', "");
+	let k = digits.len() as i32;
+	let n = e.parse::<i32>().unwrap() + 1;
+
+	let mut result = if f < 0.0 {
+		"-".to_owned()
+	} else {
+		String::new()
+	};
+
+	if k <= n && n <= 21 {
+		result.push_str(&digits);
+		result.push_str(&"0".repeat((n - k) as usize))
+	} else if 0 < n && n <= 21 {
+		let (integer, fraction) = digits.split_at(n as usize);
+		result.push_str(integer);
+		result.push('.');
+		result.push_str(fraction)
+	} else if -6 < n && n <= 0 {
+		result.push_str("0.");
+		result.push_str(&"0".repeat(-n as usize));
+		result.push_str(&digits)
+	} else {
+		let (first, rest) = digits.split_at(1);
+		result.push_str(first);
+		if !rest.is_empty() {
+			result.push('.');
+			result.push_str(rest)
+		}
+
+		let e = n - 1;
+		result.push_str(&format!("e{}{}", if e < 0 { '-' } else { '+' }, e.abs()))
+	}
+
+	result
+}
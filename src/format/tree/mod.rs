@@ -0,0 +1,1091 @@
+use core::fmt;
+use std::io::{self, BufRead, Write};
+
+use clap::builder::TypedValueParser;
+use json_syntax::Print;
+use treeldr_layouts::{
+	distill::de::Selection,
+	layout::LayoutType,
+	value::{
+		cbor::TagError,
+		msgpack::{InvalidExtType, InvalidMsgpackValue},
+		toml::NonTomlValue,
+		NonJsonValue,
+	},
+	LayoutRegistry, Ref,
+};
+
+pub mod compression;
+mod csv;
+mod jcs;
+pub mod xml;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LoadError {
+	#[error("JSON parse error: {0}")]
+	Json(json_syntax::parse::Error<io::Error>),
+
+	#[error("CBOR parse error: {0}")]
+	Cbor(serde_cbor::Error),
+
+	#[error("invalid CBOR tag: {0}")]
+	CborTag(TagError),
+
+	#[error("YAML parse error: {0}")]
+	Yaml(serde_norway::Error),
+
+	#[error("TOML parse error: {0}")]
+	Toml(toml::de::Error),
+
+	#[error("MessagePack parse error: {0}")]
+	MessagePack(rmpv::decode::Error),
+
+	#[error(transparent)]
+	InvalidMessagePack(InvalidMsgpackValue),
+
+	#[error("{0} error: {1}")]
+	Csv(TreeFormat, csv::LoadError),
+
+	#[error("XML error: {0}")]
+	Xml(xml::LoadError),
+
+	#[error(transparent)]
+	IO(io::Error),
+
+	#[error("`{0}` requires a layout")]
+	Untyped(TreeFormat),
+
+	#[error("`{0}` input holds multiple values")]
+	Stream(TreeFormat),
+
+	#[error("`{0}` does not support batch mode")]
+	Batch(TreeFormat),
+
+	#[error("`{0}` does not support compression")]
+	Compression(TreeFormat),
+
+	#[error(transparent)]
+	ProtobufSchema(treeldr_gen_protobuf::Error),
+
+	#[error("Protocol Buffers parse error: {0}")]
+	Protobuf(treeldr_gen_protobuf::DecodeError),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WriteError {
+	#[error(transparent)]
+	NonJsonValue(NonJsonValue),
+
+	#[error(transparent)]
+	NonTomlValue(NonTomlValue),
+
+	#[error(transparent)]
+	IO(#[from] io::Error),
+
+	#[error("invalid CBOR tag: {0}")]
+	CborTag(treeldr_layouts::value::cbor::InvalidTag),
+
+	#[error(transparent)]
+	Cbor(serde_cbor::Error),
+
+	#[error(transparent)]
+	Yaml(serde_norway::Error),
+
+	#[error(transparent)]
+	Toml(toml::ser::Error),
+
+	#[error("invalid MessagePack extension type: {0}")]
+	MessagePackExtType(InvalidExtType),
+
+	#[error(transparent)]
+	MessagePack(rmpv::encode::Error),
+
+	#[error("{0} error: {1}")]
+	Csv(TreeFormat, csv::WriteError),
+
+	#[error("XML error: {0}")]
+	Xml(xml::WriteError),
+
+	#[error("`{0}` requires a layout")]
+	Untyped(TreeFormat),
+
+	#[error("`{0}` cannot hold multiple documents")]
+	Stream(TreeFormat),
+
+	#[error("`{0}` does not support batch mode")]
+	Batch(TreeFormat),
+
+	#[error("`{0}` does not support compression")]
+	Compression(TreeFormat),
+
+	#[error("`{0}` does not support canonical output")]
+	Canonical(TreeFormat),
+
+	#[error("number `{0}` has no exact canonical JSON representation")]
+	InexactNumber(treeldr_layouts::value::Number),
+
+	#[error(transparent)]
+	ProtobufSchema(treeldr_gen_protobuf::Error),
+
+	#[error(transparent)]
+	Protobuf(treeldr_gen_protobuf::EncodeError),
+}
+
+/// Output style.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+	/// Compact output.
+	#[default]
+	Compact,
+
+	/// Pretty printed output, for formats that support it.
+	Pretty,
+
+	/// Canonical output, following the JSON Canonicalization Scheme
+	/// (RFC 8785). Only supported by JSON.
+	Canonical,
+}
+
+impl Style {
+	pub fn new(pretty: bool, canonical: bool) -> Self {
+		if canonical {
+			Self::Canonical
+		} else if pretty {
+			Self::Pretty
+		} else {
+			Self::Compact
+		}
+	}
+
+	pub fn is_pretty(&self) -> bool {
+		matches!(self, Self::Pretty)
+	}
+}
+
+#[derive(Debug, Clone)]
+pub enum TreeFormat {
+	Json,
+	Cbor,
+	Protobuf,
+	Yaml,
+	Toml,
+	MessagePack,
+	Csv,
+	Tsv,
+	Xml,
+}
+
+impl TreeFormat {
+	pub const POSSIBLE_VALUES: &'static [&'static str] = &[
+		"application/json",
+		"json",
+		"application/cbor",
+		"cbor",
+		"application/protobuf",
+		"protobuf",
+		"application/yaml",
+		"yaml",
+		"yml",
+		"application/toml",
+		"toml",
+		"application/msgpack",
+		"msgpack",
+		"messagepack",
+		"text/csv",
+		"csv",
+		"text/tab-separated-values",
+		"tsv",
+		"application/xml",
+		"text/xml",
+		"xml",
+	];
+
+	pub fn parser(
+	) -> clap::builder::MapValueParser<clap::builder::PossibleValuesParser, fn(String) -> Self> {
+		clap::builder::PossibleValuesParser::new(Self::POSSIBLE_VALUES)
+			.map(|s| Self::new(&s).unwrap())
+	}
+
+	pub fn new(name: &str) -> Option<Self> {
+		match name {
+			"application/json" | "json" => Some(Self::Json),
+			"application/cbor" | "cbor" => Some(Self::Cbor),
+			"application/protobuf" | "protobuf" => Some(Self::Protobuf),
+			"application/yaml" | "yaml" | "yml" => Some(Self::Yaml),
+			"application/toml" | "toml" => Some(Self::Toml),
+			"application/msgpack" | "msgpack" | "messagepack" => Some(Self::MessagePack),
+			"text/csv" | "csv" => Some(Self::Csv),
+			"text/tab-separated-values" | "tsv" => Some(Self::Tsv),
+			"application/xml" | "text/xml" | "xml" => Some(Self::Xml),
+			_ => None,
+		}
+	}
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::Json => "application/json",
+			Self::Cbor => "application/cbor",
+			Self::Protobuf => "application/protobuf",
+			Self::Yaml => "application/yaml",
+			Self::Toml => "application/toml",
+			Self::MessagePack => "application/msgpack",
+			Self::Csv => "text/csv",
+			Self::Tsv => "text/tab-separated-values",
+			Self::Xml => "application/xml",
+		}
+	}
+
+	/// Returns the field delimiter of the CSV and TSV formats.
+	fn delimiter(&self) -> u8 {
+		match self {
+			Self::Tsv => b'\t',
+			_ => b',',
+		}
+	}
+
+	pub fn load(&self, mut input: impl BufRead) -> Result<treeldr_layouts::Value, LoadError> {
+		match self {
+			Self::Json => {
+				use json_syntax::Parse;
+				let utf8_input = utf8_decode::UnsafeDecoder::new(input.bytes());
+				let (json, _) =
+					json_syntax::Value::parse_utf8(utf8_input).map_err(LoadError::Json)?;
+				Ok(json.into())
+			}
+			Self::Cbor => serde_cbor::from_reader(input).map_err(LoadError::Cbor),
+			Self::Protobuf => Err(LoadError::Untyped(self.clone())),
+			Self::Yaml => serde_norway::from_reader(input).map_err(LoadError::Yaml),
+			Self::Toml => {
+				let content = io::read_to_string(input).map_err(LoadError::IO)?;
+				let table: toml::Table = toml::from_str(&content).map_err(LoadError::Toml)?;
+				Ok(toml::Value::Table(table).into())
+			}
+			Self::MessagePack => rmpv::decode::read_value(&mut input)
+				.map_err(LoadError::MessagePack)?
+				.try_into()
+				.map_err(LoadError::InvalidMessagePack),
+			Self::Csv | Self::Tsv | Self::Xml => Err(LoadError::Untyped(self.clone())),
+		}
+	}
+
+	/// Loads every tree value of the input.
+	///
+	/// YAML streams may contain any number of documents. CSV and TSV rows
+	/// are read with [`Self::load_typed_all`]. Other formats hold exactly one
+	/// value.
+	pub fn load_all(&self, input: impl BufRead) -> Result<Vec<treeldr_layouts::Value>, LoadError> {
+		match self {
+			Self::Yaml => load_yaml_stream(input),
+			_ => Ok(vec![self.load(input)?]),
+		}
+	}
+
+	/// Loads a tree value expected to match the given layout.
+	///
+	/// Unlike [`Self::load`], this also supports formats that are not
+	/// self-describing. CBOR tags are checked against the layout, and used to
+	/// select sum layout variants, as returned by the [`Selection`].
+	pub fn load_typed(
+		&self,
+		layouts: &impl LayoutRegistry,
+		layout_ref: &Ref<LayoutType>,
+		mut input: impl BufRead,
+	) -> Result<(treeldr_layouts::Value, Selection), LoadError> {
+		match self {
+			Self::Cbor => {
+				let cbor = serde_cbor::from_reader(input).map_err(LoadError::Cbor)?;
+				treeldr_layouts::Value::try_from_tagged_serde_cbor(cbor, layouts, layout_ref)
+					.map_err(LoadError::CborTag)
+			}
+			Self::Protobuf => {
+				let schema =
+					protobuf_schema(layouts, layout_ref).map_err(LoadError::ProtobufSchema)?;
+				let mut bytes = Vec::new();
+				input.read_to_end(&mut bytes).map_err(LoadError::IO)?;
+				let value = schema.decode(&bytes).map_err(LoadError::Protobuf)?;
+				Ok((value, Selection::Any))
+			}
+			Self::Csv | Self::Tsv => {
+				let mut values = csv::load_typed(layouts, layout_ref, self.delimiter(), input)
+					.map_err(|e| LoadError::Csv(self.clone(), e))?;
+				if values.len() == 1 {
+					Ok((values.pop().unwrap(), Selection::Any))
+				} else {
+					Err(LoadError::Stream(self.clone()))
+				}
+			}
+			Self::Xml => {
+				let value = xml::load_typed(layouts, layout_ref, input).map_err(LoadError::Xml)?;
+				Ok((value, Selection::Any))
+			}
+			_ => Ok((self.load(input)?, Selection::Any)),
+		}
+	}
+
+	/// Loads every tree value of the input, expected to match the given
+	/// layout.
+	///
+	/// See [`Self::load_all`] and [`Self::load_typed`]. CSV and TSV inputs
+	/// hold one value per row, unless the layout is a list of records.
+	pub fn load_typed_all(
+		&self,
+		layouts: &impl LayoutRegistry,
+		layout_ref: &Ref<LayoutType>,
+		input: impl BufRead,
+	) -> Result<Vec<(treeldr_layouts::Value, Selection)>, LoadError> {
+		let values = match self {
+			Self::Yaml => load_yaml_stream(input)?,
+			Self::Csv | Self::Tsv => csv::load_typed(layouts, layout_ref, self.delimiter(), input)
+				.map_err(|e| LoadError::Csv(self.clone(), e))?,
+			_ => return Ok(vec![self.load_typed(layouts, layout_ref, input)?]),
+		};
+
+		Ok(values.into_iter().map(|v| (v, Selection::Any)).collect())
+	}
+
+	/// Returns the CBOR tag of the input value, without consuming the input.
+	///
+	/// This is used to select the layout of the value. Returns `None` for
+	/// other formats.
+	pub fn peek_tag(&self, input: &mut impl BufRead) -> Result<Option<u64>, LoadError> {
+		match self {
+			Self::Cbor => {
+				let bytes = input.fill_buf().map_err(LoadError::IO)?;
+				match bytes.first() {
+					Some(head) if head >> 5 == 6 => {
+						let len = match head & 0x1f {
+							n @ 0..=23 => return Ok(Some(n as u64)),
+							24 => 1,
+							25 => 2,
+							26 => 4,
+							27 => 8,
+							_ => return Ok(None),
+						};
+
+						Ok(bytes
+							.get(1..=len)
+							.map(|b| b.iter().fold(0, |n, b| (n << 8) | *b as u64)))
+					}
+					_ => Ok(None),
+				}
+			}
+			_ => Ok(None),
+		}
+	}
+
+	pub fn write_typed(
+		&self,
+		layouts: &impl LayoutRegistry,
+		value: treeldr_layouts::TypedValue,
+		style: Style,
+		mut output: impl Write,
+	) -> Result<(), WriteError> {
+		match self {
+			Self::Json => self.write_untyped(value.into_untyped(), style, output),
+			_ if style == Style::Canonical => Err(WriteError::Canonical(self.clone())),
+			Self::Cbor => {
+				let cbor = value
+					.try_into_tagged_serde_cbor(layouts)
+					.map_err(WriteError::CborTag)?;
+				serde_cbor::to_writer(output, &cbor).map_err(WriteError::Cbor)
+			}
+			Self::Protobuf => {
+				let layout_ref = value
+					.type_()
+					.cloned()
+					.ok_or_else(|| WriteError::Untyped(self.clone()))?;
+				let schema =
+					protobuf_schema(layouts, &layout_ref).map_err(WriteError::ProtobufSchema)?;
+				let bytes = schema
+					.encode(&value.into_untyped())
+					.map_err(WriteError::Protobuf)?;
+				output.write_all(&bytes).map_err(WriteError::IO)
+			}
+			Self::Yaml => {
+				serde_norway::to_writer(output, &value.into_untyped()).map_err(WriteError::Yaml)
+			}
+			Self::Toml => write_toml(value.into_untyped(), style.is_pretty(), output),
+			Self::MessagePack => {
+				let msgpack = value
+					.try_into_tagged_rmpv(layouts)
+					.map_err(WriteError::MessagePackExtType)?;
+				rmpv::encode::write_value(&mut output, &msgpack).map_err(WriteError::MessagePack)
+			}
+			Self::Csv | Self::Tsv => {
+				let columns = match value.type_() {
+					Some(layout_ref) => csv::layout_columns(layouts, layout_ref)
+						.map_err(|e| WriteError::Csv(self.clone(), csv::WriteError::NotFlat(e)))?,
+					None => Vec::new(),
+				};
+
+				csv::write(value.into_untyped(), columns, self.delimiter(), output)
+					.map_err(|e| WriteError::Csv(self.clone(), e))
+			}
+			Self::Xml => {
+				xml::write_typed(layouts, value, style.is_pretty(), output).map_err(WriteError::Xml)
+			}
+		}
+	}
+
+	pub fn write_untyped(
+		&self,
+		value: treeldr_layouts::Value,
+		style: Style,
+		mut output: impl Write,
+	) -> Result<(), WriteError> {
+		match self {
+			Self::Json => match style {
+				Style::Canonical => jcs::write(&value, &mut output),
+				style => {
+					let json: json_syntax::Value =
+						value.try_into().map_err(WriteError::NonJsonValue)?;
+					if style.is_pretty() {
+						write!(output, "{}", json.pretty_print()).map_err(WriteError::IO)
+					} else {
+						write!(output, "{}", json.compact_print()).map_err(WriteError::IO)
+					}
+				}
+			},
+			_ if style == Style::Canonical => Err(WriteError::Canonical(self.clone())),
+			Self::Cbor => serde_cbor::to_writer(output, &value).map_err(WriteError::Cbor),
+			Self::Protobuf => Err(WriteError::Untyped(self.clone())),
+			Self::Yaml => serde_norway::to_writer(output, &value).map_err(WriteError::Yaml),
+			Self::Toml => write_toml(value, style.is_pretty(), output),
+			Self::MessagePack => rmpv::encode::write_value(&mut output, &value.into())
+				.map_err(WriteError::MessagePack),
+			Self::Csv | Self::Tsv => csv::write(value, Vec::new(), self.delimiter(), output)
+				.map_err(|e| WriteError::Csv(self.clone(), e)),
+			Self::Xml => Err(WriteError::Untyped(self.clone())),
+		}
+	}
+
+	/// Writes every given tree value.
+	///
+	/// Multiple values are written as a YAML stream, or as CSV and TSV rows.
+	/// Other formats can only hold one value.
+	pub fn write_untyped_all(
+		&self,
+		mut values: Vec<treeldr_layouts::Value>,
+		style: Style,
+		output: impl Write,
+	) -> Result<(), WriteError> {
+		match self {
+			_ if style == Style::Canonical && !matches!(self, Self::Json) => {
+				Err(WriteError::Canonical(self.clone()))
+			}
+			Self::Yaml => {
+				use serde::Serialize;
+				let mut serializer = serde_norway::Serializer::new(output);
+				for value in values {
+					value.serialize(&mut serializer).map_err(WriteError::Yaml)?;
+				}
+
+				Ok(())
+			}
+			Self::Csv | Self::Tsv if values.len() != 1 => csv::write(
+				treeldr_layouts::Value::List(values),
+				Vec::new(),
+				self.delimiter(),
+				output,
+			)
+			.map_err(|e| WriteError::Csv(self.clone(), e)),
+			_ if values.len() == 1 => self.write_untyped(values.pop().unwrap(), style, output),
+			_ => Err(WriteError::Stream(self.clone())),
+		}
+	}
+
+	/// Checks if this format supports compression.
+	///
+	/// See [`compression::Codebook`].
+	pub fn supports_compression(&self) -> bool {
+		matches!(self, Self::Cbor)
+	}
+
+	/// Writes the given tree value, compressed using the given codebook.
+	///
+	/// Only CBOR supports compression. Records of a batch output are written
+	/// the same way, concatenated.
+	pub fn write_compressed(
+		&self,
+		layouts: &impl LayoutRegistry,
+		codebook: &compression::Codebook,
+		value: treeldr_layouts::TypedValue,
+		output: impl Write,
+	) -> Result<(), WriteError> {
+		match self {
+			Self::Cbor => {
+				let cbor = value
+					.clone()
+					.try_into_tagged_serde_cbor(layouts)
+					.map_err(WriteError::CborTag)?;
+				let cbor = codebook
+					.compress(layouts, &value, cbor)
+					.map_err(WriteError::CborTag)?;
+				serde_cbor::to_writer(output, &cbor).map_err(WriteError::Cbor)
+			}
+			_ => Err(WriteError::Compression(self.clone())),
+		}
+	}
+
+	/// Checks if this format supports batch mode.
+	///
+	/// See [`Self::load_records`] and [`Self::write_record`].
+	pub fn supports_batch(&self) -> bool {
+		matches!(self, Self::Json | Self::Cbor | Self::Yaml)
+	}
+
+	/// Reads the input as a stream of records, one at a time.
+	///
+	/// The layout of each record is given by `layout_ref`, called with the
+	/// CBOR tag of the record, if any, and returned with the record.
+	///
+	/// JSON records are newline-delimited (NDJSON), or delimited by the
+	/// record separator if the input is a JSON text sequence (RFC 7464), in
+	/// which case records may span multiple lines. YAML records are the
+	/// documents of a YAML stream, split on their start (`---`) and end
+	/// (`...`) markers. CBOR records are concatenated, and their tags are
+	/// handled as in [`Self::load_typed`]. Other formats do not support batch
+	/// mode.
+	///
+	/// A malformed JSON record or YAML document does not prevent reading the
+	/// next ones, while reading CBOR stops at the first syntax error.
+	pub fn load_records<'a, E: 'a + From<LoadError>>(
+		&self,
+		layouts: &'a impl LayoutRegistry,
+		mut layout_ref: impl 'a + FnMut(Option<u64>) -> Result<Ref<LayoutType>, E>,
+		input: impl 'a + BufRead,
+	) -> Result<Records<'a, E>, LoadError> {
+		match self {
+			Self::Json => {
+				Ok(Box::new(JsonRecords::new(input).map(move |record| {
+					Ok((record?, Selection::Any, layout_ref(None)?))
+				})))
+			}
+			Self::Yaml => {
+				Ok(Box::new(YamlRecords::new(input).map(move |record| {
+					Ok((record?, Selection::Any, layout_ref(None)?))
+				})))
+			}
+			Self::Cbor => Ok(Box::new(
+				serde_cbor::Deserializer::from_reader(input)
+					.into_iter::<serde_cbor::Value>()
+					.scan(false, |failed, record| {
+						if *failed {
+							None
+						} else {
+							*failed = record.is_err();
+							Some(record.map_err(LoadError::Cbor))
+						}
+					})
+					.map(move |record| {
+						let record = record?;
+						let tag = match &record {
+							serde_cbor::Value::Tag(tag, _) => Some(*tag),
+							_ => None,
+						};
+
+						let layout_ref = layout_ref(tag)?;
+						let (value, selection) =
+							treeldr_layouts::Value::try_from_tagged_serde_cbor(
+								record,
+								layouts,
+								&layout_ref,
+							)
+							.map_err(LoadError::CborTag)?;
+						Ok((value, selection, layout_ref))
+					}),
+			)),
+			_ => Err(LoadError::Batch(self.clone())),
+		}
+	}
+
+	/// Writes one record of a batch output.
+	///
+	/// JSON records are written on their own line, in canonical form if
+	/// `canonical` is true, YAML records are written as the documents of a
+	/// YAML stream, and CBOR records are concatenated.
+	pub fn write_record(
+		&self,
+		layouts: &impl LayoutRegistry,
+		value: treeldr_layouts::TypedValue,
+		canonical: bool,
+		mut output: impl Write,
+	) -> Result<(), WriteError> {
+		let style = Style::new(false, canonical);
+		match self {
+			Self::Json => {
+				self.write_typed(layouts, value, style, &mut output)?;
+				writeln!(output).map_err(WriteError::IO)
+			}
+			Self::Yaml => {
+				writeln!(output, "---").map_err(WriteError::IO)?;
+				self.write_typed(layouts, value, style, output)
+			}
+			Self::Cbor => self.write_typed(layouts, value, style, output),
+			_ => Err(WriteError::Batch(self.clone())),
+		}
+	}
+}
+
+fn write_toml(
+	value: treeldr_layouts::Value,
+	pretty: bool,
+	mut output: impl Write,
+) -> Result<(), WriteError> {
+	let table = value
+		.try_into_toml_table()
+		.map_err(WriteError::NonTomlValue)?;
+	let toml = if pretty {
+		toml::to_string_pretty(&table)
+	} else {
+		toml::to_string(&table)
+	}
+	.map_err(WriteError::Toml)?;
+	write!(output, "{toml}").map_err(WriteError::IO)
+}
+
+/// Stream of records read in batch mode, with their layout.
+pub type Records<'a, E = LoadError> =
+	Box<dyn 'a + Iterator<Item = Result<(treeldr_layouts::Value, Selection, Ref<LayoutType>), E>>>;
+
+/// Record separator of JSON text sequences (RFC 7464).
+const RS: u8 = 0x1e;
+
+/// Newline-delimited JSON records, or JSON text sequence records.
+struct JsonRecords<R> {
+	input: R,
+	buffer: Vec<u8>,
+
+	/// Record delimiter, detected from the first non-whitespace byte of the
+	/// input: [`RS`] for JSON text sequences, a newline otherwise.
+	delimiter: Option<u8>,
+}
+
+impl<R: BufRead> JsonRecords<R> {
+	fn new(input: R) -> Self {
+		Self {
+			input,
+			buffer: Vec::new(),
+			delimiter: None,
+		}
+	}
+
+	fn delimiter(&mut self) -> io::Result<u8> {
+		if let Some(delimiter) = self.delimiter {
+			return Ok(delimiter);
+		}
+
+		loop {
+			let bytes = self.input.fill_buf()?;
+			match bytes.iter().position(|b| !b.is_ascii_whitespace()) {
+				Some(i) => {
+					let delimiter = if bytes[i] == RS { RS } else { b'\n' };
+					self.delimiter = Some(delimiter);
+					break Ok(delimiter);
+				}
+				None if bytes.is_empty() => break Ok(b'\n'),
+				None => {
+					let len = bytes.len();
+					self.input.consume(len)
+				}
+			}
+		}
+	}
+}
+
+impl<R: BufRead> Iterator for JsonRecords<R> {
+	type Item = Result<treeldr_layouts::Value, LoadError>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		use json_syntax::Parse;
+		let delimiter = match self.delimiter() {
+			Ok(delimiter) => delimiter,
+			Err(e) => return Some(Err(LoadError::IO(e))),
+		};
+
+		loop {
+			self.buffer.clear();
+			match self.input.read_until(delimiter, &mut self.buffer) {
+				Ok(0) => break None,
+				Ok(_) => {
+					let record = self
+						.buffer
+						.strip_suffix(&[delimiter])
+						.unwrap_or(&self.buffer);
+					let record = record.strip_prefix(&[RS]).unwrap_or(record);
+
+					if !record.iter().all(u8::is_ascii_whitespace) {
+						let utf8_input = utf8_decode::UnsafeDecoder::new(io::Read::bytes(record));
+						break Some(
+							json_syntax::Value::parse_utf8(utf8_input)
+								.map(|(json, _)| json.into())
+								.map_err(LoadError::Json),
+						);
+					}
+				}
+				Err(e) => break Some(Err(LoadError::IO(e))),
+			}
+		}
+	}
+}
+
+/// Documents of a YAML stream.
+///
+/// Document markers can only appear at the start of a line, so the stream is
+/// split line by line and only the current document is held in memory.
+struct YamlRecords<R> {
+	input: R,
+	line: Vec<u8>,
+	buffer: Vec<u8>,
+
+	/// Whether the current document has started, either with a start marker
+	/// or with some content.
+	started: bool,
+}
+
+impl<R: BufRead> YamlRecords<R> {
+	fn new(input: R) -> Self {
+		Self {
+			input,
+			line: Vec::new(),
+			buffer: Vec::new(),
+			started: false,
+		}
+	}
+
+	fn is_marker(line: &[u8], marker: &[u8; 3]) -> bool {
+		line.strip_prefix(marker)
+			.is_some_and(|rest| matches!(rest.first(), None | Some(b' ' | b'\t' | b'\r' | b'\n')))
+	}
+
+	/// Checks if the given line is document content, and not a directive, a
+	/// comment or a blank line.
+	fn is_content(line: &[u8]) -> bool {
+		line.first() != Some(&b'%')
+			&& line
+				.iter()
+				.find(|b| !b.is_ascii_whitespace())
+				.is_some_and(|b| *b != b'#')
+	}
+
+	fn parse_document(&mut self) -> Result<treeldr_layouts::Value, LoadError> {
+		let document = serde_norway::from_slice(&self.buffer).map_err(LoadError::Yaml);
+		self.buffer.clear();
+		self.started = false;
+		document
+	}
+}
+
+impl<R: BufRead> Iterator for YamlRecords<R> {
+	type Item = Result<treeldr_layouts::Value, LoadError>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			self.line.clear();
+			match self.input.read_until(b'\n', &mut self.line) {
+				Ok(0) => break self.started.then(|| self.parse_document()),
+				Ok(_) => {
+					if Self::is_marker(&self.line, b"---") {
+						let document = self.started.then(|| self.parse_document());
+						self.buffer.extend_from_slice(&self.line);
+						self.started = true;
+						if document.is_some() {
+							break document;
+						}
+					} else if Self::is_marker(&self.line, b"...") {
+						if self.started {
+							break Some(self.parse_document());
+						}
+
+						self.buffer.clear()
+					} else {
+						self.started |= Self::is_content(&self.line);
+						self.buffer.extend_from_slice(&self.line)
+					}
+				}
+				Err(e) => break Some(Err(LoadError::IO(e))),
+			}
+		}
+	}
+}
+
+fn load_yaml_stream(input: impl BufRead) -> Result<Vec<treeldr_layouts::Value>, LoadError> {
+	use serde::Deserialize;
+	serde_norway::Deserializer::from_reader(input)
+		.map(|document| treeldr_layouts::Value::deserialize(document).map_err(LoadError::Yaml))
+		.collect()
+}
+
+fn protobuf_schema(
+	layouts: &impl LayoutRegistry,
+	layout_ref: &Ref<LayoutType>,
+) -> Result<treeldr_gen_protobuf::Schema, treeldr_gen_protobuf::Error> {
+	treeldr_gen_protobuf::Schema::new(Default::default(), layouts, layout_ref, &Default::default())
+}
+
+impl fmt::Display for TreeFormat {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Builds the given layout.
+	pub(super) fn build_layout(json: &str) -> (treeldr_layouts::Layouts, Ref<LayoutType>) {
+		use json_syntax::{Parse, TryFromJson};
+		let (json, code_map) = json_syntax::Value::parse_str(json).unwrap();
+		let layout = treeldr_layouts::abs::syntax::Layout::try_from_json(&json, &code_map).unwrap();
+		let mut builder = treeldr_layouts::abs::Builder::new();
+		let layout_ref = layout.build(&mut builder).unwrap();
+		(builder.build(), layout_ref)
+	}
+
+	/// Returns the typed value of the given tree value, by dehydrating and
+	/// hydrating it back with the given layout.
+	pub(super) fn typed(
+		layouts: &impl LayoutRegistry,
+		layout_ref: &Ref<LayoutType>,
+		value: &treeldr_layouts::Value,
+	) -> treeldr_layouts::TypedValue {
+		let (dataset, inputs) =
+			treeldr_layouts::distill::dehydrate(layouts, value, layout_ref, Default::default())
+				.unwrap();
+		treeldr_layouts::distill::hydrate(layouts, &dataset.into_indexed(), layout_ref, &inputs)
+			.unwrap()
+	}
+
+	fn json(input: &str) -> treeldr_layouts::Value {
+		TreeFormat::Json.load(input.as_bytes()).unwrap()
+	}
+
+	/// Writes the given value in the given format, and checks that loading
+	/// it back gives the same value.
+	fn round_trip(format: TreeFormat, value: treeldr_layouts::Value, style: Style) -> Vec<u8> {
+		let mut output = Vec::new();
+		format
+			.write_untyped(value.clone(), style, &mut output)
+			.unwrap();
+		assert_eq!(format.load(output.as_slice()).unwrap(), value);
+		output
+	}
+
+	const DOCUMENT: &str = r#"{
+		"name": "Bob L'éponge",
+		"age": 42,
+		"ratio": 0.25,
+		"negative": -7,
+		"active": true,
+		"tags": ["a", "b: c", "yes", "multi\nline"],
+		"address": { "city": "Bikini Bottom", "zip": "00042" },
+		"empty": []
+	}"#;
+
+	#[test]
+	fn yaml_round_trip() {
+		let mut value = json(DOCUMENT);
+		if let treeldr_layouts::Value::Map(map) = &mut value {
+			map.insert(
+				treeldr_layouts::Value::string("none".to_owned()),
+				treeldr_layouts::Value::unit(),
+			);
+		}
+
+		round_trip(TreeFormat::Yaml, value, Style::Compact);
+	}
+
+	#[test]
+	fn yaml_stream() {
+		let values = vec![json(r#"{ "a": 1 }"#), json(r#"["b"]"#), json(r#""c""#)];
+
+		let mut output = Vec::new();
+		TreeFormat::Yaml
+			.write_untyped_all(values.clone(), Style::Compact, &mut output)
+			.unwrap();
+		assert_eq!(
+			TreeFormat::Yaml.load_all(output.as_slice()).unwrap(),
+			values
+		)
+	}
+
+	#[test]
+	fn toml_round_trip() {
+		let value = json(DOCUMENT);
+		round_trip(TreeFormat::Toml, value.clone(), Style::Compact);
+		round_trip(TreeFormat::Toml, value, Style::Pretty);
+	}
+
+	#[test]
+	fn toml_non_table() {
+		for input in [r#"["a"]"#, r#"{ "a": null }"#] {
+			let mut output = Vec::new();
+			let result = TreeFormat::Toml.write_untyped(json(input), Style::Compact, &mut output);
+			assert!(matches!(result, Err(WriteError::NonTomlValue(_))))
+		}
+	}
+
+	#[test]
+	fn msgpack_round_trip() {
+		round_trip(TreeFormat::MessagePack, json(DOCUMENT), Style::Compact);
+	}
+
+	#[test]
+	fn msgpack_ext_type() {
+		let (layouts, layout_ref) = build_layout(
+			r#"{
+				"prefixes": { "msgpack": "https://schema.treeldr.org/msgpack#" },
+				"type": "record",
+				"fields": {
+					"name": {
+						"value": { "type": "string", "extra": { "msgpack:ext": 42 } },
+						"property": "https://schema.org/name"
+					}
+				}
+			}"#,
+		);
+
+		let dataset = crate::format::RDFFormat::NQuads
+			.load(
+				"<https://example.org/#bob> <https://schema.org/name> \"Bob\" .".as_bytes(),
+				&Default::default(),
+			)
+			.unwrap()
+			.into_indexed();
+		let subject =
+			rdf_types::Term::iri(static_iref::iri!("https://example.org/#bob").to_owned());
+		let value =
+			treeldr_layouts::distill::hydrate(&layouts, &dataset, &layout_ref, &[subject]).unwrap();
+
+		let mut output = Vec::new();
+		TreeFormat::MessagePack
+			.write_typed(&layouts, value, Style::Compact, &mut output)
+			.unwrap();
+
+		let msgpack = rmpv::decode::read_value(&mut output.as_slice()).unwrap();
+		assert!(matches!(
+			&msgpack.as_map().unwrap()[0].1,
+			rmpv::Value::Ext(42, _)
+		));
+		assert_eq!(
+			TreeFormat::MessagePack.load(output.as_slice()).unwrap(),
+			json(r#"{ "name": "Bob" }"#)
+		)
+	}
+
+	const RECORD: &str = r#"{
+		"type": "record",
+		"fields": {
+			"a": { "value": "https://treeldr.org/prelude#u32", "property": "https://example.org/#a" }
+		}
+	}"#;
+
+	/// Reads the records of the given input, returning the record values or
+	/// the error messages.
+	fn load_records(
+		format: TreeFormat,
+		input: &[u8],
+	) -> Vec<Result<treeldr_layouts::Value, String>> {
+		let (layouts, layout_ref) = build_layout(RECORD);
+		format
+			.load_records(&layouts, |_| Ok::<_, LoadError>(layout_ref.clone()), input)
+			.unwrap()
+			.map(|record| record.map(|(value, _, _)| value).map_err(|e| e.to_string()))
+			.collect()
+	}
+
+	#[test]
+	fn json_lines_records() {
+		let records = load_records(
+			TreeFormat::Json,
+			b"{ \"a\": 1 }\n\n{ \"a\": \n\x1e{ \"a\": 2 }\n{ \"a\": 3 }",
+		);
+
+		assert_eq!(records.len(), 4);
+		assert_eq!(records[0], Ok(json(r#"{ "a": 1 }"#)));
+		assert!(records[1].is_err());
+		assert_eq!(records[2], Ok(json(r#"{ "a": 2 }"#)));
+		assert_eq!(records[3], Ok(json(r#"{ "a": 3 }"#)))
+	}
+
+	#[test]
+	fn json_seq_records() {
+		let records = load_records(
+			TreeFormat::Json,
+			b"\n\x1e{\n\t\"a\": 1\n}\n\x1e{ \"a\": \n\x1e\x1e[\n2\n]\n",
+		);
+
+		assert_eq!(records.len(), 3);
+		assert_eq!(records[0], Ok(json(r#"{ "a": 1 }"#)));
+		assert!(records[1].is_err());
+		assert_eq!(records[2], Ok(json("[2]")))
+	}
+
+	#[test]
+	fn yaml_records() {
+		let records = load_records(TreeFormat::Yaml, b"a: 1\n---\na: 2\n--- [3]\n");
+		assert_eq!(
+			records,
+			[
+				Ok(json(r#"{ "a": 1 }"#)),
+				Ok(json(r#"{ "a": 2 }"#)),
+				Ok(json("[3]"))
+			]
+		)
+	}
+
+	#[test]
+	fn yaml_document_markers() {
+		let records = load_records(
+			TreeFormat::Yaml,
+			b"# comment\n%YAML 1.2\n---\na: |\n  ---\n  text\n...\n%YAML 1.2\n--- { a: \n...\n\n---\na: 3\n",
+		);
+
+		assert_eq!(records.len(), 3);
+		assert_eq!(records[0], Ok(json(r#"{ "a": "---\ntext\n" }"#)));
+		assert!(records[1].is_err());
+		assert_eq!(records[2], Ok(json(r#"{ "a": 3 }"#)))
+	}
+
+	#[test]
+	fn write_records() {
+		let (layouts, layout_ref) = build_layout(RECORD);
+		let layouts = layouts.with(treeldr_layouts::Prelude);
+		let values = [json(r#"{ "a": 1 }"#), json(r#"{ "a": 2 }"#)];
+
+		for format in [TreeFormat::Json, TreeFormat::Yaml, TreeFormat::Cbor] {
+			let mut output = Vec::new();
+			for value in &values {
+				format
+					.write_record(
+						&layouts,
+						typed(&layouts, &layout_ref, value),
+						false,
+						&mut output,
+					)
+					.unwrap();
+			}
+
+			let records: Vec<_> = format
+				.load_records(
+					&layouts,
+					|_| Ok::<_, LoadError>(layout_ref.clone()),
+					output.as_slice(),
+				)
+				.unwrap()
+				.map(|record| record.unwrap().0)
+				.collect();
+			assert_eq!(records, values, "{format}")
+		}
+
+		assert!(TreeFormat::Toml
+			.load_records(
+				&layouts,
+				|_| Ok::<_, LoadError>(layout_ref.clone()),
+				b"".as_slice()
+			)
+			.is_err());
+		assert!(!TreeFormat::Toml.supports_batch())
+	}
+}
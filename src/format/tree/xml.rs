@@ -0,0 +1,886 @@
+//! XML support.
+//!
+//! The mapping between XML documents and tree values is driven by the layout:
+//! record fields are child elements (or attributes when the field has the
+//! [`XML_ATTRIBUTE_IRI`] extra property), list items are repeated elements,
+//! and sum values are wrapped in an element named after their variant.
+use std::{
+	collections::BTreeMap,
+	fmt::Write as _,
+	io::{BufRead, Write},
+	str::FromStr,
+};
+
+use iref::Iri;
+use quick_xml::{
+	escape::escape,
+	events::{BytesStart, Event},
+};
+use rdf_types::Term;
+use static_iref::iri;
+use treeldr_layouts::{
+	layout::{DataLayout, LayoutType, ListLayout, LiteralLayout},
+	value::Number,
+	Layout, LayoutRegistry, Literal, PresetLayout, Ref, TypedLiteral, TypedValue, Value,
+};
+
+/// XML extension `attribute` property.
+///
+/// When set to `true` on a record field, the field is mapped to an attribute
+/// instead of a child element. The field value must be a literal.
+pub const XML_ATTRIBUTE_IRI: &Iri = iri!("https://schema.treeldr.org/xml#attribute");
+
+/// XML extension `name` property.
+///
+/// Name of the document element when the layout is used at the top level.
+/// Defaults to `value`.
+pub const XML_NAME_IRI: &Iri = iri!("https://schema.treeldr.org/xml#name");
+
+/// Name of list item elements, when the list is not a record field.
+const ITEM: &str = "item";
+
+/// Error raised when a layout cannot be mapped to XML.
+#[derive(Debug, thiserror::Error)]
+pub enum LayoutError {
+	#[error("layout `{0}` not found")]
+	NotFound(String),
+
+	#[error("layout `{0}` cannot be mapped to XML")]
+	Unsupported(String),
+
+	#[error("non-string field key")]
+	NonStringKey,
+
+	#[error("invalid XML name `{0}`")]
+	InvalidName(String),
+
+	#[error("attribute `{0}` is not a literal")]
+	NonLiteralAttribute(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LoadError {
+	#[error(transparent)]
+	Xml(#[from] quick_xml::Error),
+
+	#[error("invalid UTF-8 text")]
+	InvalidUtf8,
+
+	#[error("expected a single document element")]
+	InvalidDocument,
+
+	#[error(transparent)]
+	Layout(#[from] LayoutError),
+
+	#[error("unexpected element `{0}`")]
+	UnexpectedElement(String),
+
+	#[error("unexpected attribute `{0}`")]
+	UnexpectedAttribute(String),
+
+	#[error("element `{0}` is repeated")]
+	Repeated(String),
+
+	#[error("element `{0}` must contain exactly one variant element")]
+	MissingVariant(String),
+
+	#[error("unknown variant `{0}`")]
+	UnknownVariant(String),
+
+	#[error("`{name}`: invalid {expected} `{value}`")]
+	InvalidLiteral {
+		name: String,
+		value: String,
+		expected: LiteralKind,
+	},
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WriteError {
+	#[error(transparent)]
+	IO(#[from] std::io::Error),
+
+	#[error(transparent)]
+	Layout(#[from] LayoutError),
+
+	#[error("not a decimal number: {0}")]
+	Number(Number),
+}
+
+/// Kind of literal, determined by its layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiteralKind {
+	Unit,
+	Boolean,
+	Number,
+	ByteString,
+	Text,
+}
+
+impl std::fmt::Display for LiteralKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Unit => f.write_str("unit"),
+			Self::Boolean => f.write_str("boolean"),
+			Self::Number => f.write_str("number"),
+			Self::ByteString => f.write_str("hexadecimal byte string"),
+			Self::Text => f.write_str("text"),
+		}
+	}
+}
+
+/// XML element.
+#[derive(Default)]
+struct Element {
+	name: String,
+	attributes: BTreeMap<String, String>,
+	children: Vec<Element>,
+	text: String,
+}
+
+impl Element {
+	fn new(start: &BytesStart) -> Result<Self, LoadError> {
+		let mut attributes = BTreeMap::new();
+		for attribute in start.attributes() {
+			let attribute = attribute.map_err(quick_xml::Error::from)?;
+			if attribute.key.as_namespace_binding().is_none() {
+				let key = utf8(attribute.key.local_name().as_ref())?;
+				attributes.insert(key, attribute.unescape_value()?.into_owned());
+			}
+		}
+
+		Ok(Self {
+			name: utf8(start.local_name().as_ref())?,
+			attributes,
+			..Default::default()
+		})
+	}
+
+	fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+		self.children.iter().filter(move |c| c.name == name)
+	}
+}
+
+fn utf8(bytes: &[u8]) -> Result<String, LoadError> {
+	String::from_utf8(bytes.to_vec()).map_err(|_| LoadError::InvalidUtf8)
+}
+
+/// Parses the document element of the input.
+///
+/// Namespace prefixes are ignored, and elements are identified by their local
+/// name.
+fn parse(input: impl BufRead) -> Result<Element, LoadError> {
+	let mut reader = quick_xml::Reader::from_reader(input);
+	let mut buffer = Vec::new();
+	let mut stack: Vec<Element> = Vec::new();
+	let mut root = None;
+
+	let mut close = |stack: &mut Vec<Element>, element: Element| match stack.last_mut() {
+		Some(parent) => {
+			parent.children.push(element);
+			Ok(())
+		}
+		None if root.is_none() => {
+			root = Some(element);
+			Ok(())
+		}
+		None => Err(LoadError::InvalidDocument),
+	};
+
+	loop {
+		match reader.read_event_into(&mut buffer)? {
+			Event::Start(start) => stack.push(Element::new(&start)?),
+			Event::Empty(start) => close(&mut stack, Element::new(&start)?)?,
+			Event::End(_) => {
+				let element = stack.pop().ok_or(LoadError::InvalidDocument)?;
+				close(&mut stack, element)?
+			}
+			Event::Text(text) => {
+				if let Some(element) = stack.last_mut() {
+					element.text.push_str(&text.unescape()?)
+				}
+			}
+			Event::CData(data) => {
+				if let Some(element) = stack.last_mut() {
+					element.text.push_str(&utf8(&data)?)
+				}
+			}
+			Event::Eof => break,
+			_ => (),
+		}
+
+		buffer.clear()
+	}
+
+	root.ok_or(LoadError::InvalidDocument)
+}
+
+/// Layout-driven XML mapping.
+struct Mapping<'a, L> {
+	layouts: &'a L,
+}
+
+impl<'a, L: LayoutRegistry> Mapping<'a, L> {
+	fn get(&self, layout_ref: &Ref<LayoutType>) -> Result<&'a Layout, LayoutError> {
+		self.layouts
+			.get(layout_ref)
+			.ok_or_else(|| LayoutError::NotFound(layout_ref.id().to_string()))
+	}
+
+	/// Returns the literal kind of the given layout, if it is a literal layout.
+	fn literal_kind(
+		&self,
+		layout_ref: &Ref<LayoutType>,
+	) -> Result<Option<LiteralKind>, LayoutError> {
+		if let Some(preset) = layout_ref
+			.id()
+			.as_iri()
+			.and_then(|iri| PresetLayout::from_iri(iri))
+		{
+			return Ok(Some(match preset {
+				PresetLayout::Unit => LiteralKind::Unit,
+				PresetLayout::Boolean => LiteralKind::Boolean,
+				PresetLayout::Id | PresetLayout::String => LiteralKind::Text,
+				_ => LiteralKind::Number,
+			}));
+		}
+
+		match self.get(layout_ref)? {
+			Layout::Literal(LiteralLayout::Data(layout)) => Ok(Some(match layout {
+				DataLayout::Unit(_) => LiteralKind::Unit,
+				DataLayout::Boolean(_) => LiteralKind::Boolean,
+				DataLayout::Number(_) => LiteralKind::Number,
+				DataLayout::ByteString(_) => LiteralKind::ByteString,
+				DataLayout::TextString(_) => LiteralKind::Text,
+			})),
+			Layout::Literal(LiteralLayout::Id(_)) => Ok(Some(LiteralKind::Text)),
+			_ => Ok(None),
+		}
+	}
+
+	/// Returns the name of the document element for the given layout.
+	fn document_name(&self, layout_ref: &Ref<LayoutType>) -> Result<String, LayoutError> {
+		let name = match self.get(layout_ref) {
+			Ok(layout) => layout
+				.extra_properties()
+				.get(&Term::iri(XML_NAME_IRI.to_owned()))
+				.and_then(Term::as_literal)
+				.map(|l| l.value.clone()),
+			Err(_) => None,
+		};
+
+		check_name(name.unwrap_or_else(|| "value".to_owned()))
+	}
+
+	/// Decodes the content of the given element.
+	fn decode(&self, layout_ref: &Ref<LayoutType>, element: &Element) -> Result<Value, LoadError> {
+		if let Some(kind) = self.literal_kind(layout_ref)? {
+			return parse_literal(kind, &element.name, &element.text);
+		}
+
+		match self.get(layout_ref)? {
+			Layout::Product(layout) => {
+				let mut map = BTreeMap::new();
+				let mut attributes = Vec::new();
+				let mut children = Vec::new();
+
+				for (key, field) in &layout.fields {
+					let key = key.as_str().ok_or(LayoutError::NonStringKey)?;
+					let field_layout = &field.value.layout;
+
+					if is_attribute(&field.extra_properties) {
+						attributes.push(key);
+						if let Some(value) = element.attributes.get(key) {
+							let kind = self
+								.literal_kind(field_layout)?
+								.ok_or_else(|| LayoutError::NonLiteralAttribute(key.to_owned()))?;
+							map.insert(
+								Value::string(key.to_owned()),
+								parse_literal(kind, key, value)?,
+							);
+						}
+					} else {
+						children.push(key);
+						let mut elements = element.children_named(key);
+						match self.get(field_layout).ok() {
+							Some(Layout::List(list)) => {
+								let items = elements
+									.enumerate()
+									.map(|(i, e)| self.decode(item_layout(list, i)?, e))
+									.collect::<Result<Vec<_>, _>>()?;
+								if !items.is_empty() || field.required {
+									map.insert(Value::string(key.to_owned()), Value::List(items));
+								}
+							}
+							_ => {
+								if let Some(e) = elements.next() {
+									if elements.next().is_some() {
+										return Err(LoadError::Repeated(key.to_owned()));
+									}
+
+									map.insert(
+										Value::string(key.to_owned()),
+										self.decode(field_layout, e)?,
+									);
+								}
+							}
+						}
+					}
+				}
+
+				if let Some(name) = element
+					.attributes
+					.keys()
+					.find(|a| !attributes.contains(&a.as_str()))
+				{
+					return Err(LoadError::UnexpectedAttribute(name.clone()));
+				}
+
+				if let Some(child) = element
+					.children
+					.iter()
+					.find(|c| !children.contains(&c.name.as_str()))
+				{
+					return Err(LoadError::UnexpectedElement(child.name.clone()));
+				}
+
+				Ok(Value::Map(map))
+			}
+			Layout::List(list) => element
+				.children
+				.iter()
+				.enumerate()
+				.map(|(i, child)| {
+					if child.name == ITEM {
+						self.decode(item_layout(list, i)?, child)
+					} else {
+						Err(LoadError::UnexpectedElement(child.name.clone()))
+					}
+				})
+				.collect::<Result<Vec<_>, _>>()
+				.map(Value::List),
+			Layout::Sum(layout) => match element.children.as_slice() {
+				[child] => {
+					let variant = layout
+						.variants
+						.iter()
+						.find(|v| v.name == child.name)
+						.ok_or_else(|| LoadError::UnknownVariant(child.name.clone()))?;
+					self.decode(&variant.value.layout, child)
+				}
+				_ => Err(LoadError::MissingVariant(element.name.clone())),
+			},
+			_ => Err(LayoutError::Unsupported(layout_ref.id().to_string()).into()),
+		}
+	}
+
+	/// Writes the given value as an element.
+	fn encode(
+		&self,
+		output: &mut String,
+		name: &str,
+		value: TypedValue,
+		indent: Option<usize>,
+	) -> Result<(), WriteError> {
+		let name = check_name(name.to_owned())?;
+		match value {
+			TypedValue::Always(value) => encode_untyped(output, &name, value, indent),
+			TypedValue::Literal(literal) => {
+				let text = match literal {
+					TypedLiteral::Unit(_, _) => None,
+					TypedLiteral::Boolean(b, _) => Some(b.to_string()),
+					TypedLiteral::Number(n, _) => Some(number_text(n)?),
+					TypedLiteral::ByteString(bytes, _) => Some(hex(&bytes)),
+					TypedLiteral::TextString(s, _) | TypedLiteral::Id(s, _) => Some(s),
+				};
+
+				write_leaf(output, &name, text, indent);
+				Ok(())
+			}
+			TypedValue::Map(map, ty) => {
+				let fields = match self.get(ty.as_casted())? {
+					Layout::Product(layout) => Some(&layout.fields),
+					_ => None,
+				};
+
+				let mut attributes = Vec::new();
+				let mut children = Vec::new();
+				for (key, value) in map {
+					let key = key.as_str().ok_or(LayoutError::NonStringKey)?.to_owned();
+					let attribute = fields
+						.and_then(|f| f.get(&Value::string(key.clone())))
+						.is_some_and(|f| is_attribute(&f.extra_properties));
+
+					if attribute {
+						let text = match value {
+							TypedValue::Literal(TypedLiteral::Unit(_, _)) => String::new(),
+							TypedValue::Literal(TypedLiteral::Boolean(b, _)) => b.to_string(),
+							TypedValue::Literal(TypedLiteral::Number(n, _)) => number_text(n)?,
+							TypedValue::Literal(TypedLiteral::ByteString(bytes, _)) => hex(&bytes),
+							TypedValue::Literal(
+								TypedLiteral::TextString(s, _) | TypedLiteral::Id(s, _),
+							) => s,
+							_ => return Err(LayoutError::NonLiteralAttribute(key).into()),
+						};
+
+						attributes.push((check_name(key)?, text));
+					} else {
+						children.push((key, value));
+					}
+				}
+
+				write_start(output, &name, &attributes, children.is_empty(), indent);
+				if !children.is_empty() {
+					for (key, value) in children {
+						match value {
+							TypedValue::List(items, _) => {
+								for item in items {
+									self.encode(output, &key, item, indent.map(|i| i + 1))?
+								}
+							}
+							value => self.encode(output, &key, value, indent.map(|i| i + 1))?,
+						}
+					}
+
+					write_end(output, &name, indent);
+				}
+
+				Ok(())
+			}
+			TypedValue::List(items, _) => {
+				write_start(output, &name, &[], items.is_empty(), indent);
+				if !items.is_empty() {
+					for item in items {
+						self.encode(output, ITEM, item, indent.map(|i| i + 1))?
+					}
+
+					write_end(output, &name, indent);
+				}
+
+				Ok(())
+			}
+			TypedValue::Variant(inner, ty, index) => {
+				let variant = match self.get(ty.as_casted())? {
+					Layout::Sum(layout) => layout.variants[index as usize].name.clone(),
+					_ => unreachable!(),
+				};
+
+				write_start(output, &name, &[], false, indent);
+				self.encode(output, &variant, *inner, indent.map(|i| i + 1))?;
+				write_end(output, &name, indent);
+				Ok(())
+			}
+		}
+	}
+}
+
+fn item_layout<R>(list: &ListLayout<R>, i: usize) -> Result<&Ref<LayoutType, R>, LoadError> {
+	match list {
+		ListLayout::Unordered(l) => Ok(&l.item.value.layout),
+		ListLayout::Ordered(l) => Ok(&l.node.value.layout),
+		ListLayout::Sized(l) => l
+			.items
+			.get(i)
+			.map(|item| &item.value.layout)
+			.ok_or_else(|| LoadError::UnexpectedElement(ITEM.to_owned())),
+	}
+}
+
+fn is_attribute(extra_properties: &BTreeMap<Term, Term>) -> bool {
+	extra_properties
+		.get(&Term::iri(XML_ATTRIBUTE_IRI.to_owned()))
+		.and_then(Term::as_literal)
+		.is_some_and(|l| l.value == "true")
+}
+
+fn check_name(name: String) -> Result<String, LayoutError> {
+	let mut chars = name.chars();
+	let valid = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+		&& chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'));
+
+	if valid {
+		Ok(name)
+	} else {
+		Err(LayoutError::InvalidName(name))
+	}
+}
+
+fn parse_literal(kind: LiteralKind, name: &str, text: &str) -> Result<Value, LoadError> {
+	let invalid = || LoadError::InvalidLiteral {
+		name: name.to_owned(),
+		value: text.to_owned(),
+		expected: kind,
+	};
+
+	let literal = match kind {
+		LiteralKind::Unit => {
+			if text.trim().is_empty() {
+				Literal::Unit
+			} else {
+				return Err(invalid());
+			}
+		}
+		LiteralKind::Boolean => match text.trim() {
+			"true" | "1" => Literal::Boolean(true),
+			"false" | "0" => Literal::Boolean(false),
+			_ => return Err(invalid()),
+		},
+		LiteralKind::Number => Literal::Number(
+			json_syntax::NumberBuf::from_str(text.trim())
+				.map_err(|_| invalid())?
+				.into(),
+		),
+		LiteralKind::ByteString => {
+			let text = text.trim();
+			if text.len() % 2 != 0 {
+				return Err(invalid());
+			}
+
+			Literal::ByteString(
+				(0..text.len())
+					.step_by(2)
+					.map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+					.collect::<Option<_>>()
+					.ok_or_else(invalid)?,
+			)
+		}
+		LiteralKind::Text => Literal::TextString(text.to_owned()),
+	};
+
+	Ok(Value::Literal(literal))
+}
+
+fn number_text(n: Number) -> Result<String, WriteError> {
+	n.decimal_representation().ok_or(WriteError::Number(n))
+}
+
+fn hex(bytes: &[u8]) -> String {
+	let mut result = String::with_capacity(bytes.len() * 2);
+	for b in bytes {
+		write!(result, "{b:02x}").unwrap();
+	}
+
+	result
+}
+
+fn write_indent(output: &mut String, indent: Option<usize>) {
+	if let Some(i) = indent {
+		for _ in 0..i {
+			output.push_str("  ")
+		}
+	}
+}
+
+fn write_newline(output: &mut String, indent: Option<usize>) {
+	if indent.is_some() {
+		output.push('\n')
+	}
+}
+
+fn write_start(
+	output: &mut String,
+	name: &str,
+	attributes: &[(String, String)],
+	empty: bool,
+	indent: Option<usize>,
+) {
+	write_indent(output, indent);
+	write!(output, "<{name}").unwrap();
+	for (key, value) in attributes {
+		write!(output, " {key}=\"{}\"", escape(value.as_str())).unwrap();
+	}
+
+	if empty {
+		output.push_str("/>");
+	} else {
+		output.push('>');
+	}
+
+	write_newline(output, indent)
+}
+
+fn write_end(output: &mut String, name: &str, indent: Option<usize>) {
+	write_indent(output, indent);
+	write!(output, "</{name}>").unwrap();
+	write_newline(output, indent)
+}
+
+fn write_leaf(output: &mut String, name: &str, text: Option<String>, indent: Option<usize>) {
+	write_indent(output, indent);
+	match text {
+		Some(text) => write!(output, "<{name}>{}</{name}>", escape(text.as_str())).unwrap(),
+		None => write!(output, "<{name}/>").unwrap(),
+	}
+
+	write_newline(output, indent)
+}
+
+/// Writes a value matched by the top layout, mapping maps to child elements
+/// and lists to `item` elements.
+fn encode_untyped(
+	output: &mut String,
+	name: &str,
+	value: Value,
+	indent: Option<usize>,
+) -> Result<(), WriteError> {
+	match value {
+		Value::Literal(literal) => {
+			let text = match literal {
+				Literal::Unit => None,
+				Literal::Boolean(b) => Some(b.to_string()),
+				Literal::Number(n) => Some(number_text(n)?),
+				Literal::ByteString(bytes) => Some(hex(&bytes)),
+				Literal::TextString(s) => Some(s),
+			};
+
+			write_leaf(output, name, text, indent);
+		}
+		Value::Map(map) => {
+			write_start(output, name, &[], map.is_empty(), indent);
+			if !map.is_empty() {
+				for (key, value) in map {
+					let key =
+						check_name(key.as_str().ok_or(LayoutError::NonStringKey)?.to_owned())?;
+					encode_untyped(output, &key, value, indent.map(|i| i + 1))?
+				}
+
+				write_end(output, name, indent);
+			}
+		}
+		Value::List(items) => {
+			write_start(output, name, &[], items.is_empty(), indent);
+			if !items.is_empty() {
+				for item in items {
+					encode_untyped(output, ITEM, item, indent.map(|i| i + 1))?
+				}
+
+				write_end(output, name, indent);
+			}
+		}
+	}
+
+	Ok(())
+}
+
+/// Reads an XML document matching the given layout.
+pub fn load_typed(
+	layouts: &impl LayoutRegistry,
+	layout_ref: &Ref<LayoutType>,
+	input: impl BufRead,
+) -> Result<Value, LoadError> {
+	let element = parse(input)?;
+	Mapping { layouts }.decode(layout_ref, &element)
+}
+
+/// Writes the given value as an XML document.
+pub fn write_typed(
+	layouts: &impl LayoutRegistry,
+	value: TypedValue,
+	pretty: bool,
+	mut output: impl Write,
+) -> Result<(), WriteError> {
+	let mapping = Mapping { layouts };
+	let name = match value.type_() {
+		Some(layout_ref) => mapping.document_name(layout_ref)?,
+		None => "value".to_owned(),
+	};
+
+	let mut document = String::new();
+	mapping.encode(&mut document, &name, value, pretty.then_some(0))?;
+	write!(output, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+	if pretty {
+		writeln!(output)?;
+	}
+
+	write!(output, "{document}")?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use treeldr_layouts::{Layouts, Prelude};
+
+	use super::*;
+	use crate::format::tree::tests::{build_layout, typed};
+
+	const PERSON: &str = r#"{
+		"prefixes": {
+			"xml": "https://schema.treeldr.org/xml#",
+			"schema": "https://schema.org/"
+		},
+		"type": "record",
+		"extra": { "xml:name": "person" },
+		"fields": {
+			"id": {
+				"value": "https://treeldr.org/prelude#string",
+				"property": "schema:identifier",
+				"extra": { "xml:attribute": "true" }
+			},
+			"age": {
+				"value": "https://treeldr.org/prelude#u32",
+				"property": "schema:age",
+				"extra": { "xml:attribute": "true" }
+			},
+			"name": {
+				"value": "https://treeldr.org/prelude#string",
+				"property": "schema:name"
+			},
+			"tag": {
+				"intro": [],
+				"value": {
+					"layout": {
+						"type": "set",
+						"item": {
+							"value": "https://treeldr.org/prelude#string",
+							"property": "schema:keywords"
+						}
+					},
+					"input": "_:self"
+				},
+				"required": true
+			},
+			"contact": {
+				"value": {
+					"type": "sum",
+					"variants": {
+						"email": { "value": "https://treeldr.org/prelude#string" },
+						"phone": { "value": { "type": "number", "datatype": "http://www.w3.org/2001/XMLSchema#integer" } }
+					}
+				},
+				"property": "schema:contactPoint"
+			}
+		}
+	}"#;
+
+	fn load(layouts: &impl LayoutRegistry, layout_ref: &Ref<LayoutType>, input: &str) -> Value {
+		load_typed(layouts, layout_ref, input.as_bytes()).unwrap()
+	}
+
+	fn write(layouts: &impl LayoutRegistry, value: TypedValue, pretty: bool) -> String {
+		let mut output = Vec::new();
+		write_typed(layouts, value, pretty, &mut output).unwrap();
+		String::from_utf8(output).unwrap()
+	}
+
+	fn field<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+		match value {
+			Value::Map(map) => map.get(&Value::string(key.to_owned())),
+			_ => None,
+		}
+	}
+
+	/// Loads the given document, writes it back and checks that the output
+	/// is the same document.
+	fn round_trip(layouts: &Layouts, layout_ref: &Ref<LayoutType>, document: &str) -> Value {
+		let layouts = layouts.with(Prelude);
+		let value = load(&layouts, layout_ref, document);
+		let output = write(&layouts, typed(&layouts, layout_ref, &value), false);
+		assert_eq!(
+			output,
+			format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>{document}")
+		);
+		assert_eq!(load(&layouts, layout_ref, &output), value);
+		value
+	}
+
+	#[test]
+	fn attributes() {
+		let (layouts, layout_ref) = build_layout(PERSON);
+		let value = round_trip(
+			&layouts,
+			&layout_ref,
+			r#"<person age="42" id="bob"><name>Bob</name></person>"#,
+		);
+
+		assert_eq!(
+			field(&value, "age"),
+			Some(&Value::Literal(Literal::Number(
+				json_syntax::NumberBuf::from_str("42").unwrap().into()
+			)))
+		)
+	}
+
+	#[test]
+	fn repeated_elements() {
+		let (layouts, layout_ref) = build_layout(PERSON);
+		let value = round_trip(
+			&layouts,
+			&layout_ref,
+			"<person><name>Bob</name><tag>a</tag><tag>b</tag></person>",
+		);
+
+		assert_eq!(
+			field(&value, "tag"),
+			Some(&Value::List(vec![
+				Value::string("a".to_owned()),
+				Value::string("b".to_owned())
+			]))
+		)
+	}
+
+	#[test]
+	fn variants() {
+		let (layouts, layout_ref) = build_layout(PERSON);
+		round_trip(
+			&layouts,
+			&layout_ref,
+			"<person><contact><email>bob@example.org</email></contact><name>Bob</name></person>",
+		);
+		round_trip(
+			&layouts,
+			&layout_ref,
+			"<person><contact><phone>5551234</phone></contact><name>Bob</name></person>",
+		);
+
+		let error = load_typed(
+			&layouts.with(Prelude),
+			&layout_ref,
+			"<person><contact><fax>0</fax></contact></person>".as_bytes(),
+		)
+		.unwrap_err();
+		assert!(matches!(error, LoadError::UnknownVariant(name) if name == "fax"))
+	}
+
+	#[test]
+	fn escaping() {
+		let (layouts, layout_ref) = build_layout(PERSON);
+		let value = round_trip(
+			&layouts,
+			&layout_ref,
+			r#"<person id="&quot;b&amp;b&quot;"><name>Bob &lt;&amp;&gt; &apos;Jr&apos;</name></person>"#,
+		);
+
+		assert_eq!(
+			field(&value, "id"),
+			Some(&Value::string("\"b&b\"".to_owned()))
+		);
+		assert_eq!(
+			field(&value, "name"),
+			Some(&Value::string("Bob <&> 'Jr'".to_owned()))
+		)
+	}
+
+	#[test]
+	fn empty_lists() {
+		let (layouts, layout_ref) = build_layout(PERSON);
+		let value = round_trip(&layouts, &layout_ref, "<person><name>Bob</name></person>");
+		assert_eq!(field(&value, "tag"), Some(&Value::List(Vec::new())));
+
+		let (layouts, layout_ref) = build_layout(
+			r#"{
+				"type": "set",
+				"item": {
+					"value": "https://treeldr.org/prelude#string",
+					"property": "https://example.org/#item"
+				}
+			}"#,
+		);
+		let value = round_trip(&layouts, &layout_ref, "<value/>");
+		assert_eq!(value, Value::List(Vec::new()));
+		round_trip(
+			&layouts,
+			&layout_ref,
+			"<value><item>a</item><item>b</item></value>",
+		);
+	}
+}
@@ -6,17 +6,32 @@ use codespan_reporting::{
 		termcolor::{ColorChoice, StandardStream},
 	},
 };
-use rdf_types::{generator, Generator, Term};
+use iref::IriBuf;
+use json_syntax::Print;
+use rdf_types::{
+	dataset::{BTreeDataset, PatternMatchingDataset},
+	generator, BlankIdBuf, Generator, Id, Quad, Term,
+};
 use std::{
 	fs,
-	io::{self, BufReader},
+	io::{self, BufRead, BufReader},
 	path::PathBuf,
 	process::ExitCode,
 };
-use treeldr_layouts::{layout::LayoutType, LayoutRegistry, Layouts, Prelude, Ref};
+use treeldr_layouts::{
+	distill::de::{self, Selection},
+	layout::LayoutType,
+	value::cbor::get_layout_tag,
+	LayoutRegistry, Layouts, Prelude, Ref,
+};
 
+mod check;
+mod fmt;
 mod format;
-use format::{RDFFormat, TreeFormat};
+use format::{
+	tree::{compression::Codebook, Style},
+	RDFFormat, TreeFormat,
+};
 mod rdf;
 
 #[derive(clap::Parser)]
@@ -39,6 +54,16 @@ struct Args {
 	#[arg(short, long, global = true)]
 	no_prelude: bool,
 
+	/// Loads remote JSON-LD documents starting with the given IRI prefix from
+	/// a local directory, with the `<IRI>=<DIR>` syntax.
+	#[arg(long, value_parser = format::rdf::parse_mount_point, global = true)]
+	context_mount: Vec<(IriBuf, PathBuf)>,
+
+	/// Loads remote JSON-LD documents from the given cache directory, where
+	/// the document `https://host/path` is stored at `host/path`.
+	#[arg(long, global = true)]
+	context_cache: Option<PathBuf>,
+
 	#[command(subcommand)]
 	command: Option<Command>,
 }
@@ -78,8 +103,39 @@ enum Command {
 		subjects: Vec<Term>,
 
 		/// Pretty print the output.
-		#[arg(short, long)]
+		#[arg(short, long, conflicts_with = "batch")]
 		pretty: bool,
+
+		/// Write the output JSON in canonical form (RFC 8785).
+		///
+		/// Object keys are sorted and numbers are normalized, so that equal
+		/// values are written byte-identically.
+		#[arg(long, conflicts_with_all = ["pretty", "compress"])]
+		canonical: bool,
+
+		/// Hydrate each subject on its own, writing one record per subject.
+		///
+		/// JSON records are written one per line, YAML records as the
+		/// documents of a YAML stream, and CBOR records are concatenated.
+		/// Errors are reported for each record without stopping the batch.
+		#[arg(long)]
+		batch: bool,
+
+		/// Read the batch subjects from the given file, one per line.
+		#[arg(long, requires = "batch")]
+		subjects_file: Option<PathBuf>,
+
+		/// Compress the CBOR value, replacing IRIs with their integer code.
+		///
+		/// Codes are taken from the `--codebook`, or derived from the IRIs
+		/// used by the layouts.
+		#[arg(long)]
+		compress: bool,
+
+		/// Codebook used for compression, as a JSON object mapping IRIs to
+		/// their integer code.
+		#[arg(long, requires = "compress")]
+		codebook: Option<PathBuf>,
 	},
 
 	/// Deserializes a tree value into an RDF dataset.
@@ -99,6 +155,72 @@ enum Command {
 		/// Otherwise, this argument is required.
 		#[arg(short, long, value_parser = rdf::parse_term)]
 		layout: Option<Term>,
+
+		/// Read the input as a stream of records, dehydrated one at a time.
+		///
+		/// JSON records are newline-delimited, or delimited by record
+		/// separators in a JSON text sequence. YAML records are the documents
+		/// of a YAML stream, split on the `---` and `...` marker lines, and
+		/// CBOR records are concatenated. The output format must be N-Quads
+		/// or N-Triples. Errors are reported for each record without stopping
+		/// the batch.
+		#[arg(long)]
+		batch: bool,
+
+		/// Write the canonical form of the output dataset (RDFC-1.0).
+		///
+		/// Blank nodes are relabeled deterministically and quads are sorted,
+		/// so that equivalent datasets are written byte-identically. The
+		/// output format must be N-Quads or N-Triples.
+		#[arg(long, conflicts_with = "batch")]
+		canonical: bool,
+
+		/// How anonymous resources are identified.
+		///
+		/// `blank` uses fresh blank node identifiers, `uuid` random
+		/// `urn:uuid:` IRIs, and `genid` random Skolem IRIs under the
+		/// authority of `--id-base`. `hash` uses IRIs derived from the
+		/// content of each record, prefixed by `--id-base`, so that
+		/// dehydrating the same record twice gives the same IRIs.
+		#[arg(long, value_parser = rdf::IdStrategy::parser(), default_value = "blank")]
+		ids: rdf::IdStrategy,
+
+		/// Base IRI of the identifiers generated by the `genid` and `hash`
+		/// strategies.
+		#[arg(long, value_parser = parse_iri, required_if_eq_any([("ids", "genid"), ("ids", "hash")]))]
+		id_base: Option<IriBuf>,
+
+		/// Decompress the CBOR value, replacing integer codes with their IRI.
+		///
+		/// Codes are taken from the `--codebook`, or derived from the IRIs
+		/// used by the layouts.
+		#[arg(long)]
+		decompress: bool,
+
+		/// Codebook used for decompression, as a JSON object mapping IRIs to
+		/// their integer code.
+		#[arg(long, requires = "decompress")]
+		codebook: Option<PathBuf>,
+	},
+
+	/// Checks the layouts, reporting every error found.
+	///
+	/// Each layout file is parsed and built, and every referenced layout
+	/// must be defined, either in the given files or in the prelude. Exits
+	/// with a non-zero status if any error is found.
+	Check,
+
+	/// Formats the layouts, rewriting the files in place.
+	///
+	/// Each layout is normalized (full IRIs are compacted using the declared
+	/// prefixes, default values are omitted, dataset quads equivalent to a
+	/// field `property` are replaced by it) and its entries are sorted in
+	/// canonical order.
+	Fmt {
+		/// Do not rewrite the files, only print the path of those that are not
+		/// formatted. Exits with a non-zero status if any is found.
+		#[arg(long)]
+		check: bool,
 	},
 
 	/// Convert a tree value or RDF dataset without changing its shape.
@@ -106,6 +228,12 @@ enum Command {
 		#[command(subcommand)]
 		command: Convert,
 	},
+
+	/// Generate code from a layout.
+	Generate {
+		#[command(subcommand)]
+		command: Generate,
+	},
 }
 
 #[derive(clap::Subcommand)]
@@ -123,6 +251,10 @@ pub enum Convert {
 		/// Pretty print the output.
 		#[arg(short, long)]
 		pretty: bool,
+
+		/// Write the output JSON in canonical form (RFC 8785).
+		#[arg(long, conflicts_with = "pretty")]
+		canonical: bool,
 	},
 
 	/// Convert an RDF dataset into another format.
@@ -134,7 +266,154 @@ pub enum Convert {
 		/// Format of the output RDF dataset.
 		#[arg(short, long, value_parser = RDFFormat::parser(), default_value = "n-quads")]
 		output: RDFFormat,
+
+		/// Write the canonical form of the output dataset (RDFC-1.0).
+		///
+		/// Blank nodes are relabeled deterministically and quads are sorted,
+		/// so that equivalent datasets are written byte-identically. The
+		/// output format must be N-Quads or N-Triples.
+		#[arg(long)]
+		canonical: bool,
+	},
+}
+
+#[derive(clap::Subcommand)]
+pub enum Generate {
+	/// Generate TypeScript type declarations.
+	Typescript {
+		/// Layout to generate.
+		///
+		/// If only one layout file is given with a single top-level layout,
+		/// this layout will be selected by TreeLDR by default.
+		/// Otherwise, this argument is required.
+		#[arg(short, long, value_parser = rdf::parse_term)]
+		layout: Option<Term>,
+
+		/// Also generate runtime validators.
+		#[arg(long)]
+		validators: bool,
+	},
+
+	/// Generate Python models.
+	Python {
+		/// Layout to generate.
+		///
+		/// If only one layout file is given with a single top-level layout,
+		/// this layout will be selected by TreeLDR by default.
+		/// Otherwise, this argument is required.
+		#[arg(short, long, value_parser = rdf::parse_term)]
+		layout: Option<Term>,
+
+		/// Kind of generated models.
+		#[arg(short, long, value_parser = python_model_parser(), default_value = "pydantic")]
+		model: treeldr_gen_py::Model,
+	},
+
+	/// Generate Go type declarations.
+	Go {
+		/// Layout to generate.
+		///
+		/// If only one layout file is given with a single top-level layout,
+		/// this layout will be selected by TreeLDR by default.
+		/// Otherwise, this argument is required.
+		#[arg(short, long, value_parser = rdf::parse_term)]
+		layout: Option<Term>,
+
+		/// Name of the generated package.
+		#[arg(short, long, default_value = "model")]
+		package: String,
+	},
+
+	/// Generate a GraphQL schema.
+	Graphql {
+		/// Layout to generate.
+		///
+		/// If only one layout file is given with a single top-level layout,
+		/// this layout will be selected by TreeLDR by default.
+		/// Otherwise, this argument is required.
+		#[arg(short, long, value_parser = rdf::parse_term)]
+		layout: Option<Term>,
+
+		/// Generate a JavaScript module exporting the schema along with
+		/// resolvers scaffolding.
+		#[arg(long)]
+		resolvers: bool,
+	},
+
+	/// Generate Protocol Buffers message definitions.
+	Protobuf {
+		/// Layout to generate.
+		///
+		/// If only one layout file is given with a single top-level layout,
+		/// this layout will be selected by TreeLDR by default.
+		/// Otherwise, this argument is required.
+		#[arg(short, long, value_parser = rdf::parse_term)]
+		layout: Option<Term>,
+
+		/// Package of the generated definitions.
+		#[arg(short, long)]
+		package: Option<String>,
+	},
+
+	/// Generate a CDDL description of the layout CBOR values.
+	Cddl {
+		/// Layout to generate.
+		///
+		/// If only one layout file is given with a single top-level layout,
+		/// this layout will be selected by TreeLDR by default.
+		/// Otherwise, this argument is required.
+		#[arg(short, long, value_parser = rdf::parse_term)]
+		layout: Option<Term>,
+	},
+
+	/// Generate SQL `CREATE TABLE` statements storing the layout records.
+	Sql {
+		/// Layout to generate.
+		///
+		/// If only one layout file is given with a single top-level layout,
+		/// this layout will be selected by TreeLDR by default.
+		/// Otherwise, this argument is required.
+		#[arg(short, long, value_parser = rdf::parse_term)]
+		layout: Option<Term>,
+
+		/// SQL dialect.
+		#[arg(short, long, value_parser = sql_dialect_parser(), default_value = "sqlite")]
+		dialect: treeldr_gen_sql::Dialect,
 	},
+
+	/// Generate an OpenAPI document describing the layouts JSON values.
+	Openapi {
+		/// Layouts to describe.
+		///
+		/// If no layout is given, all the top-level layouts of the layout
+		/// files are described.
+		#[arg(short, long, value_parser = rdf::parse_term)]
+		layout: Vec<Term>,
+
+		/// Title of the document.
+		#[arg(short, long, default_value = "TreeLDR layouts")]
+		title: String,
+
+		/// Version of the document.
+		#[arg(long, default_value = "0.0.0")]
+		version: String,
+	},
+}
+
+fn python_model_parser() -> impl clap::builder::TypedValueParser<Value = treeldr_gen_py::Model> {
+	use clap::builder::TypedValueParser;
+	clap::builder::PossibleValuesParser::new(treeldr_gen_py::Model::POSSIBLE_VALUES)
+		.map(|s| treeldr_gen_py::Model::new(&s).unwrap())
+}
+
+fn parse_iri(input: &str) -> Result<IriBuf, iref::InvalidIri<String>> {
+	IriBuf::new(input.to_owned())
+}
+
+fn sql_dialect_parser() -> impl clap::builder::TypedValueParser<Value = treeldr_gen_sql::Dialect> {
+	use clap::builder::TypedValueParser;
+	clap::builder::PossibleValuesParser::new(treeldr_gen_sql::Dialect::POSSIBLE_VALUES)
+		.map(|s| treeldr_gen_sql::Dialect::new(&s).unwrap())
 }
 
 fn main() -> ExitCode {
@@ -152,65 +431,142 @@ fn main() -> ExitCode {
 	match run(&mut files, args) {
 		Ok(()) => ExitCode::SUCCESS,
 		Err(e) => {
-			let diagnostic = e.into_diagnostic();
-			let writer = StandardStream::stderr(ColorChoice::Always);
-			let config = codespan_reporting::term::Config::default();
-			term::emit(&mut writer.lock(), &config, &files, &diagnostic).unwrap();
+			report(&files, e.into_diagnostic());
 			ExitCode::FAILURE
 		}
 	}
 }
 
+fn report(files: &SimpleFiles<String, String>, diagnostic: Diagnostic<usize>) {
+	let writer = StandardStream::stderr(ColorChoice::Always);
+	let config = codespan_reporting::term::Config::default();
+	term::emit(&mut writer.lock(), &config, files, &diagnostic).unwrap();
+}
+
 enum DefaultLayoutRef {
 	None,
 	Some(Ref<LayoutType>),
-	Ambiguous,
+	Ambiguous(Vec<Ref<LayoutType>>),
 }
 
 impl DefaultLayoutRef {
 	fn set(&mut self, layout_ref: Ref<LayoutType>) {
-		match self {
+		match std::mem::replace(self, Self::None) {
 			Self::None => *self = Self::Some(layout_ref),
-			Self::Some(_) => *self = Self::Ambiguous,
-			_ => (),
+			Self::Some(first) => *self = Self::Ambiguous(vec![first, layout_ref]),
+			Self::Ambiguous(mut layout_refs) => {
+				layout_refs.push(layout_ref);
+				*self = Self::Ambiguous(layout_refs)
+			}
+		}
+	}
+
+	/// Returns the preferred layouts if any, or all the top-level layouts.
+	fn all(self, preferred_layouts: Vec<Term>) -> Result<Vec<Ref<LayoutType>>, Error> {
+		if preferred_layouts.is_empty() {
+			match self {
+				Self::None => Err(Error::NoDefaultLayout),
+				Self::Some(layout_ref) => Ok(vec![layout_ref]),
+				Self::Ambiguous(layout_refs) => Ok(layout_refs),
+			}
+		} else {
+			Ok(preferred_layouts.into_iter().map(Ref::new).collect())
 		}
 	}
 
-	fn get(self, preferred_layout: Option<Term>) -> Result<Ref<LayoutType>, Error> {
+	/// Returns the preferred layout if any, or the default layout.
+	///
+	/// If the default layout is ambiguous, the top-level layout with the given
+	/// CBOR tag is selected.
+	fn get_tagged(
+		&self,
+		layouts: &impl LayoutRegistry,
+		preferred_layout: Option<Term>,
+		tag: Option<u64>,
+	) -> Result<Ref<LayoutType>, Error> {
+		match (self, preferred_layout, tag) {
+			(Self::Ambiguous(layout_refs), None, Some(tag)) => {
+				let mut selected = layout_refs.iter().filter(|layout_ref| {
+					matches!(
+						get_layout_tag(&(), &(), layouts, layout_ref),
+						Ok(Some(t)) if t == tag
+					)
+				});
+
+				match (selected.next(), selected.next()) {
+					(Some(layout_ref), None) => Ok(layout_ref.clone()),
+					_ => Err(Error::AmbiguousDefaultLayout),
+				}
+			}
+			(this, preferred_layout, _) => this.get(preferred_layout),
+		}
+	}
+
+	fn get(&self, preferred_layout: Option<Term>) -> Result<Ref<LayoutType>, Error> {
 		match preferred_layout {
 			Some(term) => Ok(Ref::new(term)),
 			None => match self {
 				Self::None => Err(Error::NoDefaultLayout),
-				Self::Some(layout_ref) => Ok(layout_ref),
-				Self::Ambiguous => Err(Error::AmbiguousDefaultLayout),
+				Self::Some(layout_ref) => Ok(layout_ref.clone()),
+				Self::Ambiguous(_) => Err(Error::AmbiguousDefaultLayout),
 			},
 		}
 	}
 }
 
 fn run(files: &mut SimpleFiles<String, String>, args: Args) -> Result<(), Error> {
+	if let Some(Command::Check) = args.command {
+		let prelude = args.prelude();
+		return check::check(files, args.layouts.into_iter().chain(args.include), prelude);
+	}
+
+	if let Some(Command::Fmt { check }) = args.command {
+		return fmt::fmt(files, args.layouts.into_iter().chain(args.include), check);
+	}
+
 	let mut layouts = Layouts::new();
 	let mut generator = generator::Blank::new();
 	let prelude = args.prelude();
 	let mut default_layout = DefaultLayoutRef::None;
+	let mut rdf_options = format::rdf::Options::default();
+	for (prefix, path) in args.context_mount {
+		rdf_options.contexts.mount(prefix, path)
+	}
+
+	if let Some(path) = args.context_cache {
+		rdf_options.contexts.set_cache(path)
+	}
+
 	for filename in args.layouts {
 		let content = fs::read_to_string(&filename).map_err(Error::IO)?;
 		let file_id = files.add(filename.to_string_lossy().into_owned(), content);
-		let layout_ref = load_layout(files, file_id, &mut layouts, &mut generator)?;
+		let layout_ref = load_layout(
+			files,
+			file_id,
+			&mut layouts,
+			&mut generator,
+			&mut rdf_options.prefixes,
+		)?;
 		default_layout.set(layout_ref);
 	}
 
 	for filename in args.include {
 		let content = fs::read_to_string(&filename).map_err(Error::IO)?;
 		let file_id = files.add(filename.to_string_lossy().into_owned(), content);
-		load_layout(files, file_id, &mut layouts, &mut generator)?;
+		load_layout(
+			files,
+			file_id,
+			&mut layouts,
+			&mut generator,
+			&mut rdf_options.prefixes,
+		)?;
 	}
 
 	let layouts = layouts.with(prelude);
 
 	match args.command {
 		None => Ok(()),
-		Some(command) => command.run(layouts, default_layout),
+		Some(command) => command.run(layouts, default_layout, &rdf_options),
 	}
 }
 
@@ -219,6 +575,7 @@ impl Command {
 		self,
 		layouts: impl LayoutRegistry,
 		default_layout: DefaultLayoutRef,
+		rdf_options: &format::rdf::Options,
 	) -> Result<(), Error> {
 		match self {
 			Self::Hydrate {
@@ -227,57 +584,523 @@ impl Command {
 				layout,
 				subjects,
 				pretty,
+				canonical,
+				batch,
+				subjects_file,
+				compress,
+				codebook,
 			} => {
 				let layout_ref = default_layout.get(layout)?;
+				let codebook = load_codebook(&layouts, [&layout_ref], compress, codebook)?;
+				if codebook.is_some() && !output.supports_compression() {
+					return Err(Error::CreateTree(format::tree::WriteError::Compression(
+						output,
+					)));
+				}
+
 				let stdin = BufReader::new(io::stdin());
-				let input = input.load(stdin).map_err(Error::LoadRdf)?.into_indexed();
+				let input = input
+					.load(stdin, rdf_options)
+					.map_err(Error::LoadRdf)?
+					.into_indexed();
+
+				if batch {
+					return hydrate_batch(
+						&layouts,
+						&input,
+						&layout_ref,
+						subjects,
+						subjects_file,
+						HydrateOutput {
+							format: output,
+							canonical,
+							codebook: codebook.as_ref(),
+						},
+					);
+				}
+
 				let output_data =
 					treeldr_layouts::distill::hydrate(&layouts, &input, &layout_ref, &subjects)
 						.map_err(Error::Hydrate)?;
-				output
-					.write_typed(&layouts, output_data, pretty, io::stdout())
-					.map_err(Error::CreateTree)
+				match &codebook {
+					Some(codebook) => {
+						output.write_compressed(&layouts, codebook, output_data, io::stdout())
+					}
+					None => output.write_typed(
+						&layouts,
+						output_data,
+						Style::new(pretty, canonical),
+						io::stdout(),
+					),
+				}
+				.map_err(Error::CreateTree)
 			}
 			Self::Dehydrate {
 				input,
 				output,
 				layout,
+				batch,
+				decompress,
+				codebook,
+				canonical,
+				ids,
+				id_base,
 			} => {
-				let layout_ref = default_layout.get(layout)?;
-				let stdin = BufReader::new(io::stdin());
-				let input = input.load(stdin).map_err(Error::LoadTree)?;
-				let (output_data, _) = treeldr_layouts::distill::dehydrate(
-					&layouts,
-					&input,
-					&layout_ref,
-					Default::default(),
-				)
-				.map_err(Error::Dehydrate)?;
-				output.write(output_data, io::stdout()).map_err(Error::IO)
+				// The base IRI is required by clap when needed.
+				let ids = rdf::Identifiers::new(ids, id_base).unwrap();
+				let mut stdin = BufReader::new(io::stdin());
+				if decompress && !input.supports_compression() {
+					return Err(Error::LoadTree(format::tree::LoadError::Compression(input)));
+				}
+
+				if batch {
+					// Without `--layout`, each CBOR record selects its layout
+					// with its own tag.
+					let layout_refs = match &default_layout {
+						DefaultLayoutRef::Ambiguous(layout_refs)
+							if layout.is_none() && matches!(input, TreeFormat::Cbor) =>
+						{
+							layout_refs.clone()
+						}
+						_ => vec![default_layout.get(layout)?],
+					};
+
+					let codebook = load_codebook(&layouts, &layout_refs, decompress, codebook)?;
+					return dehydrate_batch(
+						&layouts,
+						|tag| match layout_refs.as_slice() {
+							[layout_ref] => Ok(layout_ref.clone()),
+							_ => default_layout.get_tagged(&layouts, None, tag),
+						},
+						input,
+						stdin,
+						codebook.as_ref(),
+						DehydrateOutput {
+							format: output,
+							rdf_options,
+							ids: &ids,
+						},
+					);
+				}
+
+				let tag = input.peek_tag(&mut stdin).map_err(Error::LoadTree)?;
+				let layout_ref = default_layout.get_tagged(&layouts, layout, tag)?;
+				let codebook = load_codebook(&layouts, [&layout_ref], decompress, codebook)?;
+				let mut inputs = input
+					.load_typed_all(&layouts, &layout_ref, stdin)
+					.map_err(Error::LoadTree)?;
+				if let Some(codebook) = &codebook {
+					inputs = inputs
+						.into_iter()
+						.map(|(value, selection)| {
+							let value = codebook
+								.decompress(&layouts, &layout_ref, &selection, value)
+								.map_err(Error::Decompress)?;
+							Ok((value, selection))
+						})
+						.collect::<Result<_, Error>>()?
+				}
+				let output_data = dehydrate_all(&layouts, inputs, &layout_ref, &ids)?;
+				if canonical {
+					output.write_canonical(output_data, io::stdout())
+				} else {
+					output.write(output_data, rdf_options, io::stdout())
+				}
+				.map_err(Error::WriteRdf)
+			}
+			Self::Check => unreachable!("layouts are checked before being loaded"),
+			Self::Fmt { .. } => unreachable!("layouts are formatted before being loaded"),
+			Self::Convert { command } => command.run(rdf_options),
+			Self::Generate { command } => command.run(layouts, default_layout),
+		}
+	}
+}
+
+/// Returns the compression codebook, if (de)compression is `enabled`.
+///
+/// The codebook is loaded from the given file if any, or derived from the
+/// layouts reachable from `layout_refs`.
+fn load_codebook<'a>(
+	layouts: &'a impl LayoutRegistry,
+	layout_refs: impl IntoIterator<Item = &'a Ref<LayoutType>>,
+	enabled: bool,
+	path: Option<PathBuf>,
+) -> Result<Option<Codebook>, Error> {
+	if !enabled {
+		return Ok(None);
+	}
+
+	match path {
+		Some(path) => Codebook::load(path),
+		None => Codebook::from_layouts(layouts, layout_refs),
+	}
+	.map(Some)
+	.map_err(Error::Codebook)
+}
+
+/// Dehydrates every given tree value, merging the resulting datasets.
+///
+/// When there is more than one value, the blank node identifiers of the `i`th
+/// value are prefixed with `d{i}_` so that values do not share anonymous
+/// resources.
+fn dehydrate_all(
+	layouts: &impl LayoutRegistry,
+	values: Vec<(treeldr_layouts::Value, Selection)>,
+	layout_ref: &Ref<LayoutType>,
+	ids: &rdf::Identifiers,
+) -> Result<BTreeDataset, Error> {
+	let mut result = BTreeDataset::new();
+	let count = values.len();
+
+	for (i, (value, selection)) in values.into_iter().enumerate() {
+		let dataset = dehydrate(layouts, &value, selection, layout_ref, ids)?;
+
+		if count == 1 {
+			return Ok(dataset);
+		}
+
+		result.extend(relabel(dataset, i));
+	}
+
+	Ok(result)
+}
+
+/// Dehydrates the given tree value, identifying its anonymous resources
+/// using the given strategy.
+fn dehydrate(
+	layouts: &impl LayoutRegistry,
+	value: &treeldr_layouts::Value,
+	selection: Selection,
+	layout_ref: &Ref<LayoutType>,
+	ids: &rdf::Identifiers,
+) -> Result<BTreeDataset, Error> {
+	use treeldr_layouts::distill::de::id;
+	let options = de::Options::default().with_selection(selection);
+
+	let (dataset, _) = match ids {
+		rdf::Identifiers::Blank | rdf::Identifiers::Hash(_) => {
+			treeldr_layouts::distill::dehydrate(layouts, value, layout_ref, options)
+		}
+		rdf::Identifiers::Uuid => treeldr_layouts::distill::dehydrate(
+			layouts,
+			value,
+			layout_ref,
+			options.with_generated_inputs().with_generator(id::Uuid),
+		),
+		rdf::Identifiers::Genid(base) => treeldr_layouts::distill::dehydrate(
+			layouts,
+			value,
+			layout_ref,
+			options
+				.with_generated_inputs()
+				.with_generator(id::Genid::new(base)),
+		),
+	}
+	.map_err(Error::Dehydrate)?;
+
+	match ids {
+		rdf::Identifiers::Hash(prefix) => {
+			let (quads, _) = treeldr_layouts::canonical::hash_blank_nodes(dataset, prefix)
+				.map_err(Error::Canonicalization)?;
+			Ok(quads.into_iter().collect())
+		}
+		_ => Ok(dataset),
+	}
+}
+
+/// Prefixes the blank node identifiers of the given dataset with `d{i}_`.
+fn relabel(dataset: BTreeDataset, i: usize) -> impl Iterator<Item = Quad> {
+	let relabel = move |term: Term| match term {
+		Term::Id(Id::Blank(b)) => {
+			Term::blank(BlankIdBuf::new(format!("_:d{i}_{}", b.suffix())).unwrap())
+		}
+		term => term,
+	};
+
+	dataset
+		.into_iter()
+		.map(move |Quad(s, p, o, g)| Quad(relabel(s), relabel(p), relabel(o), g.map(relabel)))
+}
+
+/// Reports the error of the given batch record, without interrupting the
+/// batch.
+fn report_record(failures: &mut usize, n: usize, e: Error) {
+	let mut diagnostic = e.into_diagnostic();
+	diagnostic.message = format!("record {n}: {}", diagnostic.message);
+	report(&SimpleFiles::new(), diagnostic);
+	*failures += 1
+}
+
+/// Output of [`dehydrate_batch`].
+struct DehydrateOutput<'a> {
+	/// Output RDF format, which must be line based.
+	format: RDFFormat,
+
+	rdf_options: &'a format::rdf::Options,
+
+	/// How anonymous resources are identified.
+	ids: &'a rdf::Identifiers,
+}
+
+/// Dehydrates the records of the standard input one at a time, writing their
+/// quads as soon as they are produced.
+///
+/// The layout of each record is given by `layout_ref`, called with the CBOR
+/// tag of the record, if any. Blank node identifiers are relabeled as in
+/// [`dehydrate_all`], so that records do not share anonymous resources.
+fn dehydrate_batch(
+	layouts: &impl LayoutRegistry,
+	layout_ref: impl FnMut(Option<u64>) -> Result<Ref<LayoutType>, Error>,
+	input: TreeFormat,
+	stdin: impl BufRead,
+	codebook: Option<&Codebook>,
+	output: DehydrateOutput,
+) -> Result<(), Error> {
+	if !output.format.is_line_based() {
+		return Err(Error::BatchOutput(output.format));
+	}
+
+	let records = input
+		.load_records(layouts, layout_ref, stdin)
+		.map_err(Error::LoadTree)?;
+	let mut stdout = io::BufWriter::new(io::stdout().lock());
+	let mut count = 0;
+	let mut failures = 0;
+
+	for (i, record) in records.enumerate() {
+		count += 1;
+		let result = record.and_then(|(value, selection, layout_ref)| {
+			let value = match codebook {
+				Some(codebook) => codebook
+					.decompress(layouts, &layout_ref, &selection, value)
+					.map_err(Error::Decompress)?,
+				None => value,
+			};
+			let dataset = dehydrate(layouts, &value, selection, &layout_ref, output.ids)?;
+			output
+				.format
+				.write(relabel(dataset, i), output.rdf_options, &mut stdout)
+				.map_err(Error::WriteRdf)
+		});
+
+		if let Err(e) = result {
+			report_record(&mut failures, i + 1, e)
+		}
+	}
+
+	io::Write::flush(&mut stdout).map_err(Error::IO)?;
+	Error::batch(failures, count)
+}
+
+/// Output of [`hydrate_batch`].
+struct HydrateOutput<'a> {
+	/// Output tree format, which must support batches.
+	format: TreeFormat,
+
+	/// Write records in canonical form.
+	canonical: bool,
+
+	/// Compression codebook, if compression is enabled.
+	codebook: Option<&'a Codebook>,
+}
+
+/// Hydrates every given subject on its own, writing one record per subject.
+///
+/// Subjects given in `subjects_file` are read one line at a time, after the
+/// ones given on the command line.
+fn hydrate_batch(
+	layouts: &impl LayoutRegistry,
+	input: &impl PatternMatchingDataset<Resource = Term>,
+	layout_ref: &Ref<LayoutType>,
+	subjects: Vec<Term>,
+	subjects_file: Option<PathBuf>,
+	output: HydrateOutput,
+) -> Result<(), Error> {
+	if !output.format.supports_batch() {
+		return Err(Error::CreateTree(format::tree::WriteError::Batch(
+			output.format,
+		)));
+	}
+
+	let lines: Box<dyn Iterator<Item = io::Result<String>>> = match subjects_file {
+		Some(path) => Box::new(io::BufRead::lines(BufReader::new(
+			fs::File::open(path).map_err(Error::IO)?,
+		))),
+		None => Box::new(std::iter::empty()),
+	};
+
+	let subjects = subjects.into_iter().map(Ok).chain(
+		lines
+			.filter(|line| line.as_ref().map_or(true, |l| !l.trim().is_empty()))
+			.map(|line| {
+				let line = line.map_err(Error::IO)?;
+				rdf::parse_term(line.trim()).map_err(Error::InvalidSubject)
+			}),
+	);
+
+	let mut stdout = io::BufWriter::new(io::stdout().lock());
+	let mut count = 0;
+	let mut failures = 0;
+
+	for (i, subject) in subjects.enumerate() {
+		count += 1;
+		let result = subject.and_then(|subject| {
+			let value = treeldr_layouts::distill::hydrate(layouts, input, layout_ref, &[subject])
+				.map_err(Error::Hydrate)?;
+			match output.codebook {
+				Some(codebook) => {
+					output
+						.format
+						.write_compressed(layouts, codebook, value, &mut stdout)
+				}
+				None => output
+					.format
+					.write_record(layouts, value, output.canonical, &mut stdout),
 			}
-			Self::Convert { command } => command.run(),
+			.map_err(Error::CreateTree)
+		});
+
+		if let Err(e) = result {
+			report_record(&mut failures, i + 1, e)
 		}
 	}
+
+	io::Write::flush(&mut stdout).map_err(Error::IO)?;
+	Error::batch(failures, count)
 }
 
 impl Convert {
-	fn run(self) -> Result<(), Error> {
+	fn run(self, rdf_options: &format::rdf::Options) -> Result<(), Error> {
 		match self {
 			Self::Tree {
 				input,
 				output,
 				pretty,
+				canonical,
 			} => {
 				let stdin = BufReader::new(io::stdin());
-				let value = input.load(stdin).map_err(Error::LoadTree)?;
+				let values = input.load_all(stdin).map_err(Error::LoadTree)?;
 				output
-					.write_untyped(value, pretty, io::stdout())
+					.write_untyped_all(values, Style::new(pretty, canonical), io::stdout())
 					.map_err(Error::CreateTree)
 			}
-			Self::Rdf { input, output } => {
+			Self::Rdf {
+				input,
+				output,
+				canonical,
+			} => {
 				let stdin = BufReader::new(io::stdin());
-				let dataset = input.load(stdin).map_err(Error::LoadRdf)?.into_indexed();
-				output.write(dataset, io::stdout()).map_err(Error::IO)
+				let dataset = input
+					.load(stdin, rdf_options)
+					.map_err(Error::LoadRdf)?
+					.into_indexed();
+				if canonical {
+					output.write_canonical(dataset, io::stdout())
+				} else {
+					output.write(dataset, rdf_options, io::stdout())
+				}
+				.map_err(Error::WriteRdf)
+			}
+		}
+	}
+}
+
+impl Generate {
+	fn run(
+		self,
+		layouts: impl LayoutRegistry,
+		default_layout: DefaultLayoutRef,
+	) -> Result<(), Error> {
+		match self {
+			Self::Typescript { layout, validators } => {
+				let layout_ref = default_layout.get(layout)?;
+				let options = treeldr_gen_ts::Options::new().with_validators(validators);
+				let output =
+					treeldr_gen_ts::generate(Default::default(), &layouts, &layout_ref, &options)
+						.map_err(Error::GenerateTypescript)?;
+				print!("{output}");
+				Ok(())
+			}
+			Self::Python { layout, model } => {
+				let layout_ref = default_layout.get(layout)?;
+				let options = treeldr_gen_py::Options::new().with_model(model);
+				let output =
+					treeldr_gen_py::generate(Default::default(), &layouts, &layout_ref, &options)
+						.map_err(Error::GeneratePython)?;
+				print!("{output}");
+				Ok(())
+			}
+			Self::Go { layout, package } => {
+				let layout_ref = default_layout.get(layout)?;
+				let options = treeldr_gen_go::Options::new().with_package(package);
+				let output =
+					treeldr_gen_go::generate(Default::default(), &layouts, &layout_ref, &options)
+						.map_err(Error::GenerateGo)?;
+				print!("{output}");
+				Ok(())
+			}
+			Self::Graphql { layout, resolvers } => {
+				let layout_ref = default_layout.get(layout)?;
+				let options = treeldr_gen_graphql::Options::new().with_resolvers(resolvers);
+				let output = treeldr_gen_graphql::generate(
+					Default::default(),
+					&layouts,
+					&layout_ref,
+					&options,
+				)
+				.map_err(Error::GenerateGraphql)?;
+				print!("{output}");
+				Ok(())
+			}
+			Self::Protobuf { layout, package } => {
+				let layout_ref = default_layout.get(layout)?;
+				let options = treeldr_gen_protobuf::Options::new().with_package(package);
+				let output = treeldr_gen_protobuf::generate(
+					Default::default(),
+					&layouts,
+					&layout_ref,
+					&options,
+				)
+				.map_err(Error::GenerateProtobuf)?;
+				print!("{output}");
+				Ok(())
+			}
+			Self::Cddl { layout } => {
+				let layout_ref = default_layout.get(layout)?;
+				let options = treeldr_gen_cddl::Options::new();
+				let output =
+					treeldr_gen_cddl::generate(Default::default(), &layouts, &layout_ref, &options)
+						.map_err(Error::GenerateCddl)?;
+				print!("{output}");
+				Ok(())
+			}
+			Self::Sql { layout, dialect } => {
+				let layout_ref = default_layout.get(layout)?;
+				let options = treeldr_gen_sql::Options::new().with_dialect(dialect);
+				let output =
+					treeldr_gen_sql::generate(Default::default(), &layouts, &layout_ref, &options)
+						.map_err(Error::GenerateSql)?;
+				print!("{output}");
+				Ok(())
+			}
+			Self::Openapi {
+				layout,
+				title,
+				version,
+			} => {
+				let layout_refs = default_layout.all(layout)?;
+				let options = treeldr_gen_openapi::Options::new()
+					.with_title(title)
+					.with_version(version);
+				let output = treeldr_gen_openapi::generate(
+					Default::default(),
+					&layouts,
+					&layout_refs,
+					&options,
+				)
+				.map_err(Error::GenerateOpenapi)?;
+				println!("{}", output.pretty_print());
+				Ok(())
 			}
 		}
 	}
@@ -291,17 +1114,66 @@ enum Error {
 		json_syntax::CodeMap,
 		treeldr_layouts::abs::syntax::Error,
 	),
-	LayoutBuild(usize, treeldr_layouts::abs::syntax::BuildError),
+	LayoutBuild(
+		usize,
+		std::ops::Range<usize>,
+		treeldr_layouts::abs::syntax::BuildError,
+	),
 	NoDefaultLayout,
 	AmbiguousDefaultLayout,
 	LoadRdf(format::rdf::LoadError),
+	WriteRdf(format::rdf::WriteError),
 	LoadTree(format::tree::LoadError),
 	Hydrate(treeldr_layouts::distill::hy::Error),
 	Dehydrate(treeldr_layouts::distill::de::Error),
+	Canonicalization(treeldr_layouts::canonical::Error),
 	CreateTree(format::tree::WriteError),
+	Codebook(format::tree::compression::CodebookError),
+	Decompress(format::tree::compression::DecompressError),
+	InvalidSubject(rdf::InvalidTerm),
+	BatchOutput(RDFFormat),
+	Batch(usize, usize),
+	Check(usize, usize),
+	SerializeLayout(json_syntax::SerializeError),
+	Unformatted(usize, usize),
+	GenerateTypescript(treeldr_gen_ts::Error),
+	GeneratePython(treeldr_gen_py::Error),
+	GenerateGo(treeldr_gen_go::Error),
+	GenerateGraphql(treeldr_gen_graphql::Error),
+	GenerateProtobuf(treeldr_gen_protobuf::Error),
+	GenerateCddl(treeldr_gen_cddl::Error),
+	GenerateSql(treeldr_gen_sql::Error),
+	GenerateOpenapi(treeldr_gen_openapi::Error),
+}
+
+impl From<format::tree::LoadError> for Error {
+	fn from(e: format::tree::LoadError) -> Self {
+		Self::LoadTree(e)
+	}
 }
 
 impl Error {
+	/// Creates a layout build error, located in the given layout definition.
+	fn layout_build(
+		file_id: usize,
+		json: &json_syntax::Value,
+		code_map: &json_syntax::CodeMap,
+		e: treeldr_layouts::abs::syntax::BuildError,
+	) -> Self {
+		let span = code_map.get(e.position(json, code_map)).unwrap().span;
+		Self::LayoutBuild(file_id, span.into(), e)
+	}
+
+	/// Returns the result of a batch of `count` records, `failures` of which
+	/// have already been reported.
+	fn batch(failures: usize, count: usize) -> Result<(), Self> {
+		if failures == 0 {
+			Ok(())
+		} else {
+			Err(Self::Batch(failures, count))
+		}
+	}
+
 	fn into_diagnostic(self) -> Diagnostic<usize> {
 		match self {
 			Self::IO(e) => Diagnostic::error().with_message(e.to_string()),
@@ -333,7 +1205,11 @@ impl Error {
 					.with_labels(labels)
 					.with_notes(notes)
 			}
-			Self::LayoutBuild(_file_id, e) => Diagnostic::error().with_message(e.to_string()),
+			Self::LayoutBuild(file_id, span, e) => Diagnostic::error()
+				.with_message("Layout build error")
+				.with_labels(vec![
+					Label::primary(file_id, span).with_message(e.to_string())
+				]),
 			Self::NoDefaultLayout => Diagnostic::error()
 				.with_message("no default layout")
 				.with_notes(vec![
@@ -345,50 +1221,107 @@ impl Error {
 					"use the `--layout` option to specify what layout to use".to_owned(),
 				]),
 			Self::LoadRdf(e) => Diagnostic::error().with_message(e.to_string()),
+			Self::WriteRdf(e) => Diagnostic::error().with_message(e.to_string()),
 			Self::LoadTree(e) => Diagnostic::error().with_message(e.to_string()),
 			Self::Hydrate(e) => Diagnostic::error().with_message(e.to_string()),
 			Self::Dehydrate(e) => Diagnostic::error().with_message(e.to_string()),
+			Self::Canonicalization(e) => Diagnostic::error().with_message(e.to_string()),
 			Self::CreateTree(e) => Diagnostic::error().with_message(e.to_string()),
+			Self::Codebook(e) => Diagnostic::error().with_message(e.to_string()),
+			Self::Decompress(e) => Diagnostic::error().with_message(e.to_string()),
+			Self::InvalidSubject(e) => Diagnostic::error().with_message(e.to_string()),
+			Self::BatchOutput(format) => Diagnostic::error()
+				.with_message(format!("`{format}` does not support batch mode"))
+				.with_notes(vec!["use `n-quads` or `n-triples` instead".to_owned()]),
+			Self::Batch(failures, count) => {
+				Diagnostic::error().with_message(format!("{failures} of {count} records failed"))
+			}
+			Self::Check(failures, count) => Diagnostic::error()
+				.with_message(format!("{failures} of {count} layout files have errors")),
+			Self::SerializeLayout(e) => Diagnostic::error().with_message(e.to_string()),
+			Self::Unformatted(n, count) => Diagnostic::error()
+				.with_message(format!("{n} of {count} layout files are not formatted")),
+			Self::GenerateTypescript(e) => Diagnostic::error().with_message(e.to_string()),
+			Self::GeneratePython(e) => Diagnostic::error().with_message(e.to_string()),
+			Self::GenerateGo(e) => Diagnostic::error().with_message(e.to_string()),
+			Self::GenerateGraphql(e) => Diagnostic::error().with_message(e.to_string()),
+			Self::GenerateProtobuf(e) => Diagnostic::error().with_message(e.to_string()),
+			Self::GenerateCddl(e) => Diagnostic::error().with_message(e.to_string()),
+			Self::GenerateSql(e) => Diagnostic::error().with_message(e.to_string()),
+			Self::GenerateOpenapi(e) => Diagnostic::error().with_message(e.to_string()),
+		}
+	}
+}
+
+/// Parses a layout file.
+fn parse_layout(
+	files: &SimpleFiles<String, String>,
+	file_id: usize,
+) -> Result<
+	(
+		json_syntax::Value,
+		json_syntax::CodeMap,
+		treeldr_layouts::abs::syntax::Layout,
+	),
+	Error,
+> {
+	use json_syntax::{Parse, TryFromJson};
+
+	match json_syntax::Value::parse_str(files.get(file_id).unwrap().source().as_str()) {
+		Ok((json, code_map)) => {
+			match treeldr_layouts::abs::syntax::Layout::try_from_json(&json, &code_map) {
+				Ok(layout) => Ok((json, code_map, layout)),
+				Err(e) => Err(Error::LayoutSyntax(file_id, code_map, e)),
+			}
 		}
+		Err(e) => Err(Error::JsonSyntax(file_id, e)),
 	}
 }
 
 /// Loads a layout file.
+///
+/// The IRI prefixes declared in the top-level layout header are added to
+/// `prefixes`, unless a prefix with the same name is already defined.
 fn load_layout(
 	files: &SimpleFiles<String, String>,
 	file_id: usize,
 	layouts: &mut Layouts,
 	generator: &mut impl Generator,
+	prefixes: &mut format::rdf::Prefixes,
 ) -> Result<Ref<LayoutType>, Error> {
-	use json_syntax::{Parse, TryFromJson};
+	use treeldr_layouts::abs::syntax::{BuildError, Scope};
+
+	let (json, code_map, layout) = parse_layout(files, file_id)?;
+	let build_error = |e: BuildError| Error::layout_build(file_id, &json, &code_map, e);
 
 	let mut builder = treeldr_layouts::abs::Builder::new();
 	let mut context = builder.with_generator_mut(generator);
+	let layout_ref = layout
+		.build_with_context(&mut context)
+		.map_err(build_error)?;
 
-	match json_syntax::Value::parse_str(files.get(file_id).unwrap().source().as_str()) {
-		Ok((json, code_map)) => {
-			match treeldr_layouts::abs::syntax::Layout::try_from_json(&json, &code_map) {
-				Ok(layout) => match layout.build_with_context(&mut context) {
-					Ok(layout_ref) => {
-						let new_layouts = builder.build();
-
-						for (id, layout) in new_layouts {
-							let (_, old_layout) = layouts.insert(id.into_id(), layout);
-							if old_layout.is_some() {
-								return Err(Error::LayoutBuild(
-									file_id,
-									treeldr_layouts::abs::syntax::BuildError::LayoutRedefinition,
-								));
-							}
-						}
-
-						Ok(layout_ref)
-					}
-					Err(e) => Err(Error::LayoutBuild(file_id, e)),
-				},
-				Err(e) => Err(Error::LayoutSyntax(file_id, code_map, e)),
+	if let Some(header) = layout.header() {
+		let scope = Scope::default().with_header(header).map_err(build_error)?;
+		for (name, prefix) in &header.prefixes {
+			if !prefixes.contains_key(name) {
+				let iri = prefix
+					.resolve(&scope)
+					.map_err(|e| build_error(e.at(name.as_str()).at("prefixes")))?;
+				prefixes.insert(name.clone(), iri);
 			}
 		}
-		Err(e) => Err(Error::JsonSyntax(file_id, e)),
 	}
+
+	let new_layouts = builder.build();
+
+	for (id, layout) in new_layouts {
+		let is_root = id == layout_ref;
+		let (_, old_layout) = layouts.insert(id.into_id(), layout);
+		if old_layout.is_some() {
+			let e = BuildError::LayoutRedefinition;
+			return Err(build_error(if is_root { e.at("id") } else { e }));
+		}
+	}
+
+	Ok(layout_ref)
 }
@@ -1,4 +1,5 @@
-use iref::Iri;
+use clap::builder::TypedValueParser;
+use iref::{Iri, IriBuf};
 use rdf_types::{BlankId, Term};
 
 #[derive(Debug, thiserror::Error)]
@@ -14,3 +15,63 @@ pub fn parse_term(input: &str) -> Result<Term, InvalidTerm> {
 		},
 	}
 }
+
+/// Strategy used to identify anonymous resources when dehydrating.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum IdStrategy {
+	/// Fresh blank node identifiers.
+	#[default]
+	Blank,
+
+	/// Random `urn:uuid:` IRIs.
+	Uuid,
+
+	/// Random Skolem IRIs (`/.well-known/genid/`) under a base IRI.
+	Genid,
+
+	/// IRIs derived from the hash of the dehydrated record.
+	Hash,
+}
+
+impl IdStrategy {
+	pub const POSSIBLE_VALUES: &'static [&'static str] = &["blank", "uuid", "genid", "hash"];
+
+	pub fn parser(
+	) -> clap::builder::MapValueParser<clap::builder::PossibleValuesParser, fn(String) -> Self> {
+		clap::builder::PossibleValuesParser::new(Self::POSSIBLE_VALUES)
+			.map(|s| Self::new(&s).unwrap())
+	}
+
+	pub fn new(name: &str) -> Option<Self> {
+		match name {
+			"blank" => Some(Self::Blank),
+			"uuid" => Some(Self::Uuid),
+			"genid" => Some(Self::Genid),
+			"hash" => Some(Self::Hash),
+			_ => None,
+		}
+	}
+}
+
+/// Identification of anonymous resources, with its parameters.
+pub enum Identifiers {
+	Blank,
+	Uuid,
+	Genid(IriBuf),
+	Hash(IriBuf),
+}
+
+impl Identifiers {
+	/// Creates the identification scheme for the given strategy.
+	///
+	/// The `base` IRI is required by the `genid` and `hash` strategies, and
+	/// ignored otherwise.
+	pub fn new(strategy: IdStrategy, base: Option<IriBuf>) -> Option<Self> {
+		match strategy {
+			IdStrategy::Blank => Some(Self::Blank),
+			IdStrategy::Uuid => Some(Self::Uuid),
+			IdStrategy::Genid => base.map(Self::Genid),
+			IdStrategy::Hash => base.map(Self::Hash),
+		}
+	}
+}
@@ -0,0 +1,208 @@
+use std::{
+	io::Write,
+	process::{Command, Output, Stdio},
+};
+
+fn layout_path() -> String {
+	format!(
+		"{}/layouts/examples/record.json",
+		env!("CARGO_MANIFEST_DIR")
+	)
+}
+
+/// Runs `tldr` with the record example layout and the given arguments.
+fn tldr(args: &[&str], stdin: &str) -> Output {
+	run(&[layout_path()], args, stdin.as_bytes())
+}
+
+/// Runs `tldr` with the given layout files and arguments.
+fn run(layouts: &[String], args: &[&str], stdin: &[u8]) -> Output {
+	let mut child = Command::new(env!("CARGO_BIN_EXE_tldr"))
+		.args(layouts)
+		.args(args)
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.unwrap();
+
+	child.stdin.take().unwrap().write_all(stdin).unwrap();
+	child.wait_with_output().unwrap()
+}
+
+fn lines(bytes: &[u8]) -> Vec<String> {
+	let mut lines: Vec<_> = String::from_utf8_lossy(bytes)
+		.lines()
+		.map(ToOwned::to_owned)
+		.collect();
+	lines.sort();
+	lines
+}
+
+#[test]
+fn dehydrate_batch() {
+	let output = tldr(
+		&["dehydrate", "--batch"],
+		"{ \"id\": \"https://example.org/#alice\", \"name\": \"Alice\" }\n\
+		{ \"id\": \"https://example.org/#bob\", \"name\": \"Bob\" }\n",
+	);
+
+	assert!(output.status.success());
+	assert_eq!(
+		lines(&output.stdout),
+		[
+			"<https://example.org/#alice> <https://schema.org/name> \"Alice\" .",
+			"<https://example.org/#bob> <https://schema.org/name> \"Bob\" ."
+		]
+	)
+}
+
+#[test]
+fn dehydrate_batch_errors() {
+	let output = tldr(
+		&["dehydrate", "--batch"],
+		"{ \"id\": \"https://example.org/#alice\", \"name\": \"Alice\" }\n\
+		{ \"id\": \"https://example.org/#bob\", \n\
+		{ \"id\": \"https://example.org/#carol\", \"name\": 42 }\n\
+		{ \"id\": \"https://example.org/#dave\", \"name\": \"Dave\" }\n",
+	);
+
+	// Records 2 and 3 fail, without preventing records 1 and 4.
+	assert!(!output.status.success());
+	assert_eq!(
+		lines(&output.stdout),
+		[
+			"<https://example.org/#alice> <https://schema.org/name> \"Alice\" .",
+			"<https://example.org/#dave> <https://schema.org/name> \"Dave\" ."
+		]
+	);
+
+	let stderr = String::from_utf8_lossy(&output.stderr);
+	assert!(!stderr.contains("record 1:"));
+	assert!(stderr.contains("record 2:"));
+	assert!(stderr.contains("record 3:"));
+	assert!(!stderr.contains("record 4:"));
+	assert!(stderr.contains("2 of 4"))
+}
+
+#[test]
+fn dehydrate_batch_json_seq() {
+	let output = tldr(
+		&["dehydrate", "--batch"],
+		"\x1e{\n\t\"id\": \"https://example.org/#alice\",\n\t\"name\": \"Alice\"\n}\n\
+		\x1e{\n\t\"id\": \"https://example.org/#bob\",\n\t\"name\": \"Bob\"\n}\n",
+	);
+
+	assert!(output.status.success());
+	assert_eq!(lines(&output.stdout).len(), 2)
+}
+
+#[test]
+fn dehydrate_batch_cbor_tags() {
+	// Two top-level layouts, told apart by their CBOR tag.
+	let dir = std::env::temp_dir().join(format!("tldr-batch-{}", std::process::id()));
+	std::fs::create_dir_all(&dir).unwrap();
+	let layouts: Vec<String> = [("person", 1000, "name"), ("company", 1001, "legalName")]
+		.into_iter()
+		.map(|(name, tag, property)| {
+			let path = dir.join(format!("{name}.json"));
+			std::fs::write(
+				&path,
+				format!(
+					r#"{{
+						"id": "https://example.org/#{name}",
+						"type": "record",
+						"prefixes": {{ "tldr": "https://treeldr.org/prelude#" }},
+						"extra": {{ "https://schema.treeldr.org/cbor#tag": {tag} }},
+						"fields": {{
+							"id": {{ "intro": [], "value": {{ "layout": "tldr:id", "input": ["_:self"] }} }},
+							"name": {{ "value": "tldr:string", "property": "https://schema.org/{property}" }}
+						}}
+					}}"#
+				),
+			)
+			.unwrap();
+			path.to_string_lossy().into_owned()
+		})
+		.collect();
+
+	let dataset = "<https://example.org/#alice> <https://schema.org/name> \"Alice\" .\n\
+		<https://example.org/#acme> <https://schema.org/legalName> \"ACME\" .\n";
+
+	let mut records = Vec::new();
+	for (layout, subject) in [
+		("https://example.org/#person", "https://example.org/#alice"),
+		("https://example.org/#company", "https://example.org/#acme"),
+	] {
+		let output = run(
+			&layouts,
+			&["hydrate", "-o", "cbor", "--layout", layout, subject],
+			dataset.as_bytes(),
+		);
+		assert!(output.status.success());
+		records.extend(output.stdout)
+	}
+
+	// Each record selects its own layout from its tag.
+	let output = run(&layouts, &["dehydrate", "-i", "cbor", "--batch"], &records);
+	std::fs::remove_dir_all(&dir).unwrap();
+
+	assert!(output.status.success());
+	assert_eq!(lines(&output.stdout), lines(dataset.as_bytes()))
+}
+
+#[test]
+fn hydrate_batch() {
+	let dataset = "<https://example.org/#alice> <https://schema.org/name> \"Alice\" .\n\
+		<https://example.org/#bob> <https://schema.org/name> \"Bob\" .\n";
+
+	let output = tldr(
+		&[
+			"hydrate",
+			"--batch",
+			"https://example.org/#alice",
+			"https://example.org/#bob",
+		],
+		dataset,
+	);
+
+	assert!(output.status.success());
+	assert_eq!(
+		String::from_utf8_lossy(&output.stdout),
+		"{\"id\":\"https://example.org/#alice\",\"name\":\"Alice\"}\n\
+		{\"id\":\"https://example.org/#bob\",\"name\":\"Bob\"}\n"
+	)
+}
+
+#[test]
+fn hydrate_batch_errors() {
+	let dataset = "<https://example.org/#alice> <https://schema.org/name> \"Alice\" .\n\
+		<https://example.org/#carol> <https://schema.org/name> \"Carol\" .\n\
+		<https://example.org/#carol> <https://schema.org/name> \"Caroline\" .\n\
+		<https://example.org/#bob> <https://schema.org/name> \"Bob\" .\n";
+
+	let output = tldr(
+		&[
+			"hydrate",
+			"--batch",
+			"--output",
+			"yaml",
+			"https://example.org/#alice",
+			"https://example.org/#carol",
+			"https://example.org/#bob",
+		],
+		dataset,
+	);
+
+	// Carol has two names.
+	assert!(!output.status.success());
+	assert_eq!(
+		String::from_utf8_lossy(&output.stdout),
+		"---\nid: https://example.org/#alice\nname: Alice\n\
+		---\nid: https://example.org/#bob\nname: Bob\n"
+	);
+
+	let stderr = String::from_utf8_lossy(&output.stderr);
+	assert!(stderr.contains("record 2: ambiguous key"));
+	assert!(!stderr.contains("record 1:") && !stderr.contains("record 3:"))
+}